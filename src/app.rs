@@ -4,17 +4,29 @@
 //! between the MIDI project, audio engine, and TUI interface.
 
 use crate::audio::{engine::AudioEngine, engine::PlaybackState};
-use crate::history::{HistoryManager, StateSnapshot};
-use crate::midi::{note_to_name, NoteId, Project, TICKS_PER_BEAT};
+use crate::autosave::{AutosaveEntry, AutosaveStore};
+use crate::command::{Command, CommandLine};
+use crate::config::Config;
+use crate::history::{HistoryManager, Selection};
+use crate::keymap::{Action, Keymap};
+use crate::midi::{
+    name_to_note, note_to_name, ticks_to_seconds, AccompanimentStyle, ArpSettings, Chord,
+    ChordQuality, ChordVoicing, DrumMap, DrumMapEntry, EchoSettings, EventColumn, EventFilter,
+    EventSort, GridFeel, GrooveTemplate, HumanizeSettings, Lyric, Marker, Note, NoteEffect, NoteId,
+    Project, QuantizeSettings, Scale, ScaleKind, TimeFormat, TrackId, GRID_DIVISIONS, SLOT_TICKS,
+    TICKS_PER_BEAT,
+};
+use crate::recent::RecentFiles;
+use crate::theme::Theme;
+use crate::vim::{Mark, NoteRegister, PendingKeys, UNNAMED_REGISTER};
 use anyhow::Result;
 use ratatui::layout::Rect;
-use std::collections::HashSet;
-use std::path::PathBuf;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 use std::time::{Duration, Instant};
 
-/// Autosave delay in seconds after last modification.
-const AUTOSAVE_DELAY_SECS: u64 = 5;
-
 /// Save file format options.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum SaveFormat {
@@ -27,6 +39,17 @@ pub enum SaveFormat {
     Midi,
 }
 
+impl SaveFormat {
+    /// Returns the format a file is saved in, by extension (JSON if unknown).
+    pub fn for_path(path: &Path) -> Self {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("oxm") => SaveFormat::Oxm,
+            Some("mid") | Some("midi") | Some("kar") => SaveFormat::Midi,
+            _ => SaveFormat::Json,
+        }
+    }
+}
+
 /// State for the save dialog.
 #[derive(Debug, Clone, Default)]
 pub struct SaveDialogState {
@@ -65,6 +88,88 @@ impl Default for FileBrowserState {
     }
 }
 
+/// State for the recent files dialog.
+#[derive(Debug, Clone, Default)]
+pub struct RecentDialogState {
+    /// Whether the dialog is open.
+    pub open: bool,
+    /// Index of the highlighted file.
+    pub selected: usize,
+}
+
+/// An action that abandons the current project, held while asking to save.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GuardedAction {
+    /// Quit the application.
+    Quit,
+    /// Replace the project with a new, empty one.
+    NewProject,
+    /// Load a project or MIDI file.
+    Load(PathBuf),
+    /// Recover an autosave version.
+    Recover(PathBuf),
+}
+
+/// State for the crash-recovery dialog.
+#[derive(Debug, Clone, Default)]
+pub struct RecoveryDialogState {
+    /// Whether the dialog is open.
+    pub open: bool,
+    /// Autosave versions, newest first.
+    pub entries: Vec<AutosaveEntry>,
+    /// Index of the highlighted version.
+    pub selected: usize,
+}
+
+/// State for the undo history panel.
+#[derive(Debug, Clone, Default)]
+pub struct HistoryDialogState {
+    /// Whether the panel is open.
+    pub open: bool,
+    /// Highlighted point in the history (0 = before the first edit).
+    pub selected: usize,
+}
+
+/// Number of adjustable rows in the settings dialog.
+pub const SETTINGS_DIALOG_ROWS: usize = 10;
+
+/// Note lengths offered for new notes in the settings dialog (1/16 to whole).
+const NOTE_LENGTH_CHOICES: [u32; 5] = [
+    TICKS_PER_BEAT / 4,
+    TICKS_PER_BEAT / 2,
+    TICKS_PER_BEAT,
+    TICKS_PER_BEAT * 2,
+    TICKS_PER_BEAT * 4,
+];
+
+/// Sample rates offered for WAV export in the settings dialog.
+const SAMPLE_RATE_CHOICES: [u32; 5] = [22050, 32000, 44100, 48000, 96000];
+
+/// State for the settings dialog.
+#[derive(Debug, Clone, Default)]
+pub struct SettingsDialogState {
+    /// Whether the dialog is open.
+    pub open: bool,
+    /// Highlighted row (velocity, note length, zoom, highlight, theme,
+    /// autosave delay, poll interval, history, sample rate, release tail).
+    pub selected: usize,
+    /// Settings being edited, applied and saved on confirm.
+    pub draft: Config,
+    /// Themes to choose from, built-in first.
+    pub themes: Vec<String>,
+}
+
+/// State for the unsaved changes prompt (Save / Discard / Cancel).
+#[derive(Debug, Clone, Default)]
+pub struct UnsavedDialogState {
+    /// Whether the prompt is shown.
+    pub open: bool,
+    /// Action to run once the changes are saved or discarded.
+    pub action: Option<GuardedAction>,
+    /// Highlighted choice (0 = Save, 1 = Discard, 2 = Cancel).
+    pub selected: usize,
+}
+
 /// State for the new project confirmation dialog.
 #[derive(Debug, Clone, Default)]
 pub struct NewProjectDialogState {
@@ -74,6 +179,204 @@ pub struct NewProjectDialogState {
     pub selected: usize,
 }
 
+/// State for the marker list dialog.
+#[derive(Debug, Clone, Default)]
+pub struct MarkerDialogState {
+    /// Whether the dialog is open.
+    pub open: bool,
+    /// Index of the highlighted marker.
+    pub selected: usize,
+    /// Whether the highlighted marker is being renamed.
+    pub renaming: bool,
+    /// Buffer for marker rename input.
+    pub rename_buffer: String,
+}
+
+/// State for the insert/delete time dialog.
+#[derive(Debug, Clone, Default)]
+pub struct TimeDialogState {
+    /// Whether the dialog is open.
+    pub open: bool,
+    /// Whether time is deleted (true) or inserted (false).
+    pub delete: bool,
+    /// Length of the span in beats.
+    pub length_beats: u32,
+}
+
+/// Number of adjustable rows in the quantize dialog.
+pub const QUANTIZE_DIALOG_ROWS: usize = 7;
+
+/// State for the quantize dialog.
+#[derive(Debug, Clone, Default)]
+pub struct QuantizeDialogState {
+    /// Whether the dialog is open.
+    pub open: bool,
+    /// Highlighted row (grid, feel, strength, swing, window, starts, ends).
+    pub selected: usize,
+    /// Current settings (kept between uses).
+    pub settings: QuantizeSettings,
+    /// Whether the result is previewed on the track while the dialog is open.
+    pub preview: bool,
+    /// Track being quantized.
+    pub track_index: usize,
+    /// Notes to quantize, as they were when the dialog opened.
+    pub originals: Vec<Note>,
+}
+
+/// Number of adjustable rows in the groove dialog.
+pub const GROOVE_DIALOG_ROWS: usize = 6;
+
+/// First groove dialog row belonging to the groove template section.
+pub const GROOVE_DIALOG_TEMPLATE_ROW: usize = 4;
+
+/// State for the humanize/groove dialog.
+#[derive(Debug, Clone)]
+pub struct GrooveDialogState {
+    /// Whether the dialog is open.
+    pub open: bool,
+    /// Highlighted row (timing, velocity, length, seed, template, strength).
+    pub selected: usize,
+    /// Humanize settings (kept between uses).
+    pub humanize: HumanizeSettings,
+    /// Index into the available templates (presets, then project grooves).
+    pub template: usize,
+    /// Groove strength in percent.
+    pub strength: u32,
+}
+
+impl Default for GrooveDialogState {
+    fn default() -> Self {
+        Self {
+            open: false,
+            selected: 0,
+            humanize: HumanizeSettings::default(),
+            template: 0,
+            strength: 100,
+        }
+    }
+}
+
+/// Number of adjustable rows in the scale dialog.
+pub const SCALE_DIALOG_ROWS: usize = 4;
+
+/// Scale dialog row holding the custom pitch class toggles.
+pub const SCALE_DIALOG_CUSTOM_ROW: usize = 3;
+
+/// State for the key/scale dialog.
+#[derive(Debug, Clone, Default)]
+pub struct ScaleDialogState {
+    /// Whether the dialog is open.
+    pub open: bool,
+    /// Highlighted row (root, scale, snap, custom pitch classes).
+    pub selected: usize,
+    /// Scale being edited.
+    pub scale: Scale,
+    /// Interval highlighted in the custom row (0-11 semitones above the root).
+    pub custom_cursor: u8,
+}
+
+/// Number of adjustable rows in the chord dialog.
+pub const CHORD_DIALOG_ROWS: usize = 6;
+
+/// State for the chord dialog (chord entry settings and chord track tools).
+#[derive(Debug, Clone, Default)]
+pub struct ChordDialogState {
+    /// Whether the dialog is open.
+    pub open: bool,
+    /// Highlighted row (entry, quality, inversion, voicing, style, target track).
+    pub selected: usize,
+    /// Accompaniment style for generating from the chord track.
+    pub style: AccompanimentStyle,
+    /// Track that receives generated accompaniment.
+    pub target_track: usize,
+}
+
+/// State for the note effects dialog (arpeggiator and echo).
+#[derive(Debug, Clone, Default)]
+pub struct EffectsDialogState {
+    /// Whether the dialog is open.
+    pub open: bool,
+    /// Highlighted row (effect type, then the effect's settings).
+    pub selected: usize,
+    /// Whether the echo is being edited instead of the arpeggiator.
+    pub echo: bool,
+    /// Arpeggiator settings (kept between uses).
+    pub arp_settings: ArpSettings,
+    /// Echo settings (kept between uses).
+    pub echo_settings: EchoSettings,
+}
+
+impl EffectsDialogState {
+    /// Returns the effect being edited.
+    pub fn effect(&self) -> NoteEffect {
+        if self.echo {
+            NoteEffect::Echo(self.echo_settings)
+        } else {
+            NoteEffect::Arpeggiator(self.arp_settings)
+        }
+    }
+
+    /// Returns the number of rows for the current effect type.
+    pub fn row_count(&self) -> usize {
+        if self.echo {
+            4
+        } else {
+            5
+        }
+    }
+}
+
+/// Number of adjustable columns in the drum map dialog (pitch, output, velocity).
+pub const DRUM_MAP_COLUMNS: usize = 3;
+
+/// What the event list's text field edits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventListInput {
+    /// The highlighted cell.
+    Cell,
+    /// The pitch/measure filter.
+    Filter,
+}
+
+/// State of the event list view.
+#[derive(Debug, Clone, Default)]
+pub struct EventListState {
+    /// Highlighted row (index into the filtered, sorted rows).
+    pub row: usize,
+    /// Highlighted column.
+    pub column: EventColumn,
+    /// Sort order of the rows.
+    pub sort: EventSort,
+    /// Pitch/measure filter.
+    pub filter: EventFilter,
+    /// Text field being edited and its contents.
+    pub input: Option<(EventListInput, String)>,
+}
+
+/// Text being typed in the drum map dialog.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DrumMapInput {
+    /// New name for the highlighted instrument.
+    Rename,
+    /// Path of a drum map file to load.
+    Load,
+    /// Path to save the drum map to.
+    Save,
+}
+
+/// State for the drum map dialog.
+#[derive(Debug, Clone, Default)]
+pub struct DrumMapDialogState {
+    /// Whether the dialog is open.
+    pub open: bool,
+    /// Index of the highlighted instrument.
+    pub selected: usize,
+    /// Highlighted column (0 = pitch, 1 = output pitch, 2 = velocity).
+    pub column: usize,
+    /// Active text input and its buffer.
+    pub input: Option<(DrumMapInput, String)>,
+}
+
 /// State for the SoundFont browser dialog.
 /// Similar to FileBrowserState but filters for .sf2 files.
 #[derive(Debug, Clone)]
@@ -113,6 +416,9 @@ pub const PIANO_KEY_WIDTH: u16 = 5;
 /// to pitch, since the ruler occupies the first row of the grid area.
 const TIME_RULER_HEIGHT: u16 = 1;
 
+/// Height of the velocity lane under the piano roll grid (header row plus bar rows).
+pub const VELOCITY_LANE_HEIGHT: u16 = 6;
+
 /// Layout regions for mouse hit testing.
 /// Stores the screen coordinates of each UI panel.
 #[derive(Debug, Clone, Default)]
@@ -134,6 +440,40 @@ pub struct LayoutRegions {
     /// Number of visible pitch rows in the piano roll grid.
     /// Dynamically calculated based on terminal height.
     pub visible_pitches: u8,
+    /// The velocity lane bar area (aligned with grid columns, empty when hidden).
+    pub velocity_lane: Rect,
+    /// The step sequencer grid (set during rendering, empty in other views).
+    pub step_grid: StepGridLayout,
+    /// The staff view measures (set during rendering, empty in other views).
+    pub staff: StaffLayout,
+    /// The event list rows (set during rendering, empty in other views).
+    pub event_rows: Rect,
+    /// Index of the event list row shown at the top.
+    pub event_first_row: usize,
+}
+
+/// Screen geometry of the staff view, for click-to-seek.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StaffLayout {
+    /// Area of the drawn measures (right of the clef, key and time signatures).
+    pub measures: Rect,
+    /// Index of the first drawn measure.
+    pub first_measure: u32,
+    /// Width of one measure in columns, including its barline.
+    pub measure_width: u16,
+    /// Width of one sixteenth slot in columns.
+    pub slot_width: u16,
+}
+
+/// Screen geometry of the step sequencer grid, for mouse hit testing.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StepGridLayout {
+    /// Area of the step cells (right of the instrument names, below the step ruler).
+    pub cells: Rect,
+    /// Width of one step cell in columns.
+    pub cell_width: u16,
+    /// Index of the instrument row shown at the top.
+    pub first_row: usize,
 }
 
 impl LayoutRegions {
@@ -171,6 +511,26 @@ impl LayoutRegions {
         self.contains(self.piano_roll_grid, x, y)
     }
 
+    /// Checks if a point is within the event list rows.
+    pub fn is_in_event_rows(&self, x: u16, y: u16) -> bool {
+        self.contains(self.event_rows, x, y)
+    }
+
+    /// Checks if a point is within the staff view measures.
+    pub fn is_in_staff(&self, x: u16, y: u16) -> bool {
+        self.contains(self.staff.measures, x, y)
+    }
+
+    /// Checks if a point is within the step sequencer cells.
+    pub fn is_in_step_grid(&self, x: u16, y: u16) -> bool {
+        self.contains(self.step_grid.cells, x, y)
+    }
+
+    /// Checks if a point is within the velocity lane bars.
+    pub fn is_in_velocity_lane(&self, x: u16, y: u16) -> bool {
+        self.contains(self.velocity_lane, x, y)
+    }
+
     /// Checks if a point is within any time ruler and returns the relative X position.
     ///
     /// Returns `Some((relative_x, ruler_width))` if clicking on a ruler, `None` otherwise.
@@ -210,14 +570,15 @@ pub enum DragState {
         /// Original pitch when drag started.
         start_pitch: u8,
     },
+    /// Drawing velocities in the velocity lane.
+    DrawingVelocity {
+        /// Last mouse X position (columns in between are interpolated).
+        last_x: u16,
+        /// Velocity set at the last position.
+        last_velocity: u8,
+    },
 }
 
-/// Default note velocity for new notes.
-pub const DEFAULT_VELOCITY: u8 = 100;
-
-/// Default note duration in ticks (quarter note).
-pub const DEFAULT_NOTE_DURATION: u32 = TICKS_PER_BEAT;
-
 /// The currently focused UI panel.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum FocusedPanel {
@@ -240,6 +601,8 @@ pub enum EditMode {
     Insert,
     /// Select mode - selecting notes for editing.
     Select,
+    /// Lyric mode - typing lyric syllables note-by-note.
+    Lyric,
 }
 
 /// The current view mode for the main content area.
@@ -253,12 +616,71 @@ pub enum ViewMode {
     PianoRoll,
     /// Project timeline view - shows all tracks on a combined timeline.
     ProjectTimeline,
+    /// Step sequencer - drum grid for drum tracks, one row per instrument.
+    StepSequencer,
+    /// Staff view - read-only notation of the selected track.
+    Staff,
+    /// Event list - table of the selected track's notes for exact edits.
+    EventList,
+}
+
+/// Velocity of an accented step.
+pub const ACCENT_VELOCITY: u8 = 120;
+
+/// Step lengths the step sequencer cycles through (fractions of a whole note).
+pub const STEP_DIVISIONS: [u32; 4] = [4, 8, 16, 32];
+
+/// State of the step sequencer view.
+#[derive(Debug, Clone)]
+pub struct StepSequencerState {
+    /// Highlighted instrument row.
+    pub row: usize,
+    /// Highlighted step within the pattern.
+    pub step: u32,
+    /// Pattern length in steps (1-32).
+    pub steps: u32,
+    /// Step length as a fraction of a whole note (16 = sixteenths).
+    pub division: u32,
+    /// Tick where the displayed pattern starts.
+    pub pattern_start: u32,
+    /// Whether playback loops the displayed pattern.
+    pub looping: bool,
+}
+
+impl Default for StepSequencerState {
+    fn default() -> Self {
+        Self {
+            row: 1, // Bass Drum 1
+            step: 0,
+            steps: 16,
+            division: 16,
+            pattern_start: 0,
+            looping: false,
+        }
+    }
+}
+
+impl StepSequencerState {
+    /// Returns the length of one step in ticks.
+    pub fn step_ticks(&self) -> u32 {
+        TICKS_PER_BEAT * 4 / self.division
+    }
+
+    /// Returns the length of the pattern in ticks.
+    pub fn pattern_ticks(&self) -> u32 {
+        self.step_ticks() * self.steps
+    }
+
+    /// Returns the start tick of a step.
+    pub fn step_tick(&self, step: u32) -> u32 {
+        self.pattern_start + step * self.step_ticks()
+    }
 }
 
 /// Highlight mode for active notes during playback.
 /// Controls which views show white highlighting for notes being played.
 /// Cycled with Shift+W in the order: PianoRollOnly -> Both -> Off -> TimelineOnly -> repeat.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
 pub enum HighlightMode {
     /// Only highlight active notes in the Piano Roll view (default).
     #[default]
@@ -271,37 +693,53 @@ pub enum HighlightMode {
     TimelineOnly,
 }
 
-/// Keyboard key to MIDI note mapping for the computer keyboard.
-/// Uses a piano-like layout on QWERTY keyboards.
-pub const KEYBOARD_MAP: [(char, u8); 25] = [
-    // Lower row (Z-M) = C3 to B3
-    ('z', 48), // C3
-    ('s', 49), // C#3
-    ('x', 50), // D3
-    ('d', 51), // D#3
-    ('c', 52), // E3
-    ('v', 53), // F3
-    ('g', 54), // F#3
-    ('b', 55), // G3
-    ('h', 56), // G#3
-    ('n', 57), // A3
-    ('j', 58), // A#3
-    ('m', 59), // B3
-    // Upper row (Q-U) = C4 to B4
-    ('q', 60), // C4 (Middle C)
-    ('2', 61), // C#4
-    ('w', 62), // D4
-    ('3', 63), // D#4
-    ('e', 64), // E4
-    ('r', 65), // F4
-    ('5', 66), // F#4
-    ('t', 67), // G4
-    ('6', 68), // G#4
-    ('y', 69), // A4
-    ('7', 70), // A#4
-    ('u', 71), // B4
-    ('i', 72), // C5
-];
+impl HighlightMode {
+    /// Returns the next mode in cycle order.
+    pub fn next(self) -> Self {
+        match self {
+            Self::PianoRollOnly => Self::Both,
+            Self::Both => Self::Off,
+            Self::Off => Self::TimelineOnly,
+            Self::TimelineOnly => Self::PianoRollOnly,
+        }
+    }
+
+    /// Returns the previous mode in cycle order.
+    pub fn prev(self) -> Self {
+        match self {
+            Self::PianoRollOnly => Self::TimelineOnly,
+            Self::Both => Self::PianoRollOnly,
+            Self::Off => Self::Both,
+            Self::TimelineOnly => Self::Off,
+        }
+    }
+
+    /// Returns a short display name.
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::PianoRollOnly => "Piano Roll only",
+            Self::Both => "Piano Roll + Timeline",
+            Self::Off => "Off",
+            Self::TimelineOnly => "Timeline only",
+        }
+    }
+}
+
+/// Which other tracks the piano roll draws as ghost notes.
+///
+/// Cycled with Shift+O in the order: Off -> Above -> All -> Chosen -> repeat.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GhostMode {
+    /// Only the selected track is drawn (default).
+    #[default]
+    Off,
+    /// The track directly above the selected one.
+    Above,
+    /// Every other track.
+    All,
+    /// The tracks marked with Shift+R.
+    Chosen,
+}
 
 /// Main application state.
 pub struct App {
@@ -356,38 +794,89 @@ pub struct App {
     pub rename_buffer: String,
     /// Whether to show expanded track view (two lines per track).
     pub expanded_tracks: bool,
+    /// Whether to show the velocity lane under the piano roll.
+    pub show_velocity_lane: bool,
     /// Tracks currently playing audio (track indices with active notes).
     /// Updated during sequencer playback for visual feedback.
     pub active_tracks: HashSet<usize>,
+    /// Flattened notes (clips unpacked, effects applied) of the tracks that
+    /// need them during playback. Cleared whenever the project changes.
+    playback_cache: HashMap<TrackId, Vec<Note>>,
     /// Path to the current project file (None if unsaved).
     pub project_path: Option<PathBuf>,
     /// Last time the project was modified (for autosave).
     last_modified: Option<Instant>,
+    /// Whether the project changed since it was last saved to or loaded from `project_path`.
+    unsaved_changes: bool,
+    /// Unsaved changes prompt state.
+    pub unsaved_dialog: UnsavedDialogState,
+    /// Set when the application should exit at the end of the current loop.
+    pub quit_requested: bool,
     /// Last time autosave was performed.
     last_autosave: Option<Instant>,
-    /// Path to the autosave file.
-    autosave_path: PathBuf,
+    /// Per-project autosave slots.
+    autosave: AutosaveStore,
+    /// Crash-recovery dialog state.
+    pub recovery_dialog: RecoveryDialogState,
+    /// Undo history panel state.
+    pub history_dialog: HistoryDialogState,
     /// Save dialog state.
     pub save_dialog: SaveDialogState,
     /// File browser state for loading.
     pub file_browser: FileBrowserState,
+    /// Recently opened files, persisted in the config directory.
+    pub recent_files: RecentFiles,
+    /// Recent files dialog state.
+    pub recent_dialog: RecentDialogState,
     /// New project confirmation dialog state.
     pub new_project_dialog: NewProjectDialogState,
     /// Soundfont browser dialog state.
     pub soundfont_dialog: SoundfontDialogState,
+    /// Marker list dialog state.
+    pub marker_dialog: MarkerDialogState,
+    /// Insert/delete time dialog state.
+    pub time_dialog: TimeDialogState,
+    /// Quantize dialog state.
+    pub quantize_dialog: QuantizeDialogState,
+    /// Humanize/groove dialog state.
+    pub groove_dialog: GrooveDialogState,
+    /// Key/scale dialog state.
+    pub scale_dialog: ScaleDialogState,
+    /// Chord dialog state.
+    pub chord_dialog: ChordDialogState,
+    /// Note effects dialog state.
+    pub effects_dialog: EffectsDialogState,
+    /// Step sequencer view state.
+    pub step_seq: StepSequencerState,
+    /// Event list view state.
+    pub event_list: EventListState,
+    /// Drum map dialog state.
+    pub drum_map_dialog: DrumMapDialogState,
+    /// Whether placing a note places a whole chord.
+    pub chord_entry: bool,
+    /// Chord shape used by chord entry.
+    pub chord_voicing: ChordVoicing,
     /// Highlight mode for active notes during playback.
     /// Controls which views show white highlighting for notes being played.
     pub highlight_mode: HighlightMode,
+    /// Which other tracks are drawn as ghost notes in the piano roll.
+    pub ghost_mode: GhostMode,
+    /// Tracks shown as ghost notes in `GhostMode::Chosen`.
+    pub ghost_tracks: HashSet<TrackId>,
     /// Display offset in ticks to compensate for rendering latency.
     /// The visual playhead is advanced by this many ticks to appear synchronized
     /// with the audio output. Set to 0 to disable.
     pub display_offset_ticks: u32,
     /// Help menu scroll offset (for viewing all shortcuts).
     pub help_scroll: u16,
+    /// Syllable being typed in Lyric mode (for the note at the cursor).
+    pub lyric_buffer: String,
+    /// Whether the next syllable entered in Lyric mode starts a new line.
+    lyric_line_break: bool,
 
     /// Undo/redo history manager.
-    /// Stores up to 8 snapshots of the project state for undo/redo functionality.
-    history: HistoryManager,
+    /// Stores a compact diff of each edit, shown in the history panel.
+    pub history: HistoryManager,
 
     // ==================== Insert Mode Recording State ====================
     // These fields manage the real-time recording behavior in Insert Mode,
@@ -415,6 +904,25 @@ pub struct App {
     pub recently_added_note: Option<(NoteId, u32)>,
     /// Pitch of the most recently added note (for blue highlighting on keyboard).
     pub recently_added_pitch: Option<u8>,
+
+    /// Settings dialog state.
+    pub settings_dialog: SettingsDialogState,
+    /// User settings, written back by the settings dialog.
+    pub config: Config,
+    /// Key bindings and piano keys.
+    pub keymap: Keymap,
+    /// Colors the views are drawn with.
+    pub theme: Theme,
+    /// The `:` command line.
+    pub command_line: CommandLine,
+    /// Count, register and mark keys typed ahead of an action.
+    pub pending_keys: PendingKeys,
+    /// Note clipboards by register name.
+    registers: HashMap<char, NoteRegister>,
+    /// Saved cursor positions by mark name.
+    marks: HashMap<char, Mark>,
+    /// The last edit and its count, for `.` to repeat.
+    pub last_edit: Option<(Action, usize)>,
 }
 
 impl App {
@@ -423,6 +931,8 @@ impl App {
     /// # Arguments
     ///
     /// * `soundfont_path` - Path to the SoundFont file
+    /// * `config` - User settings
+    /// * `keymap` - Key bindings and piano keys
     ///
     /// # Returns
     ///
@@ -431,11 +941,12 @@ impl App {
     /// # Errors
     ///
     /// Returns error if the audio engine cannot be initialized
-    pub fn new(soundfont_path: PathBuf) -> Result<Self> {
+    pub fn new(soundfont_path: PathBuf, config: Config, keymap: Keymap) -> Result<Self> {
         let audio = AudioEngine::new(&soundfont_path)?;
+        let theme = config.load_theme();
 
-        Ok(Self {
-            project: Project::with_default_track("New Project"),
+        let mut app = Self {
+            project: config.new_project(),
             audio,
             soundfont_path,
             focused_panel: FocusedPanel::PianoRoll,
@@ -446,8 +957,8 @@ impl App {
             cursor_tick: 0,
             cursor_pitch: 60, // Middle C
             scroll_x: 0,
-            scroll_y: 48,             // Start viewing from C3
-            zoom: TICKS_PER_BEAT / 4, // 4 columns per beat
+            scroll_y: 48, // Start viewing from C3
+            zoom: config.default_zoom,
             held_notes: HashSet::new(),
             octave_offset: 0,
             status_message: None,
@@ -460,18 +971,43 @@ impl App {
             renaming_track: false,
             rename_buffer: String::new(),
             expanded_tracks: true, // Two-line track view enabled by default
+            show_velocity_lane: false,
             active_tracks: HashSet::new(),
+            playback_cache: HashMap::new(),
             project_path: None,
             last_modified: None,
+            unsaved_changes: false,
+            unsaved_dialog: UnsavedDialogState::default(),
+            quit_requested: false,
             last_autosave: None,
-            autosave_path: PathBuf::from(".autosave.oxm"),
+            autosave: AutosaveStore::default(),
+            recovery_dialog: RecoveryDialogState::default(),
+            history_dialog: HistoryDialogState::default(),
             save_dialog: SaveDialogState::default(),
             file_browser: FileBrowserState::default(),
+            recent_files: RecentFiles::load(),
+            recent_dialog: RecentDialogState::default(),
             new_project_dialog: NewProjectDialogState::default(),
             soundfont_dialog: SoundfontDialogState::default(),
-            highlight_mode: HighlightMode::default(), // Piano roll highlighting on by default
+            marker_dialog: MarkerDialogState::default(),
+            time_dialog: TimeDialogState::default(),
+            quantize_dialog: QuantizeDialogState::default(),
+            groove_dialog: GrooveDialogState::default(),
+            scale_dialog: ScaleDialogState::default(),
+            chord_dialog: ChordDialogState::default(),
+            effects_dialog: EffectsDialogState::default(),
+            step_seq: StepSequencerState::default(),
+            event_list: EventListState::default(),
+            drum_map_dialog: DrumMapDialogState::default(),
+            chord_entry: false,
+            chord_voicing: ChordVoicing::default(),
+            highlight_mode: config.highlight_mode,
+            ghost_mode: GhostMode::default(),
+            ghost_tracks: HashSet::new(),
             display_offset_ticks: 12, // ~25ms at 120 BPM to compensate for display latency
             help_scroll: 0,
+            lyric_buffer: String::new(),
+            lyric_line_break: false,
             history: HistoryManager::new(),
             // Insert Mode recording state
             insert_recording_active: false,
@@ -482,7 +1018,28 @@ impl App {
             recently_added_beat: None,
             recently_added_note: None,
             recently_added_pitch: None,
-        })
+            settings_dialog: SettingsDialogState::default(),
+            config,
+            keymap,
+            theme: Theme::default(),
+            command_line: CommandLine::default(),
+            pending_keys: PendingKeys::default(),
+            registers: HashMap::new(),
+            marks: HashMap::new(),
+            last_edit: None,
+        };
+        match theme {
+            Ok(theme) => app.theme = theme,
+            Err(e) => app.set_status(e),
+        }
+        let soundfont_path = app.soundfont_path.clone();
+        app.recent_files.set_soundfont(&soundfont_path);
+
+        // A template project may bring its own instruments
+        for track in app.project.tracks() {
+            app.audio.configure_track(track);
+        }
+        Ok(app)
     }
 
     // ==================== Accessor methods ====================
@@ -495,6 +1052,7 @@ impl App {
     }
 
     /// Returns a mutable reference to the project.
+    #[allow(dead_code)]
     pub fn project_mut(&mut self) -> &mut Project {
         &mut self.project
     }
@@ -523,6 +1081,8 @@ impl App {
 
     /// Transposes all selected notes by a number of semitones.
     ///
+    /// With scale snapping on, notes move by scale degrees instead.
+    ///
     /// # Arguments
     ///
     /// * `semitones` - Amount to transpose (positive = up, negative = down)
@@ -532,10 +1092,14 @@ impl App {
         }
         self.save_state("Transpose notes");
         let ids: Vec<_> = self.selected_notes.iter().copied().collect();
+        let snap_scale = self.snap_scale();
         if let Some(track) = self.project.track_at_mut(self.selected_track_index) {
             for note in track.notes_mut() {
                 if ids.contains(&note.id) {
-                    let new_pitch = (note.pitch as i16 + semitones as i16).clamp(0, 127) as u8;
+                    let new_pitch = match snap_scale {
+                        Some(scale) => scale.step(note.pitch, semitones as i32),
+                        None => (note.pitch as i16 + semitones as i16).clamp(0, 127) as u8,
+                    };
                     note.pitch = new_pitch;
                 }
             }
@@ -651,76 +1215,75 @@ impl App {
     ///
     /// true if the key was handled as a note
     pub fn handle_note_key(&mut self, key: char) -> bool {
-        let key_lower = key.to_ascii_lowercase();
-
         // Find the note for this key
-        for (k, base_note) in KEYBOARD_MAP.iter() {
-            if *k == key_lower {
-                let note = (*base_note as i16 + self.octave_offset as i16 * 12) as u8;
-                if note > 127 {
-                    return false;
+        if let Some(note) = self.key_to_pitch(key) {
+            let channel = self.selected_track().map(|t| t.channel).unwrap_or(0);
+            let already_held = self.held_notes.contains(&note);
+
+            // In Insert Mode, allow repeated presses of the same key by re-triggering
+            // the note. This works around terminals that don't send key release events.
+            if self.edit_mode == EditMode::Insert {
+                // If note is already held, send note_off first to create clean attack
+                if already_held {
+                    self.audio.note_off(channel, note);
+                } else {
+                    self.held_notes.insert(note);
                 }
 
-                let channel = self.selected_track().map(|t| t.channel).unwrap_or(0);
-                let already_held = self.held_notes.contains(&note);
-
-                // In Insert Mode, allow repeated presses of the same key by re-triggering
-                // the note. This works around terminals that don't send key release events.
-                if self.edit_mode == EditMode::Insert {
-                    // If note is already held, send note_off first to create clean attack
-                    if already_held {
-                        self.audio.note_off(channel, note);
-                    } else {
-                        self.held_notes.insert(note);
-                    }
+                // Play the note
+                self.audio
+                    .note_on(channel, note, self.config.default_velocity);
 
-                    // Play the note
-                    self.audio.note_on(channel, note, DEFAULT_VELOCITY);
+                let now = Instant::now();
 
-                    let now = Instant::now();
+                // Start recording if not already active
+                if !self.insert_recording_active {
+                    self.insert_recording_active = true;
+                    self.insert_recording_start_time = Some(now);
+                    self.insert_recording_start_tick = self.cursor_tick;
+                }
 
-                    // Start recording if not already active
-                    if !self.insert_recording_active {
-                        self.insert_recording_active = true;
-                        self.insert_recording_start_time = Some(now);
-                        self.insert_recording_start_tick = self.cursor_tick;
-                    }
+                // Calculate the current tick position based on elapsed time
+                // This allows simultaneous notes to be placed at the same position
+                let insert_tick = self.get_insert_recording_tick();
 
-                    // Calculate the current tick position based on elapsed time
-                    // This allows simultaneous notes to be placed at the same position
-                    let insert_tick = self.get_insert_recording_tick();
-
-                    self.save_state("Insert note");
-                    let note_id = self.selected_track_mut().map(|track| {
-                        track.create_note(
-                            note,
-                            DEFAULT_VELOCITY,
-                            insert_tick,
-                            DEFAULT_NOTE_DURATION,
-                        )
-                    });
+                // Chord entry records the whole chord rooted at the key's note
+                let pitches = if self.chord_entry {
+                    self.chord_voicing.pitches(note)
+                } else {
+                    vec![note]
+                };
+
+                let velocity = self.config.default_velocity;
+                let duration = self.config.default_note_duration;
+                self.save_state("Insert note");
+                for pitch in pitches {
+                    let note_id = self
+                        .selected_track_mut()
+                        .map(|track| track.create_note(pitch, velocity, insert_tick, duration));
 
                     // Register the note for blue highlighting and auto-scroll
                     if let Some(id) = note_id {
-                        self.register_added_note(id, note, insert_tick);
+                        self.register_added_note(id, pitch, insert_tick);
                     }
+                }
 
-                    // Update last note time for timeout detection
-                    self.last_insert_note_time = Some(now);
+                // Update last note time for timeout detection
+                self.last_insert_note_time = Some(now);
 
-                    // Update cursor to follow recording position
-                    self.cursor_tick = insert_tick;
+                // Update cursor to follow recording position
+                self.cursor_tick = insert_tick;
 
-                    self.mark_modified();
-                    return true;
-                }
+                self.mark_modified();
+                return true;
+            }
 
-                // Normal/Select mode: only trigger if not already held
-                if !already_held {
-                    self.held_notes.insert(note);
-                    self.audio.note_on(channel, note, DEFAULT_VELOCITY);
-                    return true;
-                }
+            // Normal/Select mode: only trigger if not already held
+            if !already_held {
+                self.held_notes.insert(note);
+                self.audio
+                    .note_on(channel, note, self.config.default_velocity);
+                return true;
             }
         }
         false
@@ -922,19 +1485,28 @@ impl App {
     ///
     /// * `key` - The character key released
     pub fn handle_note_key_release(&mut self, key: char) {
-        let key_lower = key.to_ascii_lowercase();
-
-        for (k, base_note) in KEYBOARD_MAP.iter() {
-            if *k == key_lower {
-                let note = (*base_note as i16 + self.octave_offset as i16 * 12) as u8;
-                if self.held_notes.remove(&note) {
-                    let channel = self.selected_track().map(|t| t.channel).unwrap_or(0);
-                    self.audio.note_off(channel, note);
-                }
+        if let Some(note) = self.key_to_pitch(key) {
+            if self.held_notes.remove(&note) {
+                let channel = self.selected_track().map(|t| t.channel).unwrap_or(0);
+                self.audio.note_off(channel, note);
             }
         }
     }
 
+    /// Returns the pitch played by a piano key at the current octave,
+    /// snapped to the project scale when scale snapping is on.
+    fn key_to_pitch(&self, key: char) -> Option<u8> {
+        let base_note = self.keymap.piano_note(key)?;
+        let note = base_note as i16 + self.octave_offset as i16 * 12;
+        if !(0..=127).contains(&note) {
+            return None;
+        }
+        Some(match self.snap_scale() {
+            Some(scale) => scale.snap(note as u8),
+            None => note as u8,
+        })
+    }
+
     /// Releases all held notes (native only).
     pub fn release_all_notes(&mut self) {
         // Get channel first, then drain notes
@@ -1021,7 +1593,21 @@ impl App {
             let elapsed = start_time.elapsed().as_secs_f64();
             let ticks_elapsed =
                 (elapsed * self.project.tempo as f64 / 60.0 * TICKS_PER_BEAT as f64) as u32;
-            let current_tick = self.playback_start_tick + ticks_elapsed;
+            let mut current_tick = self.playback_start_tick + ticks_elapsed;
+
+            // Wrap around the step sequencer pattern when looping it
+            if let Some((loop_start, loop_end)) = self.pattern_loop() {
+                if current_tick >= loop_end {
+                    let length = loop_end - loop_start;
+                    let wraps = (current_tick - loop_start) / length;
+                    let shift = ticks_to_seconds(wraps * length, self.project.tempo);
+                    self.playback_start_time = Some(start_time + Duration::from_secs_f64(shift));
+                    current_tick -= wraps * length;
+                    self.audio.all_notes_off(false);
+                    // Notes at the loop start are triggered again
+                    self.last_sequencer_tick = loop_start.checked_sub(1);
+                }
+            }
 
             // Update position
             self.audio.set_position_ticks(current_tick);
@@ -1039,13 +1625,22 @@ impl App {
                     continue;
                 }
 
+                // Tracks with clips or effects play their flattened notes
+                let notes: &[Note] = if track.clips().is_empty() && track.effects.is_empty() {
+                    track.notes()
+                } else {
+                    self.playback_cache
+                        .entry(track.id)
+                        .or_insert_with(|| self.project.playback_notes(track))
+                };
+
                 // Check if any note is currently active for this track
-                let has_active_note = track.notes().iter().any(|n| n.is_active_at(current_tick));
+                let has_active_note = notes.iter().any(|n| n.is_active_at(current_tick));
                 if has_active_note {
                     self.active_tracks.insert(track_idx);
                 }
 
-                for note in track.notes() {
+                for note in notes.iter() {
                     // Note on: trigger if in range (last_tick, current_tick]
                     // On first frame (None), trigger all notes with start_tick <= current_tick
                     let should_note_on = match self.last_sequencer_tick {
@@ -1084,7 +1679,7 @@ impl App {
 
             // Check if we've reached the end
             let end_tick = self.project.duration_ticks();
-            if current_tick > end_tick + TICKS_PER_BEAT * 2 {
+            if self.pattern_loop().is_none() && current_tick > end_tick + TICKS_PER_BEAT * 2 {
                 self.stop_playback();
             }
         }
@@ -1092,11 +1687,38 @@ impl App {
 
     /// Adds a new track to the project.
     pub fn add_track(&mut self) {
-        self.save_state("Add track");
         let track_num = self.project.track_count() + 1;
-        self.project.create_track(format!("Track {}", track_num));
+        self.add_named_track(format!("Track {}", track_num));
+    }
+
+    /// Adds a new track with the given name and selects it.
+    pub fn add_named_track(&mut self, name: String) {
+        self.save_state("Add track");
+        self.set_status(format!("Added {}", name));
+        self.project.create_track(name);
+        self.selected_track_index = self.project.track_count() - 1;
+        self.mark_modified();
+    }
+
+    /// Renames the selected track.
+    pub fn rename_selected_track(&mut self, name: String) {
+        if self.selected_track().is_none() {
+            return;
+        }
+        self.save_state("Rename track");
+        self.set_status(format!("Renamed to: {}", name));
+        if let Some(track) = self.selected_track_mut() {
+            track.name = name;
+        }
+        self.mark_modified();
+    }
+
+    /// Adds a new drum track (channel 10) to the project.
+    pub fn add_drum_track(&mut self) {
+        self.save_state("Add drum track");
+        self.project.create_drum_track("Drums");
         self.selected_track_index = self.project.track_count() - 1;
-        self.set_status(format!("Added Track {}", track_num));
+        self.set_status("Added drum track");
         self.mark_modified();
     }
 
@@ -1153,12 +1775,7 @@ impl App {
         if self.renaming_track {
             let new_name = self.rename_buffer.trim().to_string();
             if !new_name.is_empty() {
-                self.save_state("Rename track");
-                if let Some(track) = self.selected_track_mut() {
-                    track.name = new_name.clone();
-                }
-                self.set_status(format!("Renamed to: {}", new_name));
-                self.mark_modified();
+                self.rename_selected_track(new_name);
             } else {
                 self.set_status("Rename cancelled - name cannot be empty");
             }
@@ -1177,145 +1794,191 @@ impl App {
     }
 
     /// Marks the project as modified, triggering autosave after delay.
+    ///
+    /// The project also counts as unsaved until it is next saved to its file.
     pub fn mark_modified(&mut self) {
         self.last_modified = Some(Instant::now());
+        self.unsaved_changes = true;
+        self.playback_cache.clear();
     }
 
-    // ==================== Undo/Redo Methods ====================
-    // These methods manage the undo/redo history for user-initiated changes.
+    /// Returns true if the project changed since it was last saved or loaded.
+    pub fn has_unsaved_changes(&self) -> bool {
+        self.unsaved_changes
+    }
 
-    /// Saves the current state to the undo history.
+    // ==================== Unsaved Changes Guard ====================
+
+    /// Runs `action` now, or asks to save first if there are unsaved changes.
     ///
-    /// Call this BEFORE making any user-initiated change to the project.
-    /// This captures a snapshot of the current state that can be restored later.
+    /// # Returns
     ///
-    /// # Arguments
+    /// true if the action ran and succeeded
+    pub fn guard_unsaved(&mut self, action: GuardedAction) -> bool {
+        if !self.unsaved_changes {
+            return self.run_guarded_action(action);
+        }
+        self.unsaved_dialog = UnsavedDialogState {
+            open: true,
+            action: Some(action),
+            selected: 0,
+        };
+        false
+    }
+
+    /// Performs an action that abandons the current project.
     ///
-    /// * `description` - Brief description of the operation (e.g., "Place note")
+    /// # Returns
     ///
-    /// # Example
+    /// false if loading a file failed
+    fn run_guarded_action(&mut self, action: GuardedAction) -> bool {
+        match action {
+            GuardedAction::Quit => self.quit_requested = true,
+            GuardedAction::NewProject => {
+                self.remove_autosave_slot();
+                self.reset_to_new_project();
+            }
+            GuardedAction::Load(path) => {
+                self.remove_autosave_slot();
+                return self.load_project(path);
+            }
+            GuardedAction::Recover(path) => return self.recover_autosave(path),
+        }
+        true
+    }
+
+    /// Moves the highlighted choice in the unsaved changes prompt.
+    pub fn unsaved_dialog_move(&mut self, delta: i32) {
+        self.unsaved_dialog.selected =
+            (self.unsaved_dialog.selected as i32 + delta).clamp(0, 2) as usize;
+    }
+
+    /// Confirms a choice in the unsaved changes prompt (0 = Save, 1 = Discard, 2 = Cancel).
+    pub fn unsaved_dialog_choose(&mut self, choice: usize) {
+        self.unsaved_dialog.open = false;
+        match choice {
+            0 => {
+                if self.project_path.is_some() {
+                    let action = self.unsaved_dialog.action.take();
+                    if let (true, Some(action)) = (self.save_project(), action) {
+                        self.run_guarded_action(action);
+                    }
+                } else {
+                    // The save dialog runs the action once a file name is chosen
+                    self.open_save_dialog();
+                }
+            }
+            1 => {
+                if let Some(action) = self.unsaved_dialog.action.take() {
+                    self.run_guarded_action(action);
+                }
+            }
+            _ => self.unsaved_dialog_cancel(),
+        }
+    }
+
+    /// Dismisses the unsaved changes prompt without doing anything.
+    pub fn unsaved_dialog_cancel(&mut self) {
+        self.unsaved_dialog.open = false;
+        self.unsaved_dialog.action = None;
+        self.set_status("Cancelled");
+    }
+
+    // ==================== Undo/Redo Methods ====================
+    // These methods manage the undo/redo history for user-initiated changes.
+
+    /// Saves the current state to the undo history.
+    ///
+    /// Call this BEFORE making any user-initiated change to the project.
+    /// This captures a snapshot of the current state that can be restored later.
+    ///
+    /// # Arguments
+    ///
+    /// * `description` - Brief description of the operation (e.g., "Place note")
+    ///
+    /// # Example
     ///
     /// ```ignore
     /// self.save_state("Add track");
     /// self.project.create_track("Track 2");
     /// ```
     pub fn save_state(&mut self, description: impl Into<String>) {
-        let snapshot = StateSnapshot::new(
-            &self.project,
-            self.selected_track_index,
-            &self.selected_notes,
-            description,
-        );
-        self.history.push_undo(snapshot);
+        let selection = self.current_selection();
+        self.history.record(&self.project, selection, description);
+    }
+
+    /// Starts grouping edits so a repeated action undoes in one step.
+    pub fn begin_history_group(&mut self) {
+        let selection = self.current_selection();
+        self.history.begin_group(&self.project, &selection);
+    }
+
+    /// Ends a group started by [`App::begin_history_group`].
+    pub fn end_history_group(&mut self) {
+        self.history.end_group();
+    }
+
+    /// Returns the track and note selection for the history.
+    fn current_selection(&self) -> Selection {
+        Selection::new(self.selected_track_index, &self.selected_notes)
+    }
+
+    /// Restores a selection saved in the history after the project changed.
+    fn restore_selection(&mut self, selection: &Selection) {
+        let selection = selection.valid_in(&self.project);
+        self.selected_track_index = selection.track_index;
+        self.selected_notes = selection.notes;
     }
 
     /// Undoes the last user-initiated change.
     ///
     /// Restores the project, track selection, and note selection to
-    /// their previous state. The current state is saved for potential redo.
-    ///
-    /// If the undo state is invalid (e.g., due to external changes),
-    /// the history is cleared to prevent cascading errors.
+    /// their previous state. The change stays in the history for redo.
     ///
     /// # Returns
     ///
-    /// true if undo was successful, false if nothing to undo or state was invalid
+    /// true if undo was successful, false if nothing to undo
     pub fn undo(&mut self) -> bool {
-        if let Some(prev_state) = self.history.pop_undo() {
-            // Validate the snapshot before applying
-            if !prev_state.is_valid() {
-                // State is invalid - clear history as per requirements
-                self.history.clear();
-                self.set_status("Undo failed: history cleared due to invalid state");
-                return false;
-            }
-
-            // Extract data from prev_state before moving project out
-            let description = prev_state.description.clone();
-            let selected_track_index = prev_state.selected_track_index;
-            let valid_notes = prev_state.valid_selected_notes();
-
-            // Save current state to redo stack before restoring
-            let current_snapshot = StateSnapshot::new(
-                &self.project,
-                self.selected_track_index,
-                &self.selected_notes,
-                description.clone(),
-            );
-            self.history.push_redo(current_snapshot);
-
-            // Restore the previous state
-            self.project = prev_state.project;
-            self.selected_track_index =
-                selected_track_index.min(self.project.track_count().saturating_sub(1));
-            self.selected_notes = valid_notes;
-
-            // Re-sync audio engine with restored tracks
-            self.sync_audio_after_restore();
-
-            self.set_status(format!("Undo: {}", description));
-            self.mark_modified();
-
-            true
-        } else {
+        let selection = self.current_selection();
+        let Some(entry) = self.history.undo(&mut self.project, &selection) else {
             self.set_status("Nothing to undo");
-            false
-        }
+            return false;
+        };
+        let description = entry.description.clone();
+        let restored = entry.selection_before.clone();
+        self.restore_selection(&restored);
+
+        // Re-sync audio engine with restored tracks
+        self.sync_audio_after_restore();
+
+        self.set_status(format!("Undo: {}", description));
+        self.mark_modified();
+        true
     }
 
     /// Redoes the last undone change.
     ///
-    /// Restores the project to the state before the last undo operation.
-    /// The current state is saved for potential undo.
-    ///
-    /// If the redo state is invalid, the history is cleared.
+    /// Re-applies the change and restores the selection it left behind.
     ///
     /// # Returns
     ///
-    /// true if redo was successful, false if nothing to redo or state was invalid
+    /// true if redo was successful, false if nothing to redo
     pub fn redo(&mut self) -> bool {
-        if let Some(next_state) = self.history.pop_redo() {
-            // Validate the snapshot before applying
-            if !next_state.is_valid() {
-                // State is invalid - clear history as per requirements
-                self.history.clear();
-                self.set_status("Redo failed: history cleared due to invalid state");
-                return false;
-            }
-
-            // Extract data from next_state before moving project out
-            let description = next_state.description.clone();
-            let selected_track_index = next_state.selected_track_index;
-            let valid_notes = next_state.valid_selected_notes();
-
-            // Save current state to undo stack before restoring.
-            // IMPORTANT: Use push_undo_preserve_redo to avoid clearing remaining redo states.
-            // This allows multiple consecutive redos (e.g., undo 4x then redo 4x).
-            let current_snapshot = StateSnapshot::new(
-                &self.project,
-                self.selected_track_index,
-                &self.selected_notes,
-                description.clone(),
-            );
-            self.history.push_undo_preserve_redo(current_snapshot);
-
-            // Restore the next state
-            self.project = next_state.project;
-            self.selected_track_index =
-                selected_track_index.min(self.project.track_count().saturating_sub(1));
-            self.selected_notes = valid_notes;
-
-            // Re-sync audio engine with restored tracks
-            self.sync_audio_after_restore();
-
-            self.set_status(format!("Redo: {}", description));
-            self.mark_modified();
-
-            true
-        } else {
+        let selection = self.current_selection();
+        let Some(entry) = self.history.redo(&mut self.project, &selection) else {
             self.set_status("Nothing to redo");
-            false
-        }
+            return false;
+        };
+        let description = entry.description.clone();
+        let restored = entry.selection_after.clone();
+        self.restore_selection(&restored);
+
+        // Re-sync audio engine with restored tracks
+        self.sync_audio_after_restore();
+
+        self.set_status(format!("Redo: {}", description));
+        self.mark_modified();
+        true
     }
 
     /// Re-syncs the audio engine after restoring state.
@@ -1345,7 +2008,7 @@ impl App {
     pub fn check_autosave(&mut self) {
         if let Some(modified_time) = self.last_modified {
             let should_autosave = modified_time.elapsed()
-                >= Duration::from_secs(AUTOSAVE_DELAY_SECS)
+                >= Duration::from_secs(self.config.autosave_delay_secs)
                 && self.last_autosave.is_none_or(|t| t < modified_time);
 
             if should_autosave {
@@ -1357,16 +2020,39 @@ impl App {
     /// Forces an immediate autosave, bypassing the delay timer.
     /// Useful when critical state changes (like SoundFont selection) should be persisted immediately.
     pub fn force_autosave(&mut self) {
+        // The quantize preview lives in the project outside the history; wait
+        // until the dialog is confirmed or cancelled (check_autosave retries)
+        if self.quantize_dialog.open {
+            return;
+        }
+
+        // Finish the edit in progress so the saved history includes it
+        let selection = self.current_selection();
+        self.history.commit(&self.project, &selection);
+
         // Save SoundFont path before autosaving
         self.project.set_soundfont_path(Some(&self.soundfont_path));
 
-        if let Err(e) = self.project.save_to_binary(&self.autosave_path) {
+        let history = self.config.persist_history.then_some(&self.history);
+        if let Err(e) = self
+            .autosave
+            .save(&self.project, self.project_path.as_deref(), history)
+        {
             tracing::error!("Autosave failed: {}", e);
         } else {
             self.last_autosave = Some(Instant::now());
         }
     }
 
+    /// Deletes the current project's autosaves once it is saved or closed
+    /// cleanly, so they aren't offered for recovery on the next start.
+    pub fn remove_autosave_slot(&mut self) {
+        self.last_modified = None;
+        if let Err(e) = self.autosave.remove_slot(self.project.id()) {
+            tracing::warn!("Could not delete autosaves: {}", e);
+        }
+    }
+
     /// Returns the instrument name for a given program number.
     ///
     /// The name is derived from the currently loaded SoundFont's presets.
@@ -1426,6 +2112,15 @@ impl App {
         }
     }
 
+    /// Sets the tempo, kept within 20-300 BPM.
+    pub fn set_tempo(&mut self, bpm: u32) {
+        self.save_state("Adjust tempo");
+        self.project.tempo = bpm.clamp(20, 300);
+        self.audio.set_tempo(self.project.tempo);
+        self.set_status(format!("Tempo: {} BPM", self.project.tempo));
+        self.mark_modified();
+    }
+
     /// Adjusts the time signature numerator (beats per measure).
     ///
     /// # Arguments
@@ -1458,7 +2153,10 @@ impl App {
         self.mark_modified();
     }
 
-    /// Cycles through view modes: Combined -> PianoRoll -> ProjectTimeline -> Combined.
+    /// Cycles through view modes: Combined -> PianoRoll -> ProjectTimeline -> Staff ->
+    /// EventList -> Combined.
+    ///
+    /// When a drum track is selected, the step sequencer takes the place of the staff view.
     pub fn toggle_view_mode(&mut self) {
         self.view_mode = match self.view_mode {
             ViewMode::Combined => {
@@ -1469,7 +2167,21 @@ impl App {
                 self.set_status("Project Timeline View");
                 ViewMode::ProjectTimeline
             }
+            ViewMode::ProjectTimeline
+                if self.selected_track().is_some_and(|t| t.is_percussion()) =>
+            {
+                self.enter_step_sequencer();
+                ViewMode::StepSequencer
+            }
             ViewMode::ProjectTimeline => {
+                self.set_status("Staff View");
+                ViewMode::Staff
+            }
+            ViewMode::StepSequencer | ViewMode::Staff => {
+                self.set_status("Event List View");
+                ViewMode::EventList
+            }
+            ViewMode::EventList => {
                 self.set_status("Combined View");
                 ViewMode::Combined
             }
@@ -1486,6 +2198,16 @@ impl App {
         }
     }
 
+    /// Toggles the velocity lane under the piano roll.
+    pub fn toggle_velocity_lane(&mut self) {
+        self.show_velocity_lane = !self.show_velocity_lane;
+        if self.show_velocity_lane {
+            self.set_status("Velocity lane shown");
+        } else {
+            self.set_status("Velocity lane hidden");
+        }
+    }
+
     /// Cycles the highlight mode for active notes during playback.
     ///
     /// Cycles through: PianoRollOnly -> Both -> Off -> TimelineOnly -> repeat.
@@ -1529,6 +2251,98 @@ impl App {
         )
     }
 
+    /// Cycles which other tracks are drawn as ghost notes.
+    ///
+    /// Cycles through: Off -> Above -> All -> Chosen -> repeat.
+    pub fn cycle_ghost_mode(&mut self) {
+        self.ghost_mode = match self.ghost_mode {
+            GhostMode::Off => {
+                self.set_status("Ghost notes: track above");
+                GhostMode::Above
+            }
+            GhostMode::Above => {
+                self.set_status("Ghost notes: all tracks");
+                GhostMode::All
+            }
+            GhostMode::All => {
+                self.set_status(format!(
+                    "Ghost notes: chosen tracks ({} marked, Shift+R to mark)",
+                    self.ghost_tracks.len()
+                ));
+                GhostMode::Chosen
+            }
+            GhostMode::Chosen => {
+                self.set_status("Ghost notes: off");
+                GhostMode::Off
+            }
+        };
+    }
+
+    /// Marks or unmarks the selected track as a ghost source for `GhostMode::Chosen`.
+    pub fn toggle_ghost_track(&mut self) {
+        let Some(track) = self.selected_track() else {
+            return;
+        };
+        let (id, name) = (track.id, track.name.clone());
+        if self.ghost_tracks.remove(&id) {
+            self.set_status(format!("{} is no longer a ghost track", name));
+        } else {
+            self.ghost_tracks.insert(id);
+            self.set_status(format!("{} marked as ghost track", name));
+        }
+    }
+
+    /// Returns the indices of the tracks drawn as ghost notes, never the selected one.
+    pub fn ghost_track_indices(&self) -> Vec<usize> {
+        let selected = self.selected_track_index;
+        match self.ghost_mode {
+            GhostMode::Off => Vec::new(),
+            GhostMode::Above => selected.checked_sub(1).into_iter().collect(),
+            GhostMode::All => (0..self.project.track_count())
+                .filter(|&i| i != selected)
+                .collect(),
+            GhostMode::Chosen => self
+                .project
+                .tracks()
+                .iter()
+                .enumerate()
+                .filter(|(i, t)| *i != selected && self.ghost_tracks.contains(&t.id))
+                .map(|(i, _)| i)
+                .collect(),
+        }
+    }
+
+    /// Returns the index of the track owning a ghost note at the given position.
+    ///
+    /// When ghost notes overlap, the track nearest the top of the list wins,
+    /// matching the drawing order of the piano roll.
+    pub fn ghost_note_owner(&self, tick: u32, pitch: u8) -> Option<usize> {
+        self.ghost_track_indices().into_iter().find(|&i| {
+            self.project.track_at(i).is_some_and(|track| {
+                track
+                    .notes()
+                    .iter()
+                    .any(|n| n.pitch == pitch && n.is_active_at(tick))
+            })
+        })
+    }
+
+    /// Switches the edited track to the owner of the ghost note under the cursor.
+    pub fn select_ghost_owner(&mut self) {
+        match self.ghost_note_owner(self.cursor_tick, self.cursor_pitch) {
+            Some(index) => {
+                self.selected_track_index = index;
+                if let Some(track) = self.selected_track() {
+                    self.set_status(format!("Editing {}", track.name));
+                }
+            }
+            None if self.ghost_mode == GhostMode::Off => {
+                self.set_status("Ghost notes are off (Shift+O to show)");
+            }
+            None => self.set_status("No ghost note under cursor"),
+        }
+    }
+
     /// Returns the display position in ticks, adjusted for visual latency compensation.
     /// This is the playhead position advanced by `display_offset_ticks` to appear
     /// synchronized with the audio output.
@@ -1593,6 +2407,9 @@ impl App {
     }
 
     /// Confirms and executes the save.
+    ///
+    /// If the save was asked for by the unsaved changes prompt, the action
+    /// that was waiting on it runs afterwards.
     pub fn save_dialog_confirm(&mut self) -> bool {
         if !self.save_dialog.open || self.save_dialog.filename.is_empty() {
             return false;
@@ -1605,22 +2422,56 @@ impl App {
         };
         let path = PathBuf::from(format!("{}.{}", self.save_dialog.filename, extension));
 
+        self.save_dialog.open = false;
+        let saved = self.write_project(path, self.save_dialog.format);
+        let pending = self.unsaved_dialog.action.take();
+        if let (true, Some(action)) = (saved, pending) {
+            self.run_guarded_action(action);
+        }
+        saved
+    }
+
+    /// Saves the project to its current file, or opens the save dialog if it has none.
+    ///
+    /// # Returns
+    ///
+    /// true if the project was written
+    pub fn save_project(&mut self) -> bool {
+        let Some(path) = self.project_path.clone() else {
+            self.open_save_dialog();
+            return false;
+        };
+        let format = SaveFormat::for_path(&path);
+        self.write_project(path, format)
+    }
+
+    /// Writes the project to `path` and makes it the current file.
+    ///
+    /// MIDI files lose clips, effects and other project data, so writing one
+    /// is an export: the current file and unsaved changes stay as they were.
+    fn write_project(&mut self, path: PathBuf, format: SaveFormat) -> bool {
         // Save the current SoundFont path to the project before saving (not applicable for MIDI)
-        if self.save_dialog.format != SaveFormat::Midi {
+        if format != SaveFormat::Midi {
             self.project.set_soundfont_path(Some(&self.soundfont_path));
         }
 
-        let result = match self.save_dialog.format {
+        let result = match format {
             SaveFormat::Json => self.project.save_to_file(&path),
             SaveFormat::Oxm => self.project.save_to_binary(&path),
             SaveFormat::Midi => crate::midi::export_to_midi(&self.project, &path),
         };
 
-        self.save_dialog.open = false;
-
         match result {
+            Ok(()) if format == SaveFormat::Midi => {
+                self.recent_files.add(&path);
+                self.set_status(format!("Exported: {}", path.display()));
+                true
+            }
             Ok(()) => {
+                self.recent_files.add(&path);
                 self.project_path = Some(path.clone());
+                self.unsaved_changes = false;
+                self.remove_autosave_slot();
                 self.set_status(format!("Saved: {}", path.display()));
                 true
             }
@@ -1634,11 +2485,15 @@ impl App {
     /// Cancels the save dialog.
     pub fn save_dialog_cancel(&mut self) {
         self.save_dialog.open = false;
+        self.unsaved_dialog.action = None;
         self.set_status("Save cancelled");
     }
 
     /// Loads a project from a file (JSON or OXM based on extension).
     ///
+    /// MIDI files are imported: the project gets no current file, so saving
+    /// asks for a new name instead of overwriting the MIDI file.
+    ///
     /// # Arguments
     ///
     /// * `path` - Path to the project file
@@ -1649,8 +2504,8 @@ impl App {
     pub fn load_project(&mut self, path: PathBuf) -> bool {
         let result = match path.extension().and_then(|e| e.to_str()) {
             Some("oxm") => Project::load_from_binary(&path),
-            Some("mid") | Some("midi") => {
-                // Import MIDI file using the midi_import module
+            Some("mid") | Some("midi") | Some("kar") => {
+                // Import MIDI (or .kar karaoke) file using the midi_import module
                 crate::midi::import_from_midi(&path).map_err(std::io::Error::other)
             }
             _ => Project::load_from_file(&path),
@@ -1658,46 +2513,14 @@ impl App {
 
         match result {
             Ok(project) => {
-                // Stop any current playback and reset position
-                self.audio.stop();
-                self.playback_start_time = None;
-                self.last_sequencer_tick = None;
-                self.playback_start_tick = 0;
-                self.active_tracks.clear();
-
-                // Check if project has a SoundFont path and try to load it
-                let should_load_soundfont = project.get_soundfont_path().is_some_and(|sf_path| {
-                    let sf_pathbuf = PathBuf::from(sf_path);
-                    sf_pathbuf.exists() && sf_pathbuf != self.soundfont_path
-                });
-
-                self.project = project;
-                self.project_path = Some(path.clone());
-                self.selected_track_index = 0;
-                self.selected_notes.clear();
-                self.cursor_tick = 0;
-                self.scroll_x = 0;
-
-                // Clear undo/redo history when loading a new project
-                self.clear_history();
-
-                // Load the project's SoundFont if different from current
-                if should_load_soundfont {
-                    if let Some(sf_path) = self.project.get_soundfont_path() {
-                        let sf_pathbuf = PathBuf::from(sf_path);
-                        if self.load_soundfont(sf_pathbuf) {
-                            self.set_status(format!("Loaded: {} (with soundfont)", path.display()));
-                            return true;
-                        }
-                    }
-                }
-
-                // Configure audio engine for all tracks
-                for track in self.project.tracks() {
-                    self.audio.configure_track(track);
+                self.recent_files.add(&path);
+                let project_path =
+                    (SaveFormat::for_path(&path) != SaveFormat::Midi).then(|| path.clone());
+                if self.install_project(project, project_path) {
+                    self.set_status(format!("Loaded: {} (with soundfont)", path.display()));
+                } else {
+                    self.set_status(format!("Loaded: {}", path.display()));
                 }
-
-                self.set_status(format!("Loaded: {}", path.display()));
                 true
             }
             Err(e) => {
@@ -1707,6 +2530,57 @@ impl App {
         }
     }
 
+    /// Replaces the current project with a loaded one.
+    ///
+    /// Stops playback, resets the view and history, and loads the project's
+    /// SoundFont if it names a different one.
+    ///
+    /// # Returns
+    ///
+    /// true if the project's SoundFont was loaded
+    fn install_project(&mut self, project: Project, path: Option<PathBuf>) -> bool {
+        // Stop any current playback and reset position
+        self.audio.stop();
+        self.playback_start_time = None;
+        self.last_sequencer_tick = None;
+        self.playback_start_tick = 0;
+        self.active_tracks.clear();
+
+        // Check if project has a SoundFont path and try to load it
+        let should_load_soundfont = project.get_soundfont_path().is_some_and(|sf_path| {
+            let sf_pathbuf = PathBuf::from(sf_path);
+            sf_pathbuf.exists() && sf_pathbuf != self.soundfont_path
+        });
+
+        self.project = project;
+        self.project_path = path;
+        self.unsaved_changes = false;
+        self.playback_cache.clear();
+        self.selected_track_index = 0;
+        self.selected_notes.clear();
+        self.cursor_tick = 0;
+        self.scroll_x = 0;
+
+        // Clear undo/redo history when loading a new project
+        self.clear_history();
+
+        // Load the project's SoundFont if different from current
+        if should_load_soundfont {
+            if let Some(sf_path) = self.project.get_soundfont_path() {
+                let sf_pathbuf = PathBuf::from(sf_path);
+                if self.load_soundfont(sf_pathbuf) {
+                    return true;
+                }
+            }
+        }
+
+        // Configure audio engine for all tracks
+        for track in self.project.tracks() {
+            self.audio.configure_track(track);
+        }
+        false
+    }
+
     /// Opens the file browser for loading a project (native only).
     pub fn open_file_browser(&mut self) {
         self.file_browser.open = true;
@@ -1735,8 +2609,13 @@ impl App {
                 if path.is_dir() {
                     dirs.push(path);
                 } else if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
-                    // Support native formats (.json, .oxm) and MIDI files (.mid, .midi)
-                    if ext == "json" || ext == "oxm" || ext == "mid" || ext == "midi" {
+                    // Support native formats (.json, .oxm) and MIDI files (.mid, .midi, .kar)
+                    if ext == "json"
+                        || ext == "oxm"
+                        || ext == "mid"
+                        || ext == "midi"
+                        || ext == "kar"
+                    {
                         files.push(path);
                     }
                 }
@@ -1807,7 +2686,7 @@ impl App {
             // Load the file
             let path = selected_path.clone();
             self.file_browser.open = false;
-            self.load_project(path)
+            self.guard_unsaved(GuardedAction::Load(path))
         }
     }
 
@@ -1817,6 +2696,60 @@ impl App {
         self.set_status("Load cancelled");
     }
 
+    // ========== RECENT FILES DIALOG METHODS ==========
+
+    /// Opens the recent files dialog.
+    pub fn open_recent_dialog(&mut self) {
+        if self.recent_files.files.is_empty() {
+            self.set_status("No recent files");
+            return;
+        }
+        self.recent_dialog.open = true;
+        self.recent_dialog.selected = 0;
+    }
+
+    /// Moves the highlight in the recent files dialog.
+    pub fn recent_dialog_move(&mut self, delta: i32) {
+        let last = self.recent_files.files.len().saturating_sub(1) as i32;
+        self.recent_dialog.selected =
+            (self.recent_dialog.selected as i32 + delta).clamp(0, last.max(0)) as usize;
+    }
+
+    /// Opens the highlighted recent file.
+    ///
+    /// # Returns
+    ///
+    /// true if the file was loaded
+    pub fn recent_dialog_select(&mut self) -> bool {
+        let Some(file) = self.recent_files.files.get(self.recent_dialog.selected) else {
+            return false;
+        };
+        if !file.exists() {
+            let name = file.path.display().to_string();
+            self.set_status(format!("File no longer exists: {} (x to forget)", name));
+            return false;
+        }
+        let path = file.path.clone();
+        self.recent_dialog.open = false;
+        self.guard_unsaved(GuardedAction::Load(path))
+    }
+
+    /// Removes the highlighted file from the recent files list.
+    pub fn recent_dialog_remove(&mut self) {
+        self.recent_files.remove(self.recent_dialog.selected);
+        if self.recent_files.files.is_empty() {
+            self.recent_dialog.open = false;
+            self.set_status("Recent files cleared");
+        } else {
+            self.recent_dialog_move(0);
+        }
+    }
+
+    /// Closes the recent files dialog.
+    pub fn recent_dialog_cancel(&mut self) {
+        self.recent_dialog.open = false;
+    }
+
     // ========== SOUNDFONT DIALOG METHODS ==========
 
     /// Opens the SoundFont browser dialog.
@@ -1989,8 +2922,8 @@ impl App {
                         .unwrap_or("unknown")
                 ));
 
-                // Force immediate autosave so SoundFont selection persists across restarts
-                self.force_autosave();
+                // Remember the SoundFont for the next start
+                self.recent_files.set_soundfont(&path);
 
                 true
             }
@@ -2004,59 +2937,288 @@ impl App {
 
     // ========== AUTOSAVE RECOVERY METHODS ==========
 
-    /// Attempts to load the autosave file on startup (native only).
-    /// If the autosave file exists and loads successfully, shows a status message.
-    /// If it fails or doesn't exist, silently continues with a new project.
-    pub fn try_load_autosave(&mut self) {
-        if self.autosave_path.exists() {
-            match Project::load_from_binary(&self.autosave_path) {
-                Ok(project) => {
-                    self.project = project;
-                    self.selected_track_index = 0;
-                    self.selected_notes.clear();
-                    self.cursor_tick = 0;
-                    self.scroll_x = 0;
+    /// Opens the recovery dialog listing every autosave version.
+    ///
+    /// # Returns
+    ///
+    /// true if there was anything to recover
+    pub fn open_recovery_dialog(&mut self) -> bool {
+        self.recovery_dialog.entries = self.autosave.list();
+        self.recovery_dialog.selected = 0;
+        self.recovery_dialog.open = !self.recovery_dialog.entries.is_empty();
+        self.recovery_dialog.open
+    }
 
-                    // Configure audio engine for all tracks
-                    for track in self.project.tracks() {
-                        self.audio.configure_track(track);
-                    }
+    /// Moves the highlight in the recovery dialog.
+    pub fn recovery_dialog_move(&mut self, delta: i32) {
+        let last = self.recovery_dialog.entries.len().saturating_sub(1) as i32;
+        self.recovery_dialog.selected =
+            (self.recovery_dialog.selected as i32 + delta).clamp(0, last.max(0)) as usize;
+    }
 
-                    self.set_status("Recovered from autosave");
-                    tracing::info!("Loaded autosave from {:?}", self.autosave_path);
-                }
-                Err(e) => {
-                    tracing::warn!("Failed to load autosave: {}", e);
-                    // Continue with default new project
+    /// Recovers the highlighted autosave version, asking to save unsaved changes first.
+    pub fn recovery_dialog_select(&mut self) {
+        let Some(entry) = self
+            .recovery_dialog
+            .entries
+            .get(self.recovery_dialog.selected)
+        else {
+            return;
+        };
+        let path = entry.path.clone();
+        self.recovery_dialog.open = false;
+        self.guard_unsaved(GuardedAction::Recover(path));
+    }
+
+    /// Deletes the highlighted autosave version.
+    pub fn recovery_dialog_remove(&mut self) {
+        let index = self.recovery_dialog.selected;
+        let Some(entry) = self.recovery_dialog.entries.get(index) else {
+            return;
+        };
+        if let Err(e) = self.autosave.remove(entry) {
+            self.set_status(format!("Delete failed: {}", e));
+            return;
+        }
+        self.recovery_dialog.entries.remove(index);
+        if self.recovery_dialog.entries.is_empty() {
+            self.recovery_dialog.open = false;
+            self.set_status("All autosaves deleted");
+        } else {
+            self.recovery_dialog_move(0);
+        }
+    }
+
+    /// Closes the recovery dialog, keeping the current project.
+    pub fn recovery_dialog_cancel(&mut self) {
+        self.recovery_dialog.open = false;
+    }
+
+    /// Loads an autosave version in place of the current project.
+    ///
+    /// The recovered project keeps the file it came from, but counts as
+    /// unsaved until it is saved there again.
+    fn recover_autosave(&mut self, path: PathBuf) -> bool {
+        let source = self
+            .recovery_dialog
+            .entries
+            .iter()
+            .find(|e| e.path == path)
+            .and_then(|e| e.source.clone())
+            .filter(|source| source.exists());
+        match Project::load_from_binary(&path) {
+            Ok(project) => {
+                let name = project.name.clone();
+                self.install_project(project, source);
+                let mut status = format!("Recovered {} from autosave", name);
+                match crate::autosave::load_history(&path) {
+                    Ok(Some(history)) => self.history = history,
+                    Ok(None) => {}
+                    Err(e) => {
+                        tracing::warn!("Discarded undo history of {:?}: {}", path, e);
+                        status.push_str(" (its undo history could not be read and was discarded)");
+                    }
                 }
+                self.unsaved_changes = true;
+                self.set_status(status);
+                tracing::info!("Recovered autosave from {:?}", path);
+                true
+            }
+            Err(e) => {
+                self.set_status(format!("Recovery failed: {}", e));
+                false
             }
         }
     }
 
-    // ========== NEW PROJECT DIALOG METHODS ==========
+    // ========== UNDO HISTORY PANEL METHODS ==========
 
-    /// Opens the new project confirmation dialog.
-    pub fn open_new_project_dialog(&mut self) {
-        self.new_project_dialog.open = true;
-        self.new_project_dialog.selected = 1; // Default to "No" for safety
+    /// Opens the undo history panel with the current point highlighted.
+    pub fn open_history_dialog(&mut self) {
+        let selection = self.current_selection();
+        self.history.commit(&self.project, &selection);
+        self.history_dialog.selected = self.history.position();
+        self.history_dialog.open = true;
     }
 
-    /// Moves selection left in the new project dialog (selects Yes).
-    pub fn new_project_dialog_left(&mut self) {
-        if self.new_project_dialog.open {
-            self.new_project_dialog.selected = 0;
-        }
+    /// Moves the highlight in the history panel.
+    pub fn history_dialog_move(&mut self, delta: i32) {
+        let last = self.history.entries().len() as i32;
+        self.history_dialog.selected =
+            (self.history_dialog.selected as i32 + delta).clamp(0, last) as usize;
     }
 
-    /// Moves selection right in the new project dialog (selects No).
-    pub fn new_project_dialog_right(&mut self) {
-        if self.new_project_dialog.open {
-            self.new_project_dialog.selected = 1;
+    /// Undoes or redoes edits until the highlighted point is reached.
+    pub fn history_dialog_jump(&mut self) {
+        let target = self.history_dialog.selected;
+        let selection = self.current_selection();
+        let Some(restored) = self.history.jump_to(&mut self.project, &selection, target) else {
+            return;
+        };
+        self.restore_selection(&restored);
+        self.sync_audio_after_restore();
+        self.mark_modified();
+        match target.checked_sub(1) {
+            Some(last) => {
+                let description = &self.history.entries()[last].description;
+                self.set_status(format!("History: after {}", description));
+            }
+            None => self.set_status("History: before first edit"),
         }
     }
 
-    /// Confirms the new project dialog selection.
-    /// If "Yes" is selected, resets to a new project.
+    /// Toggles writing the undo history with each autosave.
+    pub fn toggle_persist_history(&mut self) {
+        self.config.persist_history = !self.config.persist_history;
+        self.set_status(if self.config.persist_history {
+            "History is saved with autosaves"
+        } else {
+            "History is no longer saved with autosaves"
+        });
+    }
+
+    /// Closes the undo history panel.
+    pub fn history_dialog_cancel(&mut self) {
+        self.history_dialog.open = false;
+    }
+
+    // ========== SETTINGS DIALOG METHODS ==========
+
+    /// Opens the settings dialog with a copy of the current settings.
+    pub fn open_settings_dialog(&mut self) {
+        self.settings_dialog = SettingsDialogState {
+            open: true,
+            selected: 0,
+            draft: self.config.clone(),
+            themes: crate::theme::available_themes(),
+        };
+    }
+
+    /// Moves the highlighted row in the settings dialog.
+    pub fn settings_dialog_move(&mut self, delta: i32) {
+        self.settings_dialog.selected = (self.settings_dialog.selected as i32 + delta)
+            .clamp(0, SETTINGS_DIALOG_ROWS as i32 - 1)
+            as usize;
+    }
+
+    /// Changes the highlighted setting one step up or down.
+    pub fn settings_dialog_adjust(&mut self, direction: i32) {
+        // Steps through a list of choices, starting from the nearest one
+        fn choose(choices: &[u32], current: u32, direction: i32) -> u32 {
+            let pos = choices
+                .iter()
+                .position(|&c| c >= current)
+                .unwrap_or(choices.len() - 1) as i32;
+            choices[(pos + direction).clamp(0, choices.len() as i32 - 1) as usize]
+        }
+
+        let draft = &mut self.settings_dialog.draft;
+        match self.settings_dialog.selected {
+            0 => {
+                draft.default_velocity =
+                    (draft.default_velocity as i32 + direction * 5).clamp(1, 127) as u8;
+            }
+            1 => {
+                draft.default_note_duration =
+                    choose(&NOTE_LENGTH_CHOICES, draft.default_note_duration, direction);
+            }
+            2 => {
+                draft.default_zoom = if direction < 0 {
+                    draft.default_zoom / 2
+                } else {
+                    draft.default_zoom * 2
+                };
+            }
+            3 => {
+                draft.highlight_mode = if direction < 0 {
+                    draft.highlight_mode.prev()
+                } else {
+                    draft.highlight_mode.next()
+                };
+            }
+            4 => {
+                let themes = &self.settings_dialog.themes;
+                let pos = themes.iter().position(|t| *t == draft.theme).unwrap_or(0) as i32;
+                let len = themes.len() as i32;
+                if len > 0 {
+                    draft.theme = themes[(pos + direction).rem_euclid(len) as usize].clone();
+                }
+            }
+            5 => {
+                draft.autosave_delay_secs = draft
+                    .autosave_delay_secs
+                    .saturating_add_signed(direction as i64);
+            }
+            6 => {
+                draft.poll_interval_ms = draft
+                    .poll_interval_ms
+                    .saturating_add_signed(direction as i64);
+            }
+            7 => draft.persist_history = !draft.persist_history,
+            8 => {
+                draft.export.wav.sample_rate = choose(
+                    &SAMPLE_RATE_CHOICES,
+                    draft.export.wav.sample_rate,
+                    direction,
+                );
+            }
+            _ => draft.export.wav.tail_seconds += direction as f64 * 0.5,
+        }
+        draft.clamp();
+    }
+
+    /// Applies the edited settings and writes them to the config file.
+    pub fn settings_dialog_confirm(&mut self) {
+        self.settings_dialog.open = false;
+        self.config = self.settings_dialog.draft.clone();
+        match self.config.save() {
+            Ok(()) => {
+                let path = self.config.path().map(|p| p.display().to_string());
+                self.set_status(format!("Settings saved to {}", path.unwrap_or_default()));
+            }
+            Err(e) => self.set_status(format!("Settings applied but not saved: {}", e)),
+        }
+        match self.config.load_theme() {
+            Ok(theme) => self.theme = theme,
+            Err(e) => self.set_status(e),
+        }
+    }
+
+    /// Closes the settings dialog without changing anything.
+    pub fn settings_dialog_cancel(&mut self) {
+        self.settings_dialog.open = false;
+    }
+
+    // ========== NEW PROJECT DIALOG METHODS ==========
+
+    /// Opens the new project confirmation dialog.
+    ///
+    /// With unsaved changes the Save / Discard / Cancel prompt is shown instead.
+    pub fn open_new_project_dialog(&mut self) {
+        if self.unsaved_changes {
+            self.guard_unsaved(GuardedAction::NewProject);
+            return;
+        }
+        self.new_project_dialog.open = true;
+        self.new_project_dialog.selected = 1; // Default to "No" for safety
+    }
+
+    /// Moves selection left in the new project dialog (selects Yes).
+    pub fn new_project_dialog_left(&mut self) {
+        if self.new_project_dialog.open {
+            self.new_project_dialog.selected = 0;
+        }
+    }
+
+    /// Moves selection right in the new project dialog (selects No).
+    pub fn new_project_dialog_right(&mut self) {
+        if self.new_project_dialog.open {
+            self.new_project_dialog.selected = 1;
+        }
+    }
+
+    /// Confirms the new project dialog selection.
+    /// If "Yes" is selected, resets to a new project.
     /// Returns true if a new project was created.
     pub fn new_project_dialog_confirm(&mut self) -> bool {
         if !self.new_project_dialog.open {
@@ -2090,8 +3252,9 @@ impl App {
         self.audio.stop();
         self.playback_start_time = None;
 
-        // Create fresh project
-        self.project = Project::with_default_track("New Project");
+        // Create fresh project (from the template, if one is configured)
+        self.project = self.config.new_project();
+        self.playback_cache.clear();
 
         // Reset position and view state (keep edit_mode and octave_offset unchanged)
         self.project_path = None;
@@ -2103,6 +3266,7 @@ impl App {
         self.scroll_y = 48;
         // Note: edit_mode and octave_offset are intentionally preserved
         self.last_modified = None;
+        self.unsaved_changes = false;
         self.last_autosave = None;
         self.active_tracks.clear();
         self.held_notes.clear();
@@ -2116,166 +3280,2637 @@ impl App {
         self.recently_added_note = None;
         self.recently_added_pitch = None;
 
-        // Clear undo/redo history when creating a new project
-        self.clear_history();
+        // Clear undo/redo history when creating a new project
+        self.clear_history();
+
+        // Configure audio for the new project's tracks
+        for track in self.project.tracks() {
+            self.audio.configure_track(track);
+        }
+
+        self.set_status("New project created");
+    }
+
+    /// Seeks playback to a specific tick position.
+    ///
+    /// Updates the cursor position and, if playing, adjusts the playback
+    /// position to the new tick. This is called when clicking on time rulers.
+    ///
+    /// # Arguments
+    ///
+    /// * `tick` - The tick position to seek to
+    pub fn seek_to_tick(&mut self, tick: u32) {
+        // Update cursor position
+        self.cursor_tick = tick;
+
+        // If playing, update the playback position
+        if self.audio.is_playing() {
+            // Stop all currently playing notes to avoid hanging notes
+            self.audio.all_notes_off(true);
+
+            // Reset playback timing to the new position
+            self.playback_start_time = Some(Instant::now());
+            self.playback_start_tick = tick;
+            self.last_sequencer_tick = Some(tick);
+            self.audio.set_position_ticks(tick);
+        } else {
+            // Even when stopped, update the audio position so playback
+            // will start from this position
+            self.audio.set_position_ticks(tick);
+        }
+
+        // Adjust scroll to keep cursor in view
+        let visible_cols = if self.layout.piano_roll_grid.width > 0 {
+            self.layout.piano_roll_grid.width as u32
+        } else {
+            60
+        };
+        let visible_ticks = self.zoom * visible_cols;
+
+        // Center the cursor if it's out of view
+        if tick < self.scroll_x || tick >= self.scroll_x + visible_ticks {
+            self.scroll_x = tick.saturating_sub(visible_ticks / 2);
+        }
+
+        // Show position in status
+        let (measure, beat, sub_tick) = self.project.tick_to_position(tick);
+        self.set_status(format!("Seek to {}:{:02}:{:03}", measure, beat, sub_tick));
+    }
+
+    // ==================== Markers ====================
+
+    /// Returns the tick that marker navigation starts from (playhead or cursor).
+    fn marker_reference_tick(&self) -> u32 {
+        if self.audio.is_playing() {
+            self.display_position_ticks()
+        } else {
+            self.cursor_tick
+        }
+    }
+
+    /// Adds a marker at the cursor and opens the marker list to name it.
+    pub fn add_marker_at_cursor(&mut self) {
+        let tick = self.cursor_tick;
+        let count = self.project.markers().len();
+        let name = format!("Section {}", count + 1);
+
+        self.save_state("Add marker");
+        let index = self
+            .project
+            .add_marker(Marker::new(tick, name.clone(), count as u8));
+        self.mark_modified();
+
+        self.marker_dialog.open = true;
+        self.marker_dialog.selected = index;
+        self.marker_dialog.renaming = true;
+        self.marker_dialog.rename_buffer = name;
+    }
+
+    /// Seeks to the next marker after the playhead/cursor.
+    pub fn jump_to_next_marker(&mut self) {
+        let from = self.marker_reference_tick();
+        match self.project.next_marker_after(from) {
+            Some(index) => self.jump_to_marker(index),
+            None => self.set_status("No marker after this point"),
+        }
+    }
+
+    /// Seeks to the previous marker before the playhead/cursor.
+    pub fn jump_to_prev_marker(&mut self) {
+        let from = self.marker_reference_tick();
+        match self.project.prev_marker_before(from) {
+            Some(index) => self.jump_to_marker(index),
+            None => self.set_status("No marker before this point"),
+        }
+    }
+
+    /// Seeks to a marker by index.
+    fn jump_to_marker(&mut self, index: usize) {
+        if let Some(marker) = self.project.markers().get(index).cloned() {
+            self.seek_to_tick(marker.tick);
+            self.set_status(format!("Marker: {}", marker.name));
+        }
+    }
+
+    /// Opens the marker list dialog, highlighting the section at the cursor.
+    pub fn open_marker_dialog(&mut self) {
+        let tick = self.cursor_tick;
+        let markers = self.project.markers();
+        self.marker_dialog.selected = markers
+            .partition_point(|m| m.tick <= tick)
+            .saturating_sub(1);
+        self.marker_dialog.renaming = false;
+        self.marker_dialog.rename_buffer.clear();
+        self.marker_dialog.open = true;
+    }
+
+    /// Closes the marker list dialog.
+    pub fn marker_dialog_close(&mut self) {
+        self.marker_dialog.open = false;
+        self.marker_dialog.renaming = false;
+    }
+
+    /// Moves the marker list selection up.
+    pub fn marker_dialog_up(&mut self) {
+        self.marker_dialog.selected = self.marker_dialog.selected.saturating_sub(1);
+    }
+
+    /// Moves the marker list selection down.
+    pub fn marker_dialog_down(&mut self) {
+        let count = self.project.markers().len();
+        if self.marker_dialog.selected + 1 < count {
+            self.marker_dialog.selected += 1;
+        }
+    }
+
+    /// Seeks to the highlighted marker and closes the dialog.
+    pub fn marker_dialog_jump(&mut self) {
+        let index = self.marker_dialog.selected;
+        self.marker_dialog_close();
+        self.jump_to_marker(index);
+    }
+
+    /// Starts renaming the highlighted marker.
+    pub fn marker_dialog_start_rename(&mut self) {
+        if let Some(marker) = self.project.markers().get(self.marker_dialog.selected) {
+            self.marker_dialog.rename_buffer = marker.name.clone();
+            self.marker_dialog.renaming = true;
+        }
+    }
+
+    /// Adds a character to the marker rename buffer.
+    pub fn marker_rename_input(&mut self, c: char) {
+        if self.marker_dialog.rename_buffer.len() < 32 {
+            self.marker_dialog.rename_buffer.push(c);
+        }
+    }
+
+    /// Removes the last character from the marker rename buffer.
+    pub fn marker_rename_backspace(&mut self) {
+        self.marker_dialog.rename_buffer.pop();
+    }
+
+    /// Confirms the marker rename.
+    pub fn marker_rename_confirm(&mut self) {
+        self.marker_dialog.renaming = false;
+        let name = self.marker_dialog.rename_buffer.trim().to_string();
+        let index = self.marker_dialog.selected;
+        let unchanged = self
+            .project
+            .markers()
+            .get(index)
+            .is_none_or(|m| m.name == name);
+        if name.is_empty() || unchanged {
+            return;
+        }
+        self.save_state("Rename marker");
+        self.project.rename_marker(index, name);
+        self.mark_modified();
+    }
+
+    /// Cancels the marker rename.
+    pub fn marker_rename_cancel(&mut self) {
+        self.marker_dialog.renaming = false;
+        self.marker_dialog.rename_buffer.clear();
+    }
+
+    /// Cycles the color of the highlighted marker.
+    pub fn marker_dialog_cycle_color(&mut self) {
+        let index = self.marker_dialog.selected;
+        if index < self.project.markers().len() {
+            self.save_state("Change marker color");
+            self.project.cycle_marker_color(index);
+            self.mark_modified();
+        }
+    }
+
+    /// Removes the highlighted marker (the notes of its section are kept).
+    pub fn marker_dialog_remove_marker(&mut self) {
+        let index = self.marker_dialog.selected;
+        if index >= self.project.markers().len() {
+            return;
+        }
+        self.save_state("Remove marker");
+        if let Some(marker) = self.project.remove_marker(index) {
+            self.set_status(format!("Removed marker '{}'", marker.name));
+        }
+        self.marker_dialog.selected = index.min(self.project.markers().len().saturating_sub(1));
+        self.mark_modified();
+    }
+
+    /// Duplicates the highlighted section across all tracks.
+    pub fn marker_dialog_duplicate_section(&mut self) {
+        let index = self.marker_dialog.selected;
+        let Some(name) = self.project.markers().get(index).map(|m| m.name.clone()) else {
+            return;
+        };
+        self.save_state("Duplicate section");
+        self.project.duplicate_section(index);
+        self.marker_dialog.selected = index + 1;
+        self.set_status(format!("Duplicated section '{}'", name));
+        self.mark_modified();
+    }
+
+    /// Deletes the highlighted section (notes, lyrics and marker) across all tracks.
+    pub fn marker_dialog_delete_section(&mut self) {
+        let index = self.marker_dialog.selected;
+        let Some(name) = self.project.markers().get(index).map(|m| m.name.clone()) else {
+            return;
+        };
+        self.save_state("Delete section");
+        self.project.delete_section(index);
+        self.selected_notes.clear();
+        self.marker_dialog.selected = index.min(self.project.markers().len().saturating_sub(1));
+        self.set_status(format!("Deleted section '{}'", name));
+        self.mark_modified();
+    }
+
+    /// Moves the highlighted section earlier or later by swapping it with its neighbor.
+    ///
+    /// # Arguments
+    ///
+    /// * `direction` - Negative to move earlier, positive to move later
+    pub fn marker_dialog_move_section(&mut self, direction: i32) {
+        let index = self.marker_dialog.selected;
+        let count = self.project.markers().len();
+        let (first, new_selected) = if direction < 0 {
+            if index == 0 {
+                return;
+            }
+            (index - 1, index - 1)
+        } else {
+            if index + 1 >= count {
+                return;
+            }
+            (index, index + 1)
+        };
+
+        self.save_state("Move section");
+        if self.project.swap_sections(first) {
+            self.marker_dialog.selected = new_selected;
+            self.set_status("Moved section");
+            self.mark_modified();
+        }
+    }
+
+    /// Moves the cursor by a number of ticks.
+    pub fn move_cursor_horizontal(&mut self, ticks: i32) {
+        if ticks < 0 {
+            self.cursor_tick = self.cursor_tick.saturating_sub((-ticks) as u32);
+        } else {
+            self.cursor_tick = self.cursor_tick.saturating_add(ticks as u32);
+        }
+
+        // Scroll if cursor is out of view
+        let visible_ticks = self.zoom * 80;
+        if self.cursor_tick < self.scroll_x {
+            self.scroll_x = self.cursor_tick;
+        } else if self.cursor_tick > self.scroll_x + visible_ticks {
+            self.scroll_x = self.cursor_tick.saturating_sub(visible_ticks);
+        }
+    }
+
+    /// Moves the cursor pitch up or down.
+    ///
+    /// With scale snapping on, the cursor moves by scale degrees instead of semitones.
+    pub fn move_cursor_vertical(&mut self, semitones: i8) {
+        let new_pitch = match self.snap_scale() {
+            Some(scale) => scale.step(self.cursor_pitch, semitones as i32) as i16,
+            None => self.cursor_pitch as i16 + semitones as i16,
+        };
+        if (0..=127).contains(&new_pitch) {
+            self.cursor_pitch = new_pitch as u8;
+
+            // Scroll if cursor is out of view (use dynamic visible_pitches)
+            let visible = self.layout.visible_pitches.max(1);
+            if self.cursor_pitch < self.scroll_y {
+                self.scroll_y = self.cursor_pitch;
+            } else if self.cursor_pitch > self.scroll_y + visible {
+                self.scroll_y = self.cursor_pitch.saturating_sub(visible);
+            }
+        }
+    }
+
+    /// Places a note at the current cursor position.
+    pub fn place_note(&mut self) {
+        if let Some(scale) = self.snap_scale() {
+            self.cursor_pitch = scale.snap(self.cursor_pitch);
+        }
+        if self.chord_entry {
+            self.place_chord();
+            return;
+        }
+        // Copy values to avoid borrow checker issues
+        let cursor_pitch = self.cursor_pitch;
+        let cursor_tick = self.cursor_tick;
+
+        // Get channel before mutable borrow
+        let channel = self.selected_track().map(|t| t.channel).unwrap_or(0);
+
+        let velocity = self.config.default_velocity;
+        let duration = self.config.default_note_duration;
+
+        self.save_state("Place note");
+        let note_id = self
+            .selected_track_mut()
+            .map(|track| track.create_note(cursor_pitch, velocity, cursor_tick, duration));
+
+        // Register the note for blue highlighting and auto-scroll
+        if let Some(id) = note_id {
+            self.register_added_note(id, cursor_pitch, cursor_tick);
+        }
+
+        // Play the note audio as feedback (short preview)
+        self.audio
+            .note_on(channel, cursor_pitch, self.config.default_velocity);
+        // Schedule note off after a short duration (handled by held_notes system isn't
+        // ideal here, so we'll just trigger a short note - the audio engine handles it)
+
+        // Percussion tracks name the instrument instead of the pitch
+        let name = self
+            .selected_track()
+            .and_then(|t| t.effective_drum_map())
+            .map_or_else(|| note_to_name(cursor_pitch), |map| map.label(cursor_pitch));
+        self.set_status(format!(
+            "Added {} at {}",
+            name,
+            cursor_tick / TICKS_PER_BEAT
+        ));
+        self.mark_modified();
+    }
+
+    /// Deletes the note under the cursor.
+    pub fn delete_note_at_cursor(&mut self) {
+        // Copy values to avoid borrow checker issues
+        let cursor_pitch = self.cursor_pitch;
+        let cursor_tick = self.cursor_tick;
+
+        let note_id = self.selected_track().and_then(|track| {
+            track
+                .notes()
+                .iter()
+                .find(|n| n.pitch == cursor_pitch && n.is_active_at(cursor_tick))
+                .map(|n| n.id)
+        });
+
+        if let Some(id) = note_id {
+            self.delete_note_by_id(id);
+        }
+    }
+
+    /// Deletes a note by its ID.
+    ///
+    /// # Arguments
+    ///
+    /// * `note_id` - The ID of the note to delete
+    pub fn delete_note_by_id(&mut self, note_id: NoteId) {
+        self.save_state("Delete note");
+        if let Some(track) = self.selected_track_mut() {
+            track.remove_note(note_id);
+        }
+        // Remove from selection if selected
+        self.selected_notes.remove(&note_id);
+        self.set_status("Deleted note");
+        self.mark_modified();
+    }
+
+    // ==================== Velocity Editing ====================
+
+    /// Changes the velocity of all selected notes.
+    ///
+    /// # Arguments
+    ///
+    /// * `delta` - Amount to add (clamped to 1-127)
+    pub fn adjust_selected_velocity(&mut self, delta: i32) {
+        if self.selected_notes.is_empty() {
+            return;
+        }
+        self.save_state("Change velocity");
+        let ids = self.selected_notes.clone();
+        let mut last = None;
+        if let Some(track) = self.project.track_at_mut(self.selected_track_index) {
+            for note in track.notes_mut().iter_mut().filter(|n| ids.contains(&n.id)) {
+                note.velocity = (note.velocity as i32 + delta).clamp(1, 127) as u8;
+                last = Some(note.velocity);
+            }
+        }
+        if let Some(velocity) = last {
+            self.set_status(format!("Velocity: {}", velocity));
+        }
+        self.mark_modified();
+    }
+
+    /// Draws a linear velocity ramp across the selected notes.
+    ///
+    /// The first and last selected notes (by start time) keep their velocities
+    /// and the notes in between are interpolated, e.g. for a crescendo.
+    pub fn ramp_selected_velocity(&mut self) {
+        let Some(track) = self.project.track_at(self.selected_track_index) else {
+            return;
+        };
+        let selected: Vec<&Note> = track
+            .notes()
+            .iter()
+            .filter(|n| self.selected_notes.contains(&n.id))
+            .collect();
+        let (Some(first), Some(last)) = (selected.first(), selected.last()) else {
+            return;
+        };
+        if selected.len() < 3 || first.start_tick == last.start_tick {
+            self.set_status("Select at least 3 notes to ramp velocity");
+            return;
+        }
+        let (start_tick, span) = (first.start_tick, last.start_tick - first.start_tick);
+        let (from, to) = (first.velocity as i64, last.velocity as i64);
+
+        self.save_state("Velocity ramp");
+        let ids = self.selected_notes.clone();
+        if let Some(track) = self.project.track_at_mut(self.selected_track_index) {
+            for note in track.notes_mut().iter_mut().filter(|n| ids.contains(&n.id)) {
+                let pos = (note.start_tick - start_tick) as i64;
+                note.velocity = (from + (to - from) * pos / span as i64).clamp(1, 127) as u8;
+            }
+        }
+        self.set_status(format!("Velocity ramp {} -> {}", from, to));
+        self.mark_modified();
+    }
+
+    /// Converts a screen row in the velocity lane to a velocity value.
+    fn velocity_at_lane_row(&self, y: u16) -> u8 {
+        let lane = self.layout.velocity_lane;
+        let rows = lane.height.max(1) as u32;
+        let row_from_bottom = (lane.y + lane.height)
+            .saturating_sub(y)
+            .clamp(1, rows as u16) as u32;
+        (row_from_bottom * 127 / rows).clamp(1, 127) as u8
+    }
+
+    /// Sets the velocity of the notes starting in a velocity lane column (no undo).
+    ///
+    /// When notes are selected only selected notes are changed.
+    fn set_velocity_at_column(&mut self, x: u16, velocity: u8) {
+        let relative_x = x.saturating_sub(self.layout.velocity_lane.x) as u32;
+        let tick = self.scroll_x + relative_x * self.zoom;
+        let tick_end = tick + self.zoom;
+        let selected = &self.selected_notes;
+        if let Some(track) = self.project.track_at_mut(self.selected_track_index) {
+            for note in track.notes_mut() {
+                if (tick..tick_end).contains(&note.start_tick)
+                    && (selected.is_empty() || selected.contains(&note.id))
+                {
+                    note.velocity = velocity;
+                }
+            }
+        }
+    }
+
+    /// Sets velocities for every column between two lane positions, interpolating
+    /// the value so fast drags draw a straight line.
+    fn draw_velocity_line(&mut self, from: (u16, u8), to: (u16, u8)) {
+        let (x0, v0) = (from.0 as i32, from.1 as i32);
+        let (x1, v1) = (to.0 as i32, to.1 as i32);
+        let steps = (x1 - x0).abs().max(1);
+        for i in 0..=steps {
+            let x = x0 + (x1 - x0).signum() * i;
+            let v = v0 + (v1 - v0) * i / steps;
+            self.set_velocity_at_column(x as u16, v.clamp(1, 127) as u8);
+        }
+    }
+
+    // ==================== Insert/Delete Time ====================
+
+    /// Opens the insert/delete time dialog at the cursor (one measure by default).
+    pub fn open_time_dialog(&mut self) {
+        self.time_dialog.open = true;
+        if self.time_dialog.length_beats == 0 {
+            self.time_dialog.length_beats = self.project.time_sig_numerator as u32;
+        }
+    }
+
+    /// Closes the insert/delete time dialog without changes.
+    pub fn time_dialog_close(&mut self) {
+        self.time_dialog.open = false;
+    }
+
+    /// Switches between inserting and deleting time.
+    pub fn time_dialog_toggle_op(&mut self) {
+        self.time_dialog.delete = !self.time_dialog.delete;
+    }
+
+    /// Adjusts the span length in beats (at least one beat).
+    pub fn time_dialog_adjust(&mut self, beats: i32) {
+        let length = self.time_dialog.length_beats as i32 + beats;
+        self.time_dialog.length_beats = length.clamp(1, 9999) as u32;
+    }
+
+    /// Applies the insert/delete time operation at the cursor as a single undo step.
+    pub fn time_dialog_confirm(&mut self) {
+        let start = self.cursor_tick;
+        let beats = self.time_dialog.length_beats;
+        let beat_ticks =
+            self.project.ticks_per_measure() / self.project.time_sig_numerator.max(1) as u32;
+        let length = beats * beat_ticks;
+        self.time_dialog.open = false;
+
+        if self.time_dialog.delete {
+            self.save_state("Delete time");
+            self.project.delete_time(start, start + length);
+            self.set_status(format!("Deleted {} beats at cursor", beats));
+        } else {
+            self.save_state("Insert time");
+            self.project.insert_time(start, length);
+            self.set_status(format!("Inserted {} beats at cursor", beats));
+        }
+        self.selected_notes.clear();
+        self.audio.all_notes_off(true);
+        self.mark_modified();
+    }
+
+    // ==================== Quantize ====================
+
+    /// Opens the quantize dialog for the selected notes, or the whole track
+    /// when nothing is selected.
+    pub fn open_quantize_dialog(&mut self) {
+        let Some(track) = self.project.track_at(self.selected_track_index) else {
+            return;
+        };
+        let targets = (!self.selected_notes.is_empty()).then(|| self.selected_notes.clone());
+        let originals: Vec<Note> = track
+            .notes()
+            .iter()
+            .filter(|n| targets.as_ref().is_none_or(|ids| ids.contains(&n.id)))
+            .cloned()
+            .collect();
+        if originals.is_empty() {
+            self.set_status("No notes to quantize");
+            return;
+        }
+
+        let dialog = &mut self.quantize_dialog;
+        dialog.open = true;
+        dialog.track_index = self.selected_track_index;
+        dialog.originals = originals;
+        self.quantize_refresh_preview();
+    }
+
+    /// Returns the number of notes the quantize dialog applies to.
+    pub fn quantize_target_count(&self) -> usize {
+        self.quantize_dialog.originals.len()
+    }
+
+    /// Moves the quantize dialog highlight up.
+    pub fn quantize_dialog_up(&mut self) {
+        self.quantize_dialog.selected = self.quantize_dialog.selected.saturating_sub(1);
+    }
+
+    /// Moves the quantize dialog highlight down.
+    pub fn quantize_dialog_down(&mut self) {
+        self.quantize_dialog.selected =
+            (self.quantize_dialog.selected + 1).min(QUANTIZE_DIALOG_ROWS - 1);
+    }
+
+    /// Adjusts the highlighted quantize setting.
+    ///
+    /// # Arguments
+    ///
+    /// * `direction` - Negative to decrease, positive to increase (toggles flip either way)
+    pub fn quantize_dialog_adjust(&mut self, direction: i32) {
+        let step = |value: u8, delta: i32| (value as i32 + delta).clamp(0, 100) as u8;
+        let settings = &mut self.quantize_dialog.settings;
+        match self.quantize_dialog.selected {
+            0 => {
+                let pos = GRID_DIVISIONS
+                    .iter()
+                    .position(|&d| d == settings.division)
+                    .unwrap_or(0) as i32;
+                let pos = (pos + direction).clamp(0, GRID_DIVISIONS.len() as i32 - 1);
+                settings.division = GRID_DIVISIONS[pos as usize];
+            }
+            1 => {
+                settings.feel = if direction < 0 {
+                    settings.feel.prev()
+                } else {
+                    settings.feel.next()
+                };
+            }
+            2 => settings.strength = step(settings.strength, direction * 10),
+            3 => settings.swing = step(settings.swing, direction * 10),
+            4 => settings.window = step(settings.window, direction * 10).max(10),
+            5 => settings.starts = !settings.starts,
+            _ => settings.ends = !settings.ends,
+        }
+        self.quantize_refresh_preview();
+    }
+
+    /// Toggles the live preview of the quantize result.
+    pub fn quantize_dialog_toggle_preview(&mut self) {
+        self.quantize_dialog.preview = !self.quantize_dialog.preview;
+        self.quantize_refresh_preview();
+    }
+
+    /// Re-applies (or removes) the preview on the track from the original notes.
+    fn quantize_refresh_preview(&mut self) {
+        let dialog = &self.quantize_dialog;
+        let Some(track) = self.project.track_at_mut(dialog.track_index) else {
+            return;
+        };
+        track.replace_notes(&dialog.originals);
+        if dialog.preview {
+            track.quantize_with(Some(&Self::quantize_ids(dialog)), &dialog.settings);
+        }
+        self.playback_cache.clear();
+        self.audio.all_notes_off(true);
+    }
+
+    /// Returns the IDs of the notes the quantize dialog applies to.
+    fn quantize_ids(dialog: &QuantizeDialogState) -> HashSet<NoteId> {
+        dialog.originals.iter().map(|n| n.id).collect()
+    }
+
+    /// Closes the quantize dialog, discarding any preview.
+    pub fn quantize_dialog_cancel(&mut self) {
+        self.quantize_dialog.open = false;
+        if let Some(track) = self.project.track_at_mut(self.quantize_dialog.track_index) {
+            track.replace_notes(&self.quantize_dialog.originals);
+        }
+        self.quantize_dialog.originals.clear();
+        self.playback_cache.clear();
+        self.set_status("Quantize cancelled");
+    }
+
+    /// Applies the quantize settings as a single undoable change.
+    pub fn quantize_dialog_confirm(&mut self) {
+        let track_index = self.quantize_dialog.track_index;
+        let ids = Self::quantize_ids(&self.quantize_dialog);
+        let settings = self.quantize_dialog.settings;
+
+        // Undo must capture the notes as they were before any preview
+        if let Some(track) = self.project.track_at_mut(track_index) {
+            track.replace_notes(&self.quantize_dialog.originals);
+        }
+        self.quantize_dialog.open = false;
+        self.quantize_dialog.originals.clear();
+
+        self.save_state("Quantize");
+        let changed = self
+            .project
+            .track_at_mut(track_index)
+            .map_or(0, |t| t.quantize_with(Some(&ids), &settings));
+        self.set_status(format!(
+            "Quantized {} notes to {}",
+            changed,
+            settings.grid_label()
+        ));
+        self.mark_modified();
+    }
+
+    // ==================== Humanize & Groove ====================
+
+    /// Opens the humanize/groove dialog.
+    pub fn open_groove_dialog(&mut self) {
+        self.groove_dialog.open = true;
+        let count = self.groove_templates().len();
+        self.groove_dialog.template = self.groove_dialog.template.min(count.saturating_sub(1));
+    }
+
+    /// Closes the humanize/groove dialog.
+    pub fn groove_dialog_close(&mut self) {
+        self.groove_dialog.open = false;
+    }
+
+    /// Returns the available groove templates: built-in presets, then project grooves.
+    pub fn groove_templates(&self) -> Vec<GrooveTemplate> {
+        let mut templates = GrooveTemplate::presets();
+        templates.extend(self.project.grooves().iter().cloned());
+        templates
+    }
+
+    /// Moves the groove dialog highlight up.
+    pub fn groove_dialog_up(&mut self) {
+        self.groove_dialog.selected = self.groove_dialog.selected.saturating_sub(1);
+    }
+
+    /// Moves the groove dialog highlight down.
+    pub fn groove_dialog_down(&mut self) {
+        self.groove_dialog.selected = (self.groove_dialog.selected + 1).min(GROOVE_DIALOG_ROWS - 1);
+    }
+
+    /// Adjusts the highlighted humanize/groove setting.
+    ///
+    /// # Arguments
+    ///
+    /// * `direction` - Negative to decrease, positive to increase
+    pub fn groove_dialog_adjust(&mut self, direction: i32) {
+        let step =
+            |value: u32, delta: i32, max: u32| (value as i32 + delta).clamp(0, max as i32) as u32;
+        let template_count = self.groove_templates().len();
+        let dialog = &mut self.groove_dialog;
+        match dialog.selected {
+            0 => {
+                dialog.humanize.timing =
+                    step(dialog.humanize.timing, direction * 5, TICKS_PER_BEAT / 2)
+            }
+            1 => dialog.humanize.velocity = step(dialog.humanize.velocity, direction * 2, 64),
+            2 => {
+                dialog.humanize.length = step(dialog.humanize.length, direction * 5, TICKS_PER_BEAT)
+            }
+            3 => dialog.humanize.seed = dialog.humanize.seed.wrapping_add_signed(direction as i64),
+            4 => {
+                let count = template_count as i32;
+                dialog.template =
+                    (dialog.template as i32 + direction).rem_euclid(count.max(1)) as usize;
+            }
+            _ => dialog.strength = step(dialog.strength, direction * 10, 100),
+        }
+    }
+
+    /// Returns the notes the humanize/groove dialog applies to: the selection,
+    /// or None for the whole track.
+    fn groove_targets(&self) -> Option<HashSet<NoteId>> {
+        (!self.selected_notes.is_empty()).then(|| self.selected_notes.clone())
+    }
+
+    /// Applies the section of the highlighted row: humanize or groove.
+    pub fn groove_dialog_apply(&mut self) {
+        let targets = self.groove_targets();
+        let track_index = self.selected_track_index;
+        if self.project.track_at(track_index).is_none() {
+            return;
+        }
+        self.groove_dialog.open = false;
+
+        if self.groove_dialog.selected < GROOVE_DIALOG_TEMPLATE_ROW {
+            let settings = self.groove_dialog.humanize;
+            self.save_state("Humanize");
+            let count = self
+                .project
+                .track_at_mut(track_index)
+                .map_or(0, |t| t.humanize(targets.as_ref(), &settings));
+            self.set_status(format!(
+                "Humanized {} notes (seed {})",
+                count, settings.seed
+            ));
+        } else {
+            let Some(template) = self
+                .groove_templates()
+                .get(self.groove_dialog.template)
+                .cloned()
+            else {
+                return;
+            };
+            let strength = self.groove_dialog.strength;
+            self.save_state("Apply groove");
+            let count = self
+                .project
+                .track_at_mut(track_index)
+                .map_or(0, |t| t.apply_groove(targets.as_ref(), &template, strength));
+            self.set_status(format!("Applied '{}' to {} notes", template.name, count));
+        }
+        self.audio.all_notes_off(true);
+        self.mark_modified();
+    }
+
+    /// Extracts a groove template from the selected track and selects it.
+    pub fn groove_dialog_extract(&mut self) {
+        self.save_state("Extract groove");
+        match self.project.extract_groove(self.selected_track_index) {
+            Some(index) => {
+                self.groove_dialog.template = GrooveTemplate::presets().len() + index;
+                self.groove_dialog.selected = GROOVE_DIALOG_TEMPLATE_ROW;
+                let name = self.project.grooves()[index].name.clone();
+                self.set_status(format!("Extracted '{}'", name));
+                self.mark_modified();
+            }
+            None => self.set_status("Track has no notes to extract a groove from"),
+        }
+    }
+
+    // ==================== Key & Scale ====================
+
+    /// Returns the project scale if editing should snap to it.
+    fn snap_scale(&self) -> Option<Scale> {
+        self.project.scale.filter(|scale| scale.snap)
+    }
+
+    /// Opens the key/scale dialog with the project scale (or C major).
+    pub fn open_scale_dialog(&mut self) {
+        self.scale_dialog.open = true;
+        self.scale_dialog.scale = self.project.scale.unwrap_or_default();
+    }
+
+    /// Closes the key/scale dialog without changes.
+    pub fn scale_dialog_close(&mut self) {
+        self.scale_dialog.open = false;
+    }
+
+    /// Moves the scale dialog highlight up.
+    pub fn scale_dialog_up(&mut self) {
+        self.scale_dialog.selected = self.scale_dialog.selected.saturating_sub(1);
+    }
+
+    /// Moves the scale dialog highlight down.
+    pub fn scale_dialog_down(&mut self) {
+        self.scale_dialog.selected = (self.scale_dialog.selected + 1).min(SCALE_DIALOG_ROWS - 1);
+    }
+
+    /// Adjusts the highlighted scale setting.
+    ///
+    /// On the custom row this moves the pitch class cursor instead.
+    ///
+    /// # Arguments
+    ///
+    /// * `direction` - Negative to decrease, positive to increase
+    pub fn scale_dialog_adjust(&mut self, direction: i32) {
+        let dialog = &mut self.scale_dialog;
+        match dialog.selected {
+            0 => dialog.scale.root = (dialog.scale.root as i32 + direction).rem_euclid(12) as u8,
+            1 => {
+                let kinds = ScaleKind::ALL;
+                let index = kinds
+                    .iter()
+                    .position(|&k| k == dialog.scale.kind)
+                    .unwrap_or(0) as i32;
+                dialog.scale.kind =
+                    kinds[(index + direction).rem_euclid(kinds.len() as i32) as usize];
+            }
+            2 => dialog.scale.snap = !dialog.scale.snap,
+            _ => {
+                dialog.custom_cursor =
+                    (dialog.custom_cursor as i32 + direction).rem_euclid(12) as u8
+            }
+        }
+    }
+
+    /// Toggles the highlighted pitch class of the custom scale.
+    ///
+    /// Starts from the current scale's pitch classes when switching to custom.
+    pub fn scale_dialog_toggle_custom(&mut self) {
+        let dialog = &mut self.scale_dialog;
+        if dialog.selected != SCALE_DIALOG_CUSTOM_ROW || dialog.custom_cursor == 0 {
+            return;
+        }
+        if dialog.scale.kind != ScaleKind::Custom {
+            dialog.scale.custom_mask = dialog.scale.mask();
+            dialog.scale.kind = ScaleKind::Custom;
+        }
+        dialog.scale.custom_mask ^= 1 << dialog.custom_cursor;
+    }
+
+    /// Sets the project scale from the dialog.
+    pub fn scale_dialog_confirm(&mut self) {
+        let scale = self.scale_dialog.scale;
+        self.scale_dialog.open = false;
+        if self.project.scale == Some(scale) {
+            return;
+        }
+        self.save_state("Set scale");
+        self.project.scale = Some(scale);
+        self.set_status(format!(
+            "Key: {}{}",
+            scale.name(),
+            if scale.snap { " (snap)" } else { "" }
+        ));
+        self.mark_modified();
+    }
+
+    /// Removes the project scale.
+    pub fn scale_dialog_clear(&mut self) {
+        self.scale_dialog.open = false;
+        if self.project.scale.is_none() {
+            return;
+        }
+        self.save_state("Clear scale");
+        self.project.scale = None;
+        self.set_status("Key cleared");
+        self.mark_modified();
+    }
+
+    // ==================== Chords ====================
+
+    /// Places a chord rooted at the cursor using the chord entry voicing.
+    pub fn place_chord(&mut self) {
+        let cursor_tick = self.cursor_tick;
+        let pitches = self.chord_voicing.pitches(self.cursor_pitch);
+        let channel = self.selected_track().map(|t| t.channel).unwrap_or(0);
+        let velocity = self.config.default_velocity;
+        let duration = self.config.default_note_duration;
+
+        self.save_state("Place chord");
+        for &pitch in &pitches {
+            let note_id = self
+                .selected_track_mut()
+                .map(|track| track.create_note(pitch, velocity, cursor_tick, duration));
+            if let Some(id) = note_id {
+                self.register_added_note(id, pitch, cursor_tick);
+            }
+            self.audio.note_on(channel, pitch, velocity);
+        }
+
+        let chord = Chord::new(self.cursor_pitch, self.chord_voicing.quality);
+        self.set_status(format!(
+            "Added {} at {}",
+            chord.symbol(),
+            cursor_tick / TICKS_PER_BEAT
+        ));
+        self.mark_modified();
+    }
+
+    /// Toggles chord entry mode.
+    pub fn toggle_chord_entry(&mut self) {
+        self.chord_entry = !self.chord_entry;
+        if self.chord_entry {
+            self.set_status(format!("Chord entry: {}", self.chord_voicing.label()));
+        } else {
+            self.set_status("Chord entry off");
+        }
+    }
+
+    /// Returns the chord sounding at the cursor (the playhead during playback).
+    pub fn detected_chord(&self) -> Option<Chord> {
+        self.project.detect_chord_at(self.cursor_tick)
+    }
+
+    /// Opens the chord dialog.
+    pub fn open_chord_dialog(&mut self) {
+        self.chord_dialog.open = true;
+        if self
+            .project
+            .track_at(self.chord_dialog.target_track)
+            .is_none()
+        {
+            self.chord_dialog.target_track = self.selected_track_index;
+        }
+    }
+
+    /// Closes the chord dialog.
+    pub fn chord_dialog_close(&mut self) {
+        self.chord_dialog.open = false;
+    }
+
+    /// Moves the chord dialog highlight up.
+    pub fn chord_dialog_up(&mut self) {
+        self.chord_dialog.selected = self.chord_dialog.selected.saturating_sub(1);
+    }
+
+    /// Moves the chord dialog highlight down.
+    pub fn chord_dialog_down(&mut self) {
+        self.chord_dialog.selected = (self.chord_dialog.selected + 1).min(CHORD_DIALOG_ROWS - 1);
+    }
+
+    /// Adjusts the highlighted chord setting.
+    ///
+    /// # Arguments
+    ///
+    /// * `direction` - Negative to decrease, positive to increase
+    pub fn chord_dialog_adjust(&mut self, direction: i32) {
+        let track_count = self.project.track_count().max(1) as i32;
+        let voicing = &mut self.chord_voicing;
+        match self.chord_dialog.selected {
+            0 => self.toggle_chord_entry(),
+            1 => {
+                let qualities = ChordQuality::ALL;
+                let index = qualities
+                    .iter()
+                    .position(|&q| q == voicing.quality)
+                    .unwrap_or(0) as i32;
+                voicing.quality =
+                    qualities[(index + direction).rem_euclid(qualities.len() as i32) as usize];
+            }
+            2 => {
+                let tones = voicing.quality.intervals().len() as i32;
+                voicing.inversion = (voicing.inversion as i32 + direction).rem_euclid(tones) as u8;
+            }
+            3 => voicing.spread = !voicing.spread,
+            4 => {
+                let dialog = &mut self.chord_dialog;
+                dialog.style = if direction > 0 {
+                    dialog.style.next()
+                } else {
+                    dialog.style.next().next()
+                };
+            }
+            _ => {
+                let dialog = &mut self.chord_dialog;
+                dialog.target_track =
+                    (dialog.target_track as i32 + direction).rem_euclid(track_count) as usize;
+            }
+        }
+    }
+
+    /// Adds the chord entry chord, rooted at the cursor pitch, to the chord track at the cursor.
+    pub fn chord_dialog_add_symbol(&mut self) {
+        let chord = Chord::new(self.cursor_pitch, self.chord_voicing.quality);
+        self.set_chord_symbol(chord);
+    }
+
+    /// Adds the chord detected at the cursor to the chord track.
+    pub fn chord_dialog_capture(&mut self) {
+        match self.detected_chord() {
+            Some(chord) => self.set_chord_symbol(chord),
+            None => self.set_status("No chord detected at cursor"),
+        }
+    }
+
+    /// Puts a chord symbol on the chord track at the cursor.
+    fn set_chord_symbol(&mut self, chord: Chord) {
+        self.save_state("Set chord");
+        self.project.set_chord(self.cursor_tick, chord);
+        self.set_status(format!("Chord track: {}", chord.symbol()));
+        self.mark_modified();
+    }
+
+    /// Removes the chord symbol in effect at the cursor.
+    pub fn chord_dialog_remove_symbol(&mut self) {
+        let Some(index) = self.project.chord_index_at(self.cursor_tick) else {
+            self.set_status("No chord symbol at cursor");
+            return;
+        };
+        self.save_state("Remove chord");
+        if let Some(symbol) = self.project.remove_chord(index) {
+            self.set_status(format!("Removed {}", symbol.chord.symbol()));
+        }
+        self.mark_modified();
+    }
+
+    /// Generates accompaniment from the chord track on the target track.
+    pub fn chord_dialog_generate(&mut self) {
+        if self.project.chords().is_empty() {
+            self.set_status("Chord track is empty");
+            return;
+        }
+        let target = self.chord_dialog.target_track;
+        let style = self.chord_dialog.style;
+        self.save_state("Generate accompaniment");
+        let count = self.project.generate_accompaniment(target, style);
+        self.chord_dialog.open = false;
+        self.audio.all_notes_off(true);
+        let name = self
+            .project
+            .track_at(target)
+            .map_or(String::new(), |t| t.name.clone());
+        self.set_status(format!(
+            "Generated {} {} notes on '{}'",
+            count,
+            style.name(),
+            name
+        ));
+        self.mark_modified();
+    }
+
+    // ==================== Note Effects ====================
+
+    /// Opens the note effects dialog.
+    pub fn open_effects_dialog(&mut self) {
+        self.effects_dialog.open = true;
+    }
+
+    /// Closes the note effects dialog.
+    pub fn effects_dialog_close(&mut self) {
+        self.effects_dialog.open = false;
+    }
+
+    /// Moves the effects dialog highlight up.
+    pub fn effects_dialog_up(&mut self) {
+        self.effects_dialog.selected = self.effects_dialog.selected.saturating_sub(1);
+    }
+
+    /// Moves the effects dialog highlight down.
+    pub fn effects_dialog_down(&mut self) {
+        let last = self.effects_dialog.row_count() - 1;
+        self.effects_dialog.selected = (self.effects_dialog.selected + 1).min(last);
+    }
+
+    /// Adjusts the highlighted effect setting.
+    ///
+    /// # Arguments
+    ///
+    /// * `direction` - Negative to decrease, positive to increase
+    pub fn effects_dialog_adjust(&mut self, direction: i32) {
+        let step_rate = |rate: u32| {
+            let index = GRID_DIVISIONS.iter().position(|&d| d == rate).unwrap_or(4) as i32;
+            GRID_DIVISIONS[(index + direction).clamp(0, GRID_DIVISIONS.len() as i32 - 1) as usize]
+        };
+        let step = |value: u8, delta: i32, min: u8, max: u8| {
+            (value as i32 + delta).clamp(min as i32, max as i32) as u8
+        };
+        let dialog = &mut self.effects_dialog;
+        if dialog.selected == 0 {
+            dialog.echo = !dialog.echo;
+            dialog.selected = 0;
+            return;
+        }
+        if dialog.echo {
+            let echo = &mut dialog.echo_settings;
+            match dialog.selected {
+                1 => echo.repeats = step(echo.repeats, direction, 1, 16),
+                2 => echo.rate = step_rate(echo.rate),
+                _ => echo.decay = step(echo.decay, direction * 5, 5, 100),
+            }
+        } else {
+            let arp = &mut dialog.arp_settings;
+            match dialog.selected {
+                1 => {
+                    arp.direction = if direction > 0 {
+                        arp.direction.next()
+                    } else {
+                        arp.direction.prev()
+                    }
+                }
+                2 => arp.rate = step_rate(arp.rate),
+                3 => arp.gate = step(arp.gate, direction * 5, 5, 100),
+                _ => arp.octaves = step(arp.octaves, direction, 1, 4),
+            }
+        }
+    }
+
+    /// Renders the effect into the selected notes (or the whole track).
+    ///
+    /// The generated notes replace their source notes and become the selection.
+    pub fn effects_dialog_render(&mut self) {
+        let effect = self.effects_dialog.effect();
+        let targets = (!self.selected_notes.is_empty()).then(|| self.selected_notes.clone());
+        let track_index = self.selected_track_index;
+        if self.project.track_at(track_index).is_none() {
+            return;
+        }
+        self.effects_dialog.open = false;
+
+        self.save_state(if self.effects_dialog.echo {
+            "Echo"
+        } else {
+            "Arpeggiate"
+        });
+        let generated = self
+            .project
+            .track_at_mut(track_index)
+            .map(|t| t.render_effect(targets.as_ref(), &effect))
+            .unwrap_or_default();
+        if targets.is_some() {
+            self.selected_notes = generated.iter().copied().collect();
+        }
+        self.audio.all_notes_off(true);
+        self.set_status(format!(
+            "{}: {} notes generated",
+            effect.label(),
+            generated.len()
+        ));
+        self.mark_modified();
+    }
+
+    /// Adds the effect to the end of the selected track's effect chain.
+    pub fn effects_dialog_add_to_track(&mut self) {
+        let effect = self.effects_dialog.effect();
+        if self.selected_track().is_none() {
+            return;
+        }
+        self.save_state("Add track effect");
+        if let Some(track) = self.selected_track_mut() {
+            track.effects.push(effect);
+        }
+        self.audio.all_notes_off(true);
+        self.set_status(format!("Track effect added: {}", effect.label()));
+        self.mark_modified();
+    }
+
+    /// Removes the last effect from the selected track's effect chain.
+    pub fn effects_dialog_remove_from_track(&mut self) {
+        if self.selected_track().is_none_or(|t| t.effects.is_empty()) {
+            self.set_status("Track has no effects");
+            return;
+        }
+        self.save_state("Remove track effect");
+        let removed = self.selected_track_mut().and_then(|t| t.effects.pop());
+        if let Some(effect) = removed {
+            self.set_status(format!("Track effect removed: {}", effect.label()));
+        }
+        self.audio.all_notes_off(true);
+        self.mark_modified();
+    }
+
+    /// Renders the selected track's effect chain into its notes and clears the chain.
+    pub fn effects_dialog_bake_track(&mut self) {
+        let Some(effects) = self.selected_track().map(|t| t.effects.clone()) else {
+            return;
+        };
+        if effects.is_empty() {
+            self.set_status("Track has no effects");
+            return;
+        }
+        self.effects_dialog.open = false;
+        self.save_state("Bake track effects");
+        let mut count = 0;
+        if let Some(track) = self.selected_track_mut() {
+            for effect in &effects {
+                count = track.render_effect(None, effect).len();
+            }
+            track.effects.clear();
+        }
+        self.selected_notes.clear();
+        self.set_status(format!(
+            "Baked {} effects into {} notes",
+            effects.len(),
+            count
+        ));
+        self.mark_modified();
+    }
+
+    // ==================== Step Sequencer ====================
+
+    /// Switches to the step sequencer, showing the pattern at the cursor's measure.
+    pub fn enter_step_sequencer(&mut self) {
+        let measure = self.project.ticks_per_measure();
+        self.step_seq.pattern_start = self.cursor_tick / measure * measure;
+        self.step_seq.step = 0;
+        self.view_mode = ViewMode::StepSequencer;
+        self.set_status("Step Sequencer View");
+    }
+
+    /// Returns the drum map of the selected track (General MIDI if it has none).
+    pub fn drum_map(&self) -> Cow<'_, DrumMap> {
+        self.selected_track()
+            .and_then(|t| t.effective_drum_map())
+            .unwrap_or_else(|| Cow::Owned(DrumMap::general_midi()))
+    }
+
+    /// Returns the drum map entry of a step sequencer row.
+    fn step_entry(&self, row: usize) -> Option<DrumMapEntry> {
+        self.drum_map().entries.get(row).cloned()
+    }
+
+    /// Returns the looped tick range while the step sequencer loops its pattern.
+    pub fn pattern_loop(&self) -> Option<(u32, u32)> {
+        (self.view_mode == ViewMode::StepSequencer && self.step_seq.looping).then(|| {
+            let start = self.step_seq.pattern_start;
+            (start, start + self.step_seq.pattern_ticks())
+        })
+    }
+
+    /// Moves the step sequencer cursor.
+    ///
+    /// # Arguments
+    ///
+    /// * `rows` - Rows to move down (negative moves up)
+    /// * `steps` - Steps to move right (negative moves left)
+    pub fn step_cursor_move(&mut self, rows: i32, steps: i32) {
+        let last_row = self.drum_map().entries.len() as i32 - 1;
+        let seq = &mut self.step_seq;
+        seq.row = (seq.row as i32 + rows).clamp(0, last_row.max(0)) as usize;
+        seq.step = (seq.step as i32 + steps).clamp(0, seq.steps as i32 - 1) as u32;
+        if let Some(entry) = self.step_entry(self.step_seq.row) {
+            self.cursor_pitch = entry.pitch;
+        }
+        if !self.audio.is_playing() {
+            self.cursor_tick = self.step_seq.step_tick(self.step_seq.step);
+        }
+    }
+
+    /// Moves the step sequencer to the previous or next pattern.
+    ///
+    /// # Arguments
+    ///
+    /// * `direction` - -1 for the previous pattern, 1 for the next
+    pub fn step_page(&mut self, direction: i32) {
+        let length = self.step_seq.pattern_ticks() as i64;
+        let start = (self.step_seq.pattern_start as i64 + direction as i64 * length).max(0);
+        self.step_seq.pattern_start = start as u32;
+        self.set_status(format!(
+            "Pattern at bar {}",
+            start as u32 / self.project.ticks_per_measure() + 1
+        ));
+    }
+
+    /// Changes the pattern length by a number of steps (1-32).
+    pub fn step_adjust_length(&mut self, delta: i32) {
+        let seq = &mut self.step_seq;
+        seq.steps = (seq.steps as i32 + delta).clamp(1, 32) as u32;
+        seq.step = seq.step.min(seq.steps - 1);
+        self.set_status(format!("Pattern length: {} steps", self.step_seq.steps));
+    }
+
+    /// Cycles the step length through 1/4, 1/8, 1/16 and 1/32.
+    pub fn step_cycle_division(&mut self) {
+        let index = STEP_DIVISIONS
+            .iter()
+            .position(|&d| d == self.step_seq.division)
+            .unwrap_or(0);
+        self.step_seq.division = STEP_DIVISIONS[(index + 1) % STEP_DIVISIONS.len()];
+        self.set_status(format!("Step length: 1/{}", self.step_seq.division));
+    }
+
+    /// Toggles looping of the displayed pattern during playback.
+    pub fn step_toggle_loop(&mut self) {
+        self.step_seq.looping = !self.step_seq.looping;
+        self.set_status(if self.step_seq.looping {
+            "Pattern loop on"
+        } else {
+            "Pattern loop off"
+        });
+    }
+
+    /// Returns the note of a drum pitch in a step of the selected track, if the step is on.
+    pub fn step_note(&self, pitch: u8, step: u32) -> Option<&Note> {
+        let start = self.step_seq.step_tick(step);
+        self.selected_track()?
+            .note_starting_in(pitch, start, start + self.step_seq.step_ticks())
+    }
+
+    /// Turns a step on or off.
+    pub fn step_toggle_at(&mut self, row: usize, step: u32) {
+        let Some(entry) = self.step_entry(row) else {
+            return;
+        };
+        if self.selected_track().is_none() {
+            return;
+        }
+        let pitch = entry.pitch;
+        let existing = self.step_note(pitch, step).map(|n| n.id);
+        let tick = self.step_seq.step_tick(step);
+        let length = self.step_seq.step_ticks();
+
+        self.save_state("Toggle step");
+        let channel = self.selected_track().map(|t| t.channel).unwrap_or(9);
+        if let Some(track) = self.selected_track_mut() {
+            match existing {
+                Some(id) => {
+                    track.remove_note(id);
+                }
+                None => {
+                    track.create_note(pitch, entry.velocity, tick, length);
+                }
+            }
+        }
+        if existing.is_none() {
+            self.audio.note_on(channel, pitch, entry.velocity);
+        }
+        self.set_status(format!(
+            "{} step {} {}",
+            entry.name,
+            step + 1,
+            if existing.is_some() { "off" } else { "on" }
+        ));
+        self.mark_modified();
+    }
+
+    /// Turns the step at the cursor on or off.
+    pub fn step_toggle(&mut self) {
+        self.step_toggle_at(self.step_seq.row, self.step_seq.step);
+    }
+
+    /// Sets the velocity of the step at the cursor, turning it on if needed.
+    ///
+    /// The closure gets the current velocity (the row's default for an empty
+    /// step) and the row's default velocity.
+    fn set_step_velocity(&mut self, velocity: impl Fn(u8, u8) -> u8, description: &str) {
+        let step = self.step_seq.step;
+        let Some(entry) = self.step_entry(self.step_seq.row) else {
+            return;
+        };
+        let pitch = entry.pitch;
+        let existing = self.step_note(pitch, step).map(|n| (n.id, n.velocity));
+        let tick = self.step_seq.step_tick(step);
+        let length = self.step_seq.step_ticks();
+
+        self.save_state(description);
+        let current = existing.map_or(entry.velocity, |(_, v)| v);
+        let new_velocity = velocity(current, entry.velocity);
+        if let Some(track) = self.selected_track_mut() {
+            match existing {
+                Some((id, _)) => {
+                    if let Some(note) = track.get_note_mut(id) {
+                        note.velocity = new_velocity;
+                    }
+                }
+                None => {
+                    track.create_note(pitch, new_velocity, tick, length);
+                }
+            }
+        }
+        self.set_status(format!("Step velocity: {}", new_velocity));
+        self.mark_modified();
+    }
+
+    /// Toggles the accent of the step at the cursor.
+    pub fn step_toggle_accent(&mut self) {
+        self.set_step_velocity(
+            |v, default| {
+                if v >= ACCENT_VELOCITY {
+                    default.min(ACCENT_VELOCITY - 1)
+                } else {
+                    ACCENT_VELOCITY
+                }
+            },
+            "Accent step",
+        );
+    }
+
+    /// Changes the velocity of the step at the cursor.
+    pub fn step_adjust_velocity(&mut self, delta: i32) {
+        self.set_step_velocity(
+            |v, _| (v as i32 + delta).clamp(1, 127) as u8,
+            "Step velocity",
+        );
+    }
+
+    /// Copies the displayed pattern over the next one and moves there.
+    pub fn step_copy_to_next(&mut self) {
+        let start = self.step_seq.pattern_start;
+        let length = self.step_seq.pattern_ticks();
+        let end = start + length;
+        if self.selected_track().is_none() {
+            return;
+        }
+        self.save_state("Copy pattern");
+        if let Some(track) = self.selected_track_mut() {
+            track.remove_range(end, end + length);
+            track.copy_range(start, end).paste_into(track, length);
+        }
+        self.project.drop_unused_patterns();
+        self.step_seq.pattern_start = end;
+        self.set_status("Pattern copied to next bar");
+        self.mark_modified();
+    }
+
+    /// Toggles the step under a mouse click in the step grid.
+    fn handle_step_grid_click(&mut self, x: u16, y: u16) {
+        let grid = self.layout.step_grid;
+        if !self.layout.is_in_step_grid(x, y) || grid.cell_width == 0 {
+            return;
+        }
+        let step = ((x - grid.cells.x) / grid.cell_width) as u32;
+        let row = grid.first_row + (y - grid.cells.y) as usize;
+        if step < self.step_seq.steps && row < self.drum_map().entries.len() {
+            self.step_seq.row = row;
+            self.step_seq.step = step;
+            self.step_toggle_at(row, step);
+        }
+    }
+
+    // ==================== Drum Maps ====================
+
+    /// Opens the drum map dialog for the selected track.
+    pub fn open_drum_map_dialog(&mut self) {
+        if self.selected_track().is_none() {
+            return;
+        }
+        let count = self.drum_map().entries.len();
+        let dialog = &mut self.drum_map_dialog;
+        dialog.open = true;
+        dialog.input = None;
+        dialog.selected = dialog.selected.min(count.saturating_sub(1));
+    }
+
+    /// Closes the drum map dialog.
+    pub fn drum_map_dialog_close(&mut self) {
+        self.drum_map_dialog.open = false;
+        self.drum_map_dialog.input = None;
+    }
+
+    /// Moves the drum map dialog highlight up or down.
+    pub fn drum_map_dialog_move(&mut self, delta: i32) {
+        let last = self.drum_map().entries.len().saturating_sub(1) as i32;
+        let dialog = &mut self.drum_map_dialog;
+        dialog.selected = (dialog.selected as i32 + delta).clamp(0, last) as usize;
+    }
+
+    /// Moves the drum map dialog highlight to the previous or next column.
+    pub fn drum_map_dialog_column(&mut self, delta: i32) {
+        let dialog = &mut self.drum_map_dialog;
+        dialog.column =
+            (dialog.column as i32 + delta).clamp(0, DRUM_MAP_COLUMNS as i32 - 1) as usize;
+    }
+
+    /// Edits the selected track's drum map, giving the track its own copy first.
+    fn edit_drum_map(&mut self, description: &str, edit: impl FnOnce(&mut DrumMap)) {
+        let Some(mut map) = self.selected_track().map(|_| self.drum_map().into_owned()) else {
+            return;
+        };
+        self.save_state(description);
+        edit(&mut map);
+        if let Some(track) = self.selected_track_mut() {
+            track.drum_map = Some(map);
+        }
+        self.mark_modified();
+    }
+
+    /// Adjusts the highlighted value (pitch, output pitch or default velocity).
+    pub fn drum_map_dialog_adjust(&mut self, delta: i32) {
+        let (index, column) = (self.drum_map_dialog.selected, self.drum_map_dialog.column);
+        if index >= self.drum_map().entries.len() {
+            return;
+        }
+        let step = |value: u8, min: i32| (value as i32 + delta).clamp(min, 127) as u8;
+        self.edit_drum_map("Edit drum map", |map| {
+            let entry = &mut map.entries[index];
+            match column {
+                0 => entry.pitch = step(entry.pitch, 0),
+                1 => entry.output = Some(step(entry.output.unwrap_or(entry.pitch), 0)),
+                _ => entry.velocity = step(entry.velocity, 1),
+            }
+        });
+    }
+
+    /// Removes the output remap of the highlighted instrument.
+    pub fn drum_map_dialog_clear_output(&mut self) {
+        let index = self.drum_map_dialog.selected;
+        if self
+            .drum_map()
+            .entries
+            .get(index)
+            .is_none_or(|e| e.output.is_none())
+        {
+            return;
+        }
+        self.edit_drum_map("Clear drum remap", |map| map.entries[index].output = None);
+    }
+
+    /// Moves the highlighted instrument up or down in the display order.
+    pub fn drum_map_dialog_reorder(&mut self, delta: i32) {
+        let index = self.drum_map_dialog.selected;
+        let mut target = index;
+        self.edit_drum_map("Reorder drum map", |map| {
+            target = map.move_entry(index, delta);
+        });
+        self.drum_map_dialog.selected = target;
+    }
+
+    /// Adds an instrument for the cursor pitch (or the next unmapped pitch above it).
+    pub fn drum_map_dialog_add(&mut self) {
+        let map = self.drum_map();
+        let Some(pitch) = (self.cursor_pitch..=127).find(|&p| map.entry(p).is_none()) else {
+            self.set_status("Every pitch from the cursor up is mapped");
+            return;
+        };
+        let name = DrumMap::general_midi().label(pitch);
+        let index = (self.drum_map_dialog.selected + 1).min(map.entries.len());
+        self.edit_drum_map("Add drum", |map| {
+            map.entries.insert(index, DrumMapEntry::new(pitch, name));
+        });
+        self.drum_map_dialog.selected = index;
+    }
+
+    /// Removes the highlighted instrument from the map.
+    pub fn drum_map_dialog_remove(&mut self) {
+        let index = self.drum_map_dialog.selected;
+        if index >= self.drum_map().entries.len() {
+            return;
+        }
+        self.edit_drum_map("Remove drum", |map| {
+            map.entries.remove(index);
+        });
+        self.drum_map_dialog_move(0);
+    }
+
+    /// Replaces the track's drum map with the General MIDI map.
+    pub fn drum_map_dialog_reset(&mut self) {
+        if self.selected_track().is_none_or(|t| t.drum_map.is_none()) {
+            return;
+        }
+        self.save_state("Reset drum map");
+        if let Some(track) = self.selected_track_mut() {
+            track.drum_map = None;
+        }
+        self.drum_map_dialog_move(0);
+        self.set_status("Drum map reset to General MIDI");
+        self.mark_modified();
+    }
+
+    /// Starts typing a name or file path in the drum map dialog.
+    pub fn drum_map_dialog_start_input(&mut self, kind: DrumMapInput) {
+        let map = self.drum_map();
+        let buffer = match kind {
+            DrumMapInput::Rename => match map.entries.get(self.drum_map_dialog.selected) {
+                Some(entry) => entry.name.clone(),
+                None => return,
+            },
+            DrumMapInput::Load | DrumMapInput::Save => {
+                format!("{}.drummap.json", map.name.to_lowercase().replace(' ', "_"))
+            }
+        };
+        self.drum_map_dialog.input = Some((kind, buffer));
+    }
+
+    /// Adds a character to the drum map dialog input.
+    pub fn drum_map_input_char(&mut self, c: char) {
+        if let Some((_, buffer)) = &mut self.drum_map_dialog.input {
+            if buffer.len() < 128 {
+                buffer.push(c);
+            }
+        }
+    }
+
+    /// Removes the last character from the drum map dialog input.
+    pub fn drum_map_input_backspace(&mut self) {
+        if let Some((_, buffer)) = &mut self.drum_map_dialog.input {
+            buffer.pop();
+        }
+    }
+
+    /// Cancels the drum map dialog input.
+    pub fn drum_map_input_cancel(&mut self) {
+        self.drum_map_dialog.input = None;
+    }
+
+    /// Confirms the drum map dialog input: renames, loads or saves.
+    pub fn drum_map_input_confirm(&mut self) {
+        let Some((kind, buffer)) = self.drum_map_dialog.input.take() else {
+            return;
+        };
+        let text = buffer.trim().to_string();
+        if text.is_empty() {
+            return;
+        }
+        match kind {
+            DrumMapInput::Rename => {
+                let index = self.drum_map_dialog.selected;
+                self.edit_drum_map("Rename drum", |map| {
+                    if let Some(entry) = map.entries.get_mut(index) {
+                        entry.name = text;
+                    }
+                });
+            }
+            DrumMapInput::Load => match DrumMap::load_from_file(&text) {
+                Ok(map) => {
+                    let status = format!("Loaded drum map: {}", map.name);
+                    self.edit_drum_map("Load drum map", |current| *current = map);
+                    self.drum_map_dialog_move(0);
+                    self.set_status(status);
+                }
+                Err(e) => self.set_status(format!("Failed to load drum map: {}", e)),
+            },
+            DrumMapInput::Save => match self.drum_map().save_to_file(&text) {
+                Ok(()) => self.set_status(format!("Saved drum map to {}", text)),
+                Err(e) => self.set_status(format!("Failed to save drum map: {}", e)),
+            },
+        }
+    }
+
+    // ==================== Event List ====================
+
+    /// Returns the time format of the project's time signature.
+    pub fn time_format(&self) -> TimeFormat {
+        TimeFormat::new(
+            self.project.time_sig_numerator,
+            self.project.time_sig_denominator,
+        )
+    }
+
+    /// Returns the notes of the selected track shown in the event list,
+    /// filtered and sorted.
+    pub fn event_rows(&self) -> Vec<&Note> {
+        let Some(track) = self.selected_track() else {
+            return Vec::new();
+        };
+        let format = self.time_format();
+        let list = &self.event_list;
+        let mut rows: Vec<&Note> = track
+            .notes()
+            .iter()
+            .filter(|n| list.filter.matches(n, &format))
+            .collect();
+        rows.sort_by(|a, b| list.sort.compare(a, b));
+        rows
+    }
+
+    /// Returns the ID of the note in the highlighted event list row.
+    fn event_row_id(&self) -> Option<NoteId> {
+        self.event_rows().get(self.event_list.row).map(|n| n.id)
+    }
+
+    /// Keeps the highlighted row on a note after the rows changed order.
+    fn event_list_follow(&mut self, id: NoteId) {
+        if let Some(row) = self.event_rows().iter().position(|n| n.id == id) {
+            self.event_list.row = row;
+        }
+    }
+
+    /// Moves the highlighted row, moving the cursor to its note.
+    ///
+    /// # Arguments
+    ///
+    /// * `rows` - Rows to move down (negative moves up)
+    /// * `extend` - Add the rows passed over to the selection
+    pub fn event_list_move(&mut self, rows: i32, extend: bool) {
+        let count = self.event_rows().len();
+        if count == 0 {
+            self.event_list.row = 0;
+            return;
+        }
+        let from = self.event_list.row.min(count - 1);
+        let to = (from as i64 + rows as i64).clamp(0, count as i64 - 1) as usize;
+        if extend {
+            let ids: Vec<NoteId> = self.event_rows()[from.min(to)..=from.max(to)]
+                .iter()
+                .map(|n| n.id)
+                .collect();
+            self.selected_notes.extend(ids);
+        }
+        self.event_list.row = to;
+        if let Some((tick, pitch)) = self.event_rows().get(to).map(|n| (n.start_tick, n.pitch)) {
+            if !self.audio.is_playing() {
+                self.cursor_tick = tick;
+            }
+            self.cursor_pitch = pitch;
+        }
+    }
+
+    /// Moves the highlighted column.
+    pub fn event_list_column(&mut self, delta: i32) {
+        self.event_list.column = self.event_list.column.offset(delta);
+    }
+
+    /// Sorts by the highlighted column, reversing the order if already sorted by it.
+    pub fn event_list_sort(&mut self) {
+        let id = self.event_row_id();
+        let sort = &mut self.event_list.sort;
+        if sort.column == self.event_list.column {
+            sort.descending = !sort.descending;
+        } else {
+            sort.column = self.event_list.column;
+            sort.descending = false;
+        }
+        if let Some(id) = id {
+            self.event_list_follow(id);
+        }
+        let sort = self.event_list.sort;
+        self.set_status(format!(
+            "Sorted by {} ({})",
+            sort.column.title().to_lowercase(),
+            if sort.descending {
+                "descending"
+            } else {
+                "ascending"
+            }
+        ));
+    }
+
+    /// Adds or removes the highlighted note from the selection.
+    pub fn event_list_toggle_select(&mut self) {
+        if let Some(id) = self.event_row_id() {
+            if !self.selected_notes.remove(&id) {
+                self.selected_notes.insert(id);
+            }
+        }
+    }
+
+    /// Selects every listed note, or clears the selection if all are selected.
+    pub fn event_list_select_all(&mut self) {
+        let ids: Vec<NoteId> = self.event_rows().iter().map(|n| n.id).collect();
+        if ids.iter().all(|id| self.selected_notes.contains(id)) {
+            self.selected_notes.clear();
+            self.set_status("Selection cleared");
+        } else {
+            self.set_status(format!("Selected {} notes", ids.len()));
+            self.selected_notes.extend(ids);
+        }
+    }
+
+    /// Returns the notes an event list edit applies to: the selection if the
+    /// highlighted note is part of it, otherwise the highlighted note.
+    fn event_edit_targets(&self) -> Vec<NoteId> {
+        match self.event_row_id() {
+            Some(id) if self.selected_notes.contains(&id) => {
+                self.selected_notes.iter().copied().collect()
+            }
+            Some(id) => vec![id],
+            None => Vec::new(),
+        }
+    }
+
+    /// Applies an edit to the event list targets with undo support.
+    fn edit_event_notes(&mut self, description: &str, edit: impl Fn(&mut Note)) {
+        let targets = self.event_edit_targets();
+        let Some(current) = self.event_row_id() else {
+            return;
+        };
+        self.save_state(description);
+        if let Some(track) = self.selected_track_mut() {
+            for note in track.notes_mut() {
+                if targets.contains(&note.id) {
+                    edit(note);
+                }
+            }
+            track.sort_notes();
+        }
+        self.event_list_follow(current);
+        self.mark_modified();
+    }
+
+    /// Nudges the highlighted cell by its smallest step (one tick, semitone
+    /// or velocity unit).
+    pub fn event_list_nudge(&mut self, delta: i32) {
+        let description = match self.event_list.column {
+            EventColumn::Position => "Nudge note position",
+            EventColumn::Pitch => "Nudge note pitch",
+            EventColumn::Velocity => "Nudge note velocity",
+            EventColumn::Length => "Nudge note length",
+        };
+        let column = self.event_list.column;
+        self.edit_event_notes(description, |note| match column {
+            EventColumn::Position => {
+                note.start_tick = (note.start_tick as i64 + delta as i64).max(0) as u32;
+            }
+            EventColumn::Pitch => {
+                note.pitch = (note.pitch as i32 + delta).clamp(0, 127) as u8;
+            }
+            EventColumn::Velocity => {
+                note.velocity = (note.velocity as i32 + delta).clamp(1, 127) as u8;
+            }
+            EventColumn::Length => {
+                note.duration_ticks = (note.duration_ticks as i64 + delta as i64).max(1) as u32;
+            }
+        });
+    }
+
+    /// Deletes the event list targets.
+    pub fn event_list_delete(&mut self) {
+        let targets = self.event_edit_targets();
+        if targets.is_empty() {
+            return;
+        }
+        self.save_state("Delete notes");
+        if let Some(track) = self.selected_track_mut() {
+            for id in &targets {
+                track.remove_note(*id);
+            }
+        }
+        for id in &targets {
+            self.selected_notes.remove(id);
+        }
+        let count = self.event_rows().len();
+        self.event_list.row = self.event_list.row.min(count.saturating_sub(1));
+        self.set_status(format!("Deleted {} notes", targets.len()));
+        self.mark_modified();
+    }
+
+    /// Returns the text of a cell of a note.
+    pub fn event_cell_text(&self, note: &Note, column: EventColumn) -> String {
+        let format = self.time_format();
+        match column {
+            EventColumn::Position => format.position(note.start_tick),
+            EventColumn::Pitch => note_to_name(note.pitch),
+            EventColumn::Velocity => note.velocity.to_string(),
+            EventColumn::Length => format.length(note.duration_ticks),
+        }
+    }
+
+    /// Opens the event list text field for the highlighted cell or the filter.
+    pub fn event_list_start_input(&mut self, kind: EventListInput) {
+        let buffer = match kind {
+            EventListInput::Cell => {
+                let rows = self.event_rows();
+                let Some(note) = rows.get(self.event_list.row) else {
+                    return;
+                };
+                self.event_cell_text(note, self.event_list.column)
+            }
+            EventListInput::Filter => String::new(),
+        };
+        self.event_list.input = Some((kind, buffer));
+    }
+
+    /// Adds a character to the event list text field.
+    pub fn event_list_input_char(&mut self, c: char) {
+        if let Some((_, buffer)) = &mut self.event_list.input {
+            buffer.push(c);
+        }
+    }
+
+    /// Removes the last character from the event list text field.
+    pub fn event_list_input_backspace(&mut self) {
+        if let Some((_, buffer)) = &mut self.event_list.input {
+            buffer.pop();
+        }
+    }
+
+    /// Closes the event list text field without applying it.
+    pub fn event_list_input_cancel(&mut self) {
+        self.event_list.input = None;
+    }
+
+    /// Applies the event list text field.
+    ///
+    /// Positions and pitches of several selected notes move by the same
+    /// offset as the highlighted note; velocities and lengths are set.
+    pub fn event_list_input_confirm(&mut self) {
+        let Some((kind, buffer)) = self.event_list.input.take() else {
+            return;
+        };
+        let text = buffer.trim();
+        if kind == EventListInput::Filter {
+            match EventFilter::parse(text) {
+                Some(filter) => {
+                    let id = self.event_row_id();
+                    self.set_status(if filter.is_empty() {
+                        "Filter cleared".to_string()
+                    } else {
+                        format!("Filter: {}", filter.describe())
+                    });
+                    self.event_list.filter = filter;
+                    self.event_list.row = 0;
+                    if let Some(id) = id {
+                        self.event_list_follow(id);
+                    }
+                }
+                None => self.set_status(format!("Invalid filter: {}", text)),
+            }
+            return;
+        }
+
+        let current = self
+            .event_rows()
+            .get(self.event_list.row)
+            .map(|n| (n.start_tick, n.pitch));
+        let Some((current_tick, current_pitch)) = current else {
+            return;
+        };
+        let format = self.time_format();
+        match self.event_list.column {
+            EventColumn::Position => match format.parse_position(text) {
+                Some(tick) => {
+                    let offset = tick as i64 - current_tick as i64;
+                    self.edit_event_notes("Set note position", |note| {
+                        note.start_tick = (note.start_tick as i64 + offset).max(0) as u32;
+                    });
+                }
+                None => self.set_status(format!("Invalid position: {} (measure:beat:tick)", text)),
+            },
+            EventColumn::Pitch => match name_to_note(text).or_else(|| text.parse().ok()) {
+                Some(pitch) if pitch <= 127 => {
+                    let offset = pitch as i32 - current_pitch as i32;
+                    self.edit_event_notes("Set note pitch", |note| {
+                        note.pitch = (note.pitch as i32 + offset).clamp(0, 127) as u8;
+                    });
+                }
+                _ => self.set_status(format!("Invalid pitch: {} (e.g. C#4 or 61)", text)),
+            },
+            EventColumn::Velocity => match text.parse::<u8>() {
+                Ok(velocity) if (1..=127).contains(&velocity) => {
+                    self.edit_event_notes("Set note velocity", |note| note.velocity = velocity);
+                }
+                _ => self.set_status(format!("Invalid velocity: {} (1-127)", text)),
+            },
+            EventColumn::Length => match format.parse_length(text) {
+                Some(length) => {
+                    self.edit_event_notes("Set note length", |note| note.duration_ticks = length);
+                }
+                None => self.set_status(format!("Invalid length: {} (beats:ticks or ticks)", text)),
+            },
+        }
+    }
+
+    /// Highlights the event list row under a mouse click.
+    ///
+    /// Shift+click adds the rows between the highlighted row and the clicked
+    /// one to the selection.
+    fn handle_event_list_click(&mut self, x: u16, y: u16, shift_held: bool) {
+        if !self.layout.is_in_event_rows(x, y) {
+            return;
+        }
+        let row = self.layout.event_first_row + (y - self.layout.event_rows.y) as usize;
+        if row < self.event_rows().len() {
+            let delta = row as i32 - self.event_list.row as i32;
+            self.event_list_move(delta, shift_held);
+        }
+    }
+
+    // ==================== Staff View ====================
+
+    /// Seeks to the sixteenth under a mouse click in the staff view.
+    fn handle_staff_click(&mut self, x: u16, y: u16) {
+        let staff = self.layout.staff;
+        if !self.layout.is_in_staff(x, y) || staff.measure_width == 0 {
+            return;
+        }
+        let column = x - staff.measures.x;
+        let measure = staff.first_measure + (column / staff.measure_width) as u32;
+        // The first column of each measure is its barline
+        let slot = ((column % staff.measure_width).saturating_sub(1) / staff.slot_width) as u32;
+        let tick = measure * self.project.ticks_per_measure() + slot * SLOT_TICKS;
+        self.seek_to_tick(tick);
+        self.set_status(format!("Seek to bar {}", measure + 1));
+    }
+
+    // ==================== Clips ====================
+
+    /// Turns the selected notes into a pattern with a clip in their place.
+    pub fn create_clip_from_selection(&mut self) {
+        if self.selected_notes.is_empty() {
+            self.set_status("Select notes to make a clip");
+            return;
+        }
+        let track_index = self.selected_track_index;
+        let ids = self.selected_notes.clone();
+
+        self.save_state("Create clip");
+        let Some(clip_index) = self.project.create_clip_from_notes(track_index, &ids) else {
+            return;
+        };
+        self.selected_notes.clear();
+        self.edit_mode = EditMode::Normal;
+        let name = self.clip_pattern_name(track_index, clip_index);
+        self.set_status(format!("Created clip '{}'", name));
+        self.mark_modified();
+    }
+
+    /// Returns the index of the clip under the cursor on the selected track.
+    fn clip_at_cursor(&mut self) -> Option<usize> {
+        let index = self
+            .project
+            .clip_at(self.selected_track_index, self.cursor_tick);
+        if index.is_none() {
+            self.set_status("No clip at cursor");
+        }
+        index
+    }
+
+    /// Returns the pattern name of a clip, for status messages.
+    fn clip_pattern_name(&self, track_index: usize, clip_index: usize) -> String {
+        self.project
+            .track_at(track_index)
+            .and_then(|t| t.clips().get(clip_index))
+            .and_then(|c| self.project.pattern(c.pattern_id))
+            .map(|p| p.name.clone())
+            .unwrap_or_default()
+    }
+
+    /// Duplicates the clip under the cursor, placing the copy right after it.
+    ///
+    /// # Arguments
+    ///
+    /// * `linked` - Share the pattern with the original instead of copying it
+    pub fn duplicate_clip_at_cursor(&mut self, linked: bool) {
+        let Some(clip_index) = self.clip_at_cursor() else {
+            return;
+        };
+        let track_index = self.selected_track_index;
+        self.save_state(if linked {
+            "Duplicate clip (linked)"
+        } else {
+            "Duplicate clip"
+        });
+        if let Some(new_index) = self.project.duplicate_clip(track_index, clip_index, linked) {
+            if let Some(clip) = self.project.tracks()[track_index].clips().get(new_index) {
+                self.cursor_tick = clip.start_tick;
+            }
+            self.set_status(if linked {
+                "Added linked copy of clip"
+            } else {
+                "Added copy of clip"
+            });
+        }
+        self.mark_modified();
+    }
+
+    /// Gives the clip under the cursor its own copy of a shared pattern.
+    pub fn make_clip_unique_at_cursor(&mut self) {
+        let Some(clip_index) = self.clip_at_cursor() else {
+            return;
+        };
+        let track_index = self.selected_track_index;
+        let Some(pattern_id) = self.project.tracks()[track_index]
+            .clips()
+            .get(clip_index)
+            .map(|c| c.pattern_id)
+        else {
+            return;
+        };
+        if self.project.pattern_use_count(pattern_id) < 2 {
+            self.set_status("Clip is already unique");
+            return;
+        }
+        self.save_state("Make clip unique");
+        self.project.make_clip_unique(track_index, clip_index);
+        self.set_status("Clip is now unique");
+        self.mark_modified();
+    }
+
+    /// Deletes the clip under the cursor.
+    pub fn delete_clip_at_cursor(&mut self) {
+        let Some(clip_index) = self.clip_at_cursor() else {
+            return;
+        };
+        let track_index = self.selected_track_index;
+        let name = self.clip_pattern_name(track_index, clip_index);
+        self.save_state("Delete clip");
+        self.project.remove_clip(track_index, clip_index);
+        self.set_status(format!("Deleted clip '{}'", name));
+        self.mark_modified();
+    }
+
+    /// Moves the clip under the cursor by one measure.
+    ///
+    /// # Arguments
+    ///
+    /// * `direction` - Negative to move earlier, positive to move later
+    pub fn move_clip_at_cursor(&mut self, direction: i32) {
+        let Some(clip_index) = self.clip_at_cursor() else {
+            return;
+        };
+        let track_index = self.selected_track_index;
+        let delta = direction as i64 * self.project.ticks_per_measure() as i64;
+        self.save_state("Move clip");
+        if let Some(new_index) = self.project.move_clip(track_index, clip_index, delta) {
+            if let Some(clip) = self.project.tracks()[track_index].clips().get(new_index) {
+                self.cursor_tick = clip.start_tick;
+            }
+        }
+        self.mark_modified();
+    }
+
+    /// Opens the clip under the cursor for note editing, or closes the open clip.
+    ///
+    /// While a clip is open its notes are regular notes on the track; closing
+    /// it writes them back to the pattern, updating every linked copy.
+    pub fn toggle_clip_edit(&mut self) {
+        let track_index = self.selected_track_index;
+        let is_open = self
+            .project
+            .track_at(track_index)
+            .is_some_and(|t| t.open_clip.is_some());
+
+        if is_open {
+            self.save_state("Close clip");
+            self.project.close_clip(track_index);
+            self.selected_notes.clear();
+            self.set_status("Closed clip");
+            self.mark_modified();
+            return;
+        }
+
+        let Some(clip_index) = self.clip_at_cursor() else {
+            return;
+        };
+        let name = self.clip_pattern_name(track_index, clip_index);
+        self.save_state("Open clip");
+        self.project.open_clip(track_index, clip_index);
+        self.set_status(format!("Editing clip '{}' (E to close)", name));
+        self.mark_modified();
+    }
+
+    // ==================== Lyric Entry ====================
+
+    /// Enters Lyric mode at the first note at or after the cursor.
+    ///
+    /// Syllables are typed one per note; the cursor advances note-by-note.
+    pub fn enter_lyric_mode(&mut self) {
+        let cursor_tick = self.cursor_tick;
+        let target = self.selected_track().and_then(|track| {
+            let notes = track.notes();
+            notes
+                .iter()
+                .find(|n| n.start_tick >= cursor_tick)
+                .or_else(|| notes.first())
+                .map(|n| n.start_tick)
+        });
+
+        let Some(tick) = target else {
+            self.set_status("No notes on this track to attach lyrics to");
+            return;
+        };
 
-        // Configure audio for the default track
-        if let Some(track) = self.project.track_at(0) {
-            self.audio.configure_track(track);
-        }
+        self.edit_mode = EditMode::Lyric;
+        self.lyric_line_break = false;
+        self.move_lyric_cursor(tick);
+        self.set_status("LYRIC mode - Space/- next note, Enter new line, Esc done");
+    }
 
-        self.set_status("New project created");
+    /// Leaves Lyric mode, storing the syllable being typed.
+    pub fn exit_lyric_mode(&mut self) {
+        self.commit_lyric();
+        self.lyric_buffer.clear();
+        self.edit_mode = EditMode::Normal;
     }
 
-    /// Seeks playback to a specific tick position.
-    ///
-    /// Updates the cursor position and, if playing, adjusts the playback
-    /// position to the new tick. This is called when clicking on time rulers.
+    /// Appends a character to the syllable being typed.
+    pub fn lyric_input(&mut self, c: char) {
+        self.lyric_buffer.push(c);
+    }
+
+    /// Deletes the last character, or steps back to the previous note when empty.
+    pub fn lyric_backspace(&mut self) {
+        if self.lyric_buffer.pop().is_none() {
+            self.lyric_prev_note();
+        }
+    }
+
+    /// Stores the syllable and advances to the next note.
     ///
     /// # Arguments
     ///
-    /// * `tick` - The tick position to seek to
-    pub fn seek_to_tick(&mut self, tick: u32) {
-        // Update cursor position
-        self.cursor_tick = tick;
+    /// * `hyphenate` - Join the syllable to the next one (mid-word)
+    pub fn lyric_advance(&mut self, hyphenate: bool) {
+        if hyphenate && !self.lyric_buffer.is_empty() && !self.lyric_buffer.ends_with('-') {
+            self.lyric_buffer.push('-');
+        }
+        self.lyric_next_note();
+    }
 
-        // If playing, update the playback position
-        if self.audio.is_playing() {
-            // Stop all currently playing notes to avoid hanging notes
-            self.audio.all_notes_off(true);
+    /// Stores the syllable and starts a new lyric line at the next note.
+    pub fn lyric_new_line(&mut self) {
+        self.lyric_next_note();
+        self.lyric_line_break = true;
+    }
 
-            // Reset playback timing to the new position
-            self.playback_start_time = Some(Instant::now());
-            self.playback_start_tick = tick;
-            self.last_sequencer_tick = Some(tick);
-            self.audio.set_position_ticks(tick);
-        } else {
-            // Even when stopped, update the audio position so playback
-            // will start from this position
-            self.audio.set_position_ticks(tick);
+    /// Stores the syllable and moves to the next note without changing it.
+    pub fn lyric_next_note(&mut self) {
+        self.commit_lyric();
+        let cursor_tick = self.cursor_tick;
+        match self
+            .selected_track()
+            .and_then(|t| t.next_note_start_after(cursor_tick))
+        {
+            Some(tick) => self.move_lyric_cursor(tick),
+            None => {
+                self.lyric_buffer.clear();
+                self.edit_mode = EditMode::Normal;
+                self.set_status("Reached the last note - lyrics done");
+            }
         }
+    }
 
-        // Adjust scroll to keep cursor in view
-        let visible_cols = if self.layout.piano_roll_grid.width > 0 {
-            self.layout.piano_roll_grid.width as u32
-        } else {
-            60
+    /// Stores the syllable and moves to the previous note.
+    pub fn lyric_prev_note(&mut self) {
+        self.commit_lyric();
+        let cursor_tick = self.cursor_tick;
+        if let Some(tick) = self
+            .selected_track()
+            .and_then(|t| t.prev_note_start_before(cursor_tick))
+        {
+            self.move_lyric_cursor(tick);
+        }
+    }
+
+    /// Moves the cursor to a note start and loads its existing syllable for editing.
+    fn move_lyric_cursor(&mut self, tick: u32) {
+        let delta = tick as i64 - self.cursor_tick as i64;
+        self.move_cursor_horizontal(delta as i32);
+        self.lyric_buffer = self
+            .selected_track()
+            .and_then(|t| t.lyric_at(tick))
+            .map(|l| l.text.clone())
+            .unwrap_or_default();
+    }
+
+    /// Writes the typed syllable to the note at the cursor (or removes it if empty).
+    fn commit_lyric(&mut self) {
+        let tick = self.cursor_tick;
+        let text = self.lyric_buffer.trim().to_string();
+        let line_break = std::mem::take(&mut self.lyric_line_break);
+
+        let Some(track) = self.selected_track() else {
+            return;
         };
-        let visible_ticks = self.zoom * visible_cols;
+        let existing = track.lyric_at(tick).cloned();
+        let is_first = track.lyrics().first().is_none_or(|l| l.tick >= tick);
 
-        // Center the cursor if it's out of view
-        if tick < self.scroll_x || tick >= self.scroll_x + visible_ticks {
-            self.scroll_x = tick.saturating_sub(visible_ticks / 2);
+        if text.is_empty() {
+            if existing.is_some() {
+                self.save_state("Remove lyric");
+                if let Some(track) = self.selected_track_mut() {
+                    track.remove_lyric_at(tick);
+                }
+                self.mark_modified();
+            }
+            return;
         }
 
-        // Show position in status
-        let (measure, beat, sub_tick) = self.project.tick_to_position(tick);
-        self.set_status(format!("Seek to {}:{:02}:{:03}", measure, beat, sub_tick));
-    }
+        let line_start = line_break || is_first || existing.as_ref().is_some_and(|l| l.line_start);
+        let lyric = Lyric::new(tick, text, line_start);
+        if existing.as_ref() == Some(&lyric) {
+            return;
+        }
 
-    /// Moves the cursor by a number of ticks.
-    pub fn move_cursor_horizontal(&mut self, ticks: i32) {
-        if ticks < 0 {
-            self.cursor_tick = self.cursor_tick.saturating_sub((-ticks) as u32);
-        } else {
-            self.cursor_tick = self.cursor_tick.saturating_add(ticks as u32);
+        self.save_state("Enter lyric");
+        if let Some(track) = self.selected_track_mut() {
+            track.set_lyric(lyric);
         }
+        self.mark_modified();
+    }
 
-        // Scroll if cursor is out of view
-        let visible_ticks = self.zoom * 80;
-        if self.cursor_tick < self.scroll_x {
-            self.scroll_x = self.cursor_tick;
-        } else if self.cursor_tick > self.scroll_x + visible_ticks {
-            self.scroll_x = self.cursor_tick.saturating_sub(visible_ticks);
+    /// Returns the index of the track whose lyrics are shown in the karaoke strip.
+    ///
+    /// Prefers the selected track (always in Lyric mode), otherwise the first
+    /// track with lyrics.
+    pub fn lyrics_track_index(&self) -> Option<usize> {
+        let selected_has_lyrics = self.selected_track().is_some_and(|t| t.has_lyrics());
+        if self.edit_mode == EditMode::Lyric || selected_has_lyrics {
+            return Some(self.selected_track_index);
         }
+        self.project.tracks().iter().position(|t| t.has_lyrics())
     }
 
-    /// Moves the cursor pitch up or down.
-    pub fn move_cursor_vertical(&mut self, semitones: i8) {
-        let new_pitch = self.cursor_pitch as i16 + semitones as i16;
-        if (0..=127).contains(&new_pitch) {
-            self.cursor_pitch = new_pitch as u8;
+    // ==================== Registers & Marks ====================
 
-            // Scroll if cursor is out of view (use dynamic visible_pitches)
-            let visible = self.layout.visible_pitches.max(1);
-            if self.cursor_pitch < self.scroll_y {
-                self.scroll_y = self.cursor_pitch;
-            } else if self.cursor_pitch > self.scroll_y + visible {
-                self.scroll_y = self.cursor_pitch.saturating_sub(visible);
+    /// Copies the selected notes, or the note under the cursor, into a
+    /// register.
+    pub fn yank_notes(&mut self, register: char) {
+        let Some(track) = self.selected_track() else {
+            return;
+        };
+        let (cursor_tick, cursor_pitch) = (self.cursor_tick, self.cursor_pitch);
+        let yanked = if self.selected_notes.is_empty() {
+            NoteRegister::from_notes(
+                track
+                    .notes()
+                    .iter()
+                    .find(|n| n.pitch == cursor_pitch && n.is_active_at(cursor_tick)),
+            )
+        } else {
+            NoteRegister::from_notes(
+                track
+                    .notes()
+                    .iter()
+                    .filter(|n| self.selected_notes.contains(&n.id)),
+            )
+        };
+        match yanked {
+            Some(yanked) => {
+                self.set_status(format!(
+                    "Yanked {} notes into \"{}",
+                    yanked.note_count(),
+                    register
+                ));
+                self.store_register(register, yanked);
             }
+            None => self.set_status("Nothing to yank"),
         }
     }
 
-    /// Places a note at the current cursor position.
-    pub fn place_note(&mut self) {
-        // Copy values to avoid borrow checker issues
-        let cursor_pitch = self.cursor_pitch;
-        let cursor_tick = self.cursor_tick;
-
-        // Get channel before mutable borrow
-        let channel = self.selected_track().map(|t| t.channel).unwrap_or(0);
-
-        self.save_state("Place note");
-        let note_id = self.selected_track_mut().map(|track| {
-            track.create_note(
-                cursor_pitch,
-                DEFAULT_VELOCITY,
-                cursor_tick,
-                DEFAULT_NOTE_DURATION,
+    /// Deletes the selected notes, keeping them in a register.
+    pub fn delete_selection(&mut self, register: char) {
+        if self.selected_notes.is_empty() {
+            return;
+        }
+        if let Some(deleted) = self.selected_track().and_then(|track| {
+            NoteRegister::from_notes(
+                track
+                    .notes()
+                    .iter()
+                    .filter(|n| self.selected_notes.contains(&n.id)),
             )
-        });
-
-        // Register the note for blue highlighting and auto-scroll
-        if let Some(id) = note_id {
-            self.register_added_note(id, cursor_pitch, cursor_tick);
+        }) {
+            self.store_register(register, deleted);
+        }
+        self.save_state("Delete selected notes");
+        // Collect IDs first to avoid borrow conflicts
+        let ids_to_delete: Vec<_> = self.selected_notes.drain().collect();
+        let count = ids_to_delete.len();
+        if let Some(track) = self.selected_track_mut() {
+            for id in ids_to_delete {
+                track.remove_note(id);
+            }
         }
+        self.set_status(format!("Deleted {} notes", count));
+        self.mark_modified();
+    }
 
-        // Play the note audio as feedback (short preview)
-        self.audio.note_on(channel, cursor_pitch, DEFAULT_VELOCITY);
-        // Schedule note off after a short duration (handled by held_notes system isn't
-        // ideal here, so we'll just trigger a short note - the audio engine handles it)
+    /// Stores notes in a register. Named registers also fill the unnamed
+    /// one, so a plain `p` pastes the last yank.
+    fn store_register(&mut self, register: char, notes: NoteRegister) {
+        if register != UNNAMED_REGISTER {
+            self.registers.insert(UNNAMED_REGISTER, notes.clone());
+        }
+        self.registers.insert(register, notes);
+    }
 
+    /// Pastes a register at the cursor and selects the pasted notes.
+    ///
+    /// The cursor moves to the end of the pasted notes, so repeated pastes
+    /// follow one another.
+    pub fn paste_notes(&mut self, register: char) {
+        let Some(notes) = self.registers.get(&register).cloned() else {
+            self.set_status(format!("Register \"{} is empty", register));
+            return;
+        };
+        if self.selected_track().is_none() {
+            return;
+        }
+        self.save_state("Paste notes");
+        let pasted = notes.notes_at(self.cursor_tick);
+        self.selected_notes = pasted.iter().map(|n| n.id).collect();
+        if let Some(track) = self.selected_track_mut() {
+            for note in pasted {
+                track.add_note(note);
+            }
+        }
+        self.move_cursor_horizontal(notes.length as i32);
         self.set_status(format!(
-            "Added {} at {}",
-            note_to_name(cursor_pitch),
-            cursor_tick / TICKS_PER_BEAT
+            "Pasted {} notes from \"{}",
+            notes.note_count(),
+            register
         ));
         self.mark_modified();
     }
 
-    /// Deletes the note under the cursor.
-    pub fn delete_note_at_cursor(&mut self) {
-        // Copy values to avoid borrow checker issues
-        let cursor_pitch = self.cursor_pitch;
-        let cursor_tick = self.cursor_tick;
+    /// Saves the cursor tick and pitch as a mark.
+    pub fn set_mark(&mut self, name: char) {
+        let mark = Mark {
+            tick: self.cursor_tick,
+            pitch: self.cursor_pitch,
+        };
+        self.marks.insert(name, mark);
+        self.set_status(format!("Mark '{}' set", name));
+    }
 
-        let note_id = self.selected_track().and_then(|track| {
-            track
-                .notes()
-                .iter()
-                .find(|n| n.pitch == cursor_pitch && n.is_active_at(cursor_tick))
-                .map(|n| n.id)
-        });
+    /// Moves the cursor to a saved mark.
+    pub fn jump_to_mark(&mut self, name: char) {
+        let Some(mark) = self.marks.get(&name).copied() else {
+            self.set_status(format!("Mark '{}' not set", name));
+            return;
+        };
+        self.seek_to_tick(mark.tick);
+        self.cursor_pitch = mark.pitch;
+        let visible = self.layout.visible_pitches.max(1);
+        if mark.pitch < self.scroll_y || mark.pitch > self.scroll_y + visible {
+            self.scroll_y = mark.pitch.saturating_sub(visible / 2);
+        }
+        self.set_status(format!("Mark '{}'", name));
+    }
 
-        if let Some(id) = note_id {
-            self.delete_note_by_id(id);
+    // ==================== Command Line ====================
+
+    /// Opens the `:` command line.
+    pub fn open_command_line(&mut self) {
+        self.command_line.open();
+    }
+
+    /// Types a character on the command line.
+    pub fn command_line_input(&mut self, c: char) {
+        self.command_line.insert(c);
+    }
+
+    /// Deletes the last character, closing the command line when it is empty.
+    pub fn command_line_backspace(&mut self) {
+        if !self.command_line.backspace() {
+            self.command_line.close();
         }
     }
 
-    /// Deletes a note by its ID.
+    /// Shows the previous (`delta < 0`) or next matching history entry.
+    pub fn command_line_history(&mut self, delta: i32) {
+        self.command_line.browse_history(delta);
+    }
+
+    /// Completes the command line, cycling on repeated presses.
+    pub fn command_line_complete(&mut self, delta: i32) {
+        let themes = crate::theme::available_themes();
+        self.command_line.complete(delta, &themes);
+    }
+
+    /// Closes the command line without running it.
+    pub fn command_line_cancel(&mut self) {
+        self.command_line.close();
+    }
+
+    /// Runs the typed command, reporting errors in the status line.
+    pub fn command_line_confirm(&mut self) {
+        if let Some(line) = self.command_line.submit() {
+            if let Err(e) = self.run_command(&line) {
+                self.set_status(e);
+            }
+        }
+    }
+
+    /// Parses and runs a command line command.
     ///
-    /// # Arguments
+    /// # Errors
     ///
-    /// * `note_id` - The ID of the note to delete
-    pub fn delete_note_by_id(&mut self, note_id: NoteId) {
-        self.save_state("Delete note");
-        if let Some(track) = self.selected_track_mut() {
-            track.remove_note(note_id);
+    /// Returns a message if the command is invalid or can't run
+    pub fn run_command(&mut self, line: &str) -> Result<(), String> {
+        match Command::parse(line)? {
+            Command::Tempo(bpm) => self.set_tempo(bpm),
+            Command::Goto(position) => {
+                let tick = self
+                    .time_format()
+                    .parse_position(&position)
+                    .ok_or_else(|| format!("Not a position: {}", position))?;
+                self.seek_to_tick(tick);
+            }
+            Command::Transpose(semitones) => {
+                if self.selected_notes.is_empty() {
+                    return Err("No notes selected".to_string());
+                }
+                self.transpose_selected_notes(semitones);
+                self.set_status(format!(
+                    "Transposed {} notes by {}",
+                    self.selected_notes.len(),
+                    semitones
+                ));
+            }
+            Command::Quantize { division, feel } => self.quantize_to_grid(division, feel)?,
+            Command::Program(program) => self.set_instrument(program),
+            Command::TrackAdd(name) => match name {
+                Some(name) => self.add_named_track(name),
+                None => self.add_track(),
+            },
+            Command::TrackDelete => self.delete_selected_track(),
+            Command::TrackRename(name) => self.rename_selected_track(name),
+            Command::Set { option, value } => self.set_option(&option, value.as_deref())?,
+            Command::Write(None) => {
+                self.save_project();
+            }
+            Command::Write(Some(path)) => {
+                let path = if path.extension().is_none() {
+                    path.with_extension("json")
+                } else {
+                    path
+                };
+                let format = SaveFormat::for_path(&path);
+                self.write_project(path, format);
+            }
+            Command::WriteQuit => {
+                if self.save_project() {
+                    self.quit_requested = true;
+                }
+            }
+            Command::Edit(path) => {
+                if !path.is_file() {
+                    return Err(format!("No such file: {}", path.display()));
+                }
+                self.guard_unsaved(GuardedAction::Load(path));
+            }
+            Command::Quit { force: false } => {
+                self.guard_unsaved(GuardedAction::Quit);
+            }
+            Command::Quit { force: true } => self.quit_requested = true,
         }
-        // Remove from selection if selected
-        self.selected_notes.remove(&note_id);
-        self.set_status("Deleted note");
+        Ok(())
+    }
+
+    /// Quantizes the selected notes (or the whole track) to a grid, keeping
+    /// the other quantize dialog settings.
+    fn quantize_to_grid(&mut self, division: u32, feel: GridFeel) -> Result<(), String> {
+        if self.selected_track().is_none_or(|t| t.notes().is_empty()) {
+            return Err("No notes to quantize".to_string());
+        }
+        let settings = QuantizeSettings {
+            division,
+            feel,
+            ..self.quantize_dialog.settings
+        };
+        let targets = (!self.selected_notes.is_empty()).then(|| self.selected_notes.clone());
+
+        self.save_state("Quantize");
+        let changed = self
+            .project
+            .track_at_mut(self.selected_track_index)
+            .map_or(0, |t| t.quantize_with(targets.as_ref(), &settings));
+        // The dialog opens on the grid used last
+        self.quantize_dialog.settings = settings;
+        self.set_status(format!(
+            "Quantized {} notes to {}",
+            changed,
+            settings.grid_label()
+        ));
         self.mark_modified();
+        Ok(())
+    }
+
+    /// Sets an option from `:set`, or shows its value when `value` is None.
+    fn set_option(&mut self, option: &str, value: Option<&str>) -> Result<(), String> {
+        let Some(value) = value else {
+            let current = match option {
+                "zoom" => self.zoom.to_string(),
+                "velocity" => self.config.default_velocity.to_string(),
+                "length" => self.config.default_note_duration.to_string(),
+                "octave" => self.octave_offset.to_string(),
+                _ => self.config.theme.clone(),
+            };
+            self.set_status(format!("{}={}", option, current));
+            return Ok(());
+        };
+
+        let invalid = || format!("Invalid {}: {}", option, value);
+        match option {
+            "zoom" => {
+                let zoom: u32 = value.parse().map_err(|_| invalid())?;
+                self.zoom = zoom.clamp(TICKS_PER_BEAT / 16, TICKS_PER_BEAT * 4);
+            }
+            "velocity" => {
+                self.config.default_velocity = value.parse().map_err(|_| invalid())?;
+                self.config.clamp();
+            }
+            "length" => {
+                self.config.default_note_duration =
+                    self.time_format().parse_length(value).ok_or_else(invalid)?;
+                self.config.clamp();
+            }
+            "octave" => match value.parse() {
+                Ok(octave) if (-4..=4).contains(&octave) => self.octave_offset = octave,
+                _ => return Err("Octave must be -4 to 4".to_string()),
+            },
+            _ => {
+                let previous = std::mem::replace(&mut self.config.theme, value.to_string());
+                match self.config.load_theme() {
+                    Ok(theme) => self.theme = theme,
+                    Err(e) => {
+                        self.config.theme = previous;
+                        return Err(e);
+                    }
+                }
+            }
+        }
+        self.set_option(option, None)
     }
 
     /// Changes the octave offset for keyboard input.
@@ -2298,12 +5933,23 @@ impl App {
     /// # Arguments
     ///
     /// * `delta` - Direction to cycle (+1 for next, -1 for previous)
+    pub fn cycle_instrument(&mut self, delta: i8) {
+        let Some(current) = self.selected_track().map(|t| t.program) else {
+            return;
+        };
+        // Wrap around: 0-127 (128 instruments in General MIDI)
+        let program = (current as i16 + delta as i16).rem_euclid(128) as u8;
+        self.set_instrument(program);
+    }
+
+    /// Sets the instrument (program) of the selected track.
     ///
     /// Changes the MIDI program number and updates the audio engine in real-time.
-    pub fn cycle_instrument(&mut self, delta: i8) {
-        if self.selected_track().is_some() {
-            self.save_state("Change instrument");
+    pub fn set_instrument(&mut self, program: u8) {
+        if self.selected_track().is_none() {
+            return;
         }
+        self.save_state("Change instrument");
 
         // Silence all currently playing notes before switching instruments.
         // This prevents notes from playing indefinitely with the old instrument.
@@ -2312,27 +5958,20 @@ impl App {
         self.release_all_notes();
         self.audio.all_notes_off(true);
 
-        // Get current program and calculate new program with wrapping
-        let (channel, new_program) = {
-            if let Some(track) = self.selected_track_mut() {
-                let current = track.program as i16;
-                // Wrap around: 0-127 (128 instruments in General MIDI)
-                let new_program = ((current + delta as i16).rem_euclid(128)) as u8;
-                track.program = new_program;
-                (track.channel, new_program)
-            } else {
-                return;
-            }
+        let Some(track) = self.selected_track_mut() else {
+            return;
         };
+        track.program = program;
+        let channel = track.channel;
 
         // Update the audio engine with the new program
-        self.audio.set_program(channel, new_program);
+        self.audio.set_program(channel, program);
 
         // Get the instrument name from the SoundFont (after mutable borrow is released)
-        let instrument_name = self.get_instrument_name(new_program);
+        let instrument_name = self.get_instrument_name(program);
 
         // Show status with instrument name
-        self.set_status(format!("Instrument: {} ({})", instrument_name, new_program));
+        self.set_status(format!("Instrument: {} ({})", instrument_name, program));
         self.mark_modified();
     }
 
@@ -2454,6 +6093,18 @@ impl App {
 
     /// Handles a click in the piano roll (native only).
     fn handle_piano_roll_click(&mut self, x: u16, y: u16, shift_held: bool) {
+        if self.view_mode == ViewMode::StepSequencer {
+            self.handle_step_grid_click(x, y);
+            return;
+        }
+        if self.view_mode == ViewMode::Staff {
+            self.handle_staff_click(x, y);
+            return;
+        }
+        if self.view_mode == ViewMode::EventList {
+            self.handle_event_list_click(x, y, shift_held);
+            return;
+        }
         let region = self.layout.piano_roll;
         let grid_region = self.layout.piano_roll_grid;
 
@@ -2538,16 +6189,19 @@ impl App {
                 .min(127);
 
             let channel = self.selected_track().map(|t| t.channel).unwrap_or(0);
-            self.audio.note_on(channel, pitch, DEFAULT_VELOCITY);
+            self.audio
+                .note_on(channel, pitch, self.config.default_velocity);
 
             // In Insert mode, also add the note at the current cursor position
             if self.edit_mode == EditMode::Insert {
                 self.cursor_pitch = pitch;
                 self.save_state("Insert note");
                 let cursor_tick = self.cursor_tick;
-                let note_id = self.selected_track_mut().map(|track| {
-                    track.create_note(pitch, DEFAULT_VELOCITY, cursor_tick, DEFAULT_NOTE_DURATION)
-                });
+                let velocity = self.config.default_velocity;
+                let duration = self.config.default_note_duration;
+                let note_id = self
+                    .selected_track_mut()
+                    .map(|track| track.create_note(pitch, velocity, cursor_tick, duration));
                 // Register the note for blue highlighting and auto-scroll
                 if let Some(id) = note_id {
                     self.register_added_note(id, pitch, cursor_tick);
@@ -2686,17 +6340,9 @@ impl App {
                 FocusedPanel::Timeline => {
                     // Scroll timeline adjusts tempo
                     if delta_y > 0 {
-                        self.save_state("Adjust tempo");
-                        self.project.tempo = (self.project.tempo + 1).min(300);
-                        self.audio.set_tempo(self.project.tempo);
-                        self.set_status(format!("Tempo: {} BPM", self.project.tempo));
-                        self.mark_modified();
+                        self.set_tempo(self.project.tempo + 1);
                     } else if delta_y < 0 {
-                        self.save_state("Adjust tempo");
-                        self.project.tempo = self.project.tempo.saturating_sub(1).max(20);
-                        self.audio.set_tempo(self.project.tempo);
-                        self.set_status(format!("Tempo: {} BPM", self.project.tempo));
-                        self.mark_modified();
+                        self.set_tempo(self.project.tempo.saturating_sub(1));
                     }
                 }
                 FocusedPanel::Keyboard => {
@@ -2713,6 +6359,17 @@ impl App {
 
     /// Handles mouse drag start (native only).
     pub fn handle_drag_start(&mut self, x: u16, y: u16, shift_held: bool) {
+        if self.layout.is_in_velocity_lane(x, y) {
+            let velocity = self.velocity_at_lane_row(y);
+            self.save_state("Draw velocity");
+            self.set_velocity_at_column(x, velocity);
+            self.set_status(format!("Velocity: {}", velocity));
+            self.drag_state = DragState::DrawingVelocity {
+                last_x: x,
+                last_velocity: velocity,
+            };
+            return;
+        }
         if self.layout.is_in_piano_roll_grid(x, y) {
             // Convert mouse coordinates to tick/pitch
             let grid_region = self.layout.piano_roll_grid;
@@ -2850,14 +6507,31 @@ impl App {
                     start_pitch: 0,
                 };
             }
+            DragState::DrawingVelocity {
+                last_x,
+                last_velocity,
+            } => {
+                let lane = self.layout.velocity_lane;
+                let x = x.clamp(lane.x, (lane.x + lane.width).saturating_sub(1));
+                let velocity = self.velocity_at_lane_row(y);
+                self.draw_velocity_line((last_x, last_velocity), (x, velocity));
+                self.set_status(format!("Velocity: {}", velocity));
+                self.drag_state = DragState::DrawingVelocity {
+                    last_x: x,
+                    last_velocity: velocity,
+                };
+            }
             DragState::None => {}
         }
     }
 
     /// Handles mouse drag end (native only).
     pub fn handle_drag_end(&mut self) {
-        // Mark modified if we were moving notes
-        if matches!(self.drag_state, DragState::MovingNotes { .. }) {
+        // Mark modified if we were moving notes or drawing velocities
+        if matches!(
+            self.drag_state,
+            DragState::MovingNotes { .. } | DragState::DrawingVelocity { .. }
+        ) {
             self.mark_modified();
         }
         self.drag_state = DragState::None;
@@ -8,6 +8,7 @@ use crate::midi::{ticks_to_seconds, Project, TICKS_PER_BEAT};
 use anyhow::{Context, Result};
 use hound::{SampleFormat, WavSpec, WavWriter};
 use rustysynth::{SoundFont, Synthesizer, SynthesizerSettings};
+use serde::{Deserialize, Serialize};
 use std::fs::File;
 use std::io::BufReader;
 use std::path::Path;
@@ -17,6 +18,33 @@ use std::sync::Arc;
 /// Larger buffers are more efficient but use more memory.
 const RENDER_BUFFER_SIZE: usize = 4096;
 
+/// How a WAV export is rendered.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WavOptions {
+    /// Sample rate of the file, in Hz.
+    pub sample_rate: u32,
+    /// Silence rendered after the last note so releases can ring out, in seconds.
+    pub tail_seconds: f64,
+}
+
+impl Default for WavOptions {
+    fn default() -> Self {
+        Self {
+            sample_rate: SAMPLE_RATE,
+            tail_seconds: 2.0,
+        }
+    }
+}
+
+impl WavOptions {
+    /// Keeps the options within the range the synthesizer supports.
+    pub fn clamp(&mut self) {
+        self.sample_rate = self.sample_rate.clamp(16000, 192000);
+        self.tail_seconds = self.tail_seconds.clamp(0.0, 30.0);
+    }
+}
+
 /// Exports a project to a WAV file (native only).
 ///
 /// Renders the entire project through the synthesizer and writes
@@ -27,6 +55,7 @@ const RENDER_BUFFER_SIZE: usize = 4096;
 /// * `project` - The project to export
 /// * `soundfont_path` - Path to the SoundFont file
 /// * `output_path` - Path for the output WAV file
+/// * `options` - Sample rate and release tail
 /// * `progress_callback` - Optional callback for progress updates (0.0 to 1.0)
 ///
 /// # Returns
@@ -43,6 +72,7 @@ pub fn export_to_wav<P1, P2, F>(
     project: &Project,
     soundfont_path: P1,
     output_path: P2,
+    options: &WavOptions,
     mut progress_callback: Option<F>,
 ) -> Result<()>
 where
@@ -61,18 +91,19 @@ where
             .map_err(|e| anyhow::anyhow!("Failed to load SoundFont: {:?}", e))?,
     );
 
-    let settings = SynthesizerSettings::new(SAMPLE_RATE as i32);
+    let sample_rate = options.sample_rate;
+    let settings = SynthesizerSettings::new(sample_rate as i32);
     let mut synth = Synthesizer::new(&soundfont, &settings)
         .map_err(|e| anyhow::anyhow!("Failed to create synthesizer: {:?}", e))?;
 
     // Calculate total duration with a small buffer at the end for note release
     let duration_ticks = project.duration_ticks();
-    let duration_seconds = ticks_to_seconds(duration_ticks, project.tempo) + 2.0; // 2 sec buffer
-    let total_samples = (duration_seconds * SAMPLE_RATE as f64) as usize;
+    let duration_seconds = ticks_to_seconds(duration_ticks, project.tempo) + options.tail_seconds;
+    let total_samples = (duration_seconds * sample_rate as f64) as usize;
 
     let spec = WavSpec {
         channels: 2,
-        sample_rate: SAMPLE_RATE,
+        sample_rate,
         bits_per_sample: 16,
         sample_format: SampleFormat::Int,
     };
@@ -123,7 +154,7 @@ where
             continue;
         }
 
-        for note in track.notes() {
+        for note in project.playback_notes(track) {
             events.push((
                 note.start_tick,
                 true,
@@ -144,7 +175,7 @@ where
     let mut current_sample = 0usize;
     let mut event_idx = 0usize;
     let samples_per_tick =
-        SAMPLE_RATE as f64 * 60.0 / (project.tempo as f64 * TICKS_PER_BEAT as f64);
+        sample_rate as f64 * 60.0 / (project.tempo as f64 * TICKS_PER_BEAT as f64);
 
     // Pre-compute event sample positions for sample-accurate timing.
     // This avoids timing drift at high BPMs where buffer boundaries
@@ -235,6 +266,13 @@ mod tests {
 
         std::fs::create_dir_all("test_output").unwrap();
 
-        export_to_wav(&project, sf_path, output_path, None::<fn(f32)>).unwrap();
+        export_to_wav(
+            &project,
+            sf_path,
+            output_path,
+            &WavOptions::default(),
+            None::<fn(f32)>,
+        )
+        .unwrap();
     }
 }
@@ -0,0 +1,548 @@
+//! Per-project autosave slots.
+//!
+//! Each project gets its own slot directory, named after its id, holding a
+//! rolling set of timestamped `.oxm` versions. Editing a second song no
+//! longer overwrites the first song's recovery point. Slots live in
+//! `autosave/` under the user's config directory (see
+//! [`crate::config::config_dir`]), or in `.autosave/` in the working
+//! directory if that can't be found.
+//!
+//! A version can carry the undo history it was written with in a
+//! `.history` file next to it, so a recovered project can still be undone.
+//! History files start with a header naming their layout; one written by a
+//! build with a different layout is discarded rather than misread.
+//!
+//! A slot is deleted once its project is saved or closed cleanly, and only
+//! the [`AUTOSAVE_SLOTS`] most recently written slots are kept. Versions
+//! older than the file their project was saved to are not offered for
+//! recovery.
+
+use crate::history::HistoryManager;
+use crate::midi::{Project, FORMAT_VERSION};
+use serde::{Deserialize, Serialize};
+use std::path::{Component, Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Number of versions kept per project.
+pub const AUTOSAVE_VERSIONS: usize = 5;
+
+/// Number of project slots kept; the least recently written are dropped.
+pub const AUTOSAVE_SLOTS: usize = 20;
+
+/// Minimum spacing of kept versions, in milliseconds.
+///
+/// A version written sooner after the one before it is replaced by the next
+/// autosave, so the rolling set spans more than the last few seconds of
+/// editing.
+pub const AUTOSAVE_VERSION_INTERVAL_MS: u128 = 60_000;
+
+/// Single autosave file written before slots existed.
+pub const LEGACY_AUTOSAVE_PATH: &str = ".autosave.oxm";
+
+/// Magic bytes at the start of every `.history` file.
+const HISTORY_MAGIC: [u8; 4] = *b"OXH\x1a";
+
+/// Layout version of `.history` files written by this build.
+///
+/// Bump it whenever [`HistoryManager`] or the diffs it stores change shape.
+/// The history also embeds project data, so the header records the project
+/// [`FORMAT_VERSION`] too and a change to either discards old histories.
+const HISTORY_VERSION: u32 = 1;
+
+/// Length of the `.history` header (magic bytes and both versions).
+const HISTORY_HEADER_LEN: usize = HISTORY_MAGIC.len() + 8;
+
+/// File in each slot describing where the project came from.
+const SLOT_INFO_FILE: &str = "slot.json";
+
+/// Metadata kept next to a slot's versions.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct SlotInfo {
+    /// File the project was loaded from or last saved to.
+    source: Option<PathBuf>,
+}
+
+/// A recoverable autosave version.
+#[derive(Debug, Clone)]
+pub struct AutosaveEntry {
+    /// The autosave file.
+    pub path: PathBuf,
+    /// Project name stored in the autosave.
+    pub name: String,
+    /// File the project was loaded from or saved to, if any.
+    pub source: Option<PathBuf>,
+    /// When the version was written (seconds since the Unix epoch).
+    pub saved_at: u64,
+    /// Number of tracks.
+    pub tracks: usize,
+    /// Number of notes across all tracks.
+    pub notes: usize,
+}
+
+/// The set of autosave slots on disk.
+#[derive(Debug, Clone)]
+pub struct AutosaveStore {
+    /// Directory holding one subdirectory per project.
+    dir: PathBuf,
+    /// Legacy single autosave file to list alongside the slots.
+    legacy: Option<PathBuf>,
+    /// Minimum spacing between kept versions, in milliseconds.
+    interval_ms: u128,
+    /// Number of slots kept.
+    max_slots: usize,
+}
+
+impl Default for AutosaveStore {
+    fn default() -> Self {
+        let dir = crate::config::config_dir()
+            .map(|dir| dir.join("autosave"))
+            .unwrap_or_else(|| PathBuf::from(".autosave"));
+        Self::new(
+            dir,
+            Some(PathBuf::from(LEGACY_AUTOSAVE_PATH)),
+            AUTOSAVE_VERSION_INTERVAL_MS,
+        )
+    }
+}
+
+impl AutosaveStore {
+    /// Creates a store rooted at `dir` keeping versions `interval_ms` apart.
+    pub fn new(dir: PathBuf, legacy: Option<PathBuf>, interval_ms: u128) -> Self {
+        Self {
+            dir,
+            legacy,
+            interval_ms,
+            max_slots: AUTOSAVE_SLOTS,
+        }
+    }
+
+    /// Writes a new version of `project` to its slot and drops the oldest
+    /// versions beyond [`AUTOSAVE_VERSIONS`] and slots beyond [`AUTOSAVE_SLOTS`].
+    ///
+    /// # Arguments
+    ///
+    /// * `project` - The project to save
+    /// * `source` - The file the project belongs to, if any
+    /// * `history` - Undo history to keep with the version, if any
+    pub fn save(
+        &self,
+        project: &Project,
+        source: Option<&Path>,
+        history: Option<&HistoryManager>,
+    ) -> std::io::Result<PathBuf> {
+        let slot = self.slot_dir(project.id())?;
+        std::fs::create_dir_all(&slot)?;
+
+        let millis = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_millis());
+        let path = slot.join(format!("{}.oxm", millis));
+        project.save_to_binary(&path)?;
+        if let Some(history) = history {
+            std::fs::write(history_path(&path), encode_history(history)?)?;
+        }
+
+        let info = SlotInfo {
+            source: source.map(Path::to_path_buf),
+        };
+        let json = serde_json::to_string_pretty(&info).map_err(std::io::Error::other)?;
+        std::fs::write(slot.join(SLOT_INFO_FILE), json)?;
+
+        let mut versions = slot_versions(&slot);
+        // The previous version is superseded if it was written too soon
+        // after the one before it, so kept versions stay spread out
+        if let (Some((previous, _)), Some((before, _))) = (versions.get(1), versions.get(2)) {
+            if previous.saturating_sub(*before) < self.interval_ms {
+                let (_, superseded) = versions.remove(1);
+                remove_version(&superseded);
+            }
+        }
+        for (_, old) in versions.iter().skip(AUTOSAVE_VERSIONS) {
+            remove_version(old);
+        }
+        self.prune_slots();
+        Ok(path)
+    }
+
+    /// Deletes the slot of a project, along with all its versions.
+    pub fn remove_slot(&self, id: &str) -> std::io::Result<()> {
+        match std::fs::remove_dir_all(self.slot_dir(id)?) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+
+    /// Deletes the least recently written slots beyond the slot limit.
+    fn prune_slots(&self) {
+        let mut slots: Vec<(u128, PathBuf)> = std::fs::read_dir(&self.dir)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .map(|e| e.path())
+            .filter(|p| p.is_dir())
+            .map(|slot| {
+                let newest = slot_versions(&slot).first().map_or(0, |v| v.0);
+                (newest, slot)
+            })
+            .collect();
+        slots.sort_by_key(|s| std::cmp::Reverse(s.0));
+        for (_, slot) in slots.iter().skip(self.max_slots) {
+            let _ = std::fs::remove_dir_all(slot);
+        }
+    }
+
+    /// Returns the slot directory for a project id.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the id isn't a single plain file name, so a slot
+    /// can never end up outside the autosave directory
+    fn slot_dir(&self, id: &str) -> std::io::Result<PathBuf> {
+        let mut components = Path::new(id).components();
+        match (components.next(), components.next()) {
+            (Some(Component::Normal(name)), None) => Ok(self.dir.join(name)),
+            _ => Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("invalid autosave slot name: {:?}", id),
+            )),
+        }
+    }
+
+    /// Lists every readable autosave version, newest first.
+    ///
+    /// Versions written before their project's file was last saved hold
+    /// nothing newer than that file and are left out.
+    pub fn list(&self) -> Vec<AutosaveEntry> {
+        let mut entries = Vec::new();
+        if let Ok(slots) = std::fs::read_dir(&self.dir) {
+            for slot in slots.flatten().map(|e| e.path()).filter(|p| p.is_dir()) {
+                let info: SlotInfo = std::fs::read_to_string(slot.join(SLOT_INFO_FILE))
+                    .ok()
+                    .and_then(|text| serde_json::from_str(&text).ok())
+                    .unwrap_or_default();
+                let saved_ms = info.source.as_deref().and_then(modified_ms).unwrap_or(0);
+                for (_, version) in slot_versions(&slot)
+                    .into_iter()
+                    .filter(|(millis, _)| *millis > saved_ms)
+                {
+                    if let Some(entry) = read_entry(&version, info.source.clone()) {
+                        entries.push(entry);
+                    }
+                }
+            }
+        }
+        if let Some(entry) = self.legacy.as_deref().and_then(|p| read_entry(p, None)) {
+            entries.push(entry);
+        }
+        entries.sort_by_key(|e| std::cmp::Reverse(e.saved_at));
+        entries
+    }
+
+    /// Deletes an autosave version, and its slot once it is empty.
+    pub fn remove(&self, entry: &AutosaveEntry) -> std::io::Result<()> {
+        std::fs::remove_file(&entry.path)?;
+        let _ = std::fs::remove_file(history_path(&entry.path));
+        if let Some(slot) = entry.path.parent().filter(|s| s.starts_with(&self.dir)) {
+            if slot_versions(slot).is_empty() {
+                let _ = std::fs::remove_dir_all(slot);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Loads the undo history kept with an autosave version.
+///
+/// # Returns
+///
+/// `Ok(None)` if the version has no history
+///
+/// # Errors
+///
+/// Returns an error if the history can't be read, such as one written by a
+/// build with a different history layout
+pub fn load_history(path: &Path) -> std::io::Result<Option<HistoryManager>> {
+    let data = match std::fs::read(history_path(path)) {
+        Ok(data) => data,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e),
+    };
+    decode_history(&data).map(Some)
+}
+
+/// Encodes undo history behind its header.
+fn encode_history(history: &HistoryManager) -> std::io::Result<Vec<u8>> {
+    let payload = bincode::serialize(history).map_err(std::io::Error::other)?;
+    let mut data = Vec::with_capacity(HISTORY_HEADER_LEN + payload.len());
+    data.extend_from_slice(&HISTORY_MAGIC);
+    data.extend_from_slice(&HISTORY_VERSION.to_le_bytes());
+    data.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+    data.extend_from_slice(&payload);
+    Ok(data)
+}
+
+/// Decodes undo history written by [`encode_history`] with this build's
+/// layout.
+fn decode_history(data: &[u8]) -> std::io::Result<HistoryManager> {
+    let invalid = |message: String| std::io::Error::new(std::io::ErrorKind::InvalidData, message);
+    let header = data
+        .get(..HISTORY_HEADER_LEN)
+        .filter(|h| h.starts_with(&HISTORY_MAGIC))
+        .ok_or_else(|| invalid("history file has no version header".to_string()))?;
+    let version = |at: usize| {
+        u32::from_le_bytes([header[at], header[at + 1], header[at + 2], header[at + 3]])
+    };
+    let (history_version, format_version) = (version(4), version(8));
+    if (history_version, format_version) != (HISTORY_VERSION, FORMAT_VERSION) {
+        return Err(invalid(format!(
+            "history was written with layout {}.{}, this build uses {}.{}",
+            history_version, format_version, HISTORY_VERSION, FORMAT_VERSION
+        )));
+    }
+    bincode::deserialize(&data[HISTORY_HEADER_LEN..]).map_err(|e| invalid(e.to_string()))
+}
+
+/// Returns the history file kept next to an autosave version.
+fn history_path(version: &Path) -> PathBuf {
+    version.with_extension("history")
+}
+
+/// Deletes an autosave version and its history.
+fn remove_version(version: &Path) {
+    let _ = std::fs::remove_file(version);
+    let _ = std::fs::remove_file(history_path(version));
+}
+
+/// Returns the version files of a slot with their save times, newest first.
+fn slot_versions(slot: &Path) -> Vec<(u128, PathBuf)> {
+    let mut versions: Vec<(u128, PathBuf)> = std::fs::read_dir(slot)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|e| e.path())
+        .filter_map(|path| {
+            let millis = path
+                .extension()
+                .filter(|ext| *ext == "oxm")
+                .and(path.file_stem())?
+                .to_str()?
+                .parse()
+                .ok()?;
+            Some((millis, path))
+        })
+        .collect();
+    versions.sort_by_key(|v| std::cmp::Reverse(v.0));
+    versions
+}
+
+/// Returns when a file was last modified, in milliseconds since the Unix epoch.
+fn modified_ms(path: &Path) -> Option<u128> {
+    let modified = std::fs::metadata(path).and_then(|m| m.modified()).ok()?;
+    Some(modified.duration_since(UNIX_EPOCH).ok()?.as_millis())
+}
+
+/// Loads an autosave file and summarizes it.
+fn read_entry(path: &Path, source: Option<PathBuf>) -> Option<AutosaveEntry> {
+    let project = Project::load_from_binary(path).ok()?;
+    // Slot versions are named by their save time; the legacy file is not
+    let saved_at = path
+        .file_stem()
+        .and_then(|stem| stem.to_str()?.parse::<u64>().ok())
+        .map(|millis| millis / 1000)
+        .or_else(|| modified_ms(path).map(|millis| (millis / 1000) as u64))
+        .unwrap_or(0);
+    Some(AutosaveEntry {
+        path: path.to_path_buf(),
+        name: project.name.clone(),
+        source,
+        saved_at,
+        tracks: project.track_count(),
+        notes: project.note_count(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::midi::Note;
+
+    /// Creates a project the way autosave writes it (with a SoundFont path).
+    fn project(name: &str) -> Project {
+        let mut project = Project::with_default_track(name);
+        project.set_soundfont_path(Some("test.sf2"));
+        project
+    }
+
+    /// Creates an empty store in a fresh temporary directory.
+    fn temp_store(name: &str) -> AutosaveStore {
+        let dir =
+            std::env::temp_dir().join(format!("miditui-autosave-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        AutosaveStore::new(dir, None, 0)
+    }
+
+    #[test]
+    fn test_slots_are_per_project_and_rolling() {
+        let store = temp_store("slots");
+        let mut song = project("Song");
+        let other = project("Other");
+
+        for i in 0..AUTOSAVE_VERSIONS + 2 {
+            song.track_at_mut(0)
+                .unwrap()
+                .add_note(Note::new(60, 100, i as u32 * 480, 480));
+            store
+                .save(&song, Some(Path::new("song.json")), None)
+                .unwrap();
+            std::thread::sleep(std::time::Duration::from_millis(2));
+        }
+        store.save(&other, None, None).unwrap();
+
+        let entries = store.list();
+        let song_entries: Vec<&AutosaveEntry> =
+            entries.iter().filter(|e| e.name == "Song").collect();
+        assert_eq!(song_entries.len(), AUTOSAVE_VERSIONS);
+        assert_eq!(entries.iter().filter(|e| e.name == "Other").count(), 1);
+
+        // The newest version of the song holds every note
+        let newest = song_entries.iter().max_by_key(|e| e.notes).unwrap();
+        assert_eq!(newest.notes, AUTOSAVE_VERSIONS + 2);
+        assert_eq!(newest.tracks, 1);
+        assert_eq!(newest.source.as_deref(), Some(Path::new("song.json")));
+
+        for entry in &entries {
+            store.remove(entry).unwrap();
+        }
+        assert!(store.list().is_empty());
+        let _ = std::fs::remove_dir_all(&store.dir);
+    }
+
+    #[test]
+    fn test_quick_autosaves_replace_newest_version() {
+        let mut store = temp_store("interval");
+        store.interval_ms = AUTOSAVE_VERSION_INTERVAL_MS;
+        let song = project("Song");
+
+        store.save(&song, None, None).unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        store.save(&song, None, None).unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        let newest = store.save(&song, None, None).unwrap();
+
+        // The first version is kept; the one in between was superseded
+        let entries = store.list();
+        assert_eq!(entries.len(), 2);
+        assert!(entries.iter().any(|e| e.path == newest));
+        let _ = std::fs::remove_dir_all(&store.dir);
+    }
+
+    #[test]
+    fn test_legacy_autosave_from_before_slots_is_listed() {
+        let mut store = temp_store("legacy");
+        std::fs::create_dir_all(&store.dir).unwrap();
+        // Written by miditui 0.1.8, the last release with a single autosave
+        let legacy = store.dir.join(LEGACY_AUTOSAVE_PATH);
+        std::fs::write(&legacy, include_bytes!("../tests/fixtures/v0_autosave.oxm")).unwrap();
+        store.legacy = Some(legacy.clone());
+
+        let entries = store.list();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].path, legacy);
+        assert_eq!(entries[0].name, "Fixture");
+        assert_eq!(entries[0].tracks, 2);
+        assert_eq!(entries[0].notes, 4);
+        let recovered = Project::load_from_binary(&entries[0].path).unwrap();
+        assert_eq!(recovered.tracks()[0].name, "Piano");
+        assert_eq!(
+            recovered.get_soundfont_path(),
+            Some("/usr/share/sounds/sf2/FluidR3_GM.sf2")
+        );
+        let _ = std::fs::remove_dir_all(&store.dir);
+    }
+
+    #[test]
+    fn test_history_is_kept_with_version() {
+        let store = temp_store("history");
+        let mut song = project("Song");
+        let mut history = HistoryManager::new();
+        history.record(&song, Default::default(), "Add note");
+        song.track_at_mut(0)
+            .unwrap()
+            .add_note(Note::new(60, 100, 0, 480));
+        history.commit(&song, &Default::default());
+
+        let path = store.save(&song, None, Some(&history)).unwrap();
+        let restored = load_history(&path).unwrap().unwrap();
+        assert_eq!(restored.entries().len(), 1);
+        assert_eq!(restored.entries()[0].description, "Add note");
+
+        let entry = store.list().remove(0);
+        store.remove(&entry).unwrap();
+        assert!(load_history(&path).unwrap().is_none());
+        let _ = std::fs::remove_dir_all(&store.dir);
+    }
+
+    #[test]
+    fn test_history_of_another_layout_is_reported() {
+        let history = HistoryManager::new();
+        let mut data = encode_history(&history).unwrap();
+        assert!(decode_history(&data).is_ok());
+
+        data[4..8].copy_from_slice(&(HISTORY_VERSION + 1).to_le_bytes());
+        let error = decode_history(&data).unwrap_err();
+        assert!(error.to_string().contains("layout"));
+
+        // Histories from before the header are refused, not misread
+        let unversioned = bincode::serialize(&history).unwrap();
+        assert!(decode_history(&unversioned).is_err());
+    }
+
+    #[test]
+    fn test_slot_names_stay_inside_the_store() {
+        let store = temp_store("names");
+        let id = Project::new("Song").id().to_string();
+        assert_eq!(store.slot_dir(&id).unwrap(), store.dir.join(&id));
+        for id in ["", "..", "../../x", "/tmp/x", "a/b", "."] {
+            assert!(store.slot_dir(id).is_err(), "{:?}", id);
+        }
+    }
+
+    #[test]
+    fn test_saved_and_surplus_slots_are_dropped() {
+        let mut store = temp_store("prune");
+        store.max_slots = 2;
+        let songs: Vec<Project> = (0..3).map(|i| project(&format!("Song {}", i))).collect();
+        for song in &songs {
+            store.save(song, None, None).unwrap();
+            std::thread::sleep(std::time::Duration::from_millis(2));
+        }
+        let mut names: Vec<String> = store.list().into_iter().map(|e| e.name).collect();
+        names.sort();
+        assert_eq!(names, vec!["Song 1", "Song 2"]);
+
+        store.remove_slot(songs[2].id()).unwrap();
+        store.remove_slot(songs[2].id()).unwrap();
+        assert_eq!(store.list().len(), 1);
+        let _ = std::fs::remove_dir_all(&store.dir);
+    }
+
+    #[test]
+    fn test_versions_older_than_the_saved_file_are_not_listed() {
+        let store = temp_store("saved");
+        std::fs::create_dir_all(&store.dir).unwrap();
+        let source = store.dir.join("song.json");
+        let file = std::fs::File::create(&source).unwrap();
+        let now = SystemTime::now();
+
+        file.set_modified(now - std::time::Duration::from_secs(60))
+            .unwrap();
+        store.save(&project("Song"), Some(&source), None).unwrap();
+        assert_eq!(store.list().len(), 1);
+
+        // Saved to the file after the autosave: nothing left to recover
+        file.set_modified(now + std::time::Duration::from_secs(60))
+            .unwrap();
+        assert!(store.list().is_empty());
+        let _ = std::fs::remove_dir_all(&store.dir);
+    }
+}
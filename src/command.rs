@@ -0,0 +1,518 @@
+//! The `:` command line.
+//!
+//! Commands are typed vim style (`:tempo 96`, `:goto 17:2`, `:w song.mid`)
+//! and reach things that would otherwise need a dialog. This module parses
+//! them and keeps the line being edited, with its history and tab
+//! completion; `App::run_command` carries them out.
+
+use crate::midi::{GridFeel, GRID_DIVISIONS};
+use std::path::{Path, PathBuf};
+
+/// Most entries kept in the command history.
+const HISTORY_LIMIT: usize = 100;
+
+/// Options `:set` knows.
+pub const SET_OPTIONS: [&str; 5] = ["length", "octave", "theme", "velocity", "zoom"];
+
+/// Subcommands of `:track`.
+const TRACK_ACTIONS: [&str; 3] = ["add", "delete", "rename"];
+
+/// File extensions `:e` and `:w` complete.
+const PROJECT_EXTENSIONS: [&str; 5] = ["oxm", "json", "mid", "midi", "kar"];
+
+/// A command as listed in the help and offered by completion.
+pub struct CommandSpec {
+    /// Name typed after the colon.
+    pub name: &'static str,
+    /// Arguments, for the help.
+    pub args: &'static str,
+    /// One-line description.
+    pub description: &'static str,
+}
+
+/// Every command, in help order.
+pub const COMMANDS: &[CommandSpec] = &[
+    CommandSpec {
+        name: "tempo",
+        args: "BPM",
+        description: "Set the tempo",
+    },
+    CommandSpec {
+        name: "goto",
+        args: "M[:B[:T]]",
+        description: "Move the cursor to measure:beat:tick",
+    },
+    CommandSpec {
+        name: "transpose",
+        args: "N",
+        description: "Transpose the selected notes by N semitones",
+    },
+    CommandSpec {
+        name: "quantize",
+        args: "1/N[T|.]",
+        description: "Quantize the selection (or track) to a grid",
+    },
+    CommandSpec {
+        name: "program",
+        args: "N",
+        description: "Set the track's instrument (0-127)",
+    },
+    CommandSpec {
+        name: "track",
+        args: "add|delete|rename",
+        description: "Add (with a name), delete or rename a track",
+    },
+    CommandSpec {
+        name: "set",
+        args: "OPT[=VAL]",
+        description: "Set or show zoom, velocity, length, octave, theme",
+    },
+    CommandSpec {
+        name: "w",
+        args: "[FILE]",
+        description: "Save, or save as FILE (.oxm, .json or .mid)",
+    },
+    CommandSpec {
+        name: "e",
+        args: "FILE",
+        description: "Open a project or MIDI file",
+    },
+    CommandSpec {
+        name: "wq",
+        args: "",
+        description: "Save and quit",
+    },
+    CommandSpec {
+        name: "q",
+        args: "",
+        description: "Quit (q! discards unsaved changes)",
+    },
+];
+
+/// A parsed command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    /// Set the tempo in BPM.
+    Tempo(u32),
+    /// Move the cursor to a measure:beat:tick position.
+    Goto(String),
+    /// Transpose the selected notes.
+    Transpose(i8),
+    /// Quantize to a grid division.
+    Quantize { division: u32, feel: GridFeel },
+    /// Set the selected track's program.
+    Program(u8),
+    /// Add a track, optionally named.
+    TrackAdd(Option<String>),
+    /// Delete the selected track.
+    TrackDelete,
+    /// Rename the selected track.
+    TrackRename(String),
+    /// Set an option, or show it when there is no value.
+    Set {
+        option: String,
+        value: Option<String>,
+    },
+    /// Save, to the current file or a new one.
+    Write(Option<PathBuf>),
+    /// Save and quit.
+    WriteQuit,
+    /// Open a file.
+    Edit(PathBuf),
+    /// Quit, optionally without asking about unsaved changes.
+    Quit { force: bool },
+}
+
+impl Command {
+    /// Parses a command line, with or without the leading colon.
+    ///
+    /// # Errors
+    ///
+    /// Returns a message for the status line if the command is unknown or
+    /// its arguments are wrong
+    pub fn parse(line: &str) -> Result<Self, String> {
+        let line = line.trim().trim_start_matches(':').trim();
+        let (name, rest) = line
+            .split_once(char::is_whitespace)
+            .map_or((line, ""), |(name, rest)| (name, rest.trim()));
+        let usage = || {
+            let spec = COMMANDS.iter().find(|c| c.name == name);
+            format!("Usage: :{} {}", name, spec.map_or("", |c| c.args))
+        };
+
+        match name {
+            "tempo" => match rest.parse() {
+                Ok(bpm) if (20..=300).contains(&bpm) => Ok(Command::Tempo(bpm)),
+                Ok(_) => Err("Tempo must be 20-300 BPM".to_string()),
+                Err(_) => Err(usage()),
+            },
+            "goto" if !rest.is_empty() => Ok(Command::Goto(rest.to_string())),
+            "transpose" => rest.parse().map(Command::Transpose).map_err(|_| usage()),
+            "quantize" => {
+                parse_grid(rest).map(|(division, feel)| Command::Quantize { division, feel })
+            }
+            "program" => match rest.parse() {
+                Ok(program) if program < 128 => Ok(Command::Program(program)),
+                _ => Err("Program must be 0-127".to_string()),
+            },
+            "track" => {
+                let (action, arg) = rest
+                    .split_once(char::is_whitespace)
+                    .map_or((rest, ""), |(action, arg)| (action, arg.trim()));
+                match (action, arg) {
+                    ("add", "") => Ok(Command::TrackAdd(None)),
+                    ("add", name) => Ok(Command::TrackAdd(Some(name.to_string()))),
+                    ("delete", "") => Ok(Command::TrackDelete),
+                    ("rename", name) if !name.is_empty() => {
+                        Ok(Command::TrackRename(name.to_string()))
+                    }
+                    _ => Err(usage()),
+                }
+            }
+            "set" if !rest.is_empty() => {
+                let (option, value) = rest
+                    .split_once('=')
+                    .map_or((rest, None), |(o, v)| (o, Some(v.trim().to_string())));
+                let option = option.trim();
+                if !SET_OPTIONS.contains(&option) {
+                    return Err(format!("Unknown option: {}", option));
+                }
+                Ok(Command::Set {
+                    option: option.to_string(),
+                    value,
+                })
+            }
+            "w" | "write" if rest.is_empty() => Ok(Command::Write(None)),
+            "w" | "write" => Ok(Command::Write(Some(PathBuf::from(rest)))),
+            "wq" | "x" => Ok(Command::WriteQuit),
+            "e" | "edit" if !rest.is_empty() => Ok(Command::Edit(PathBuf::from(rest))),
+            "q" | "quit" => Ok(Command::Quit { force: false }),
+            "q!" | "quit!" => Ok(Command::Quit { force: true }),
+            "" => Err("No command".to_string()),
+            _ if COMMANDS.iter().any(|c| c.name == name) => Err(usage()),
+            _ => Err(format!("Not a command: {}", name)),
+        }
+    }
+}
+
+/// Parses a grid such as "1/16", "1/8T" or "1/4." (the "1/" is optional).
+fn parse_grid(text: &str) -> Result<(u32, GridFeel), String> {
+    let text = text.trim();
+    let text = text.strip_prefix("1/").unwrap_or(text);
+    let (number, feel) = if let Some(n) = text.strip_suffix(['T', 't']) {
+        (n, GridFeel::Triplet)
+    } else if let Some(n) = text.strip_suffix('.') {
+        (n, GridFeel::Dotted)
+    } else {
+        (text, GridFeel::Straight)
+    };
+    match number.parse() {
+        Ok(division) if GRID_DIVISIONS.contains(&division) => Ok((division, feel)),
+        _ => Err(format!(
+            "Grid must be one of {}, optionally with T or .",
+            GRID_DIVISIONS
+                .iter()
+                .map(|d| format!("1/{}", d))
+                .collect::<Vec<_>>()
+                .join(" ")
+        )),
+    }
+}
+
+/// Returns the ways a partly typed command line can be completed, as whole
+/// lines. `themes` are the theme names `:set theme=` offers.
+pub fn complete(line: &str, themes: &[String]) -> Vec<String> {
+    let Some((name, rest)) = line.split_once(' ') else {
+        return COMMANDS
+            .iter()
+            .filter(|c| c.name.starts_with(line))
+            .map(|c| c.name.to_string())
+            .collect();
+    };
+    let rest = rest.trim_start();
+    let prefixed = |candidates: Vec<String>| {
+        candidates
+            .into_iter()
+            .map(|c| format!("{} {}", name, c))
+            .collect()
+    };
+
+    match name {
+        "set" => match rest.split_once('=') {
+            Some(("theme", value)) => prefixed(
+                themes
+                    .iter()
+                    .filter(|t| t.starts_with(value))
+                    .map(|t| format!("theme={}", t))
+                    .collect(),
+            ),
+            Some(_) => Vec::new(),
+            None => prefixed(
+                SET_OPTIONS
+                    .iter()
+                    .filter(|o| o.starts_with(rest))
+                    .map(|o| format!("{}=", o))
+                    .collect(),
+            ),
+        },
+        "track" => prefixed(
+            TRACK_ACTIONS
+                .iter()
+                .filter(|a| a.starts_with(rest))
+                .map(|a| a.to_string())
+                .collect(),
+        ),
+        "w" | "write" | "e" | "edit" => prefixed(complete_path(rest)),
+        _ => Vec::new(),
+    }
+}
+
+/// Completes a path to a directory or a project/MIDI file.
+fn complete_path(partial: &str) -> Vec<String> {
+    let (dir, prefix) = match partial.rfind('/') {
+        Some(slash) => (&partial[..=slash], &partial[slash + 1..]),
+        None => ("", partial),
+    };
+    let read_from = if dir.is_empty() {
+        Path::new(".")
+    } else {
+        Path::new(dir)
+    };
+    let Ok(entries) = std::fs::read_dir(read_from) else {
+        return Vec::new();
+    };
+
+    let mut matches: Vec<String> = entries
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            let name = entry.file_name().into_string().ok()?;
+            if !name.starts_with(prefix) || (name.starts_with('.') && !prefix.starts_with('.')) {
+                return None;
+            }
+            let path = entry.path();
+            if path.is_dir() {
+                return Some(format!("{}{}/", dir, name));
+            }
+            let extension = path.extension()?.to_str()?.to_lowercase();
+            PROJECT_EXTENSIONS
+                .contains(&extension.as_str())
+                .then(|| format!("{}{}", dir, name))
+        })
+        .collect();
+    matches.sort();
+    matches
+}
+
+/// The command line being edited, with its history and completions.
+#[derive(Debug, Clone, Default)]
+pub struct CommandLine {
+    /// Whether the command line is open.
+    pub open: bool,
+    /// Text typed after the colon.
+    pub input: String,
+    /// Earlier commands, oldest first.
+    history: Vec<String>,
+    /// History entry shown while browsing, and the text typed before.
+    browsing: Option<(usize, String)>,
+    /// Candidates Tab cycles through, and the one shown.
+    completions: Vec<String>,
+    completion: usize,
+}
+
+impl CommandLine {
+    /// Opens an empty command line.
+    pub fn open(&mut self) {
+        self.open = true;
+        self.input.clear();
+        self.reset();
+    }
+
+    /// Closes the command line without running it.
+    pub fn close(&mut self) {
+        self.open = false;
+        self.reset();
+    }
+
+    /// Adds a typed character.
+    pub fn insert(&mut self, c: char) {
+        self.input.push(c);
+        self.reset();
+    }
+
+    /// Deletes the last character.
+    ///
+    /// # Returns
+    ///
+    /// false if the line was already empty
+    pub fn backspace(&mut self) -> bool {
+        self.reset();
+        self.input.pop().is_some()
+    }
+
+    /// Shows the previous (`delta < 0`) or next history entry starting with
+    /// the text typed before browsing.
+    pub fn browse_history(&mut self, delta: i32) {
+        let (position, typed) = self
+            .browsing
+            .take()
+            .unwrap_or((self.history.len(), self.input.clone()));
+        let matches = |i: &usize| self.history[*i].starts_with(&typed);
+        let found = if delta < 0 {
+            (0..position).rev().find(matches)
+        } else {
+            (position + 1..self.history.len()).find(matches)
+        };
+
+        match found {
+            Some(index) => {
+                self.input = self.history[index].clone();
+                self.browsing = Some((index, typed));
+            }
+            // Past the newest entry: back to what was typed
+            None if delta > 0 => self.input = typed,
+            None => self.browsing = Some((position, typed)),
+        }
+        self.completions.clear();
+    }
+
+    /// Completes the line, cycling forward (`delta > 0`) or backward
+    /// through the candidates on repeated presses.
+    pub fn complete(&mut self, delta: i32, themes: &[String]) {
+        if self.completions.is_empty() {
+            self.completions = complete(&self.input, themes);
+            if self.completions.is_empty() {
+                return;
+            }
+            self.completion = if delta < 0 {
+                self.completions.len() - 1
+            } else {
+                0
+            };
+        } else {
+            let len = self.completions.len() as i32;
+            self.completion = (self.completion as i32 + delta).rem_euclid(len) as usize;
+        }
+        self.input = self.completions[self.completion].clone();
+        // A single match is final, so the next Tab can go deeper
+        if self.completions.len() == 1 {
+            self.completions.clear();
+        }
+    }
+
+    /// Returns the completion candidates being cycled and the one shown.
+    pub fn completions(&self) -> (&[String], usize) {
+        (&self.completions, self.completion)
+    }
+
+    /// Closes the command line and returns the command, recording it in
+    /// the history.
+    pub fn submit(&mut self) -> Option<String> {
+        self.close();
+        let line = std::mem::take(&mut self.input);
+        let line = line.trim();
+        if line.is_empty() {
+            return None;
+        }
+        self.history.retain(|h| h != line);
+        self.history.push(line.to_string());
+        if self.history.len() > HISTORY_LIMIT {
+            self.history.remove(0);
+        }
+        Some(line.to_string())
+    }
+
+    /// Forgets history browsing and completion after an edit.
+    fn reset(&mut self) {
+        self.browsing = None;
+        self.completions.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_commands() {
+        assert_eq!(Command::parse(":tempo 96"), Ok(Command::Tempo(96)));
+        assert_eq!(
+            Command::parse("goto 17:2"),
+            Ok(Command::Goto("17:2".to_string()))
+        );
+        assert_eq!(Command::parse("transpose -12"), Ok(Command::Transpose(-12)));
+        assert_eq!(
+            Command::parse("quantize 1/8T"),
+            Ok(Command::Quantize {
+                division: 8,
+                feel: GridFeel::Triplet
+            })
+        );
+        assert_eq!(
+            Command::parse("w song.mid"),
+            Ok(Command::Write(Some(PathBuf::from("song.mid"))))
+        );
+        assert_eq!(
+            Command::parse("track add Bass Line"),
+            Ok(Command::TrackAdd(Some("Bass Line".to_string())))
+        );
+        assert_eq!(Command::parse("program 33"), Ok(Command::Program(33)));
+        assert_eq!(
+            Command::parse("set zoom=60"),
+            Ok(Command::Set {
+                option: "zoom".to_string(),
+                value: Some("60".to_string())
+            })
+        );
+        assert_eq!(Command::parse("q!"), Ok(Command::Quit { force: true }));
+
+        assert!(Command::parse("tempo 5").is_err());
+        assert!(Command::parse("tempo").unwrap_err().starts_with("Usage"));
+        assert!(Command::parse("quantize 1/12").is_err());
+        assert!(Command::parse("set colour=red").is_err());
+        assert_eq!(Command::parse("frob").unwrap_err(), "Not a command: frob");
+    }
+
+    #[test]
+    fn test_complete() {
+        let themes = vec!["dark".to_string(), "light".to_string()];
+        assert_eq!(complete("t", &themes), vec!["tempo", "transpose", "track"]);
+        assert_eq!(complete("set z", &themes), vec!["set zoom="]);
+        assert_eq!(complete("set theme=l", &themes), vec!["set theme=light"]);
+        assert_eq!(complete("track r", &themes), vec!["track rename"]);
+        assert!(complete("tempo 1", &themes).is_empty());
+    }
+
+    #[test]
+    fn test_history_and_tab() {
+        let mut line = CommandLine::default();
+        for command in ["tempo 90", "goto 3", "tempo 120"] {
+            line.open();
+            line.input = command.to_string();
+            line.submit();
+        }
+
+        // Up only visits entries starting with what was typed
+        line.open();
+        line.insert('t');
+        line.browse_history(-1);
+        assert_eq!(line.input, "tempo 120");
+        line.browse_history(-1);
+        assert_eq!(line.input, "tempo 90");
+        line.browse_history(-1);
+        assert_eq!(line.input, "tempo 90");
+        line.browse_history(1);
+        line.browse_history(1);
+        assert_eq!(line.input, "t");
+
+        // Tab cycles through the matches
+        line.input = "tr".to_string();
+        line.complete(1, &[]);
+        assert_eq!(line.input, "transpose");
+        line.complete(1, &[]);
+        assert_eq!(line.input, "track");
+        line.complete(1, &[]);
+        assert_eq!(line.input, "transpose");
+        line.insert(' ');
+        assert!(line.completions().0.is_empty());
+    }
+}
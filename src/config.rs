@@ -0,0 +1,278 @@
+//! User configuration.
+//!
+//! Settings are kept in `config.json` in the user's config directory (see
+//! [`config_dir`]), or in the file given with `--config`.
+//! Every field is optional in the file; missing ones take the built-in
+//! defaults, so a config only needs the settings that differ. The settings
+//! dialog (Ctrl+P) writes changes back to the same file.
+
+use crate::app::HighlightMode;
+use crate::audio::export::WavOptions;
+use crate::midi::{Project, TICKS_PER_BEAT};
+use crate::theme::{Theme, DEFAULT_THEME};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// File name of the config inside the config directory.
+const CONFIG_FILE_NAME: &str = "config.json";
+
+/// Returns the directory holding the user's miditui settings, if it can be found.
+///
+/// This is `$XDG_CONFIG_HOME/miditui`, falling back to `~/.config/miditui`.
+pub fn config_dir() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(base.join("miditui"))
+}
+
+/// Returns the current time in seconds since the Unix epoch.
+pub fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+/// Default note velocity for new notes.
+pub const DEFAULT_VELOCITY: u8 = 100;
+
+/// Default note duration in ticks (quarter note).
+pub const DEFAULT_NOTE_DURATION: u32 = TICKS_PER_BEAT;
+
+/// Default delay after the last edit before autosaving, in seconds.
+pub const DEFAULT_AUTOSAVE_DELAY_SECS: u64 = 5;
+
+/// Default zoom in ticks per column (4 columns per beat).
+pub const DEFAULT_ZOOM: u32 = TICKS_PER_BEAT / 4;
+
+/// Default time to wait for input each frame, in milliseconds.
+pub const DEFAULT_POLL_INTERVAL_MS: u64 = 16;
+
+/// Where and how exports are written.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExportConfig {
+    /// Directory exports are written to.
+    pub dir: PathBuf,
+    /// File name of the WAV export inside `dir`.
+    pub wav_file_name: String,
+    /// How the WAV export is rendered.
+    #[serde(flatten)]
+    pub wav: WavOptions,
+}
+
+impl Default for ExportConfig {
+    fn default() -> Self {
+        Self {
+            dir: PathBuf::from("output"),
+            wav_file_name: "output.wav".to_string(),
+            wav: WavOptions::default(),
+        }
+    }
+}
+
+impl ExportConfig {
+    /// Returns the path of the WAV export.
+    pub fn wav_path(&self) -> PathBuf {
+        self.dir.join(&self.wav_file_name)
+    }
+}
+
+/// User settings, loaded at startup.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Velocity of newly placed notes.
+    pub default_velocity: u8,
+    /// Length of newly placed notes, in ticks.
+    pub default_note_duration: u32,
+    /// Delay after the last edit before autosaving, in seconds.
+    pub autosave_delay_secs: u64,
+    /// Whether the undo history is written with each autosave.
+    pub persist_history: bool,
+    /// Zoom at startup, in ticks per column.
+    pub default_zoom: u32,
+    /// Time to wait for input each frame, in milliseconds.
+    pub poll_interval_ms: u64,
+    /// Playback highlighting at startup.
+    pub highlight_mode: HighlightMode,
+    /// Color theme: a built-in one (dark, light, ansi) or a file in the
+    /// themes folder of the config directory.
+    pub theme: String,
+    /// Whether the terminal shows 24-bit color; detected from `COLORTERM`
+    /// if not set.
+    pub truecolor: Option<bool>,
+    /// SoundFont used when neither the command line nor the project names one.
+    pub soundfont: Option<PathBuf>,
+    /// Project file (.oxm or .json) that new projects start from.
+    pub template: Option<PathBuf>,
+    /// Keymap file; defaults to `keymap.json` in the config directory.
+    pub keymap: Option<PathBuf>,
+    /// Export settings.
+    pub export: ExportConfig,
+    /// Where the config was loaded from and is saved to (None keeps it in memory only).
+    #[serde(skip)]
+    path: Option<PathBuf>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            default_velocity: DEFAULT_VELOCITY,
+            default_note_duration: DEFAULT_NOTE_DURATION,
+            autosave_delay_secs: DEFAULT_AUTOSAVE_DELAY_SECS,
+            persist_history: true,
+            default_zoom: DEFAULT_ZOOM,
+            poll_interval_ms: DEFAULT_POLL_INTERVAL_MS,
+            highlight_mode: HighlightMode::default(),
+            theme: DEFAULT_THEME.to_string(),
+            truecolor: None,
+            soundfont: None,
+            template: None,
+            keymap: None,
+            export: ExportConfig::default(),
+            path: None,
+        }
+    }
+}
+
+impl Config {
+    /// Loads the config from `path`, or from the config directory if None.
+    ///
+    /// A missing file gives the defaults; it is still saved to the same
+    /// place when settings are changed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file exists but can't be read or parsed
+    pub fn load(path: Option<&Path>) -> std::io::Result<Self> {
+        let path = path
+            .map(Path::to_path_buf)
+            .or_else(|| config_dir().map(|dir| dir.join(CONFIG_FILE_NAME)));
+        let Some(path) = path else {
+            return Ok(Self::default());
+        };
+        let mut config = match std::fs::read_to_string(&path) {
+            Ok(text) => Self::parse(&text)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Self::default(),
+            Err(e) => return Err(e),
+        };
+        config.path = Some(path);
+        Ok(config)
+    }
+
+    /// Parses a config from JSON, keeping values within their valid ranges.
+    fn parse(text: &str) -> std::io::Result<Self> {
+        let mut config: Self = serde_json::from_str(text)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        config.clamp();
+        Ok(config)
+    }
+
+    /// Keeps every value within the range the app can use.
+    pub fn clamp(&mut self) {
+        self.default_velocity = self.default_velocity.clamp(1, 127);
+        self.default_note_duration = self.default_note_duration.clamp(1, TICKS_PER_BEAT * 16);
+        self.autosave_delay_secs = self.autosave_delay_secs.clamp(1, 3600);
+        self.default_zoom = self
+            .default_zoom
+            .clamp(TICKS_PER_BEAT / 16, TICKS_PER_BEAT * 4);
+        self.poll_interval_ms = self.poll_interval_ms.clamp(1, 250);
+        self.export.wav.clamp();
+    }
+
+    /// Returns where the config is saved, if anywhere.
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// Writes the config back to the file it was loaded from.
+    pub fn save(&self) -> std::io::Result<()> {
+        let Some(path) = &self.path else {
+            return Err(std::io::Error::other("no config file"));
+        };
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)?;
+        }
+        let json = serde_json::to_string_pretty(self).map_err(std::io::Error::other)?;
+        std::fs::write(path, json)
+    }
+
+    /// Loads the configured theme.
+    ///
+    /// # Errors
+    ///
+    /// Returns a message if the theme doesn't exist or its file is invalid
+    pub fn load_theme(&self) -> Result<Theme, String> {
+        let truecolor = self
+            .truecolor
+            .unwrap_or_else(crate::theme::detect_truecolor);
+        Theme::load(&self.theme, truecolor)
+    }
+
+    /// Creates a project to start from: the template if one is set and
+    /// loads, otherwise an empty project with one track.
+    pub fn new_project(&self) -> Project {
+        self.template
+            .as_deref()
+            .and_then(|path| match Project::load_template(path) {
+                Ok(project) => Some(project),
+                Err(e) => {
+                    tracing::warn!("Failed to load template {:?}: {}", path, e);
+                    None
+                }
+            })
+            .unwrap_or_else(|| Project::with_default_track("New Project"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_missing_fields_take_defaults() {
+        let config =
+            Config::parse(r#"{ "default_velocity": 80, "export": { "dir": "renders" } }"#).unwrap();
+        assert_eq!(config.default_velocity, 80);
+        assert_eq!(config.default_note_duration, DEFAULT_NOTE_DURATION);
+        assert_eq!(config.export.dir, PathBuf::from("renders"));
+        assert_eq!(config.export.wav_file_name, "output.wav");
+        assert_eq!(config.export.wav, WavOptions::default());
+        assert_eq!(config.highlight_mode, HighlightMode::PianoRollOnly);
+    }
+
+    #[test]
+    fn test_out_of_range_values_are_clamped() {
+        let config =
+            Config::parse(r#"{ "default_velocity": 0, "default_zoom": 1, "poll_interval_ms": 0 }"#)
+                .unwrap();
+        assert_eq!(config.default_velocity, 1);
+        assert_eq!(config.default_zoom, TICKS_PER_BEAT / 16);
+        assert_eq!(config.poll_interval_ms, 1);
+        assert!(Config::parse("{ not json").is_err());
+    }
+
+    #[test]
+    fn test_save_and_load_round_trip() {
+        let path = std::env::temp_dir()
+            .join(format!("miditui-config-{}", std::process::id()))
+            .join(CONFIG_FILE_NAME);
+        let _ = std::fs::remove_file(&path);
+
+        // A missing file gives the defaults but remembers where to save
+        let mut config = Config::load(Some(&path)).unwrap();
+        assert_eq!(config.default_velocity, DEFAULT_VELOCITY);
+        assert_eq!(config.path(), Some(path.as_path()));
+        config.highlight_mode = HighlightMode::Both;
+        config.export.wav.sample_rate = 48000;
+        config.save().unwrap();
+
+        let loaded = Config::load(Some(&path)).unwrap();
+        assert_eq!(loaded.highlight_mode, HighlightMode::Both);
+        assert_eq!(loaded.export.wav.sample_rate, 48000);
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+}
@@ -1,202 +1,441 @@
-use crate::midi::{NoteId, Project};
-use std::collections::HashSet;
+//! Undo history built from compact diffs.
+//!
+//! Each edit is stored as a [`ProjectDiff`] holding only what changed: the
+//! notes added and removed on a track, whole tracks when their settings
+//! changed, and project-wide settings when those changed. This keeps deep
+//! history cheap even on large imported songs, where a full copy per edit
+//! would hold every note again.
+
+use crate::midi::{Note, NoteId, Project, Track, TrackId};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// Maximum number of edits kept in the history.
+const MAX_HISTORY_SIZE: usize = 500;
+
+/// Track and note selection saved with each edit.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Selection {
+    /// Selected track index.
+    pub track_index: usize,
+
+    /// Selected notes (by ID).
+    pub notes: HashSet<NoteId>,
+}
 
-/// Maximum number of undo/redo states to keep.
-const MAX_HISTORY_SIZE: usize = 8;
+impl Selection {
+    /// Creates a selection from the current track index and notes.
+    pub fn new(track_index: usize, notes: &HashSet<NoteId>) -> Self {
+        Self {
+            track_index,
+            notes: notes.clone(),
+        }
+    }
 
-/// A snapshot of the application state at a point in time.
-///
-/// Contains all data needed to restore the application to a previous state.
-/// This includes the full project data plus UI selection state that directly
-/// relates to editing operations.
-#[derive(Debug, Clone)]
-pub struct StateSnapshot {
-    /// The complete project state (tracks, notes, tempo, etc.).
-    pub project: Project,
+    /// Returns the selection clamped to the project: the track index is kept
+    /// in bounds and notes that no longer exist are dropped.
+    pub fn valid_in(&self, project: &Project) -> Selection {
+        let track_index = self
+            .track_index
+            .min(project.track_count().saturating_sub(1));
+        let notes = project
+            .track_at(track_index)
+            .map(|track| {
+                track
+                    .notes()
+                    .iter()
+                    .map(|n| n.id)
+                    .filter(|id| self.notes.contains(id))
+                    .collect()
+            })
+            .unwrap_or_default();
+        Selection { track_index, notes }
+    }
+}
 
-    /// Currently selected track index.
-    pub selected_track_index: usize,
+/// How one track changed in an edit.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+enum TrackChange {
+    /// Only notes changed; other notes and the track settings are untouched.
+    Notes {
+        /// The changed track.
+        id: TrackId,
+        /// Notes as they were before the edit (removed or changed).
+        removed: Vec<Note>,
+        /// Notes as they are after the edit (added or changed).
+        added: Vec<Note>,
+    },
+    /// The track was added, removed, or its settings changed.
+    Track {
+        /// The changed track.
+        id: TrackId,
+        /// The track before the edit (None if it was added).
+        before: Option<Box<Track>>,
+        /// The track after the edit (None if it was removed).
+        after: Option<Box<Track>>,
+    },
+}
 
-    /// Currently selected notes (by ID).
-    /// Stored to maintain selection context across undo/redo.
-    pub selected_notes: HashSet<NoteId>,
+/// The difference between the project before and after one edit.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProjectDiff {
+    /// Project-wide settings (without tracks) before and after, if changed.
+    settings: Option<Box<(Project, Project)>>,
 
-    /// A brief description of what operation created this snapshot.
-    /// Used for status messages when undoing/redoing.
-    pub description: String,
+    /// Track order before and after, if it changed.
+    order: Option<(Vec<TrackId>, Vec<TrackId>)>,
+
+    /// Changes to individual tracks.
+    tracks: Vec<TrackChange>,
 }
 
-impl StateSnapshot {
-    /// Creates a new snapshot from the current state.
-    ///
-    /// # Arguments
-    ///
-    /// * `project` - The current project state
-    /// * `selected_track_index` - The currently selected track
-    /// * `selected_notes` - The currently selected notes
-    /// * `description` - A brief description of the operation
-    pub fn new(
-        project: &Project,
-        selected_track_index: usize,
-        selected_notes: &HashSet<NoteId>,
-        description: impl Into<String>,
-    ) -> Self {
-        Self {
-            project: project.clone(),
-            selected_track_index,
-            selected_notes: selected_notes.clone(),
-            description: description.into(),
-        }
+/// Returns true if two values serialize to the same bytes.
+fn same_encoding<T: Serialize>(a: &T, b: &T) -> bool {
+    match (bincode::serialize(a), bincode::serialize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
     }
+}
 
-    /// Validates that the snapshot can be safely restored.
-    ///
-    /// Checks invariants like:
-    /// - Selected track index is within bounds
-    /// - Selected notes exist in the project
-    ///
-    /// # Returns
-    ///
-    /// true if the snapshot is valid and can be restored
-    pub fn is_valid(&self) -> bool {
-        // If there are no tracks, index 0 is still valid (will be clamped)
-        if self.project.track_count() > 0 && self.selected_track_index >= self.project.track_count()
-        {
-            return false;
+/// Compares the notes of two versions of a track by ID.
+///
+/// # Returns
+///
+/// The notes removed or changed (as before) and added or changed (as
+/// after), or None if note IDs are not unique and can't be matched
+fn diff_notes(before: &[Note], after: &[Note]) -> Option<(Vec<Note>, Vec<Note>)> {
+    let before_by_id: HashMap<NoteId, &Note> = before.iter().map(|n| (n.id, n)).collect();
+    let after_by_id: HashMap<NoteId, &Note> = after.iter().map(|n| (n.id, n)).collect();
+    if before_by_id.len() != before.len() || after_by_id.len() != after.len() {
+        return None;
+    }
+    let removed = before
+        .iter()
+        .filter(|n| after_by_id.get(&n.id) != Some(n))
+        .cloned()
+        .collect();
+    let added = after
+        .iter()
+        .filter(|n| before_by_id.get(&n.id) != Some(n))
+        .cloned()
+        .collect();
+    Some((removed, added))
+}
+
+impl ProjectDiff {
+    /// Computes the changes that turn `before` into `after`.
+    pub fn between(before: &Project, after: &Project) -> Self {
+        let mut diff = ProjectDiff::default();
+
+        let (settings_before, settings_after) = (before.without_tracks(), after.without_tracks());
+        if !same_encoding(&settings_before, &settings_after) {
+            diff.settings = Some(Box::new((settings_before, settings_after)));
         }
 
-        // Selected notes will be filtered during restoration via valid_selected_notes()
-        // so we don't need to validate them here - just ensure the project structure is sound
+        let order_before: Vec<TrackId> = before.tracks().iter().map(|t| t.id).collect();
+        let order_after: Vec<TrackId> = after.tracks().iter().map(|t| t.id).collect();
+
+        let before_by_id: HashMap<TrackId, &Track> =
+            before.tracks().iter().map(|t| (t.id, t)).collect();
+        for track in after.tracks() {
+            let Some(old) = before_by_id.get(&track.id) else {
+                diff.tracks.push(TrackChange::Track {
+                    id: track.id,
+                    before: None,
+                    after: Some(Box::new(track.clone())),
+                });
+                continue;
+            };
+            let notes_only = same_encoding(&old.without_notes(), &track.without_notes());
+            if notes_only && old.notes() == track.notes() {
+                continue;
+            }
+            match diff_notes(old.notes(), track.notes()).filter(|_| notes_only) {
+                Some((removed, added)) => diff.tracks.push(TrackChange::Notes {
+                    id: track.id,
+                    removed,
+                    added,
+                }),
+                None => diff.tracks.push(TrackChange::Track {
+                    id: track.id,
+                    before: Some(Box::new((*old).clone())),
+                    after: Some(Box::new(track.clone())),
+                }),
+            }
+        }
+        for track in before.tracks() {
+            if !order_after.contains(&track.id) {
+                diff.tracks.push(TrackChange::Track {
+                    id: track.id,
+                    before: Some(Box::new(track.clone())),
+                    after: None,
+                });
+            }
+        }
 
-        true
+        if order_before != order_after {
+            diff.order = Some((order_before, order_after));
+        }
+        diff
     }
 
-    /// Returns a copy of selected_notes filtered to only include notes that
-    /// still exist in the project.
-    ///
-    /// This handles cases where notes were deleted in a redo operation
-    /// but the selection still references them.
-    pub fn valid_selected_notes(&self) -> HashSet<NoteId> {
-        if let Some(track) = self.project.track_at(self.selected_track_index) {
-            let track_note_ids: HashSet<NoteId> = track.notes().iter().map(|n| n.id).collect();
-            self.selected_notes
-                .intersection(&track_note_ids)
-                .copied()
-                .collect()
-        } else {
-            HashSet::new()
+    /// Returns true if the edit changed nothing.
+    pub fn is_empty(&self) -> bool {
+        self.settings.is_none() && self.order.is_none() && self.tracks.is_empty()
+    }
+
+    /// Applies the edit to a project, or reverts it if `forward` is false.
+    pub fn apply(&self, project: &mut Project, forward: bool) {
+        let mut tracks = project.take_tracks();
+        if let Some(settings) = &self.settings {
+            let (before, after) = &**settings;
+            *project = if forward { after } else { before }.clone();
+        }
+
+        for change in &self.tracks {
+            match change {
+                TrackChange::Notes { id, removed, added } => {
+                    let (remove, add) = if forward {
+                        (removed, added)
+                    } else {
+                        (added, removed)
+                    };
+                    if let Some(track) = tracks.iter_mut().find(|t| t.id == *id) {
+                        let remove_ids: HashSet<NoteId> = remove.iter().map(|n| n.id).collect();
+                        track.patch_notes(&remove_ids, add);
+                    }
+                }
+                TrackChange::Track { id, before, after } => {
+                    let target = if forward { after } else { before };
+                    let position = tracks.iter().position(|t| t.id == *id);
+                    match (position, target) {
+                        (Some(pos), Some(track)) => tracks[pos] = (**track).clone(),
+                        (Some(pos), None) => {
+                            tracks.remove(pos);
+                        }
+                        (None, Some(track)) => tracks.push((**track).clone()),
+                        (None, None) => {}
+                    }
+                }
+            }
         }
+
+        if let Some((before, after)) = &self.order {
+            let order = if forward { after } else { before };
+            tracks.sort_by_key(|t| order.iter().position(|id| *id == t.id));
+        }
+        project.set_tracks(tracks);
     }
 }
 
-/// Manages undo/redo history using a snapshot-based approach.
-///
-/// The manager maintains two stacks:
-/// - `undo_stack`: Past states that can be reverted to
-/// - `redo_stack`: Future states that can be restored after undoing
+/// One edit in the history.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    /// A brief description of the operation, e.g. "Move notes".
+    pub description: String,
+
+    /// When the edit was made (seconds since the Unix epoch).
+    pub timestamp: u64,
+
+    /// Selection before the edit, restored on undo.
+    pub selection_before: Selection,
+
+    /// Selection after the edit, restored on redo.
+    pub selection_after: Selection,
+
+    /// What the edit changed.
+    diff: ProjectDiff,
+}
+
+/// An edit that has started but whose changes are not known yet.
+#[derive(Debug, Clone)]
+struct PendingEdit {
+    /// Description of the operation.
+    description: String,
+    /// When the edit was started.
+    timestamp: u64,
+    /// Selection before the edit.
+    selection: Selection,
+    /// The project before the edit, diffed against once the edit is done.
+    baseline: Project,
+}
+
+/// Manages undo/redo as a linear timeline of edits.
 ///
-/// When a new action is performed, the current state is pushed to the
-/// undo stack and the redo stack is cleared (branching creates a new timeline).
-#[derive(Debug, Default)]
+/// `entries[..position]` are applied edits (undoable) and
+/// `entries[position..]` are undone edits (redoable). An edit is recorded
+/// before it is made; its diff is computed once the next edit starts, or
+/// before any undo, redo or jump.
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct HistoryManager {
-    /// Stack of states to undo to (most recent last).
-    undo_stack: Vec<StateSnapshot>,
+    /// Recorded edits, oldest first.
+    entries: Vec<HistoryEntry>,
+
+    /// Number of edits currently applied.
+    position: usize,
 
-    /// Stack of states to redo to (most recent last).
-    redo_stack: Vec<StateSnapshot>,
+    /// The edit in progress, if any.
+    #[serde(skip)]
+    pending: Option<PendingEdit>,
+
+    /// Whether records join the edit in progress instead of starting one.
+    #[serde(skip)]
+    grouping: bool,
 }
 
 impl HistoryManager {
     /// Creates a new empty history manager.
     pub fn new() -> Self {
-        Self {
-            undo_stack: Vec::with_capacity(MAX_HISTORY_SIZE),
-            redo_stack: Vec::with_capacity(MAX_HISTORY_SIZE),
-        }
+        Self::default()
     }
 
-    /// Records a snapshot before an operation.
+    /// Records the start of an edit.
     ///
-    /// Call this BEFORE making any changes to capture the current state.
-    /// The redo stack is cleared since we're starting a new branch of history.
+    /// Call this BEFORE making any changes. The redo branch is dropped since
+    /// we're starting a new branch of history.
     ///
     /// # Arguments
     ///
-    /// * `snapshot` - The current state snapshot
+    /// * `project` - The project before the edit
+    /// * `selection` - The selection before the edit
+    /// * `description` - A brief description of the operation
     ///
     /// # Example
     ///
     /// ```ignore
     /// // Before placing a note:
-    /// history.push_undo(StateSnapshot::new(&project, selected_idx, &selected_notes, "Place note"));
+    /// history.record(&project, Selection::new(idx, &selected_notes), "Place note");
     /// // Now make the change:
     /// track.create_note(...);
     /// ```
-    pub fn push_undo(&mut self, snapshot: StateSnapshot) {
-        // Clear redo stack - we're branching to a new timeline
-        self.redo_stack.clear();
+    pub fn record(
+        &mut self,
+        project: &Project,
+        selection: Selection,
+        description: impl Into<String>,
+    ) {
+        if self.grouping && self.pending.is_some() {
+            return;
+        }
+        self.commit(project, &selection);
+        self.entries.truncate(self.position);
+        self.pending = Some(PendingEdit {
+            description: description.into(),
+            timestamp: crate::config::unix_now(),
+            selection,
+            baseline: project.clone(),
+        });
+    }
+
+    /// Starts a group of edits that undo as one, such as an action repeated
+    /// by a count. The group takes the description of its first edit.
+    pub fn begin_group(&mut self, project: &Project, selection: &Selection) {
+        self.commit(project, selection);
+        self.grouping = true;
+    }
 
-        self.push_undo_preserve_redo(snapshot);
+    /// Ends the current group; the next record starts a new edit.
+    pub fn end_group(&mut self) {
+        self.grouping = false;
     }
 
-    /// Pushes a state to the undo stack WITHOUT clearing the redo stack.
+    /// Finishes the edit in progress by diffing it against the project.
     ///
-    /// This is used internally during redo operations. When the user redoes,
-    /// we need to push the current state to undo for potential future undos,
-    /// but we must NOT clear the remaining redo states.
+    /// Edits that changed nothing are dropped.
     ///
     /// # Arguments
     ///
-    /// * `snapshot` - The state to push to the undo stack
-    pub fn push_undo_preserve_redo(&mut self, snapshot: StateSnapshot) {
-        // Add to undo stack without clearing redo
-        self.undo_stack.push(snapshot);
-
-        // Enforce maximum history size by removing oldest entries
-        while self.undo_stack.len() > MAX_HISTORY_SIZE {
-            self.undo_stack.remove(0);
+    /// * `project` - The project after the edit
+    /// * `selection` - The selection after the edit
+    pub fn commit(&mut self, project: &Project, selection: &Selection) {
+        let Some(pending) = self.pending.take() else {
+            return;
+        };
+        let diff = ProjectDiff::between(&pending.baseline, project);
+        if diff.is_empty() {
+            return;
+        }
+        self.entries.push(HistoryEntry {
+            description: pending.description,
+            timestamp: pending.timestamp,
+            selection_before: pending.selection,
+            selection_after: selection.clone(),
+            diff,
+        });
+        if self.entries.len() > MAX_HISTORY_SIZE {
+            let excess = self.entries.len() - MAX_HISTORY_SIZE;
+            self.entries.drain(..excess);
         }
+        self.position = self.entries.len();
     }
 
-    /// Pops the most recent undo state.
-    ///
-    /// This should be called to get the state to restore to.
-    /// The caller should push the CURRENT state to redo before applying
-    /// the returned snapshot.
+    /// Reverts the most recent applied edit.
     ///
     /// # Returns
     ///
-    /// The most recent undo snapshot, or None if undo stack is empty
-    pub fn pop_undo(&mut self) -> Option<StateSnapshot> {
-        self.undo_stack.pop()
+    /// The undone edit, or None if there is nothing to undo
+    pub fn undo(&mut self, project: &mut Project, selection: &Selection) -> Option<&HistoryEntry> {
+        self.commit(project, selection);
+        if self.position == 0 {
+            return None;
+        }
+        self.position -= 1;
+        let entry = &self.entries[self.position];
+        entry.diff.apply(project, false);
+        Some(entry)
     }
 
-    /// Pushes a state to the redo stack.
+    /// Re-applies the most recent undone edit.
     ///
-    /// Called when undoing to save the current state for potential redo.
-    ///
-    /// # Arguments
+    /// # Returns
     ///
-    /// * `snapshot` - The state before the undo was applied
-    pub fn push_redo(&mut self, snapshot: StateSnapshot) {
-        self.redo_stack.push(snapshot);
-
-        // Enforce maximum history size
-        while self.redo_stack.len() > MAX_HISTORY_SIZE {
-            self.redo_stack.remove(0);
-        }
+    /// The redone edit, or None if there is nothing to redo
+    pub fn redo(&mut self, project: &mut Project, selection: &Selection) -> Option<&HistoryEntry> {
+        self.commit(project, selection);
+        let entry = self.entries.get(self.position)?;
+        entry.diff.apply(project, true);
+        self.position += 1;
+        Some(entry)
     }
 
-    /// Pops the most recent redo state.
-    ///
-    /// The caller should push the CURRENT state to undo before applying
-    /// the returned snapshot.
+    /// Undoes or redoes edits until `position` edits are applied.
     ///
     /// # Returns
     ///
-    /// The most recent redo snapshot, or None if redo stack is empty
-    pub fn pop_redo(&mut self) -> Option<StateSnapshot> {
-        self.redo_stack.pop()
+    /// The selection to restore, or None if the history is already there
+    pub fn jump_to(
+        &mut self,
+        project: &mut Project,
+        selection: &Selection,
+        position: usize,
+    ) -> Option<Selection> {
+        self.commit(project, selection);
+        let target = position.min(self.entries.len());
+        if target == self.position {
+            return None;
+        }
+        while self.position > target {
+            self.position -= 1;
+            self.entries[self.position].diff.apply(project, false);
+        }
+        while self.position < target {
+            self.entries[self.position].diff.apply(project, true);
+            self.position += 1;
+        }
+        Some(match target.checked_sub(1) {
+            Some(last) => self.entries[last].selection_after.clone(),
+            None => self.entries[0].selection_before.clone(),
+        })
+    }
+
+    /// Returns every recorded edit, oldest first.
+    pub fn entries(&self) -> &[HistoryEntry] {
+        &self.entries
+    }
+
+    /// Returns the number of edits currently applied.
+    pub fn position(&self) -> usize {
+        self.position
     }
 
     /// Clears all history.
@@ -204,34 +443,34 @@ impl HistoryManager {
     /// Called when:
     /// - Loading a new project
     /// - Creating a new project
-    /// - Encountering an invalid state that can't be recovered
     pub fn clear(&mut self) {
-        self.undo_stack.clear();
-        self.redo_stack.clear();
+        self.entries.clear();
+        self.position = 0;
+        self.pending = None;
     }
 }
 
 /// Test-only helper methods for HistoryManager.
 #[cfg(test)]
 impl HistoryManager {
-    /// Returns true if there are states available to undo to.
+    /// Returns true if there are edits available to undo.
     pub fn can_undo(&self) -> bool {
-        !self.undo_stack.is_empty()
+        self.position > 0
     }
 
-    /// Returns true if there are states available to redo to.
+    /// Returns true if there are edits available to redo.
     pub fn can_redo(&self) -> bool {
-        !self.redo_stack.is_empty()
+        self.position < self.entries.len()
     }
 
-    /// Returns the number of undo states available.
+    /// Returns the number of undoable edits.
     pub fn undo_count(&self) -> usize {
-        self.undo_stack.len()
+        self.position
     }
 
-    /// Returns the number of redo states available.
+    /// Returns the number of redoable edits.
     pub fn redo_count(&self) -> usize {
-        self.redo_stack.len()
+        self.entries.len() - self.position
     }
 }
 
@@ -239,35 +478,73 @@ impl HistoryManager {
 mod tests {
     use super::*;
 
+    /// Records an edit, applies it, and finishes it.
+    fn edit(
+        history: &mut HistoryManager,
+        project: &mut Project,
+        description: &str,
+        change: impl FnOnce(&mut Project),
+    ) {
+        history.record(project, Selection::default(), description);
+        change(project);
+        history.commit(project, &Selection::default());
+    }
+
+    /// Adds a note at a tick on the first track.
+    fn add_note(project: &mut Project, tick: u32) {
+        project
+            .track_at_mut(0)
+            .unwrap()
+            .add_note(Note::new(60, 100, tick, 480));
+    }
+
+    /// Returns the start ticks of the first track's notes.
+    fn ticks(project: &Project) -> Vec<u32> {
+        project.tracks()[0]
+            .notes()
+            .iter()
+            .map(|n| n.start_tick)
+            .collect()
+    }
+
     #[test]
     fn test_history_push_and_pop() {
         let mut history = HistoryManager::new();
+        let mut project = Project::with_default_track("Test");
 
-        let project = Project::with_default_track("Test");
-        let snapshot = StateSnapshot::new(&project, 0, &HashSet::new(), "Test action");
-
-        history.push_undo(snapshot);
+        edit(&mut history, &mut project, "Test action", |p| {
+            add_note(p, 0)
+        });
 
         assert!(history.can_undo());
         assert!(!history.can_redo());
         assert_eq!(history.undo_count(), 1);
 
-        let restored = history.pop_undo().unwrap();
+        let restored = history.undo(&mut project, &Selection::default()).unwrap();
         assert_eq!(restored.description, "Test action");
         assert!(!history.can_undo());
+        assert!(ticks(&project).is_empty());
     }
 
     #[test]
-    fn test_history_max_size() {
+    fn test_edits_without_changes_are_dropped() {
         let mut history = HistoryManager::new();
+        let mut project = Project::with_default_track("Test");
 
-        let project = Project::with_default_track("Test");
+        edit(&mut history, &mut project, "Nothing", |_| {});
+        assert!(!history.can_undo());
+    }
+
+    #[test]
+    fn test_history_max_size() {
+        let mut history = HistoryManager::new();
+        let mut project = Project::with_default_track("Test");
 
         // Push more than MAX_HISTORY_SIZE entries
         for i in 0..MAX_HISTORY_SIZE + 5 {
-            let snapshot =
-                StateSnapshot::new(&project, 0, &HashSet::new(), format!("Action {}", i));
-            history.push_undo(snapshot);
+            edit(&mut history, &mut project, &format!("Action {}", i), |p| {
+                add_note(p, i as u32 * 10)
+            });
         }
 
         // Should only keep MAX_HISTORY_SIZE entries
@@ -275,119 +552,224 @@ mod tests {
 
         // The oldest entries should have been removed
         // Most recent should still be there
-        let last = history.pop_undo().unwrap();
+        let last = history.undo(&mut project, &Selection::default()).unwrap();
         assert_eq!(last.description, format!("Action {}", MAX_HISTORY_SIZE + 4));
     }
 
     #[test]
-    fn test_redo_cleared_on_new_action() {
-        let mut history = HistoryManager::new();
-
-        let project = Project::with_default_track("Test");
-
-        // Create an undo state
-        history.push_undo(StateSnapshot::new(&project, 0, &HashSet::new(), "Action 1"));
-
-        // Pop it and push to redo (simulating an undo operation)
-        let undone = history.pop_undo().unwrap();
-        history.push_redo(undone);
-
-        assert!(history.can_redo());
-
-        // New action should clear redo stack
-        history.push_undo(StateSnapshot::new(&project, 0, &HashSet::new(), "Action 2"));
+    fn test_note_edits_store_only_changed_notes() {
+        let mut project = Project::with_default_track("Test");
+        for i in 0..1000 {
+            add_note(&mut project, i * 10);
+        }
+        let before = project.clone();
+        let id = project.tracks()[0].notes()[500].id;
+        project
+            .track_at_mut(0)
+            .unwrap()
+            .get_note_mut(id)
+            .unwrap()
+            .pitch = 72;
+
+        let diff = ProjectDiff::between(&before, &project);
+        assert!(diff.settings.is_none());
+        assert!(diff.order.is_none());
+        match &diff.tracks[..] {
+            [TrackChange::Notes { removed, added, .. }] => {
+                assert_eq!(removed.len(), 1);
+                assert_eq!(added[0].pitch, 72);
+            }
+            other => panic!("unexpected diff {:?}", other),
+        }
 
-        assert!(!history.can_redo());
+        let mut reverted = project.clone();
+        diff.apply(&mut reverted, false);
+        assert_eq!(reverted.tracks()[0].notes(), before.tracks()[0].notes());
+        diff.apply(&mut reverted, true);
+        assert_eq!(reverted.tracks()[0].notes(), project.tracks()[0].notes());
     }
 
     #[test]
-    fn test_snapshot_validation() {
-        let project = Project::with_default_track("Test");
-
-        // Valid snapshot
-        let valid = StateSnapshot::new(&project, 0, &HashSet::new(), "Valid");
-        assert!(valid.is_valid());
+    fn test_track_and_settings_changes_round_trip() {
+        let mut history = HistoryManager::new();
+        let mut project = Project::with_default_track("Test");
+        add_note(&mut project, 0);
+
+        edit(&mut history, &mut project, "Add track", |p| {
+            p.add_track(Track::new("Bass", 1));
+        });
+        edit(&mut history, &mut project, "Move track", |p| {
+            p.move_track(1, 0);
+        });
+        edit(&mut history, &mut project, "Tempo", |p| p.tempo = 90);
+        let first = project.tracks()[1].id;
+        edit(&mut history, &mut project, "Delete track", |p| {
+            p.remove_track(first);
+        });
+        assert_eq!(project.track_count(), 1);
+
+        let selection = Selection::default();
+        for _ in 0..4 {
+            history.undo(&mut project, &selection).unwrap();
+        }
+        assert_eq!(project.track_count(), 1);
+        assert_eq!(project.tracks()[0].name, "Track 1");
+        assert_eq!(project.tempo, 120);
+        assert_eq!(ticks(&project), vec![0]);
 
-        // Invalid track index
-        let invalid = StateSnapshot::new(&project, 10, &HashSet::new(), "Invalid");
-        assert!(!invalid.is_valid());
+        for _ in 0..4 {
+            history.redo(&mut project, &selection).unwrap();
+        }
+        assert_eq!(project.track_count(), 1);
+        assert_eq!(project.tracks()[0].name, "Bass");
+        assert_eq!(project.tempo, 90);
     }
 
     #[test]
     fn test_multi_level_undo_redo() {
         // Test that if user undoes 4 changes, they can redo those same 4 changes
         let mut history = HistoryManager::new();
-        let project = Project::with_default_track("Test");
+        let mut project = Project::with_default_track("Test");
+        let selection = Selection::default();
 
         // Simulate 4 user actions
         for i in 0..4 {
-            history.push_undo(StateSnapshot::new(
-                &project,
-                0,
-                &HashSet::new(),
-                format!("Action {}", i),
-            ));
+            edit(&mut history, &mut project, &format!("Action {}", i), |p| {
+                add_note(p, i * 480)
+            });
         }
 
         assert_eq!(history.undo_count(), 4);
         assert_eq!(history.redo_count(), 0);
 
-        // Undo all 4 actions (simulating what App::undo does)
+        // Undo all 4 actions
         for _ in 0..4 {
-            let undone = history.pop_undo().unwrap();
-            history.push_redo(undone);
+            history.undo(&mut project, &selection).unwrap();
         }
 
         assert_eq!(history.undo_count(), 0);
         assert_eq!(history.redo_count(), 4);
+        assert!(ticks(&project).is_empty());
 
-        // Now redo all 4 actions using push_undo_preserve_redo (as App::redo does)
+        // Now redo all 4 actions
         for _ in 0..4 {
-            let redone = history.pop_redo().unwrap();
-            // This is the key: use push_undo_preserve_redo, NOT push_undo
-            history.push_undo_preserve_redo(redone);
+            history.redo(&mut project, &selection).unwrap();
         }
 
         // Should have all 4 back in undo stack, redo should be empty
         assert_eq!(history.undo_count(), 4);
         assert_eq!(history.redo_count(), 0);
+        assert_eq!(ticks(&project), vec![0, 480, 960, 1440]);
+    }
+
+    #[test]
+    fn test_grouped_edits_undo_together() {
+        let mut history = HistoryManager::new();
+        let mut project = Project::with_default_track("Test");
+        let selection = Selection::default();
+
+        history.begin_group(&project, &selection);
+        for i in 0..3 {
+            history.record(&project, Selection::default(), format!("Action {}", i));
+            add_note(&mut project, i * 480);
+        }
+        history.end_group();
+        edit(&mut history, &mut project, "After", |p| add_note(p, 9600));
+
+        assert_eq!(history.undo_count(), 2);
+        history.undo(&mut project, &selection).unwrap();
+        let undone = history.undo(&mut project, &selection).unwrap();
+        assert_eq!(undone.description, "Action 0");
+        assert!(ticks(&project).is_empty());
     }
 
     #[test]
     fn test_new_action_clears_redo_after_undo() {
         // Test that a new action after undo clears the redo stack
         let mut history = HistoryManager::new();
-        let project = Project::with_default_track("Test");
+        let mut project = Project::with_default_track("Test");
 
         // Make 3 actions
         for i in 0..3 {
-            history.push_undo(StateSnapshot::new(
-                &project,
-                0,
-                &HashSet::new(),
-                format!("Action {}", i),
-            ));
+            edit(&mut history, &mut project, &format!("Action {}", i), |p| {
+                add_note(p, i * 480)
+            });
         }
 
         // Undo 2 of them
         for _ in 0..2 {
-            let undone = history.pop_undo().unwrap();
-            history.push_redo(undone);
+            history.undo(&mut project, &Selection::default()).unwrap();
         }
 
         assert_eq!(history.undo_count(), 1);
         assert_eq!(history.redo_count(), 2);
 
         // Make a NEW action (this should clear redo stack - branching timeline)
-        history.push_undo(StateSnapshot::new(
-            &project,
-            0,
-            &HashSet::new(),
-            "New action after undo",
-        ));
+        edit(&mut history, &mut project, "New action after undo", |p| {
+            add_note(p, 9600)
+        });
 
         // Redo stack should be cleared, undo should have 2 items
         assert_eq!(history.undo_count(), 2);
         assert_eq!(history.redo_count(), 0);
+        assert_eq!(ticks(&project), vec![0, 9600]);
+    }
+
+    #[test]
+    fn test_jump_to_any_point() {
+        let mut history = HistoryManager::new();
+        let mut project = Project::with_default_track("Test");
+        for i in 0..5 {
+            history.record(&project, Selection::new(0, &HashSet::new()), "Add note");
+            add_note(&mut project, i * 480);
+        }
+
+        let selection = Selection::default();
+        history.jump_to(&mut project, &selection, 2).unwrap();
+        assert_eq!(history.position(), 2);
+        assert_eq!(ticks(&project), vec![0, 480]);
+
+        history.jump_to(&mut project, &selection, 4).unwrap();
+        assert_eq!(ticks(&project), vec![0, 480, 960, 1440]);
+        assert!(history.jump_to(&mut project, &selection, 4).is_none());
+
+        history.jump_to(&mut project, &selection, 0).unwrap();
+        assert!(ticks(&project).is_empty());
+        assert_eq!(history.entries().len(), 5);
+    }
+
+    #[test]
+    fn test_history_survives_serialization() {
+        let mut history = HistoryManager::new();
+        let mut project = Project::with_default_track("Test");
+        edit(&mut history, &mut project, "Add note", |p| add_note(p, 0));
+        edit(&mut history, &mut project, "Rename", |p| {
+            p.name = "Song".into()
+        });
+
+        let data = bincode::serialize(&history).unwrap();
+        let mut restored: HistoryManager = bincode::deserialize(&data).unwrap();
+        assert_eq!(restored.position(), 2);
+
+        let selection = Selection::default();
+        restored.undo(&mut project, &selection).unwrap();
+        restored.undo(&mut project, &selection).unwrap();
+        assert_eq!(project.name, "Test");
+        assert!(ticks(&project).is_empty());
+    }
+
+    #[test]
+    fn test_selection_is_clamped_to_project() {
+        let mut project = Project::with_default_track("Test");
+        add_note(&mut project, 0);
+        let kept = project.tracks()[0].notes()[0].id;
+        let selection = Selection {
+            track_index: 5,
+            notes: [kept, NoteId::new()].into_iter().collect(),
+        };
+
+        let valid = selection.valid_in(&project);
+        assert_eq!(valid.track_index, 0);
+        assert_eq!(valid.notes, [kept].into_iter().collect());
     }
 }
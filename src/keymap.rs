@@ -0,0 +1,1334 @@
+//! Named actions and the keys bound to them.
+//!
+//! Every shortcut handled by the Normal, Insert and Select modes (and the
+//! global ones that work in any mode) is an [`Action`]. The [`Keymap`] maps
+//! keys to actions per [`KeyContext`], starting from the built-in bindings and
+//! overlaid with the user's `keymap.json` from the config directory (or the
+//! file named by `keymap` in the config). The same file picks the keyboard
+//! layout used for playing notes, so the piano keys sit in the same physical
+//! place on AZERTY, QWERTZ, Dvorak and Colemak keyboards.
+//!
+//! ```json
+//! {
+//!   "layout": "azerty",
+//!   "normal": { "x": "delete_note", "d": "unbound" },
+//!   "insert": { "ctrl+left": "octave_down", "ctrl+right": "octave_up" }
+//! }
+//! ```
+//!
+//! The views with their own keys (step sequencer, staff, event list), lyric
+//! mode and the dialogs are not remappable.
+
+use crossterm::event::{KeyCode, KeyModifiers};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// File name of the keymap inside the config directory.
+const KEYMAP_FILE_NAME: &str = "keymap.json";
+
+/// Number of piano keys: two octaves and the C above them.
+pub const PIANO_KEY_COUNT: usize = 25;
+
+/// MIDI note of the first piano key (C3).
+const PIANO_BASE_NOTE: u8 = 48;
+
+/// Something a key can be bound to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    /// Removes the default binding of a key in a keymap file.
+    Unbound,
+    ToggleHelp,
+    Quit,
+    ForceQuit,
+    CycleFocus,
+    TogglePlayback,
+    RestartPlayback,
+    StopPlayback,
+    Undo,
+    Redo,
+    HistoryPanel,
+    CommandLine,
+    InsertMode,
+    SelectMode,
+    LyricMode,
+    NormalMode,
+    CursorLeft,
+    CursorRight,
+    CursorUp,
+    CursorDown,
+    MeasureLeft,
+    MeasureRight,
+    GoToStart,
+    GoToEnd,
+    SetMark,
+    JumpToMark,
+    PlaceNote,
+    DeleteNote,
+    ToggleNoteSelection,
+    DeleteSelection,
+    ClearSelection,
+    MoveNotesUp,
+    MoveNotesDown,
+    MoveNotesLeft,
+    MoveNotesRight,
+    ShrinkNotes,
+    ExpandNotes,
+    Quantize,
+    Groove,
+    ToggleVelocityLane,
+    VelocityUp,
+    VelocityDown,
+    VelocityRamp,
+    Yank,
+    Paste,
+    Register,
+    Repeat,
+    Scale,
+    Chords,
+    Effects,
+    AddMarker,
+    NextMarker,
+    PrevMarker,
+    MarkerList,
+    InsertDeleteTime,
+    ClipFromSelection,
+    DuplicateClipLinked,
+    DuplicateClip,
+    MakeClipUnique,
+    EditClip,
+    DeleteClip,
+    MoveClipLeft,
+    MoveClipRight,
+    AddTrack,
+    AddDrumTrack,
+    DeleteTrack,
+    RenameTrack,
+    ToggleMute,
+    ToggleSolo,
+    NextTrack,
+    PrevTrack,
+    PrevInstrument,
+    NextInstrument,
+    VolumeDown,
+    VolumeUp,
+    PanLeft,
+    PanRight,
+    DrumMap,
+    OctaveDown,
+    OctaveUp,
+    CycleView,
+    ToggleExpandedTracks,
+    CycleHighlight,
+    CycleGhostMode,
+    ToggleGhostTrack,
+    SelectGhostOwner,
+    ZoomIn,
+    ZoomOut,
+    TempoDown,
+    TempoUp,
+    TimeSigDown,
+    TimeSigUp,
+    CycleTimeSigDenominator,
+    NewProject,
+    Save,
+    Open,
+    RecentFiles,
+    RecoverAutosave,
+    LoadSoundfont,
+    Settings,
+    ExportWav,
+    ExportMidi,
+}
+
+impl Action {
+    /// Every bindable action, in help order.
+    pub const ALL: &'static [Action] = &[
+        Action::ToggleHelp,
+        Action::Quit,
+        Action::ForceQuit,
+        Action::CycleFocus,
+        Action::TogglePlayback,
+        Action::RestartPlayback,
+        Action::StopPlayback,
+        Action::Undo,
+        Action::Redo,
+        Action::HistoryPanel,
+        Action::CommandLine,
+        Action::InsertMode,
+        Action::SelectMode,
+        Action::LyricMode,
+        Action::NormalMode,
+        Action::CursorLeft,
+        Action::CursorRight,
+        Action::CursorUp,
+        Action::CursorDown,
+        Action::MeasureLeft,
+        Action::MeasureRight,
+        Action::GoToStart,
+        Action::GoToEnd,
+        Action::SetMark,
+        Action::JumpToMark,
+        Action::PlaceNote,
+        Action::DeleteNote,
+        Action::ToggleNoteSelection,
+        Action::DeleteSelection,
+        Action::ClearSelection,
+        Action::MoveNotesUp,
+        Action::MoveNotesDown,
+        Action::MoveNotesLeft,
+        Action::MoveNotesRight,
+        Action::ShrinkNotes,
+        Action::ExpandNotes,
+        Action::Quantize,
+        Action::Groove,
+        Action::ToggleVelocityLane,
+        Action::VelocityUp,
+        Action::VelocityDown,
+        Action::VelocityRamp,
+        Action::Yank,
+        Action::Paste,
+        Action::Register,
+        Action::Repeat,
+        Action::Scale,
+        Action::Chords,
+        Action::Effects,
+        Action::AddMarker,
+        Action::NextMarker,
+        Action::PrevMarker,
+        Action::MarkerList,
+        Action::InsertDeleteTime,
+        Action::ClipFromSelection,
+        Action::DuplicateClipLinked,
+        Action::DuplicateClip,
+        Action::MakeClipUnique,
+        Action::EditClip,
+        Action::DeleteClip,
+        Action::MoveClipLeft,
+        Action::MoveClipRight,
+        Action::AddTrack,
+        Action::AddDrumTrack,
+        Action::DeleteTrack,
+        Action::RenameTrack,
+        Action::ToggleMute,
+        Action::ToggleSolo,
+        Action::NextTrack,
+        Action::PrevTrack,
+        Action::PrevInstrument,
+        Action::NextInstrument,
+        Action::VolumeDown,
+        Action::VolumeUp,
+        Action::PanLeft,
+        Action::PanRight,
+        Action::DrumMap,
+        Action::OctaveDown,
+        Action::OctaveUp,
+        Action::CycleView,
+        Action::ToggleExpandedTracks,
+        Action::CycleHighlight,
+        Action::CycleGhostMode,
+        Action::ToggleGhostTrack,
+        Action::SelectGhostOwner,
+        Action::ZoomIn,
+        Action::ZoomOut,
+        Action::TempoDown,
+        Action::TempoUp,
+        Action::TimeSigDown,
+        Action::TimeSigUp,
+        Action::CycleTimeSigDenominator,
+        Action::NewProject,
+        Action::Save,
+        Action::Open,
+        Action::RecentFiles,
+        Action::RecoverAutosave,
+        Action::LoadSoundfont,
+        Action::Settings,
+        Action::ExportWav,
+        Action::ExportMidi,
+    ];
+
+    /// Returns true if a count before the action repeats it (`8l`, `3p`).
+    pub fn takes_count(self) -> bool {
+        use Action::*;
+        matches!(
+            self,
+            CursorLeft
+                | CursorRight
+                | CursorUp
+                | CursorDown
+                | MeasureLeft
+                | MeasureRight
+                | PlaceNote
+                | MoveNotesUp
+                | MoveNotesDown
+                | MoveNotesLeft
+                | MoveNotesRight
+                | ShrinkNotes
+                | ExpandNotes
+                | VelocityUp
+                | VelocityDown
+                | Paste
+                | NextMarker
+                | PrevMarker
+                | MoveClipLeft
+                | MoveClipRight
+                | NextTrack
+                | PrevTrack
+                | PrevInstrument
+                | NextInstrument
+                | VolumeDown
+                | VolumeUp
+                | PanLeft
+                | PanRight
+                | OctaveDown
+                | OctaveUp
+                | ZoomIn
+                | ZoomOut
+                | TempoDown
+                | TempoUp
+                | TimeSigDown
+                | TimeSigUp
+        )
+    }
+
+    /// Returns true if the action is an edit that `.` repeats.
+    pub fn is_repeatable_edit(self) -> bool {
+        use Action::*;
+        matches!(
+            self,
+            PlaceNote
+                | DeleteNote
+                | DeleteSelection
+                | MoveNotesUp
+                | MoveNotesDown
+                | MoveNotesLeft
+                | MoveNotesRight
+                | ShrinkNotes
+                | ExpandNotes
+                | VelocityUp
+                | VelocityDown
+                | VelocityRamp
+                | Paste
+        )
+    }
+
+    /// Returns the help section the action is listed under.
+    pub fn section(self) -> HelpSection {
+        use Action::*;
+        match self {
+            Unbound | ToggleHelp | Quit | ForceQuit | CycleFocus | TogglePlayback
+            | RestartPlayback | StopPlayback | Undo | Redo | HistoryPanel | CommandLine => {
+                HelpSection::General
+            }
+            InsertMode | SelectMode | LyricMode | NormalMode => HelpSection::Modes,
+            CursorLeft | CursorRight | CursorUp | CursorDown | MeasureLeft | MeasureRight
+            | GoToStart | GoToEnd | SetMark | JumpToMark => HelpSection::Navigation,
+            PlaceNote | DeleteNote | ToggleNoteSelection | DeleteSelection | ClearSelection
+            | MoveNotesUp | MoveNotesDown | MoveNotesLeft | MoveNotesRight | ShrinkNotes
+            | ExpandNotes | Quantize | Groove | ToggleVelocityLane | VelocityUp | VelocityDown
+            | VelocityRamp | Yank | Paste | Register | Repeat | Scale | Chords | Effects => {
+                HelpSection::Editing
+            }
+            AddMarker | NextMarker | PrevMarker | MarkerList | InsertDeleteTime => {
+                HelpSection::Markers
+            }
+            ClipFromSelection | DuplicateClipLinked | DuplicateClip | MakeClipUnique | EditClip
+            | DeleteClip | MoveClipLeft | MoveClipRight => HelpSection::Clips,
+            AddTrack | AddDrumTrack | DeleteTrack | RenameTrack | ToggleMute | ToggleSolo
+            | NextTrack | PrevTrack | PrevInstrument | NextInstrument | VolumeDown | VolumeUp
+            | PanLeft | PanRight | DrumMap => HelpSection::Tracks,
+            OctaveDown | OctaveUp => HelpSection::Keyboard,
+            CycleView
+            | ToggleExpandedTracks
+            | CycleHighlight
+            | CycleGhostMode
+            | ToggleGhostTrack
+            | SelectGhostOwner
+            | ZoomIn
+            | ZoomOut
+            | TempoDown
+            | TempoUp
+            | TimeSigDown
+            | TimeSigUp
+            | CycleTimeSigDenominator => HelpSection::View,
+            NewProject | Save | Open | RecentFiles | RecoverAutosave | LoadSoundfont | Settings
+            | ExportWav | ExportMidi => HelpSection::File,
+        }
+    }
+
+    /// Returns the one-line description shown in the help.
+    pub fn description(self) -> &'static str {
+        use Action::*;
+        match self {
+            Unbound => "Nothing",
+            ToggleHelp => "Toggle this help",
+            Quit => "Quit (asks to save unsaved changes)",
+            ForceQuit => "Force quit",
+            CycleFocus => "Cycle focus between panels",
+            TogglePlayback => "Play / Pause",
+            RestartPlayback => "Restart playback from beginning",
+            StopPlayback => "Stop (reset to start)",
+            Undo => "Undo",
+            Redo => "Redo",
+            HistoryPanel => "Undo history (jump to any edit)",
+            CommandLine => "Command line (:tempo 96, :w song.mid, ...)",
+            InsertMode => "Enter INSERT mode",
+            SelectMode => "Enter SELECT mode",
+            LyricMode => "Enter LYRIC mode (type syllables per note)",
+            NormalMode => "Return to NORMAL mode",
+            CursorLeft => "Move cursor left",
+            CursorRight => "Move cursor right",
+            CursorUp => "Move cursor up (higher pitch)",
+            CursorDown => "Move cursor down (lower pitch)",
+            MeasureLeft => "Jump left by measure",
+            MeasureRight => "Jump right by measure",
+            GoToStart => "Go to start",
+            GoToEnd => "Go to end",
+            SetMark => "Set mark (then a-z; saves cursor tick and pitch)",
+            JumpToMark => "Jump to mark (then a-z)",
+            PlaceNote => "Place note at cursor",
+            DeleteNote => "Delete note at cursor",
+            ToggleNoteSelection => "Select/deselect note at cursor",
+            DeleteSelection => "Delete selected notes",
+            ClearSelection => "Clear selection",
+            MoveNotesUp => "Move selected notes up (or the cursor)",
+            MoveNotesDown => "Move selected notes down (or the cursor)",
+            MoveNotesLeft => "Move selected notes left (or the cursor)",
+            MoveNotesRight => "Move selected notes right (or the cursor)",
+            ShrinkNotes => "Shrink selected note duration",
+            ExpandNotes => "Expand selected note duration",
+            Quantize => "Quantize selection (or track) with preview",
+            Groove => "Humanize / apply or extract groove",
+            ToggleVelocityLane => "Show/hide velocity lane (drag bars to edit)",
+            VelocityUp => "Raise velocity of selected notes",
+            VelocityDown => "Lower velocity of selected notes",
+            VelocityRamp => "Velocity ramp across selection",
+            Yank => "Yank selected notes (or the note at cursor)",
+            Paste => "Paste notes at cursor",
+            Register => "Use register for next yank/delete/paste (then a-z)",
+            Repeat => "Repeat last edit",
+            Scale => "Set key/scale (shading and scale snap)",
+            Chords => "Chords: chord entry, chord track, accompaniment",
+            Effects => "Arpeggiator/echo: render or add as track effect",
+            AddMarker => "Add marker at cursor",
+            NextMarker => "Jump to next marker",
+            PrevMarker => "Jump to previous marker",
+            MarkerList => "Marker list (rename, color, section ops)",
+            InsertDeleteTime => "Insert/delete time at cursor (all tracks)",
+            ClipFromSelection => "Turn selected notes into a clip",
+            DuplicateClipLinked => "Linked copy of clip at cursor",
+            DuplicateClip => "Independent copy of clip at cursor",
+            MakeClipUnique => "Make linked clip unique",
+            EditClip => "Open/close clip for note editing",
+            DeleteClip => "Delete clip at cursor",
+            MoveClipLeft => "Move clip one measure earlier",
+            MoveClipRight => "Move clip one measure later",
+            AddTrack => "Add new track",
+            AddDrumTrack => "Add drum track (channel 10)",
+            DeleteTrack => "Delete selected track",
+            RenameTrack => "Rename selected track",
+            ToggleMute => "Toggle mute on selected track",
+            ToggleSolo => "Toggle solo on selected track",
+            NextTrack => "Select next track",
+            PrevTrack => "Select previous track",
+            PrevInstrument => "Previous instrument (GM)",
+            NextInstrument => "Next instrument (GM)",
+            VolumeDown => "Decrease volume",
+            VolumeUp => "Increase volume",
+            PanLeft => "Pan left",
+            PanRight => "Pan right",
+            DrumMap => "Drum map: names, order, export remap, velocity",
+            OctaveDown => "Octave down",
+            OctaveUp => "Octave up",
+            CycleView => "Cycle views (Combined/Piano/Timeline/Staff or Steps/Events)",
+            ToggleExpandedTracks => "Toggle track list view (compact/expanded)",
+            CycleHighlight => "Toggle active track highlighting",
+            CycleGhostMode => "Ghost notes: off/track above/all/chosen",
+            ToggleGhostTrack => "Mark/unmark track as chosen ghost track",
+            SelectGhostOwner => "Edit the track of the ghost note at cursor",
+            ZoomIn => "Zoom in",
+            ZoomOut => "Zoom out",
+            TempoDown => "Decrease tempo (BPM)",
+            TempoUp => "Increase tempo (BPM)",
+            TimeSigDown => "Decrease time sig numerator",
+            TimeSigUp => "Increase time sig numerator",
+            CycleTimeSigDenominator => "Cycle time sig denominator (2/4/8/16)",
+            NewProject => "New project (with confirmation)",
+            Save => "Save project",
+            Open => "Open project",
+            RecentFiles => "Open recent file",
+            RecoverAutosave => "Recover an autosave version",
+            LoadSoundfont => "Load SoundFont (.sf2)",
+            Settings => "Settings (saved to config file)",
+            ExportWav => "Export to WAV",
+            ExportMidi => "Export to MIDI (.mid)",
+        }
+    }
+}
+
+/// Help sections built from the keymap, in display order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HelpSection {
+    General,
+    Modes,
+    Navigation,
+    Editing,
+    Markers,
+    Clips,
+    Tracks,
+    Keyboard,
+    View,
+    File,
+}
+
+impl HelpSection {
+    /// Every section, in display order.
+    pub const ALL: [HelpSection; 10] = [
+        HelpSection::General,
+        HelpSection::Modes,
+        HelpSection::Navigation,
+        HelpSection::Editing,
+        HelpSection::Markers,
+        HelpSection::Clips,
+        HelpSection::Tracks,
+        HelpSection::Keyboard,
+        HelpSection::View,
+        HelpSection::File,
+    ];
+
+    /// Returns the section heading.
+    pub fn title(self) -> &'static str {
+        match self {
+            HelpSection::General => "General",
+            HelpSection::Modes => "Modes",
+            HelpSection::Navigation => "Navigation",
+            HelpSection::Editing => "Editing",
+            HelpSection::Markers => "Markers & Sections",
+            HelpSection::Clips => "Clips",
+            HelpSection::Tracks => "Tracks",
+            HelpSection::Keyboard => "Keyboard",
+            HelpSection::View => "View",
+            HelpSection::File => "File & Export",
+        }
+    }
+}
+
+/// Where a binding applies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KeyContext {
+    /// Any mode; checked before the mode's own bindings.
+    Global,
+    Normal,
+    Insert,
+    Select,
+}
+
+impl KeyContext {
+    /// Returns the name used for the context in the keymap file.
+    pub fn name(self) -> &'static str {
+        match self {
+            KeyContext::Global => "global",
+            KeyContext::Normal => "normal",
+            KeyContext::Insert => "insert",
+            KeyContext::Select => "select",
+        }
+    }
+}
+
+/// A key with its modifiers.
+///
+/// Written as modifiers and a key joined by `+`, e.g. `ctrl+z`,
+/// `shift+space`, `H` or `left`. Shift on a letter is folded into the
+/// uppercase letter, so `shift+a` is the same key as `A`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyChord {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyChord {
+    /// Creates a chord, folding Shift into uppercase letters.
+    pub fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        match code {
+            KeyCode::Char(c) if c.is_alphabetic() && modifiers.contains(KeyModifiers::SHIFT) => {
+                Self {
+                    code: KeyCode::Char(c.to_uppercase().next().unwrap_or(c)),
+                    modifiers: modifiers - KeyModifiers::SHIFT,
+                }
+            }
+            _ => Self { code, modifiers },
+        }
+    }
+}
+
+impl FromStr for KeyChord {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // "+" is a key itself, alone or after a modifier ("ctrl++")
+        let (mods, key) = match s.strip_suffix('+') {
+            Some("") => ("", "+"),
+            Some(rest) if rest.ends_with('+') => (&rest[..rest.len() - 1], "+"),
+            _ => s.rsplit_once('+').unwrap_or(("", s)),
+        };
+
+        let mut modifiers = KeyModifiers::NONE;
+        for name in mods.split('+').filter(|m| !m.is_empty()) {
+            modifiers |= match name.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => KeyModifiers::CONTROL,
+                "alt" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                _ => return Err(format!("unknown modifier '{}' in '{}'", name, s)),
+            };
+        }
+
+        let mut chars = key.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) => KeyCode::Char(c),
+            _ => match key.to_ascii_lowercase().as_str() {
+                "space" => KeyCode::Char(' '),
+                "enter" | "return" => KeyCode::Enter,
+                "esc" | "escape" => KeyCode::Esc,
+                "tab" => KeyCode::Tab,
+                "backtab" => KeyCode::BackTab,
+                "backspace" => KeyCode::Backspace,
+                "delete" | "del" => KeyCode::Delete,
+                "insert" => KeyCode::Insert,
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                "pageup" => KeyCode::PageUp,
+                "pagedown" => KeyCode::PageDown,
+                name => match name.strip_prefix('f').and_then(|n| n.parse::<u8>().ok()) {
+                    Some(n @ 1..=12) => KeyCode::F(n),
+                    _ => return Err(format!("unknown key '{}'", s)),
+                },
+            },
+        };
+        Ok(Self::new(code, modifiers))
+    }
+}
+
+impl fmt::Display for KeyChord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            f.write_str("Ctrl+")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            f.write_str("Alt+")?;
+        }
+        if self.modifiers.contains(KeyModifiers::SHIFT) {
+            f.write_str("Shift+")?;
+        }
+        match self.code {
+            KeyCode::Char(' ') => f.write_str("Space"),
+            KeyCode::Char(c) => write!(f, "{}", c),
+            KeyCode::Enter => f.write_str("Enter"),
+            KeyCode::Esc => f.write_str("Esc"),
+            KeyCode::Tab => f.write_str("Tab"),
+            KeyCode::BackTab => f.write_str("BackTab"),
+            KeyCode::Backspace => f.write_str("Backspace"),
+            KeyCode::Delete => f.write_str("Delete"),
+            KeyCode::Insert => f.write_str("Insert"),
+            KeyCode::Left => f.write_str("Left"),
+            KeyCode::Right => f.write_str("Right"),
+            KeyCode::Up => f.write_str("Up"),
+            KeyCode::Down => f.write_str("Down"),
+            KeyCode::Home => f.write_str("Home"),
+            KeyCode::End => f.write_str("End"),
+            KeyCode::PageUp => f.write_str("PageUp"),
+            KeyCode::PageDown => f.write_str("PageDown"),
+            KeyCode::F(n) => write!(f, "F{}", n),
+            other => write!(f, "{:?}", other),
+        }
+    }
+}
+
+/// Physical keyboard layout, which decides the piano keys.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KeyboardLayout {
+    #[default]
+    Qwerty,
+    Azerty,
+    Qwertz,
+    Dvorak,
+    Colemak,
+}
+
+impl KeyboardLayout {
+    /// Returns the unshifted characters of the number, top, home and bottom
+    /// letter rows, from the left.
+    fn rows(self) -> [&'static str; 4] {
+        match self {
+            KeyboardLayout::Qwerty => ["1234567890", "qwertyuiop", "asdfghjkl;", "zxcvbnm,./"],
+            KeyboardLayout::Azerty => ["&é\"'(-è_çà", "azertyuiop", "qsdfghjklm", "wxcvbn,;:!"],
+            KeyboardLayout::Qwertz => ["1234567890", "qwertzuiop", "asdfghjklö", "yxcvbnm,.-"],
+            KeyboardLayout::Dvorak => ["1234567890", "',.pyfgcrl", "aoeuidhtns", ";qjkxbmwvz"],
+            KeyboardLayout::Colemak => ["1234567890", "qwfpgjluy;", "arstdhneio", "zxcvbkm,./"],
+        }
+    }
+
+    /// Returns the 25 piano keys from C3 up, in the same physical places on
+    /// every layout: the bottom and home rows play C3-B3, the top and number
+    /// rows C4-C5.
+    pub fn piano_keys(self) -> Vec<char> {
+        // (row, column) of each key, rows as in `rows()`
+        const POSITIONS: [(usize, usize); PIANO_KEY_COUNT] = [
+            (3, 0),
+            (2, 1),
+            (3, 1),
+            (2, 2),
+            (3, 2),
+            (3, 3),
+            (2, 4),
+            (3, 4),
+            (2, 5),
+            (3, 5),
+            (2, 6),
+            (3, 6),
+            (1, 0),
+            (0, 1),
+            (1, 1),
+            (0, 2),
+            (1, 2),
+            (1, 3),
+            (0, 4),
+            (1, 4),
+            (0, 5),
+            (1, 5),
+            (0, 6),
+            (1, 6),
+            (1, 7),
+        ];
+        let rows = self.rows().map(|row| row.chars().collect::<Vec<_>>());
+        POSITIONS
+            .iter()
+            .map(|&(row, column)| rows[row][column])
+            .collect()
+    }
+}
+
+/// A key bound to an action in one context.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Binding {
+    pub context: KeyContext,
+    pub chord: KeyChord,
+    pub action: Action,
+}
+
+/// Built-in bindings, in the order they are listed in the help.
+const DEFAULT_BINDINGS: &[(KeyContext, &str, Action)] = &[
+    (KeyContext::Global, "ctrl+c", Action::ForceQuit),
+    (KeyContext::Global, "ctrl+q", Action::Quit),
+    (KeyContext::Global, "ctrl+z", Action::Undo),
+    (KeyContext::Global, "ctrl+y", Action::Redo),
+    (KeyContext::Global, "ctrl+p", Action::Settings),
+    (KeyContext::Global, "ctrl+u", Action::HistoryPanel),
+    (KeyContext::Global, "?", Action::ToggleHelp),
+    (KeyContext::Global, "esc", Action::NormalMode),
+    (KeyContext::Global, "tab", Action::CycleFocus),
+    (KeyContext::Global, "shift+space", Action::RestartPlayback),
+    (KeyContext::Global, "space", Action::TogglePlayback),
+    (KeyContext::Global, "ctrl+e", Action::ExportWav),
+    (KeyContext::Global, "ctrl+m", Action::ExportMidi),
+    (KeyContext::Global, "ctrl+s", Action::Save),
+    (KeyContext::Global, "ctrl+r", Action::RecentFiles),
+    (KeyContext::Global, "ctrl+a", Action::RecoverAutosave),
+    (KeyContext::Global, "ctrl+o", Action::Open),
+    (KeyContext::Global, "ctrl+n", Action::NewProject),
+    (KeyContext::Global, "ctrl+l", Action::LoadSoundfont),
+    (KeyContext::Global, "ctrl+k", Action::MarkerList),
+    (KeyContext::Global, "ctrl+b", Action::DrumMap),
+    (KeyContext::Normal, "q", Action::Quit),
+    (KeyContext::Normal, ":", Action::CommandLine),
+    (KeyContext::Normal, "backspace", Action::StopPlayback),
+    (KeyContext::Normal, ".", Action::Repeat),
+    (KeyContext::Normal, "i", Action::InsertMode),
+    (KeyContext::Normal, "v", Action::SelectMode),
+    (KeyContext::Normal, "I", Action::LyricMode),
+    (KeyContext::Normal, "M", Action::AddMarker),
+    (KeyContext::Normal, "N", Action::NextMarker),
+    (KeyContext::Normal, "P", Action::PrevMarker),
+    (KeyContext::Normal, "Q", Action::Quantize),
+    (KeyContext::Normal, "V", Action::ToggleVelocityLane),
+    (KeyContext::Normal, "O", Action::CycleGhostMode),
+    (KeyContext::Normal, "R", Action::ToggleGhostTrack),
+    (KeyContext::Normal, "B", Action::SelectGhostOwner),
+    (KeyContext::Normal, "G", Action::Groove),
+    (KeyContext::Normal, "T", Action::InsertDeleteTime),
+    (KeyContext::Normal, "S", Action::Scale),
+    (KeyContext::Normal, "Y", Action::Chords),
+    (KeyContext::Normal, "F", Action::Effects),
+    (KeyContext::Normal, "D", Action::DuplicateClipLinked),
+    (KeyContext::Normal, "C", Action::DuplicateClip),
+    (KeyContext::Normal, "U", Action::MakeClipUnique),
+    (KeyContext::Normal, "E", Action::EditClip),
+    (KeyContext::Normal, "X", Action::DeleteClip),
+    (KeyContext::Normal, "ctrl+left", Action::MoveClipLeft),
+    (KeyContext::Normal, "ctrl+right", Action::MoveClipRight),
+    (KeyContext::Normal, "h", Action::CursorLeft),
+    (KeyContext::Normal, "left", Action::CursorLeft),
+    (KeyContext::Normal, "l", Action::CursorRight),
+    (KeyContext::Normal, "right", Action::CursorRight),
+    (KeyContext::Normal, "k", Action::CursorUp),
+    (KeyContext::Normal, "up", Action::CursorUp),
+    (KeyContext::Normal, "j", Action::CursorDown),
+    (KeyContext::Normal, "down", Action::CursorDown),
+    (KeyContext::Normal, "H", Action::MeasureLeft),
+    (KeyContext::Normal, "L", Action::MeasureRight),
+    (KeyContext::Normal, "0", Action::GoToStart),
+    (KeyContext::Normal, "$", Action::GoToEnd),
+    (KeyContext::Normal, "f", Action::SetMark),
+    (KeyContext::Normal, "`", Action::JumpToMark),
+    (KeyContext::Normal, "J", Action::NextTrack),
+    (KeyContext::Normal, "K", Action::PrevTrack),
+    (KeyContext::Normal, "a", Action::AddTrack),
+    (KeyContext::Normal, "A", Action::AddDrumTrack),
+    (KeyContext::Normal, "x", Action::DeleteTrack),
+    (KeyContext::Normal, "d", Action::DeleteTrack),
+    (KeyContext::Normal, "r", Action::RenameTrack),
+    (KeyContext::Normal, "g", Action::CycleView),
+    (KeyContext::Normal, "t", Action::ToggleExpandedTracks),
+    (KeyContext::Normal, "m", Action::ToggleMute),
+    (KeyContext::Normal, "s", Action::ToggleSolo),
+    (KeyContext::Normal, "enter", Action::PlaceNote),
+    (KeyContext::Normal, "n", Action::PlaceNote),
+    (KeyContext::Normal, "delete", Action::DeleteNote),
+    (KeyContext::Normal, "o", Action::Yank),
+    (KeyContext::Normal, "p", Action::Paste),
+    (KeyContext::Normal, "\"", Action::Register),
+    (KeyContext::Normal, "=", Action::ZoomIn),
+    (KeyContext::Normal, "+", Action::ZoomIn),
+    (KeyContext::Normal, "-", Action::ZoomOut),
+    (KeyContext::Normal, ",", Action::OctaveDown),
+    (KeyContext::Normal, "/", Action::OctaveUp),
+    (KeyContext::Normal, "[", Action::TempoDown),
+    (KeyContext::Normal, "]", Action::TempoUp),
+    (KeyContext::Normal, "{", Action::TimeSigDown),
+    (KeyContext::Normal, "}", Action::TimeSigUp),
+    (KeyContext::Normal, "|", Action::CycleTimeSigDenominator),
+    (KeyContext::Normal, "<", Action::PrevInstrument),
+    (KeyContext::Normal, ">", Action::NextInstrument),
+    (KeyContext::Normal, ";", Action::VolumeDown),
+    (KeyContext::Normal, "'", Action::VolumeUp),
+    (KeyContext::Normal, "(", Action::PanLeft),
+    (KeyContext::Normal, ")", Action::PanRight),
+    (KeyContext::Normal, "e", Action::ExportWav),
+    (KeyContext::Normal, "W", Action::CycleHighlight),
+    (KeyContext::Insert, "left", Action::CursorLeft),
+    (KeyContext::Insert, "right", Action::CursorRight),
+    (KeyContext::Insert, "up", Action::CursorUp),
+    (KeyContext::Insert, "down", Action::CursorDown),
+    (KeyContext::Insert, ",", Action::OctaveDown),
+    (KeyContext::Insert, "/", Action::OctaveUp),
+    (KeyContext::Insert, "<", Action::PrevInstrument),
+    (KeyContext::Insert, ">", Action::NextInstrument),
+    (KeyContext::Select, ":", Action::CommandLine),
+    (KeyContext::Select, ".", Action::Repeat),
+    (KeyContext::Select, "A", Action::ShrinkNotes),
+    (KeyContext::Select, "D", Action::ExpandNotes),
+    (KeyContext::Select, "w", Action::MoveNotesUp),
+    (KeyContext::Select, "s", Action::MoveNotesDown),
+    (KeyContext::Select, "a", Action::MoveNotesLeft),
+    (KeyContext::Select, "d", Action::MoveNotesRight),
+    (KeyContext::Select, "h", Action::CursorLeft),
+    (KeyContext::Select, "left", Action::CursorLeft),
+    (KeyContext::Select, "l", Action::CursorRight),
+    (KeyContext::Select, "right", Action::CursorRight),
+    (KeyContext::Select, "k", Action::CursorUp),
+    (KeyContext::Select, "up", Action::CursorUp),
+    (KeyContext::Select, "j", Action::CursorDown),
+    (KeyContext::Select, "down", Action::CursorDown),
+    (KeyContext::Select, "m", Action::SetMark),
+    (KeyContext::Select, "'", Action::JumpToMark),
+    (KeyContext::Select, "`", Action::JumpToMark),
+    (KeyContext::Select, "enter", Action::ToggleNoteSelection),
+    (KeyContext::Select, "x", Action::DeleteSelection),
+    (KeyContext::Select, "delete", Action::DeleteSelection),
+    (KeyContext::Select, "y", Action::Yank),
+    (KeyContext::Select, "p", Action::Paste),
+    (KeyContext::Select, "\"", Action::Register),
+    (KeyContext::Select, "Q", Action::Quantize),
+    (KeyContext::Select, "+", Action::VelocityUp),
+    (KeyContext::Select, "=", Action::VelocityUp),
+    (KeyContext::Select, "-", Action::VelocityDown),
+    (KeyContext::Select, "r", Action::VelocityRamp),
+    (KeyContext::Select, "V", Action::ToggleVelocityLane),
+    (KeyContext::Select, "G", Action::Groove),
+    (KeyContext::Select, "F", Action::Effects),
+    (KeyContext::Select, "C", Action::ClipFromSelection),
+    (KeyContext::Select, "c", Action::ClearSelection),
+];
+
+/// The keymap file as written by the user.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct KeymapFile {
+    layout: KeyboardLayout,
+    /// The 25 piano keys from C3 up, overriding the layout's.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    piano: Option<String>,
+    global: BTreeMap<String, Action>,
+    normal: BTreeMap<String, Action>,
+    insert: BTreeMap<String, Action>,
+    select: BTreeMap<String, Action>,
+}
+
+impl KeymapFile {
+    /// Returns the bindings written for `context`.
+    fn bindings_mut(&mut self, context: KeyContext) -> &mut BTreeMap<String, Action> {
+        match context {
+            KeyContext::Global => &mut self.global,
+            KeyContext::Normal => &mut self.normal,
+            KeyContext::Insert => &mut self.insert,
+            KeyContext::Select => &mut self.select,
+        }
+    }
+}
+
+/// Keys bound to actions, plus the piano keys for playing notes.
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    bindings: Vec<Binding>,
+    layout: KeyboardLayout,
+    piano: Vec<char>,
+    conflicts: Vec<String>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let bindings = DEFAULT_BINDINGS
+            .iter()
+            .map(|&(context, key, action)| Binding {
+                context,
+                chord: key.parse().expect("default key binding parses"),
+                action,
+            })
+            .collect();
+        let mut keymap = Self {
+            bindings,
+            layout: KeyboardLayout::default(),
+            piano: KeyboardLayout::default().piano_keys(),
+            conflicts: Vec::new(),
+        };
+        keymap.find_conflicts();
+        keymap
+    }
+}
+
+impl Keymap {
+    /// Loads the keymap from `path`, or from the config directory if None.
+    ///
+    /// A missing file gives the built-in keymap.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file exists but can't be read, isn't valid
+    /// JSON, or names an unknown key, action or layout
+    pub fn load(path: Option<&Path>) -> std::io::Result<Self> {
+        let path: Option<PathBuf> = path
+            .map(Path::to_path_buf)
+            .or_else(|| crate::config::config_dir().map(|dir| dir.join(KEYMAP_FILE_NAME)));
+        let Some(path) = path else {
+            return Ok(Self::default());
+        };
+        match std::fs::read_to_string(&path) {
+            Ok(text) => Self::parse(&text),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Parses a keymap file, applying its bindings over the built-in ones.
+    fn parse(text: &str) -> std::io::Result<Self> {
+        let invalid = |e: String| std::io::Error::new(std::io::ErrorKind::InvalidData, e);
+        let file: KeymapFile = serde_json::from_str(text).map_err(|e| invalid(e.to_string()))?;
+
+        let mut keymap = Self::default();
+        let mut conflicts = Vec::new();
+        keymap.layout = file.layout;
+        keymap.piano = match &file.piano {
+            Some(keys) => {
+                let keys: Vec<char> = keys.chars().map(|c| c.to_ascii_lowercase()).collect();
+                if keys.len() != PIANO_KEY_COUNT {
+                    return Err(invalid(format!(
+                        "piano needs {} keys, got {}",
+                        PIANO_KEY_COUNT,
+                        keys.len()
+                    )));
+                }
+                keys
+            }
+            None => file.layout.piano_keys(),
+        };
+
+        // The layout's piano keys win over built-in Insert bindings (',' is a
+        // note on AZERTY and Dvorak); only the user's own are reported
+        let piano = keymap.piano.clone();
+        keymap.bindings.retain(|b| {
+            b.context != KeyContext::Insert
+                || !matches!(b.chord.code, KeyCode::Char(c) if piano.contains(&c))
+        });
+
+        for (context, entries) in [
+            (KeyContext::Global, &file.global),
+            (KeyContext::Normal, &file.normal),
+            (KeyContext::Insert, &file.insert),
+            (KeyContext::Select, &file.select),
+        ] {
+            // Different spellings of the same key ("shift+a", "A") collide
+            let mut seen: Vec<(KeyChord, &str)> = Vec::new();
+            for (key, &action) in entries {
+                let chord: KeyChord = key.parse().map_err(invalid)?;
+                if let Some((_, first)) = seen.iter().find(|(c, _)| *c == chord) {
+                    conflicts.push(format!(
+                        "{}: '{}' and '{}' are the same key; using '{}'",
+                        context.name(),
+                        first,
+                        key,
+                        key
+                    ));
+                }
+                seen.push((chord, key));
+                keymap.bind(context, chord, action);
+            }
+        }
+
+        keymap.find_conflicts();
+        conflicts.append(&mut keymap.conflicts);
+        keymap.conflicts = conflicts;
+        Ok(keymap)
+    }
+
+    /// Binds `chord` to `action` in `context`, replacing the key's current
+    /// binding there. [`Action::Unbound`] just removes it.
+    pub fn bind(&mut self, context: KeyContext, chord: KeyChord, action: Action) {
+        let existing = self
+            .bindings
+            .iter()
+            .position(|b| b.context == context && b.chord == chord);
+        match (existing, action) {
+            (Some(index), Action::Unbound) => {
+                self.bindings.remove(index);
+            }
+            (Some(index), _) => self.bindings[index].action = action,
+            (None, Action::Unbound) => {}
+            (None, _) => self.bindings.push(Binding {
+                context,
+                chord,
+                action,
+            }),
+        }
+    }
+
+    /// Records keys that can never trigger their action: mode bindings
+    /// hidden by a global one, Insert bindings on a piano key, and piano
+    /// keys given twice.
+    fn find_conflicts(&mut self) {
+        let mut conflicts = Vec::new();
+        for binding in &self.bindings {
+            if binding.context == KeyContext::Global {
+                continue;
+            }
+            if let Some(global) = self.lookup(KeyContext::Global, binding.chord) {
+                conflicts.push(format!(
+                    "{}: {} ({}) is hidden by the global binding ({})",
+                    binding.context.name(),
+                    binding.chord,
+                    action_name(binding.action),
+                    action_name(global)
+                ));
+            }
+            if binding.context == KeyContext::Insert && binding.chord.modifiers.is_empty() {
+                if let KeyCode::Char(c) = binding.chord.code {
+                    if self.piano.contains(&c.to_ascii_lowercase()) {
+                        conflicts.push(format!(
+                            "insert: {} ({}) hides a piano key",
+                            binding.chord,
+                            action_name(binding.action)
+                        ));
+                    }
+                }
+            }
+        }
+        for (index, key) in self.piano.iter().enumerate() {
+            if self.piano[..index].contains(key) {
+                conflicts.push(format!("piano: '{}' is used for two notes", key));
+            }
+        }
+        self.conflicts = conflicts;
+    }
+
+    /// Returns the action bound to exactly `chord` in `context`.
+    fn lookup(&self, context: KeyContext, chord: KeyChord) -> Option<Action> {
+        self.bindings
+            .iter()
+            .find(|b| b.context == context && b.chord == chord)
+            .map(|b| b.action)
+    }
+
+    /// Returns the action for a key event in `context`.
+    ///
+    /// Falls back to the key without Shift, so `shift+left` still moves the
+    /// cursor unless it has a binding of its own.
+    pub fn action(
+        &self,
+        context: KeyContext,
+        code: KeyCode,
+        modifiers: KeyModifiers,
+    ) -> Option<Action> {
+        self.lookup(context, KeyChord::new(code, modifiers))
+            .or_else(|| {
+                modifiers.contains(KeyModifiers::SHIFT).then(|| {
+                    self.lookup(
+                        context,
+                        KeyChord::new(code, modifiers - KeyModifiers::SHIFT),
+                    )
+                })?
+            })
+    }
+
+    /// Returns every binding, in help order.
+    pub fn bindings(&self) -> &[Binding] {
+        &self.bindings
+    }
+
+    /// Returns the problems found while loading the keymap.
+    pub fn conflicts(&self) -> &[String] {
+        &self.conflicts
+    }
+
+    /// Returns the label of the first key for `action` in `context` (or
+    /// globally), for short hints.
+    pub fn key_label(&self, context: KeyContext, action: Action) -> Option<String> {
+        [context, KeyContext::Global].iter().find_map(|&ctx| {
+            self.bindings
+                .iter()
+                .find(|b| b.context == ctx && b.action == action)
+                .map(|b| b.chord.to_string())
+        })
+    }
+
+    /// Returns the piano keys from C3 up.
+    pub fn piano_keys(&self) -> &[char] {
+        &self.piano
+    }
+
+    /// Returns the note (before the octave offset) a piano key plays.
+    pub fn piano_note(&self, key: char) -> Option<u8> {
+        let key = key.to_ascii_lowercase();
+        let index = self.piano.iter().position(|&k| k == key)?;
+        Some(PIANO_BASE_NOTE + index as u8)
+    }
+
+    /// Returns the piano key ranges for hints, e.g. "Z-M / Q-I".
+    pub fn piano_label(&self) -> String {
+        let upper = |c: char| c.to_uppercase().collect::<String>();
+        format!(
+            "{}-{} / {}-{}",
+            upper(self.piano[0]),
+            upper(self.piano[11]),
+            upper(self.piano[12]),
+            upper(self.piano[PIANO_KEY_COUNT - 1])
+        )
+    }
+
+    /// Returns the keymap as a keymap file, for `--print-keymap`.
+    pub fn to_json(&self) -> String {
+        let mut file = KeymapFile {
+            layout: self.layout,
+            piano: (self.piano != self.layout.piano_keys()).then(|| self.piano.iter().collect()),
+            ..KeymapFile::default()
+        };
+        for binding in &self.bindings {
+            file.bindings_mut(binding.context)
+                .insert(binding.chord.to_string(), binding.action);
+        }
+        // Removed defaults have to stay removed when the file is loaded
+        for binding in Keymap::default().bindings {
+            if self.lookup(binding.context, binding.chord).is_none() {
+                file.bindings_mut(binding.context)
+                    .insert(binding.chord.to_string(), Action::Unbound);
+            }
+        }
+        serde_json::to_string_pretty(&file).unwrap_or_default()
+    }
+}
+
+/// Returns the name an action has in the keymap file.
+fn action_name(action: Action) -> String {
+    serde_json::to_value(action)
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_chords_parse_and_print() {
+        for (text, code, modifiers, label) in [
+            (
+                "ctrl+z",
+                KeyCode::Char('z'),
+                KeyModifiers::CONTROL,
+                "Ctrl+z",
+            ),
+            (
+                "shift+space",
+                KeyCode::Char(' '),
+                KeyModifiers::SHIFT,
+                "Shift+Space",
+            ),
+            ("shift+a", KeyCode::Char('A'), KeyModifiers::NONE, "A"),
+            ("+", KeyCode::Char('+'), KeyModifiers::NONE, "+"),
+            (
+                "Ctrl++",
+                KeyCode::Char('+'),
+                KeyModifiers::CONTROL,
+                "Ctrl++",
+            ),
+            (
+                "PageDown",
+                KeyCode::PageDown,
+                KeyModifiers::NONE,
+                "PageDown",
+            ),
+            ("f5", KeyCode::F(5), KeyModifiers::NONE, "F5"),
+        ] {
+            let chord: KeyChord = text.parse().unwrap();
+            assert_eq!(chord, KeyChord { code, modifiers }, "{}", text);
+            assert_eq!(chord.to_string(), label);
+            assert_eq!(label.parse::<KeyChord>().unwrap(), chord);
+        }
+        assert!("hyper+x".parse::<KeyChord>().is_err());
+        assert!("pgdn".parse::<KeyChord>().is_err());
+    }
+
+    #[test]
+    fn test_defaults_have_no_conflicts() {
+        let keymap = Keymap::default();
+        assert!(keymap.conflicts().is_empty(), "{:?}", keymap.conflicts());
+        assert_eq!(
+            keymap.action(KeyContext::Global, KeyCode::Char(' '), KeyModifiers::SHIFT),
+            Some(Action::RestartPlayback)
+        );
+        // Shifted letters arrive with the Shift modifier
+        assert_eq!(
+            keymap.action(KeyContext::Normal, KeyCode::Char('H'), KeyModifiers::SHIFT),
+            Some(Action::MeasureLeft)
+        );
+        // Ctrl isn't dropped, so Ctrl+- doesn't zoom out
+        assert_eq!(
+            keymap.action(
+                KeyContext::Normal,
+                KeyCode::Char('-'),
+                KeyModifiers::CONTROL
+            ),
+            None
+        );
+        assert_eq!(keymap.piano_note('z'), Some(48));
+        assert_eq!(keymap.piano_note('I'), Some(72));
+        assert_eq!(keymap.piano_label(), "Z-M / Q-I");
+
+        // Clipboard and mark keys leave the piano keys playable in Normal mode
+        for binding in keymap.bindings().iter().filter(|b| {
+            b.context == KeyContext::Normal
+                && matches!(
+                    b.action,
+                    Action::Yank
+                        | Action::Paste
+                        | Action::Register
+                        | Action::SetMark
+                        | Action::JumpToMark
+                        | Action::Repeat
+                )
+        }) {
+            if let KeyCode::Char(c) = binding.chord.code {
+                assert_eq!(keymap.piano_note(c), None, "{:?}", binding.action);
+            }
+        }
+    }
+
+    #[test]
+    fn test_user_bindings_override_defaults() {
+        let keymap = Keymap::parse(
+            r#"{
+                "normal": { "x": "delete_note", "d": "unbound", "ctrl+t": "add_track" },
+                "select": { "Space": "toggle_note_selection" }
+            }"#,
+        )
+        .unwrap();
+        let normal = |code| keymap.action(KeyContext::Normal, code, KeyModifiers::NONE);
+        assert_eq!(normal(KeyCode::Char('x')), Some(Action::DeleteNote));
+        assert_eq!(normal(KeyCode::Char('d')), None);
+        assert_eq!(
+            keymap.action(
+                KeyContext::Normal,
+                KeyCode::Char('t'),
+                KeyModifiers::CONTROL
+            ),
+            Some(Action::AddTrack)
+        );
+        // Space is taken by play/pause before select mode sees it
+        assert_eq!(keymap.conflicts().len(), 1);
+        assert!(keymap.conflicts()[0].contains("toggle_playback"));
+
+        assert!(Keymap::parse(r#"{ "normal": { "x": "fly" } }"#).is_err());
+        assert!(Keymap::parse(r#"{ "normal": { "meta+x": "quit" } }"#).is_err());
+        assert!(Keymap::parse(r#"{ "piano": "abc" }"#).is_err());
+    }
+
+    #[test]
+    fn test_layouts_move_piano_keys() {
+        let azerty = Keymap::parse(r#"{ "layout": "azerty" }"#).unwrap();
+        assert_eq!(azerty.piano_note('w'), Some(48));
+        assert_eq!(azerty.piano_note('a'), Some(60));
+        assert_eq!(azerty.piano_note('é'), Some(61));
+        assert_eq!(azerty.piano_label(), "W-, / A-I");
+        assert!(azerty.conflicts().is_empty(), "{:?}", azerty.conflicts());
+        // ',' plays B3 in Insert mode instead of changing octave
+        let insert = |keymap: &Keymap, c| {
+            keymap.action(KeyContext::Insert, KeyCode::Char(c), KeyModifiers::NONE)
+        };
+        assert_eq!(insert(&azerty, ','), None);
+        assert_eq!(insert(&azerty, '/'), Some(Action::OctaveUp));
+
+        // Binding a piano key yourself is reported
+        let dvorak =
+            Keymap::parse(r#"{ "layout": "dvorak", "insert": { ",": "octave_down" } }"#).unwrap();
+        assert_eq!(dvorak.piano_note(';'), Some(48));
+        assert_eq!(dvorak.conflicts().len(), 1);
+        assert!(dvorak.conflicts()[0].contains("octave_down"));
+
+        for layout in ["qwerty", "azerty", "qwertz", "dvorak", "colemak"] {
+            let keymap = Keymap::parse(&format!(r#"{{ "layout": "{}" }}"#, layout)).unwrap();
+            assert_eq!(keymap.piano_keys().len(), PIANO_KEY_COUNT);
+            assert!(!keymap.conflicts().iter().any(|c| c.starts_with("piano")));
+        }
+    }
+
+    #[test]
+    fn test_printed_keymap_loads_back() {
+        let keymap = Keymap::parse(
+            r#"{ "layout": "qwertz", "normal": { "ctrl+t": "add_track", "x": "unbound" } }"#,
+        )
+        .unwrap();
+        let loaded = Keymap::parse(&keymap.to_json()).unwrap();
+        assert_eq!(loaded.bindings(), keymap.bindings());
+        assert_eq!(loaded.piano_keys(), keymap.piano_keys());
+        assert_eq!(
+            loaded.action(KeyContext::Normal, KeyCode::Char('x'), KeyModifiers::NONE),
+            None
+        );
+    }
+}
@@ -4,9 +4,16 @@
 
 pub mod app;
 pub mod audio;
+pub mod autosave;
+pub mod command;
+pub mod config;
 pub mod history;
+pub mod keymap;
 pub mod midi;
+pub mod recent;
+pub mod theme;
 pub mod ui;
+pub mod vim;
 
 // Re-export commonly used types
 pub use app::{App, EditMode, FocusedPanel, ViewMode};
@@ -11,26 +11,36 @@
 //! - Interactive keyboard for live note input
 //! - WAV export functionality
 //! - Project save/load (JSON format)
-//! - Autosave with automatic recovery on startup
+//! - Per-project autosave with a crash-recovery browser on startup
 //!
 //! # Usage
 //!
 //! ```bash
 //! cargo run           # Start with autosave recovery (if available)
 //! cargo run -- --new  # Start with a fresh project
+//! cargo run -- song.mid  # Open a project or MIDI file
 //! ```
 //!
 //! Press `?` for help with keyboard shortcuts.
 
 mod app;
 mod audio;
+mod autosave;
+mod command;
+mod config;
 mod history;
+mod keymap;
 mod midi;
+mod recent;
+mod theme;
 mod ui;
+mod vim;
 
-use app::{App, EditMode, FocusedPanel};
+use app::{App, DrumMapInput, EditMode, EventListInput, FocusedPanel, GuardedAction, ViewMode};
 use audio::export_to_wav;
+use keymap::{Action, KeyContext};
 use midi::TICKS_PER_BEAT;
+use vim::Awaiting;
 
 use anyhow::{Context, Result};
 use crossterm::event::{
@@ -44,7 +54,7 @@ use crossterm::terminal::{
 use ratatui::backend::CrosstermBackend;
 use ratatui::Terminal;
 use std::io::{self, Stdout};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::time::{Duration, Instant};
 
 /// Command-line options for the application.
@@ -53,6 +63,12 @@ struct CliOptions {
     new_project: bool,
     /// Path to a custom SoundFont file.
     soundfont: Option<PathBuf>,
+    /// Project or MIDI files to open, in the order given.
+    files: Vec<PathBuf>,
+    /// Config file to use instead of the one in the config directory.
+    config: Option<PathBuf>,
+    /// Print the active keymap and exit.
+    print_keymap: bool,
 }
 
 impl CliOptions {
@@ -61,11 +77,17 @@ impl CliOptions {
     /// Supports:
     /// - `--new` or `-n`: Start with a fresh project (skip autosave recovery)
     /// - `--soundfont <path>` or `-sf <path>`: Specify a custom SoundFont file
+    /// - `--config <path>`: Use a specific config file
+    /// - `--print-keymap`: Print the active keymap as a keymap file and exit
     /// - `--help` or `-h`: Print help and exit
+    /// - Positional paths: project (.oxm, .json) or MIDI (.mid, .midi, .kar) files to open
     fn parse() -> Result<Self> {
         let args: Vec<String> = std::env::args().collect();
         let mut new_project = false;
         let mut soundfont: Option<PathBuf> = None;
+        let mut files: Vec<PathBuf> = Vec::new();
+        let mut config: Option<PathBuf> = None;
+        let mut print_keymap = false;
         let mut i = 1;
 
         while i < args.len() {
@@ -79,26 +101,46 @@ impl CliOptions {
                     }
                     soundfont = Some(PathBuf::from(&args[i]));
                 }
+                "--config" => {
+                    i += 1;
+                    if i >= args.len() {
+                        eprintln!("Error: --config requires a path argument");
+                        std::process::exit(1);
+                    }
+                    config = Some(PathBuf::from(&args[i]));
+                }
+                "--print-keymap" => print_keymap = true,
                 "--help" | "-h" => {
                     eprintln!("miditui - Terminal-based MIDI sequencer");
                     eprintln!();
                     eprintln!(
-                        "Usage: {} [OPTIONS]",
+                        "Usage: {} [OPTIONS] [FILE]...",
                         args.first().unwrap_or(&"miditui".to_string())
                     );
                     eprintln!();
                     eprintln!("Options:");
                     eprintln!("  -n, --new              Start with a new project (skip autosave recovery)");
                     eprintln!("  -sf, --soundfont PATH  Load a specific SoundFont file (.sf2)");
+                    eprintln!("  --config PATH          Use a config file other than the default");
+                    eprintln!(
+                        "  --print-keymap         Print the active keymap (to edit as keymap.json)"
+                    );
                     eprintln!("  -h, --help             Print this help message");
                     eprintln!();
-                    eprintln!("If no soundfont is specified, you will be prompted to select one.");
+                    eprintln!("FILE is a project (.oxm, .json) or MIDI file (.mid, .midi, .kar).");
+                    eprintln!("The first file is opened; the rest are added to the recent files");
+                    eprintln!("list (Ctrl+R).");
+                    eprintln!();
+                    eprintln!("If no soundfont is specified and none is set in the config file,");
+                    eprintln!("you will be prompted to select one.");
                     std::process::exit(0);
                 }
                 other => {
                     // Check if it might be a SoundFont file (positional argument)
                     if other.ends_with(".sf2") {
                         soundfont = Some(PathBuf::from(other));
+                    } else if !other.starts_with('-') || PathBuf::from(other).exists() {
+                        files.push(PathBuf::from(other));
                     } else {
                         eprintln!("Unknown option: {}", other);
                         eprintln!("Use --help for usage information");
@@ -112,24 +154,31 @@ impl CliOptions {
         Ok(Self {
             new_project,
             soundfont,
+            files,
+            config,
+            print_keymap,
         })
     }
 }
 
-const AUTOSAVE_PATH: &str = ".autosave.oxm";
-
-/// Attempts to read the SoundFont path from the autosave file.
+/// Attempts to read the SoundFont path saved in a project file.
 /// Returns Some(path) if a valid SoundFont path was found, None otherwise.
-fn get_soundfont_from_autosave() -> Option<PathBuf> {
+fn get_soundfont_from_project(path: &Path) -> Option<PathBuf> {
     use crate::midi::Project;
 
-    let autosave_path = PathBuf::from(AUTOSAVE_PATH);
-    if !autosave_path.exists() {
+    if !path.exists() {
         return None;
     }
 
-    // Try to load the autosave and extract SoundFont path
-    match Project::load_from_binary(&autosave_path) {
+    // MIDI files carry no SoundFont path
+    let result = match path.extension().and_then(|e| e.to_str()) {
+        Some("oxm") => Project::load_from_binary(path),
+        Some("json") => Project::load_from_file(path),
+        _ => return None,
+    };
+
+    // Try to load the project and extract SoundFont path
+    match result {
         Ok(project) => {
             if let Some(sf_path_str) = project.get_soundfont_path() {
                 let sf_path = PathBuf::from(sf_path_str);
@@ -154,10 +203,35 @@ fn main() -> Result<()> {
         .with_writer(std::io::stderr)
         .init();
 
+    // A broken config file is reported but doesn't stop the app; it is left
+    // untouched since the defaults used instead aren't saved over it
+    let config = match config::Config::load(cli.config.as_deref()) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("Warning: Ignoring config file: {}", e);
+            config::Config::default()
+        }
+    };
+
+    // Same for the keymap, whose conflicts are shown once the app is up
+    let keymap = match keymap::Keymap::load(config.keymap.as_deref()) {
+        Ok(keymap) => keymap,
+        Err(e) => {
+            eprintln!("Warning: Ignoring keymap file: {}", e);
+            keymap::Keymap::default()
+        }
+    };
+    if cli.print_keymap {
+        println!("{}", keymap.to_json());
+        return Ok(());
+    }
+
     // Determine which SoundFont to use:
     // 1. CLI-specified SoundFont takes priority
-    // 2. Check autosave for a saved SoundFont path (unless --new flag)
-    // 3. Prompt user to select a SoundFont
+    // 2. Check the first file given on the command line
+    // 3. The SoundFont loaded last time
+    // 4. The default SoundFont from the config file
+    // 5. Prompt user to select a SoundFont
     let soundfont_path = if let Some(ref sf_path) = cli.soundfont {
         if sf_path.exists() {
             Some(sf_path.clone())
@@ -169,11 +243,15 @@ fn main() -> Result<()> {
             eprintln!("Will prompt for soundfont selection.");
             None
         }
-    } else if !cli.new_project {
-        get_soundfont_from_autosave()
+    } else if let Some(file) = cli.files.first() {
+        get_soundfont_from_project(file)
     } else {
-        None
+        recent::RecentFiles::load()
+            .soundfont
+            .filter(|path| path.exists())
     };
+    let soundfont_path =
+        soundfont_path.or_else(|| config.soundfont.clone().filter(|path| path.exists()));
 
     let mut terminal = setup_terminal().context("Failed to setup terminal")?;
 
@@ -182,7 +260,7 @@ fn main() -> Result<()> {
         Some(path) => path,
         None => {
             // Show SoundFont selection dialog
-            match run_soundfont_selector(&mut terminal)? {
+            match run_soundfont_selector(&mut terminal, &config.load_theme().unwrap_or_default())? {
                 Some(path) => path,
                 None => {
                     // User cancelled - exit cleanly
@@ -194,26 +272,28 @@ fn main() -> Result<()> {
     };
 
     // Create application with the selected SoundFont
-    let mut app = App::new(soundfont_path).context("Failed to initialize application")?;
-
-    // Attempt to load autosave unless --new flag was used
-    if !cli.new_project {
-        app.try_load_autosave();
-
-        // If autosave loaded a project with a different SoundFont path, try to load it
-        if let Some(saved_sf_path) = app.project().get_soundfont_path() {
-            let saved_path = PathBuf::from(saved_sf_path);
-            if saved_path.exists() && saved_path != app.soundfont_path {
-                // Load the project's SoundFont
-                if app.load_soundfont(saved_path) {
-                    app.set_status("Loaded soundfont from saved project");
-                }
-            }
-        }
+    let mut app =
+        App::new(soundfont_path, config, keymap).context("Failed to initialize application")?;
+    let conflicts = app.keymap.conflicts().len();
+    if conflicts > 0 {
+        app.set_status(format!(
+            "Keymap has {} conflict(s), listed in the help",
+            conflicts
+        ));
+    }
+
+    if !cli.files.is_empty() {
+        open_cli_files(&mut app, &cli.files);
+    } else if !cli.new_project {
+        // Offer to recover an autosave unless --new flag was used
+        app.open_recovery_dialog();
     }
 
     // Run main loop
     let result = run_app(&mut terminal, &mut app);
+    if result.is_ok() {
+        app.remove_autosave_slot();
+    }
 
     // Restore terminal
     restore_terminal(&mut terminal).context("Failed to restore terminal")?;
@@ -222,6 +302,46 @@ fn main() -> Result<()> {
     result
 }
 
+/// Opens the first loadable file given on the command line.
+///
+/// Every other file is added to the recent files list so it is one
+/// Ctrl+R away. The terminal is already in raw mode, so missing files are
+/// reported in the status line.
+fn open_cli_files(app: &mut App, files: &[PathBuf]) {
+    let mut opened = None;
+    let mut missing = Vec::new();
+    for (index, path) in files.iter().enumerate() {
+        if !path.exists() {
+            missing.push(path.display().to_string());
+            continue;
+        }
+        if opened.is_none() && app.load_project(path.clone()) {
+            opened = Some(index);
+        }
+    }
+
+    // Keep the opened file at the top of the list
+    for (index, path) in files.iter().enumerate().rev() {
+        if Some(index) != opened && path.exists() {
+            app.recent_files.add(path);
+        }
+    }
+    if let Some(index) = opened {
+        app.recent_files.add(&files[index]);
+        let others = files.iter().filter(|p| p.exists()).count() - 1;
+        if others > 0 {
+            app.set_status(format!(
+                "Loaded: {} ({} more in recent files, Ctrl+R)",
+                files[index].display(),
+                others
+            ));
+        }
+    }
+    if !missing.is_empty() {
+        app.set_status(format!("File not found: {}", missing.join(", ")));
+    }
+}
+
 /// State for the standalone SoundFont selector (before App is created).
 struct SoundfontSelectorState {
     current_dir: PathBuf,
@@ -325,13 +445,15 @@ impl SoundfontSelectorState {
     }
 }
 
-/// Runs a standalone SoundFont selector before the App is created.
+/// Runs a standalone SoundFont selector, drawn with the configured theme,
+/// before the App is created.
 /// Returns the selected SoundFont path, or None if the user wants to quit.
 fn run_soundfont_selector(
     terminal: &mut Terminal<CrosstermBackend<Stdout>>,
+    theme: &theme::Theme,
 ) -> Result<Option<PathBuf>> {
     use ratatui::layout::{Constraint, Direction, Layout};
-    use ratatui::style::{Color, Modifier, Style};
+    use ratatui::style::{Modifier, Style};
     use ratatui::text::{Line, Span};
     use ratatui::widgets::{Block, Borders, Clear, List, ListItem, Paragraph};
 
@@ -365,7 +487,7 @@ fn run_soundfont_selector(
             let block = Block::default()
                 .title(" Select a SoundFont to Continue ")
                 .borders(Borders::ALL)
-                .border_style(Style::default().fg(Color::Yellow));
+                .border_style(Style::default().fg(theme.accent));
 
             let inner = block.inner(popup_area);
             frame.render_widget(block, popup_area);
@@ -386,11 +508,11 @@ fn run_soundfont_selector(
                 Paragraph::new(vec![
                     Line::from(Span::styled(
                         "A SoundFont (.sf2) is required for audio playback.",
-                        Style::default().fg(Color::White),
+                        Style::default().fg(theme.text),
                     )),
                     Line::from(Span::styled(
                         "Browse to select a SoundFont file.",
-                        Style::default().fg(Color::DarkGray),
+                        Style::default().fg(theme.text_dim),
                     )),
                 ]),
                 chunks[0],
@@ -408,7 +530,7 @@ fn run_soundfont_selector(
                 path_str
             };
             frame.render_widget(
-                Paragraph::new(Span::styled(display_path, Style::default().fg(Color::Cyan))),
+                Paragraph::new(Span::styled(display_path, Style::default().fg(theme.info))),
                 chunks[1],
             );
 
@@ -421,7 +543,7 @@ fn run_soundfont_selector(
                 vec![ListItem::new(Line::from(Span::styled(
                     "No SoundFont files found in this directory",
                     Style::default()
-                        .fg(Color::DarkGray)
+                        .fg(theme.text_dim)
                         .add_modifier(Modifier::ITALIC),
                 )))]
             } else {
@@ -436,7 +558,7 @@ fn run_soundfont_selector(
                             (
                                 "[..]",
                                 "Parent Directory".to_string(),
-                                Style::default().fg(Color::Blue),
+                                Style::default().fg(theme.link),
                             )
                         } else if path.is_dir() {
                             let name = path
@@ -444,14 +566,14 @@ fn run_soundfont_selector(
                                 .and_then(|n| n.to_str())
                                 .unwrap_or("?")
                                 .to_string();
-                            ("[D]", name, Style::default().fg(Color::Blue))
+                            ("[D]", name, Style::default().fg(theme.link))
                         } else {
                             let name = path
                                 .file_name()
                                 .and_then(|n| n.to_str())
                                 .unwrap_or("?")
                                 .to_string();
-                            ("[SF2]", name, Style::default().fg(Color::Green))
+                            ("[SF2]", name, Style::default().fg(theme.active))
                         };
 
                         let display_style = if is_selected {
@@ -461,10 +583,7 @@ fn run_soundfont_selector(
                         };
 
                         ListItem::new(Line::from(vec![
-                            Span::styled(
-                                format!("{} ", icon),
-                                Style::default().fg(Color::DarkGray),
-                            ),
+                            Span::styled(format!("{} ", icon), Style::default().fg(theme.text_dim)),
                             Span::styled(name, display_style),
                         ]))
                     })
@@ -476,12 +595,12 @@ fn run_soundfont_selector(
             // Instructions
             frame.render_widget(
                 Paragraph::new(Line::from(vec![
-                    Span::styled("[Up/Down]", Style::default().fg(Color::Yellow)),
-                    Span::styled(" Navigate  ", Style::default().fg(Color::DarkGray)),
-                    Span::styled("[Enter]", Style::default().fg(Color::Yellow)),
-                    Span::styled(" Select  ", Style::default().fg(Color::DarkGray)),
-                    Span::styled("[q/Esc]", Style::default().fg(Color::Yellow)),
-                    Span::styled(" Quit", Style::default().fg(Color::DarkGray)),
+                    Span::styled("[Up/Down]", Style::default().fg(theme.accent)),
+                    Span::styled(" Navigate  ", Style::default().fg(theme.text_dim)),
+                    Span::styled("[Enter]", Style::default().fg(theme.accent)),
+                    Span::styled(" Select  ", Style::default().fg(theme.text_dim)),
+                    Span::styled("[q/Esc]", Style::default().fg(theme.accent)),
+                    Span::styled(" Quit", Style::default().fg(theme.text_dim)),
                 ])),
                 chunks[4],
             );
@@ -578,12 +697,20 @@ impl ClickTracker {
 }
 
 /// Main application loop.
+// Dialog keys that only act on some input check it inside the arm, so calls
+// like `file_browser_select()` don't end up in a match guard
+#[allow(clippy::collapsible_match)]
 fn run_app(terminal: &mut Terminal<CrosstermBackend<Stdout>>, app: &mut App) -> Result<()> {
     let mut click_tracker = ClickTracker::new();
     // Track the last mouse position for release events
     let mut last_mouse_pos: Option<(u16, u16)> = None;
 
     loop {
+        // A quit confirmed in the unsaved changes prompt (or after saving)
+        if app.quit_requested {
+            break;
+        }
+
         // Update sequencer for playback
         app.update_sequencer();
         app.clear_expired_status();
@@ -600,7 +727,7 @@ fn run_app(terminal: &mut Terminal<CrosstermBackend<Stdout>>, app: &mut App) ->
             // Draw help overlay if visible
             // SAFETY: SHOW_HELP is only accessed from the main thread
             if unsafe { SHOW_HELP } {
-                ui::render_help(frame, app.help_scroll);
+                ui::render_help(frame, &app.keymap, &app.theme, app.help_scroll);
             }
 
             // Draw save dialog if open
@@ -608,16 +735,31 @@ fn run_app(terminal: &mut Terminal<CrosstermBackend<Stdout>>, app: &mut App) ->
 
             // Draw file browser if open
             ui::render_file_browser(frame, app);
+            ui::render_recent_dialog(frame, app);
+            ui::render_recovery_dialog(frame, app);
+            ui::render_history_dialog(frame, app);
+            ui::render_settings_dialog(frame, app);
+
+            // Draw marker list dialog if open
+            ui::render_marker_dialog(frame, app);
+            ui::render_time_dialog(frame, app);
+            ui::render_quantize_dialog(frame, app);
+            ui::render_groove_dialog(frame, app);
+            ui::render_scale_dialog(frame, app);
+            ui::render_chord_dialog(frame, app);
+            ui::render_effects_dialog(frame, app);
+            ui::render_drum_map_dialog(frame, app);
 
             // Draw new project confirmation dialog if open
             ui::render_new_project_dialog(frame, app);
+            ui::render_unsaved_dialog(frame, app);
 
             // Draw SoundFont dialog if open (highest priority since it can block)
             ui::render_soundfont_dialog(frame, app);
         })?;
 
         // Handle events with a short timeout to allow sequencer updates
-        if event::poll(Duration::from_millis(16))? {
+        if event::poll(Duration::from_millis(app.config.poll_interval_ms))? {
             match event::read()? {
                 Event::Key(key) => {
                     // Only handle key press events (not release)
@@ -625,11 +767,19 @@ fn run_app(terminal: &mut Terminal<CrosstermBackend<Stdout>>, app: &mut App) ->
                         // SAFETY: SHOW_HELP is only accessed from the main thread
                         if unsafe { SHOW_HELP } {
                             // Help overlay is visible - handle close and scroll
+                            let toggle_help =
+                                app.keymap
+                                    .action(KeyContext::Global, key.code, key.modifiers)
+                                    == Some(Action::ToggleHelp);
                             match key.code {
                                 KeyCode::Char('?') | KeyCode::Esc => {
                                     unsafe { SHOW_HELP = false };
                                     app.help_scroll = 0; // Reset scroll on close
                                 }
+                                _ if toggle_help => {
+                                    unsafe { SHOW_HELP = false };
+                                    app.help_scroll = 0; // Reset scroll on close
+                                }
                                 KeyCode::Up | KeyCode::Char('k') => {
                                     app.help_scroll = app.help_scroll.saturating_sub(1);
                                 }
@@ -673,6 +823,91 @@ fn run_app(terminal: &mut Terminal<CrosstermBackend<Stdout>>, app: &mut App) ->
                             continue;
                         }
 
+                        if app.recovery_dialog.open {
+                            match key.code {
+                                KeyCode::Enter => app.recovery_dialog_select(),
+                                KeyCode::Esc => app.recovery_dialog_cancel(),
+                                KeyCode::Up | KeyCode::Char('k') => app.recovery_dialog_move(-1),
+                                KeyCode::Down | KeyCode::Char('j') => app.recovery_dialog_move(1),
+                                KeyCode::Char('x') | KeyCode::Delete => {
+                                    app.recovery_dialog_remove();
+                                }
+                                _ => {}
+                            }
+                            continue;
+                        }
+
+                        if app.command_line.open {
+                            match key.code {
+                                KeyCode::Enter => app.command_line_confirm(),
+                                KeyCode::Esc => app.command_line_cancel(),
+                                KeyCode::Char('c')
+                                    if key.modifiers.contains(KeyModifiers::CONTROL) =>
+                                {
+                                    app.command_line_cancel()
+                                }
+                                KeyCode::Backspace => app.command_line_backspace(),
+                                KeyCode::Tab => app.command_line_complete(1),
+                                KeyCode::BackTab => app.command_line_complete(-1),
+                                KeyCode::Up => app.command_line_history(-1),
+                                KeyCode::Down => app.command_line_history(1),
+                                KeyCode::Char(c) if !c.is_control() => app.command_line_input(c),
+                                _ => {}
+                            }
+                            continue;
+                        }
+
+                        if app.settings_dialog.open {
+                            match key.code {
+                                KeyCode::Enter => app.settings_dialog_confirm(),
+                                KeyCode::Esc => app.settings_dialog_cancel(),
+                                KeyCode::Up | KeyCode::Char('k') => app.settings_dialog_move(-1),
+                                KeyCode::Down | KeyCode::Char('j') => app.settings_dialog_move(1),
+                                KeyCode::Left | KeyCode::Char('h') => {
+                                    app.settings_dialog_adjust(-1)
+                                }
+                                KeyCode::Right | KeyCode::Char('l') => {
+                                    app.settings_dialog_adjust(1)
+                                }
+                                _ => {}
+                            }
+                            continue;
+                        }
+
+                        if app.history_dialog.open {
+                            match key.code {
+                                KeyCode::Enter => app.history_dialog_jump(),
+                                KeyCode::Esc => app.history_dialog_cancel(),
+                                KeyCode::Up | KeyCode::Char('k') => app.history_dialog_move(-1),
+                                KeyCode::Down | KeyCode::Char('j') => app.history_dialog_move(1),
+                                KeyCode::Home => app.history_dialog_move(i32::MIN / 2),
+                                KeyCode::End => app.history_dialog_move(i32::MAX / 2),
+                                KeyCode::Char('p') => app.toggle_persist_history(),
+                                _ => {}
+                            }
+                            continue;
+                        }
+
+                        // Handle unsaved changes prompt input
+                        if app.unsaved_dialog.open {
+                            match key.code {
+                                KeyCode::Enter => {
+                                    app.unsaved_dialog_choose(app.unsaved_dialog.selected);
+                                }
+                                KeyCode::Esc | KeyCode::Char('c') => app.unsaved_dialog_cancel(),
+                                KeyCode::Char('s') => app.unsaved_dialog_choose(0),
+                                KeyCode::Char('d') => app.unsaved_dialog_choose(1),
+                                KeyCode::Left | KeyCode::Char('h') | KeyCode::BackTab => {
+                                    app.unsaved_dialog_move(-1);
+                                }
+                                KeyCode::Right | KeyCode::Char('l') | KeyCode::Tab => {
+                                    app.unsaved_dialog_move(1);
+                                }
+                                _ => {}
+                            }
+                            continue;
+                        }
+
                         // Handle new project dialog input
                         if app.new_project_dialog.open {
                             match key.code {
@@ -705,9 +940,8 @@ fn run_app(terminal: &mut Terminal<CrosstermBackend<Stdout>>, app: &mut App) ->
                         if app.save_dialog.open {
                             match key.code {
                                 KeyCode::Enter => {
-                                    if app.save_dialog_confirm() {
-                                        app.set_status("Project saved");
-                                    }
+                                    // Sets its own status (and may run a pending load or quit)
+                                    app.save_dialog_confirm();
                                 }
                                 KeyCode::Esc => {
                                     app.save_dialog_cancel();
@@ -751,6 +985,212 @@ fn run_app(terminal: &mut Terminal<CrosstermBackend<Stdout>>, app: &mut App) ->
                             continue;
                         }
 
+                        if app.recent_dialog.open {
+                            match key.code {
+                                KeyCode::Enter => {
+                                    app.recent_dialog_select();
+                                }
+                                KeyCode::Esc => app.recent_dialog_cancel(),
+                                KeyCode::Up | KeyCode::Char('k') => app.recent_dialog_move(-1),
+                                KeyCode::Down | KeyCode::Char('j') => app.recent_dialog_move(1),
+                                KeyCode::Char('x') | KeyCode::Delete => {
+                                    app.recent_dialog_remove();
+                                }
+                                _ => {}
+                            }
+                            continue;
+                        }
+
+                        // Handle marker dialog input (rename field first)
+                        if app.marker_dialog.open && app.marker_dialog.renaming {
+                            match key.code {
+                                KeyCode::Enter => app.marker_rename_confirm(),
+                                KeyCode::Esc => app.marker_rename_cancel(),
+                                KeyCode::Backspace => app.marker_rename_backspace(),
+                                KeyCode::Char(c) if !c.is_control() => {
+                                    app.marker_rename_input(c);
+                                }
+                                _ => {}
+                            }
+                            continue;
+                        }
+                        if app.marker_dialog.open {
+                            match key.code {
+                                KeyCode::Esc => app.marker_dialog_close(),
+                                KeyCode::Enter => app.marker_dialog_jump(),
+                                KeyCode::Up | KeyCode::Char('k') => app.marker_dialog_up(),
+                                KeyCode::Down | KeyCode::Char('j') => app.marker_dialog_down(),
+                                KeyCode::Char('r') => app.marker_dialog_start_rename(),
+                                KeyCode::Char('c') => app.marker_dialog_cycle_color(),
+                                KeyCode::Char('x') | KeyCode::Delete => {
+                                    app.marker_dialog_remove_marker();
+                                }
+                                KeyCode::Char('D') => app.marker_dialog_duplicate_section(),
+                                KeyCode::Char('X') => app.marker_dialog_delete_section(),
+                                KeyCode::Char('K') => app.marker_dialog_move_section(-1),
+                                KeyCode::Char('J') => app.marker_dialog_move_section(1),
+                                _ => {}
+                            }
+                            continue;
+                        }
+                        if app.quantize_dialog.open {
+                            match key.code {
+                                KeyCode::Esc => app.quantize_dialog_cancel(),
+                                KeyCode::Enter => app.quantize_dialog_confirm(),
+                                KeyCode::Up | KeyCode::Char('k') => app.quantize_dialog_up(),
+                                KeyCode::Down | KeyCode::Char('j') => app.quantize_dialog_down(),
+                                KeyCode::Left | KeyCode::Char('h') => {
+                                    app.quantize_dialog_adjust(-1)
+                                }
+                                KeyCode::Right | KeyCode::Char('l') | KeyCode::Char(' ') => {
+                                    app.quantize_dialog_adjust(1);
+                                }
+                                KeyCode::Char('p') => app.quantize_dialog_toggle_preview(),
+                                _ => {}
+                            }
+                            continue;
+                        }
+                        if app.groove_dialog.open {
+                            match key.code {
+                                KeyCode::Esc => app.groove_dialog_close(),
+                                KeyCode::Enter => app.groove_dialog_apply(),
+                                KeyCode::Up | KeyCode::Char('k') => app.groove_dialog_up(),
+                                KeyCode::Down | KeyCode::Char('j') => app.groove_dialog_down(),
+                                KeyCode::Left | KeyCode::Char('h') => app.groove_dialog_adjust(-1),
+                                KeyCode::Right | KeyCode::Char('l') => app.groove_dialog_adjust(1),
+                                KeyCode::Char('x') => app.groove_dialog_extract(),
+                                _ => {}
+                            }
+                            continue;
+                        }
+                        if app.scale_dialog.open {
+                            match key.code {
+                                KeyCode::Esc => app.scale_dialog_close(),
+                                KeyCode::Enter => app.scale_dialog_confirm(),
+                                KeyCode::Up | KeyCode::Char('k') => app.scale_dialog_up(),
+                                KeyCode::Down | KeyCode::Char('j') => app.scale_dialog_down(),
+                                KeyCode::Left | KeyCode::Char('h') => app.scale_dialog_adjust(-1),
+                                KeyCode::Right | KeyCode::Char('l') => app.scale_dialog_adjust(1),
+                                KeyCode::Char(' ') => app.scale_dialog_toggle_custom(),
+                                KeyCode::Char('x') => app.scale_dialog_clear(),
+                                _ => {}
+                            }
+                            continue;
+                        }
+                        if app.chord_dialog.open {
+                            match key.code {
+                                KeyCode::Esc | KeyCode::Enter => app.chord_dialog_close(),
+                                KeyCode::Up | KeyCode::Char('k') => app.chord_dialog_up(),
+                                KeyCode::Down | KeyCode::Char('j') => app.chord_dialog_down(),
+                                KeyCode::Left | KeyCode::Char('h') => app.chord_dialog_adjust(-1),
+                                KeyCode::Right | KeyCode::Char('l') | KeyCode::Char(' ') => {
+                                    app.chord_dialog_adjust(1)
+                                }
+                                KeyCode::Char('a') => app.chord_dialog_add_symbol(),
+                                KeyCode::Char('c') => app.chord_dialog_capture(),
+                                KeyCode::Char('x') => app.chord_dialog_remove_symbol(),
+                                KeyCode::Char('g') => app.chord_dialog_generate(),
+                                _ => {}
+                            }
+                            continue;
+                        }
+                        if app.effects_dialog.open {
+                            match key.code {
+                                KeyCode::Esc => app.effects_dialog_close(),
+                                KeyCode::Enter => app.effects_dialog_render(),
+                                KeyCode::Up | KeyCode::Char('k') => app.effects_dialog_up(),
+                                KeyCode::Down | KeyCode::Char('j') => app.effects_dialog_down(),
+                                KeyCode::Left | KeyCode::Char('h') => app.effects_dialog_adjust(-1),
+                                KeyCode::Right | KeyCode::Char('l') | KeyCode::Char(' ') => {
+                                    app.effects_dialog_adjust(1)
+                                }
+                                KeyCode::Char('a') => app.effects_dialog_add_to_track(),
+                                KeyCode::Char('x') => app.effects_dialog_remove_from_track(),
+                                KeyCode::Char('b') => app.effects_dialog_bake_track(),
+                                _ => {}
+                            }
+                            continue;
+                        }
+                        if app.drum_map_dialog.open && app.drum_map_dialog.input.is_some() {
+                            match key.code {
+                                KeyCode::Enter => app.drum_map_input_confirm(),
+                                KeyCode::Esc => app.drum_map_input_cancel(),
+                                KeyCode::Backspace => app.drum_map_input_backspace(),
+                                KeyCode::Char(c) if !c.is_control() => {
+                                    app.drum_map_input_char(c);
+                                }
+                                _ => {}
+                            }
+                            continue;
+                        }
+                        if app.drum_map_dialog.open {
+                            match key.code {
+                                KeyCode::Esc | KeyCode::Enter => app.drum_map_dialog_close(),
+                                KeyCode::Up | KeyCode::Char('k') => app.drum_map_dialog_move(-1),
+                                KeyCode::Down | KeyCode::Char('j') => app.drum_map_dialog_move(1),
+                                KeyCode::Left | KeyCode::Char('h') => {
+                                    app.drum_map_dialog_column(-1)
+                                }
+                                KeyCode::Right | KeyCode::Char('l') => {
+                                    app.drum_map_dialog_column(1)
+                                }
+                                KeyCode::Char('+') | KeyCode::Char('=') => {
+                                    app.drum_map_dialog_adjust(1)
+                                }
+                                KeyCode::Char('-') => app.drum_map_dialog_adjust(-1),
+                                KeyCode::Char('d') => app.drum_map_dialog_clear_output(),
+                                KeyCode::Char('K') => app.drum_map_dialog_reorder(-1),
+                                KeyCode::Char('J') => app.drum_map_dialog_reorder(1),
+                                KeyCode::Char('a') => app.drum_map_dialog_add(),
+                                KeyCode::Char('x') | KeyCode::Delete => {
+                                    app.drum_map_dialog_remove()
+                                }
+                                KeyCode::Char('g') => app.drum_map_dialog_reset(),
+                                KeyCode::Char('r') => {
+                                    app.drum_map_dialog_start_input(DrumMapInput::Rename)
+                                }
+                                KeyCode::Char('o') => {
+                                    app.drum_map_dialog_start_input(DrumMapInput::Load)
+                                }
+                                KeyCode::Char('w') => {
+                                    app.drum_map_dialog_start_input(DrumMapInput::Save)
+                                }
+                                _ => {}
+                            }
+                            continue;
+                        }
+                        if app.time_dialog.open {
+                            let measure_beats = app.project().time_sig_numerator as i32;
+                            match key.code {
+                                KeyCode::Esc => app.time_dialog_close(),
+                                KeyCode::Enter => app.time_dialog_confirm(),
+                                KeyCode::Left
+                                | KeyCode::Right
+                                | KeyCode::Tab
+                                | KeyCode::Char('h')
+                                | KeyCode::Char('l') => app.time_dialog_toggle_op(),
+                                KeyCode::Up | KeyCode::Char('k') => app.time_dialog_adjust(1),
+                                KeyCode::Down | KeyCode::Char('j') => app.time_dialog_adjust(-1),
+                                KeyCode::Char('K') => app.time_dialog_adjust(measure_beats),
+                                KeyCode::Char('J') => app.time_dialog_adjust(-measure_beats),
+                                _ => {}
+                            }
+                            continue;
+                        }
+
+                        if app.event_list.input.is_some() {
+                            match key.code {
+                                KeyCode::Enter => app.event_list_input_confirm(),
+                                KeyCode::Esc => app.event_list_input_cancel(),
+                                KeyCode::Backspace => app.event_list_input_backspace(),
+                                KeyCode::Char(c) if !c.is_control() => {
+                                    app.event_list_input_char(c);
+                                }
+                                _ => {}
+                            }
+                            continue;
+                        }
+
                         // Handle rename mode input
                         if app.renaming_track {
                             match key.code {
@@ -806,6 +1246,11 @@ fn run_app(terminal: &mut Terminal<CrosstermBackend<Stdout>>, app: &mut App) ->
                         continue;
                     }
 
+                    // Editing under a quantize preview would be lost on cancel
+                    if app.quantize_dialog.open {
+                        continue;
+                    }
+
                     handle_mouse(app, mouse, &mut click_tracker, &mut last_mouse_pos);
                 }
                 _ => {}
@@ -866,6 +1311,11 @@ fn handle_mouse(
                     app.set_status("Normal mode");
                     EditMode::Normal
                 }
+                EditMode::Lyric => {
+                    app.exit_lyric_mode();
+                    app.set_status("Normal mode");
+                    EditMode::Normal
+                }
             };
         }
         MouseEventKind::Down(MouseButton::Middle) => {
@@ -900,38 +1350,62 @@ fn handle_mouse(
 ///
 /// `true` if the application should quit
 fn handle_key(app: &mut App, code: KeyCode, modifiers: KeyModifiers) -> Result<bool> {
+    // Lyric mode captures text input before the global bindings
+    if app.edit_mode == EditMode::Lyric
+        && !modifiers.contains(KeyModifiers::CONTROL)
+        && handle_lyric_mode(app, code)
+    {
+        return Ok(false);
+    }
+
     // Global key bindings (work in any mode/panel)
-    match code {
+    if let Some(action) = app.keymap.action(KeyContext::Global, code, modifiers) {
+        app.pending_keys.clear();
+        return run_action(app, action);
+    }
+
+    // Mode-specific key bindings
+    match app.edit_mode {
+        EditMode::Normal => handle_normal_mode(app, code, modifiers),
+        EditMode::Insert => handle_insert_mode(app, code, modifiers),
+        EditMode::Select => handle_select_mode(app, code, modifiers),
+        EditMode::Lyric => Ok(false),
+    }
+}
+
+/// Runs a bound action.
+///
+/// # Returns
+///
+/// `true` if the application should quit
+fn run_action(app: &mut App, action: Action) -> Result<bool> {
+    match action {
+        Action::Unbound => {}
+
         // Quit
-        KeyCode::Char('c') if modifiers.contains(KeyModifiers::CONTROL) => {
-            return Ok(true);
-        }
-        KeyCode::Char('q') if modifiers.contains(KeyModifiers::CONTROL) => {
-            return Ok(true);
-        }
-        KeyCode::Char('q') if app.edit_mode == EditMode::Normal => {
-            return Ok(true);
-        }
+        Action::ForceQuit => return Ok(true),
+        // Asks to save unsaved changes first
+        Action::Quit => return Ok(app.guard_unsaved(GuardedAction::Quit)),
 
-        // Undo/Redo (Ctrl+Z / Ctrl+Y)
-        KeyCode::Char('z') if modifiers.contains(KeyModifiers::CONTROL) => {
+        Action::Undo => {
             app.undo();
-            return Ok(false);
         }
-        KeyCode::Char('y') if modifiers.contains(KeyModifiers::CONTROL) => {
+        Action::Redo => {
             app.redo();
-            return Ok(false);
         }
+        Action::Settings => app.open_settings_dialog(),
+        Action::HistoryPanel => app.open_history_dialog(),
+        Action::CommandLine => app.open_command_line(),
+        Action::Repeat => return repeat_last_edit(app, None),
 
         // Help toggle
-        KeyCode::Char('?') => {
+        Action::ToggleHelp => {
             // SAFETY: SHOW_HELP is only accessed from the main thread
             unsafe { SHOW_HELP = !SHOW_HELP };
-            return Ok(false);
         }
 
-        // Escape - return to normal mode or quit
-        KeyCode::Esc => {
+        // Return to normal mode
+        Action::NormalMode => {
             if app.edit_mode != EditMode::Normal {
                 // Stop Insert Mode recording if active
                 app.stop_insert_recording();
@@ -939,156 +1413,116 @@ fn handle_key(app: &mut App, code: KeyCode, modifiers: KeyModifiers) -> Result<b
                 app.release_all_notes();
                 app.set_status("Normal mode");
             }
-            return Ok(false);
         }
 
-        // Tab - cycle focus
-        KeyCode::Tab => {
+        Action::CycleFocus => {
             app.focused_panel = match app.focused_panel {
                 FocusedPanel::TrackList => FocusedPanel::Timeline,
                 FocusedPanel::Timeline => FocusedPanel::PianoRoll,
                 FocusedPanel::PianoRoll => FocusedPanel::Keyboard,
                 FocusedPanel::Keyboard => FocusedPanel::TrackList,
             };
-            return Ok(false);
         }
 
         // Playback controls
-        // Shift+Space: restart playback from beginning
-        KeyCode::Char(' ') if modifiers.contains(KeyModifiers::SHIFT) => {
-            app.restart_playback();
-            return Ok(false);
-        }
-        KeyCode::Char(' ') => {
-            app.toggle_playback();
-            return Ok(false);
-        }
-        KeyCode::Char('.') if app.edit_mode == EditMode::Normal => {
-            app.stop_playback();
-            return Ok(false);
-        }
-
-        // Export WAV (Ctrl+E)
-        KeyCode::Char('e') if modifiers.contains(KeyModifiers::CONTROL) => {
-            export_project(app)?;
-            return Ok(false);
-        }
-
-        // Export MIDI (Ctrl+M)
-        KeyCode::Char('m') if modifiers.contains(KeyModifiers::CONTROL) => {
-            export_midi(app)?;
-            return Ok(false);
-        }
-
-        // Save project (Ctrl+S) - opens save dialog
-        KeyCode::Char('s') if modifiers.contains(KeyModifiers::CONTROL) => {
-            app.open_save_dialog();
-            return Ok(false);
-        }
-
-        // Load project (Ctrl+O) - opens file browser
-        KeyCode::Char('o') if modifiers.contains(KeyModifiers::CONTROL) => {
-            app.open_file_browser();
-            return Ok(false);
-        }
-
-        // New project (Ctrl+N) - opens confirmation dialog
-        KeyCode::Char('n') if modifiers.contains(KeyModifiers::CONTROL) => {
-            app.open_new_project_dialog();
-            return Ok(false);
-        }
-
-        // Load SoundFont (Ctrl+L) - opens SoundFont browser
-        KeyCode::Char('l') if modifiers.contains(KeyModifiers::CONTROL) => {
-            app.open_soundfont_dialog(false);
-            return Ok(false);
+        Action::RestartPlayback => app.restart_playback(),
+        Action::TogglePlayback => app.toggle_playback(),
+        Action::StopPlayback => app.stop_playback(),
+
+        // File and export
+        Action::ExportWav => export_project(app)?,
+        Action::ExportMidi => export_midi(app)?,
+        Action::Save => app.open_save_dialog(),
+        Action::RecentFiles => app.open_recent_dialog(),
+        Action::RecoverAutosave => {
+            if !app.open_recovery_dialog() {
+                app.set_status("No autosaves to recover");
+            }
         }
+        Action::Open => app.open_file_browser(),
+        Action::NewProject => app.open_new_project_dialog(),
+        Action::LoadSoundfont => app.open_soundfont_dialog(false),
+        Action::MarkerList => app.open_marker_dialog(),
+        Action::DrumMap => app.open_drum_map_dialog(),
 
-        _ => {}
-    }
-
-    // Mode-specific key bindings
-    match app.edit_mode {
-        EditMode::Normal => handle_normal_mode(app, code, modifiers),
-        EditMode::Insert => handle_insert_mode(app, code, modifiers),
-        EditMode::Select => handle_select_mode(app, code, modifiers),
-    }
-}
-
-/// Handles keys in normal mode.
-fn handle_normal_mode(app: &mut App, code: KeyCode, modifiers: KeyModifiers) -> Result<bool> {
-    match code {
         // Mode changes
-        KeyCode::Char('i') => {
+        Action::InsertMode => {
             app.edit_mode = EditMode::Insert;
             app.set_status("Insert mode - keys play & insert notes");
         }
-        KeyCode::Char('v') => {
+        Action::SelectMode => {
             app.edit_mode = EditMode::Select;
             app.set_status("Select mode");
         }
+        Action::LyricMode => app.enter_lyric_mode(),
+
+        // Markers
+        Action::AddMarker => app.add_marker_at_cursor(),
+        Action::NextMarker => app.jump_to_next_marker(),
+        Action::PrevMarker => app.jump_to_prev_marker(),
+
+        // Quantize the selection (or the whole track if nothing is selected)
+        Action::Quantize => app.open_quantize_dialog(),
+        Action::ToggleVelocityLane => app.toggle_velocity_lane(),
+
+        // Ghost notes from other tracks
+        Action::CycleGhostMode => app.cycle_ghost_mode(),
+        Action::ToggleGhostTrack => app.toggle_ghost_track(),
+        Action::SelectGhostOwner => app.select_ghost_owner(),
+
+        // Dialogs for the selection or the whole track
+        Action::Groove => app.open_groove_dialog(),
+        Action::InsertDeleteTime => app.open_time_dialog(),
+        Action::Scale => app.open_scale_dialog(),
+        Action::Chords => app.open_chord_dialog(),
+        Action::Effects => app.open_effects_dialog(),
+
+        // Clips
+        Action::DuplicateClipLinked => app.duplicate_clip_at_cursor(true),
+        Action::DuplicateClip => app.duplicate_clip_at_cursor(false),
+        Action::MakeClipUnique => app.make_clip_unique_at_cursor(),
+        Action::EditClip => app.toggle_clip_edit(),
+        Action::DeleteClip => app.delete_clip_at_cursor(),
+        Action::MoveClipLeft => app.move_clip_at_cursor(-1),
+        Action::MoveClipRight => app.move_clip_at_cursor(1),
+        Action::ClipFromSelection => app.create_clip_from_selection(),
 
         // Navigation
-        KeyCode::Char('h') | KeyCode::Left => {
-            app.move_cursor_horizontal(-(app.zoom as i32));
-        }
-        KeyCode::Char('l') | KeyCode::Right => {
-            app.move_cursor_horizontal(app.zoom as i32);
-        }
-        KeyCode::Char('k') | KeyCode::Up => {
-            app.move_cursor_vertical(1);
-        }
-        KeyCode::Char('j') | KeyCode::Down => {
-            app.move_cursor_vertical(-1);
-        }
-        KeyCode::Char('H') => {
-            // Jump left by measure
-            app.move_cursor_horizontal(-(TICKS_PER_BEAT as i32 * 4));
-        }
-        KeyCode::Char('L') => {
-            // Jump right by measure
-            app.move_cursor_horizontal(TICKS_PER_BEAT as i32 * 4);
-        }
-        KeyCode::Char('0') => {
+        Action::CursorLeft => app.move_cursor_horizontal(-(app.zoom as i32)),
+        Action::CursorRight => app.move_cursor_horizontal(app.zoom as i32),
+        Action::CursorUp => app.move_cursor_vertical(1),
+        Action::CursorDown => app.move_cursor_vertical(-1),
+        Action::MeasureLeft => app.move_cursor_horizontal(-(TICKS_PER_BEAT as i32 * 4)),
+        Action::MeasureRight => app.move_cursor_horizontal(TICKS_PER_BEAT as i32 * 4),
+        Action::GoToStart => {
             app.cursor_tick = 0;
             app.scroll_x = 0;
         }
-        KeyCode::Char('$') => {
+        Action::GoToEnd => {
             app.cursor_tick = app.project().duration_ticks();
         }
+        Action::SetMark => app.pending_keys.await_key(Awaiting::SetMark),
+        Action::JumpToMark => app.pending_keys.await_key(Awaiting::JumpToMark),
 
         // Track selection
-        KeyCode::Char('J') => {
+        Action::NextTrack => {
             if app.selected_track_index < app.project().track_count().saturating_sub(1) {
                 app.selected_track_index += 1;
             }
         }
-        KeyCode::Char('K') => {
-            if app.selected_track_index > 0 {
-                app.selected_track_index -= 1;
-            }
+        Action::PrevTrack => {
+            app.selected_track_index = app.selected_track_index.saturating_sub(1);
         }
 
         // Track management
-        KeyCode::Char('a') => {
-            app.add_track();
-        }
-        KeyCode::Char('d') | KeyCode::Char('x') => {
-            app.delete_selected_track();
-        }
-        KeyCode::Char('r') => {
-            // Rename selected track
-            app.start_rename_track();
-        }
-        KeyCode::Char('g') => {
-            // Toggle between piano roll and project timeline view
-            app.toggle_view_mode();
-        }
-        KeyCode::Char('t') => {
-            // Toggle expanded/compact track list view
-            app.toggle_expanded_tracks();
-        }
-        KeyCode::Char('m') => {
+        Action::AddTrack => app.add_track(),
+        Action::AddDrumTrack => app.add_drum_track(),
+        Action::DeleteTrack => app.delete_selected_track(),
+        Action::RenameTrack => app.start_rename_track(),
+        // Toggle between piano roll and project timeline view
+        Action::CycleView => app.toggle_view_mode(),
+        Action::ToggleExpandedTracks => app.toggle_expanded_tracks(),
+        Action::ToggleMute => {
             // Toggle mute and get info for status message
             if app.selected_track().is_some() {
                 app.save_state("Toggle mute");
@@ -1107,7 +1541,7 @@ fn handle_normal_mode(app: &mut App, code: KeyCode, modifiers: KeyModifiers) ->
                 app.mark_modified();
             }
         }
-        KeyCode::Char('s') => {
+        Action::ToggleSolo => {
             // Toggle solo and get info for status message
             if app.selected_track().is_some() {
                 app.save_state("Toggle solo");
@@ -1128,165 +1562,61 @@ fn handle_normal_mode(app: &mut App, code: KeyCode, modifiers: KeyModifiers) ->
         }
 
         // Note editing
-        KeyCode::Enter | KeyCode::Char('n') => {
-            app.place_note();
-        }
-        KeyCode::Delete => {
-            app.delete_note_at_cursor();
-        }
+        Action::PlaceNote => app.place_note(),
+        Action::DeleteNote => app.delete_note_at_cursor(),
 
         // Zoom
-        KeyCode::Char('=') | KeyCode::Char('+') => {
+        Action::ZoomIn => {
             app.zoom(0.5);
             app.set_status(format!("Zoom: {} ticks/col", app.zoom));
         }
-        KeyCode::Char('-') if !modifiers.contains(KeyModifiers::CONTROL) => {
+        Action::ZoomOut => {
             app.zoom(2.0);
             app.set_status(format!("Zoom: {} ticks/col", app.zoom));
         }
 
         // Octave
-        KeyCode::Char(',') => {
-            app.change_octave(-1);
-        }
-        KeyCode::Char('/') => {
-            app.change_octave(1);
-        }
+        Action::OctaveDown => app.change_octave(-1),
+        Action::OctaveUp => app.change_octave(1),
 
         // Tempo adjustment
-        KeyCode::Char('[') => {
-            app.save_state("Adjust tempo");
-            app.project_mut().tempo = app.project().tempo.saturating_sub(5).max(20);
-            app.audio.set_tempo(app.project().tempo);
-            app.set_status(format!("Tempo: {} BPM", app.project().tempo));
-            app.mark_modified();
-        }
-        KeyCode::Char(']') => {
-            app.save_state("Adjust tempo");
-            app.project_mut().tempo = (app.project().tempo + 5).min(300);
-            app.audio.set_tempo(app.project().tempo);
-            app.set_status(format!("Tempo: {} BPM", app.project().tempo));
-            app.mark_modified();
+        Action::TempoDown | Action::TempoUp => {
+            let tempo = app.project().tempo;
+            app.set_tempo(if action == Action::TempoDown {
+                tempo.saturating_sub(5)
+            } else {
+                tempo + 5
+            });
         }
 
         // Time signature adjustment
-        KeyCode::Char('{') => {
-            app.adjust_time_sig_numerator(-1);
-        }
-        KeyCode::Char('}') => {
-            app.adjust_time_sig_numerator(1);
-        }
-        KeyCode::Char('|') => {
-            app.cycle_time_sig_denominator();
-        }
+        Action::TimeSigDown => app.adjust_time_sig_numerator(-1),
+        Action::TimeSigUp => app.adjust_time_sig_numerator(1),
+        Action::CycleTimeSigDenominator => app.cycle_time_sig_denominator(),
 
-        // Instrument cycling (< and > keys, which are Shift+, and Shift+.)
-        KeyCode::Char('<') => {
-            app.cycle_instrument(-1);
-        }
-        KeyCode::Char('>') => {
-            app.cycle_instrument(1);
-        }
-
-        // Volume control (; and ' keys)
-        KeyCode::Char(';') => {
-            app.adjust_track_volume(-5);
-        }
-        KeyCode::Char('\'') => {
-            app.adjust_track_volume(5);
-        }
-
-        // Pan control (( and ) keys, Shift+9 and Shift+0)
-        KeyCode::Char('(') => {
-            app.adjust_track_pan(-8);
-        }
-        KeyCode::Char(')') => {
-            app.adjust_track_pan(8);
-        }
+        // Instrument cycling
+        // cycle_instrument() silences all playing notes before switching
+        Action::PrevInstrument => app.cycle_instrument(-1),
+        Action::NextInstrument => app.cycle_instrument(1),
 
-        // Export to WAV directly
-        KeyCode::Char('e') => {
-            export_project(app)?;
-        }
+        // Volume and pan
+        Action::VolumeDown => app.adjust_track_volume(-5),
+        Action::VolumeUp => app.adjust_track_volume(5),
+        Action::PanLeft => app.adjust_track_pan(-8),
+        Action::PanRight => app.adjust_track_pan(8),
 
         // Cycle highlight mode for active notes during playback
         // Cycles: Piano Roll -> Both -> Off -> Timeline -> repeat
-        KeyCode::Char('W') => {
-            app.cycle_highlight_mode();
-        }
-
-        // Keyboard note playing (still works in normal mode)
-        KeyCode::Char(c) => {
-            if !app.handle_note_key(c) {
-                // Key wasn't a note key
-            }
-        }
-
-        _ => {}
-    }
-
-    Ok(false)
-}
-
-/// Handles keys in insert mode.
-fn handle_insert_mode(app: &mut App, code: KeyCode, _modifiers: KeyModifiers) -> Result<bool> {
-    match code {
-        // Navigation still works
-        KeyCode::Left => {
-            app.move_cursor_horizontal(-(app.zoom as i32));
-        }
-        KeyCode::Right => {
-            app.move_cursor_horizontal(app.zoom as i32);
-        }
-        KeyCode::Up => {
-            app.move_cursor_vertical(1);
-        }
-        KeyCode::Down => {
-            app.move_cursor_vertical(-1);
-        }
-
-        // Octave
-        KeyCode::Char(',') => {
-            app.change_octave(-1);
-        }
-        KeyCode::Char('/') => {
-            app.change_octave(1);
-        }
-
-        // Instrument cycling (Shift+, and Shift+. produce '<' and '>')
-        // cycle_instrument() silences all playing notes before switching
-        KeyCode::Char('<') => {
-            app.cycle_instrument(-1);
-        }
-        KeyCode::Char('>') => {
-            app.cycle_instrument(1);
-        }
-
-        // In insert mode, keyboard keys insert and play notes
-        KeyCode::Char(c) => {
-            app.handle_note_key(c);
-        }
+        Action::CycleHighlight => app.cycle_highlight_mode(),
 
-        _ => {}
-    }
-
-    Ok(false)
-}
-
-/// Handles keys in select mode.
-fn handle_select_mode(app: &mut App, code: KeyCode, modifiers: KeyModifiers) -> Result<bool> {
-    let shift_held = modifiers.contains(KeyModifiers::SHIFT);
-
-    match code {
-        // Shift+A: shrink note duration
-        KeyCode::Char('A') if shift_held => {
+        // Shift+A / Shift+D: shrink/expand note duration
+        Action::ShrinkNotes => {
             if !app.selected_notes.is_empty() {
                 app.adjust_selected_notes_duration(-(app.zoom as i32));
                 app.set_status("Reduced note duration");
             }
         }
-        // Shift+D: expand note duration
-        KeyCode::Char('D') if shift_held => {
+        Action::ExpandNotes => {
             if !app.selected_notes.is_empty() {
                 app.adjust_selected_notes_duration(app.zoom as i32);
                 app.set_status("Expanded note duration");
@@ -1294,7 +1624,7 @@ fn handle_select_mode(app: &mut App, code: KeyCode, modifiers: KeyModifiers) ->
         }
 
         // WASD: move selected notes (if notes selected) or navigate cursor
-        KeyCode::Char('w') => {
+        Action::MoveNotesUp => {
             if !app.selected_notes.is_empty() {
                 app.transpose_selected_notes(1);
                 app.set_status("Moved notes up");
@@ -1302,7 +1632,7 @@ fn handle_select_mode(app: &mut App, code: KeyCode, modifiers: KeyModifiers) ->
                 app.move_cursor_vertical(1);
             }
         }
-        KeyCode::Char('s') => {
+        Action::MoveNotesDown => {
             if !app.selected_notes.is_empty() {
                 app.transpose_selected_notes(-1);
                 app.set_status("Moved notes down");
@@ -1310,7 +1640,7 @@ fn handle_select_mode(app: &mut App, code: KeyCode, modifiers: KeyModifiers) ->
                 app.move_cursor_vertical(-1);
             }
         }
-        KeyCode::Char('a') => {
+        Action::MoveNotesLeft => {
             if !app.selected_notes.is_empty() {
                 app.move_selected_notes_horizontal(-(app.zoom as i32));
                 app.set_status("Moved notes left");
@@ -1318,7 +1648,7 @@ fn handle_select_mode(app: &mut App, code: KeyCode, modifiers: KeyModifiers) ->
                 app.move_cursor_horizontal(-(app.zoom as i32));
             }
         }
-        KeyCode::Char('d') => {
+        Action::MoveNotesRight => {
             if !app.selected_notes.is_empty() {
                 app.move_selected_notes_horizontal(app.zoom as i32);
                 app.set_status("Moved notes right");
@@ -1327,22 +1657,8 @@ fn handle_select_mode(app: &mut App, code: KeyCode, modifiers: KeyModifiers) ->
             }
         }
 
-        // Navigation with hjkl and arrow keys
-        KeyCode::Char('h') | KeyCode::Left => {
-            app.move_cursor_horizontal(-(app.zoom as i32));
-        }
-        KeyCode::Char('l') | KeyCode::Right => {
-            app.move_cursor_horizontal(app.zoom as i32);
-        }
-        KeyCode::Char('k') | KeyCode::Up => {
-            app.move_cursor_vertical(1);
-        }
-        KeyCode::Char('j') | KeyCode::Down => {
-            app.move_cursor_vertical(-1);
-        }
-
         // Select note under cursor
-        KeyCode::Enter | KeyCode::Char(' ') => {
+        Action::ToggleNoteSelection => {
             if let Some(track) = app.selected_track() {
                 let note_id = track
                     .notes()
@@ -1362,44 +1678,321 @@ fn handle_select_mode(app: &mut App, code: KeyCode, modifiers: KeyModifiers) ->
             }
         }
 
-        // Delete selected notes
-        KeyCode::Char('x') | KeyCode::Delete => {
-            if !app.selected_notes.is_empty() {
-                app.save_state("Delete selected notes");
-                // Collect IDs first to avoid borrow conflicts
-                let ids_to_delete: Vec<_> = app.selected_notes.drain().collect();
-                let count = ids_to_delete.len();
-                if let Some(track) = app.selected_track_mut() {
-                    for id in ids_to_delete {
-                        track.remove_note(id);
-                    }
-                }
-                app.set_status(format!("Deleted {} notes", count));
-                app.mark_modified();
-            }
-        }
+        // Delete selected notes (into the register)
+        Action::DeleteSelection => app.delete_selection(app.pending_keys.register()),
+
+        // Note clipboards
+        Action::Yank => app.yank_notes(app.pending_keys.register()),
+        Action::Paste => app.paste_notes(app.pending_keys.register()),
+        Action::Register => app.pending_keys.await_key(Awaiting::Register),
 
-        // Clear selection
-        KeyCode::Char('c') => {
+        // Velocity of selected notes
+        Action::VelocityUp => app.adjust_selected_velocity(5),
+        Action::VelocityDown => app.adjust_selected_velocity(-5),
+        Action::VelocityRamp => app.ramp_selected_velocity(),
+
+        Action::ClearSelection => {
             app.selected_notes.clear();
             app.set_status("Selection cleared");
         }
-
-        _ => {}
     }
 
     Ok(false)
 }
 
+/// Runs an action typed in Normal or Select mode, with the count and
+/// register typed before it.
+///
+/// # Returns
+///
+/// `true` if the application should quit
+fn run_counted_action(app: &mut App, action: Action) -> Result<bool> {
+    // These wait for a name and keep the count for the action after it
+    if matches!(
+        action,
+        Action::Register | Action::SetMark | Action::JumpToMark
+    ) {
+        return run_action(app, action);
+    }
+    let count = app.pending_keys.take_count();
+    let result = match count {
+        _ if action == Action::Repeat => repeat_last_edit(app, count),
+        Some(count) if count > 1 && !action.takes_count() => {
+            app.set_status(format!("\"{}\" doesn't take a count", action.description()));
+            Ok(false)
+        }
+        _ => {
+            if action.is_repeatable_edit() {
+                app.last_edit = Some((action, count.unwrap_or(1)));
+            }
+            run_repeated(app, action, count.unwrap_or(1))
+        }
+    };
+    app.pending_keys.clear();
+    result
+}
+
+/// Repeats the last edit, with a new count if one was typed.
+///
+/// # Returns
+///
+/// `true` if the application should quit
+fn repeat_last_edit(app: &mut App, count: Option<usize>) -> Result<bool> {
+    let Some((action, last_count)) = app.last_edit else {
+        app.set_status("Nothing to repeat");
+        return Ok(false);
+    };
+    let count = count.unwrap_or(last_count);
+    app.last_edit = Some((action, count));
+    run_repeated(app, action, count)
+}
+
+/// Runs an action `count` times, undoable as one edit.
+///
+/// Actions that don't take a count run once.
+///
+/// # Returns
+///
+/// `true` if the application should quit
+fn run_repeated(app: &mut App, action: Action, count: usize) -> Result<bool> {
+    if count <= 1 || !action.takes_count() {
+        return run_action(app, action);
+    }
+    app.begin_history_group();
+    let mut result = Ok(false);
+    for i in 0..count {
+        // Counted notes are placed one after another
+        if i > 0 && action == Action::PlaceNote {
+            app.move_cursor_horizontal(app.config.default_note_duration as i32);
+        }
+        result = run_action(app, action);
+        if !matches!(result, Ok(false)) {
+            break;
+        }
+    }
+    app.end_history_group();
+    result
+}
+
+/// Handles keys that build up a count or name a register or mark.
+///
+/// # Arguments
+///
+/// * `counts` - Whether digits start a count
+///
+/// # Returns
+///
+/// `true` if the key was consumed
+fn handle_pending_keys(app: &mut App, code: KeyCode, counts: bool) -> bool {
+    let key = match code {
+        KeyCode::Char(c) => Some(c),
+        _ => None,
+    };
+    let Some(awaiting) = app.pending_keys.take_awaiting() else {
+        return counts && key.is_some_and(|c| app.pending_keys.push_digit(c));
+    };
+    match (awaiting, key) {
+        (Awaiting::Register, Some(c)) if vim::is_register(c) => app.pending_keys.set_register(c),
+        (Awaiting::SetMark, Some(c)) if vim::is_mark(c) => {
+            app.pending_keys.clear();
+            app.set_mark(c);
+        }
+        (Awaiting::JumpToMark, Some(c)) if vim::is_mark(c) => {
+            app.pending_keys.clear();
+            app.jump_to_mark(c);
+        }
+        // Anything else cancels what was typed
+        _ => app.pending_keys.clear(),
+    }
+    true
+}
+
+/// Handles keys in lyric mode.
+///
+/// # Returns
+///
+/// `true` if the key was consumed
+fn handle_lyric_mode(app: &mut App, code: KeyCode) -> bool {
+    match code {
+        KeyCode::Esc => {
+            app.exit_lyric_mode();
+            app.set_status("Normal mode");
+        }
+        // Space ends a word, '-' ends a syllable mid-word
+        KeyCode::Char(' ') => app.lyric_advance(false),
+        KeyCode::Char('-') => app.lyric_advance(true),
+        KeyCode::Enter => app.lyric_new_line(),
+        KeyCode::Backspace => app.lyric_backspace(),
+        KeyCode::Right | KeyCode::Tab => app.lyric_next_note(),
+        KeyCode::Left | KeyCode::BackTab => app.lyric_prev_note(),
+        KeyCode::Char(c) => app.lyric_input(c),
+        _ => return false,
+    }
+    true
+}
+
+/// Handles keys in the step sequencer view (percussion tracks only).
+///
+/// # Returns
+///
+/// `true` if the key was consumed
+fn handle_step_sequencer(app: &mut App, code: KeyCode) -> bool {
+    if app.view_mode != ViewMode::StepSequencer
+        || !app.selected_track().is_some_and(|t| t.is_percussion())
+    {
+        return false;
+    }
+    match code {
+        KeyCode::Char('h') | KeyCode::Left => app.step_cursor_move(0, -1),
+        KeyCode::Char('l') | KeyCode::Right => app.step_cursor_move(0, 1),
+        KeyCode::Char('k') | KeyCode::Up => app.step_cursor_move(-1, 0),
+        KeyCode::Char('j') | KeyCode::Down => app.step_cursor_move(1, 0),
+        KeyCode::Char('H') => app.step_page(-1),
+        KeyCode::Char('L') => app.step_page(1),
+        KeyCode::Enter | KeyCode::Char('n') => app.step_toggle(),
+        KeyCode::Char('a') => app.step_toggle_accent(),
+        KeyCode::Char('+') | KeyCode::Char('=') => app.step_adjust_velocity(10),
+        KeyCode::Char('-') => app.step_adjust_velocity(-10),
+        KeyCode::Char(',') => app.step_adjust_length(-1),
+        KeyCode::Char('/') => app.step_adjust_length(1),
+        KeyCode::Char('z') => app.step_cycle_division(),
+        KeyCode::Char('c') => app.step_copy_to_next(),
+        KeyCode::Char('o') => app.step_toggle_loop(),
+        _ => return false,
+    }
+    true
+}
+
+/// Returns true if the current view handles navigation keys itself (see
+/// [`handle_step_sequencer`], [`handle_staff_view`] and [`handle_event_list`]).
+fn view_has_own_keys(app: &App) -> bool {
+    match app.view_mode {
+        ViewMode::StepSequencer => app.selected_track().is_some_and(|t| t.is_percussion()),
+        ViewMode::Staff | ViewMode::EventList => true,
+        _ => false,
+    }
+}
+
+/// Handles keys in the staff view, which is read-only.
+///
+/// # Returns
+///
+/// `true` if the key was consumed
+fn handle_staff_view(app: &mut App, code: KeyCode) -> bool {
+    if app.view_mode != ViewMode::Staff {
+        return false;
+    }
+    let measure = app.project().ticks_per_measure() as i32;
+    let beat = measure / app.project().time_sig_numerator.max(1) as i32;
+    match code {
+        KeyCode::Char('h') | KeyCode::Left => app.move_cursor_horizontal(-beat),
+        KeyCode::Char('l') | KeyCode::Right => app.move_cursor_horizontal(beat),
+        KeyCode::Char('H') => app.move_cursor_horizontal(-measure),
+        KeyCode::Char('L') => app.move_cursor_horizontal(measure),
+        KeyCode::Enter | KeyCode::Char('n') | KeyCode::Delete => {
+            app.set_status("Staff view is read-only - switch views (g) to edit");
+        }
+        _ => return false,
+    }
+    true
+}
+
+/// Handles keys in the event list view.
+///
+/// # Returns
+///
+/// `true` if the key was consumed
+fn handle_event_list(app: &mut App, code: KeyCode) -> bool {
+    if app.view_mode != ViewMode::EventList {
+        return false;
+    }
+    match code {
+        KeyCode::Char('j') | KeyCode::Down => app.event_list_move(1, false),
+        KeyCode::Char('k') | KeyCode::Up => app.event_list_move(-1, false),
+        KeyCode::Char('J') => app.event_list_move(1, true),
+        KeyCode::Char('K') => app.event_list_move(-1, true),
+        KeyCode::PageDown => app.event_list_move(10, false),
+        KeyCode::PageUp => app.event_list_move(-10, false),
+        KeyCode::Char('h') | KeyCode::Left => app.event_list_column(-1),
+        KeyCode::Char('l') | KeyCode::Right => app.event_list_column(1),
+        KeyCode::Enter => app.event_list_start_input(EventListInput::Cell),
+        KeyCode::Char('/') => app.event_list_start_input(EventListInput::Filter),
+        KeyCode::Char('+') | KeyCode::Char('=') => app.event_list_nudge(1),
+        KeyCode::Char('-') => app.event_list_nudge(-1),
+        KeyCode::Char('v') => app.event_list_toggle_select(),
+        KeyCode::Char('a') => app.event_list_select_all(),
+        KeyCode::Char('o') => app.event_list_sort(),
+        KeyCode::Char('x') | KeyCode::Delete => app.event_list_delete(),
+        _ => return false,
+    }
+    true
+}
+
+/// Handles keys in normal mode.
+fn handle_normal_mode(app: &mut App, code: KeyCode, modifiers: KeyModifiers) -> Result<bool> {
+    // Views with keys of their own move one step at a time, so digits
+    // don't start a count there
+    if handle_pending_keys(app, code, !view_has_own_keys(app)) {
+        return Ok(false);
+    }
+    if handle_step_sequencer(app, code)
+        || handle_staff_view(app, code)
+        || handle_event_list(app, code)
+    {
+        app.pending_keys.clear();
+        return Ok(false);
+    }
+    match app.keymap.action(KeyContext::Normal, code, modifiers) {
+        Some(action) => run_counted_action(app, action),
+        // Keyboard note playing (still works in normal mode)
+        None => {
+            app.pending_keys.clear();
+            if let KeyCode::Char(c) = code {
+                app.handle_note_key(c);
+            }
+            Ok(false)
+        }
+    }
+}
+
+/// Handles keys in insert mode.
+fn handle_insert_mode(app: &mut App, code: KeyCode, modifiers: KeyModifiers) -> Result<bool> {
+    match app.keymap.action(KeyContext::Insert, code, modifiers) {
+        Some(action) => run_action(app, action),
+        // In insert mode, keyboard keys insert and play notes
+        None => {
+            if let KeyCode::Char(c) = code {
+                app.handle_note_key(c);
+            }
+            Ok(false)
+        }
+    }
+}
+
+/// Handles keys in select mode.
+fn handle_select_mode(app: &mut App, code: KeyCode, modifiers: KeyModifiers) -> Result<bool> {
+    if handle_pending_keys(app, code, true) {
+        return Ok(false);
+    }
+    match app.keymap.action(KeyContext::Select, code, modifiers) {
+        Some(action) => run_counted_action(app, action),
+        None => {
+            app.pending_keys.clear();
+            Ok(false)
+        }
+    }
+}
+
 /// Exports the current project to a WAV file.
 fn export_project(app: &mut App) -> Result<()> {
-    app.set_status("Exporting to output.wav...");
+    let export = app.config.export.clone();
+    app.set_status(format!("Exporting to {}...", export.wav_file_name));
     app.exporting = true;
 
     // Create output directory if needed
-    std::fs::create_dir_all("output")?;
+    std::fs::create_dir_all(&export.dir)?;
 
-    let output_path = PathBuf::from("output/output.wav");
+    let output_path = export.wav_path();
     let soundfont_path = app.soundfont_path.clone();
 
     // Export with progress callback
@@ -1407,6 +2000,7 @@ fn export_project(app: &mut App) -> Result<()> {
         app.project(),
         &soundfont_path,
         &output_path,
+        &export.wav,
         Some(|_progress: f32| {
             // Progress updates happen but we can't easily update the UI during export
             // For a more advanced implementation, this would use channels
@@ -1436,7 +2030,8 @@ fn export_midi(app: &mut App) -> Result<()> {
     app.set_status("Exporting to MIDI...");
 
     // Create output directory if needed
-    std::fs::create_dir_all("output")?;
+    let export_dir = app.config.export.dir.clone();
+    std::fs::create_dir_all(&export_dir)?;
 
     // Generate filename from project name or path
     let filename = app
@@ -1460,7 +2055,7 @@ fn export_midi(app: &mut App) -> Result<()> {
         filename
     };
 
-    let output_path = PathBuf::from(format!("output/{}.mid", filename));
+    let output_path = export_dir.join(format!("{}.mid", filename));
 
     match app.project().export_to_midi(&output_path) {
         Ok(()) => {
@@ -0,0 +1,417 @@
+//! Chords: qualities, voicings, detection and accompaniment.
+//!
+//! A chord is a root pitch class plus a quality (the intervals above the
+//! root). Voicings turn a chord into concrete pitches for entry, detection
+//! names the chord formed by a set of sounding pitches, and the chord track
+//! (a list of timed chord symbols) can be rendered into block, strummed or
+//! arpeggiated accompaniment.
+
+use super::note::Note;
+use super::{NOTE_NAMES, TICKS_PER_BEAT};
+use serde::{Deserialize, Serialize};
+
+/// Chord qualities, with their intervals above the root.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ChordQuality {
+    /// Major triad.
+    #[default]
+    Major,
+    /// Minor triad.
+    Minor,
+    /// Diminished triad.
+    Diminished,
+    /// Augmented triad.
+    Augmented,
+    /// Root, 2nd and 5th.
+    Sus2,
+    /// Root, 4th and 5th.
+    Sus4,
+    /// Major triad with a major 7th.
+    Major7,
+    /// Minor triad with a minor 7th.
+    Minor7,
+    /// Major triad with a minor 7th.
+    Dominant7,
+    /// Diminished triad with a minor 7th.
+    HalfDiminished7,
+    /// Diminished triad with a diminished 7th.
+    Diminished7,
+    /// Major triad with an added 9th.
+    Add9,
+    /// Minor triad with an added 9th.
+    MinorAdd9,
+    /// Major triad with an added 6th.
+    Sixth,
+    /// Minor triad with an added 6th.
+    Minor6,
+}
+
+impl ChordQuality {
+    /// All qualities, in dialog order. Detection also prefers earlier entries.
+    pub const ALL: [ChordQuality; 15] = [
+        ChordQuality::Major,
+        ChordQuality::Minor,
+        ChordQuality::Diminished,
+        ChordQuality::Augmented,
+        ChordQuality::Sus2,
+        ChordQuality::Sus4,
+        ChordQuality::Major7,
+        ChordQuality::Minor7,
+        ChordQuality::Dominant7,
+        ChordQuality::HalfDiminished7,
+        ChordQuality::Diminished7,
+        ChordQuality::Add9,
+        ChordQuality::MinorAdd9,
+        ChordQuality::Sixth,
+        ChordQuality::Minor6,
+    ];
+
+    /// Returns the intervals above the root, in semitones, lowest first.
+    pub fn intervals(self) -> &'static [u8] {
+        match self {
+            ChordQuality::Major => &[0, 4, 7],
+            ChordQuality::Minor => &[0, 3, 7],
+            ChordQuality::Diminished => &[0, 3, 6],
+            ChordQuality::Augmented => &[0, 4, 8],
+            ChordQuality::Sus2 => &[0, 2, 7],
+            ChordQuality::Sus4 => &[0, 5, 7],
+            ChordQuality::Major7 => &[0, 4, 7, 11],
+            ChordQuality::Minor7 => &[0, 3, 7, 10],
+            ChordQuality::Dominant7 => &[0, 4, 7, 10],
+            ChordQuality::HalfDiminished7 => &[0, 3, 6, 10],
+            ChordQuality::Diminished7 => &[0, 3, 6, 9],
+            ChordQuality::Add9 => &[0, 4, 7, 14],
+            ChordQuality::MinorAdd9 => &[0, 3, 7, 14],
+            ChordQuality::Sixth => &[0, 4, 7, 9],
+            ChordQuality::Minor6 => &[0, 3, 7, 9],
+        }
+    }
+
+    /// Returns the chord symbol suffix (e.g. "m7" or "sus4").
+    pub fn suffix(self) -> &'static str {
+        match self {
+            ChordQuality::Major => "",
+            ChordQuality::Minor => "m",
+            ChordQuality::Diminished => "dim",
+            ChordQuality::Augmented => "aug",
+            ChordQuality::Sus2 => "sus2",
+            ChordQuality::Sus4 => "sus4",
+            ChordQuality::Major7 => "maj7",
+            ChordQuality::Minor7 => "m7",
+            ChordQuality::Dominant7 => "7",
+            ChordQuality::HalfDiminished7 => "m7b5",
+            ChordQuality::Diminished7 => "dim7",
+            ChordQuality::Add9 => "add9",
+            ChordQuality::MinorAdd9 => "madd9",
+            ChordQuality::Sixth => "6",
+            ChordQuality::Minor6 => "m6",
+        }
+    }
+
+    /// Returns the pitch classes of the quality relative to the root as a
+    /// 12-bit mask (bit n = n semitones above the root).
+    fn mask(self) -> u16 {
+        self.intervals()
+            .iter()
+            .fold(0, |mask, &i| mask | 1 << (i % 12))
+    }
+}
+
+/// A named chord: root pitch class, quality and optional bass note.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Chord {
+    /// Root pitch class (0 = C, 11 = B).
+    pub root: u8,
+    /// Chord quality.
+    pub quality: ChordQuality,
+    /// Bass pitch class when it differs from the root (slash chords).
+    #[serde(default)]
+    pub bass: Option<u8>,
+}
+
+impl Chord {
+    /// Creates a root-position chord.
+    pub fn new(root: u8, quality: ChordQuality) -> Self {
+        Self {
+            root: root % 12,
+            quality,
+            bass: None,
+        }
+    }
+
+    /// Returns the chord symbol, e.g. "Cmaj7" or "Am/E".
+    pub fn symbol(&self) -> String {
+        let mut symbol = format!(
+            "{}{}",
+            NOTE_NAMES[self.root as usize % 12],
+            self.quality.suffix()
+        );
+        if let Some(bass) = self.bass {
+            symbol.push('/');
+            symbol.push_str(NOTE_NAMES[bass as usize % 12]);
+        }
+        symbol
+    }
+
+    /// Names the chord formed by a set of pitches.
+    ///
+    /// The pitch classes must match a chord quality exactly. When several
+    /// roots fit (e.g. C6 and Am7), the one in the bass wins.
+    ///
+    /// # Returns
+    ///
+    /// The chord, or None for fewer than three pitch classes or no match
+    pub fn detect(pitches: &[u8]) -> Option<Self> {
+        let bass = *pitches.iter().min()? % 12;
+        let set = pitches.iter().fold(0u16, |set, &p| set | 1 << (p % 12));
+        if set.count_ones() < 3 {
+            return None;
+        }
+
+        // Try the bass first, then the other pitch classes from C up
+        let roots = std::iter::once(bass).chain((0..12).filter(|&pc| pc != bass));
+        for root in roots.filter(|&pc| set & (1 << pc) != 0) {
+            let relative = ((set >> root) | (set << (12 - root))) & 0x0FFF;
+            if let Some(&quality) = ChordQuality::ALL.iter().find(|q| q.mask() == relative) {
+                return Some(Self {
+                    root,
+                    quality,
+                    bass: (bass != root).then_some(bass),
+                });
+            }
+        }
+        None
+    }
+}
+
+/// Settings for turning a chord quality into pitches.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ChordVoicing {
+    /// Chord quality.
+    pub quality: ChordQuality,
+    /// Number of lowest tones moved up an octave (0 = root position).
+    pub inversion: u8,
+    /// Open voicing: every second tone (from the bottom) is raised an octave.
+    pub spread: bool,
+}
+
+impl ChordVoicing {
+    /// Returns the pitches of the chord built on a root pitch, lowest first.
+    ///
+    /// Pitches above 127 are dropped.
+    pub fn pitches(&self, root: u8) -> Vec<u8> {
+        let intervals = self.quality.intervals();
+        let inversion = self.inversion as usize % intervals.len();
+        let mut pitches: Vec<u16> = intervals
+            .iter()
+            .enumerate()
+            .map(|(i, &interval)| {
+                let octave = if i < inversion { 12 } else { 0 };
+                root as u16 + interval as u16 + octave
+            })
+            .collect();
+        pitches.sort_unstable();
+        if self.spread {
+            for pitch in pitches.iter_mut().skip(1).step_by(2) {
+                *pitch += 12;
+            }
+            pitches.sort_unstable();
+        }
+        pitches
+            .into_iter()
+            .filter(|&p| p <= 127)
+            .map(|p| p as u8)
+            .collect()
+    }
+
+    /// Returns the inversion name, e.g. "root" or "1st inv".
+    pub fn inversion_name(&self) -> String {
+        match self.inversion as usize % self.quality.intervals().len() {
+            0 => "root".to_string(),
+            1 => "1st inv".to_string(),
+            2 => "2nd inv".to_string(),
+            3 => "3rd inv".to_string(),
+            n => format!("{}th inv", n),
+        }
+    }
+
+    /// Returns a short description, e.g. "maj7, 1st inv, open".
+    pub fn label(&self) -> String {
+        let suffix = match self.quality.suffix() {
+            "" => "maj",
+            suffix => suffix,
+        };
+        let spread = if self.spread { "open" } else { "close" };
+        format!("{}, {}, {}", suffix, self.inversion_name(), spread)
+    }
+}
+
+/// A chord symbol on the chord track.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChordSymbol {
+    /// Position in ticks. The chord lasts until the next symbol.
+    pub tick: u32,
+    /// The chord.
+    pub chord: Chord,
+}
+
+/// How accompaniment is played from the chord track.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AccompanimentStyle {
+    /// All chord tones together for the whole chord.
+    #[default]
+    Block,
+    /// Chord tones started one after another, low to high.
+    Strum,
+    /// Chord tones cycled upward in eighth notes.
+    Arpeggio,
+}
+
+impl AccompanimentStyle {
+    /// Returns the next style in the cycle.
+    pub fn next(self) -> Self {
+        match self {
+            AccompanimentStyle::Block => AccompanimentStyle::Strum,
+            AccompanimentStyle::Strum => AccompanimentStyle::Arpeggio,
+            AccompanimentStyle::Arpeggio => AccompanimentStyle::Block,
+        }
+    }
+
+    /// Returns the display name.
+    pub fn name(self) -> &'static str {
+        match self {
+            AccompanimentStyle::Block => "block",
+            AccompanimentStyle::Strum => "strum",
+            AccompanimentStyle::Arpeggio => "arpeggio",
+        }
+    }
+}
+
+/// Delay between strummed chord tones in ticks.
+const STRUM_TICKS: u32 = TICKS_PER_BEAT / 24;
+
+/// Velocity of generated accompaniment notes.
+const ACCOMPANIMENT_VELOCITY: u8 = 90;
+
+/// Lowest pitch used for accompaniment roots (C3).
+const ACCOMPANIMENT_BASE_PITCH: u8 = 48;
+
+/// Generates accompaniment notes from chord symbols.
+///
+/// Each chord lasts until the next symbol; the last one lasts until `end_tick`.
+/// Chords are voiced in root position from the octave starting at C3.
+///
+/// # Arguments
+///
+/// * `symbols` - Chord track, sorted by tick
+/// * `end_tick` - End of the last chord
+/// * `style` - Block, strum or arpeggio
+pub fn accompaniment(
+    symbols: &[ChordSymbol],
+    end_tick: u32,
+    style: AccompanimentStyle,
+) -> Vec<Note> {
+    let mut notes = Vec::new();
+    for (i, symbol) in symbols.iter().enumerate() {
+        let start = symbol.tick;
+        let end = symbols.get(i + 1).map_or(end_tick, |next| next.tick);
+        if end <= start {
+            continue;
+        }
+        let voicing = ChordVoicing {
+            quality: symbol.chord.quality,
+            ..Default::default()
+        };
+        let pitches = voicing.pitches(ACCOMPANIMENT_BASE_PITCH + symbol.chord.root);
+
+        match style {
+            AccompanimentStyle::Block | AccompanimentStyle::Strum => {
+                for (j, &pitch) in pitches.iter().enumerate() {
+                    let offset = if style == AccompanimentStyle::Strum {
+                        (j as u32 * STRUM_TICKS).min(end - start - 1)
+                    } else {
+                        0
+                    };
+                    notes.push(Note::new(
+                        pitch,
+                        ACCOMPANIMENT_VELOCITY,
+                        start + offset,
+                        end - start - offset,
+                    ));
+                }
+            }
+            AccompanimentStyle::Arpeggio => {
+                let step = TICKS_PER_BEAT / 2;
+                for (j, tick) in (start..end).step_by(step as usize).enumerate() {
+                    let pitch = pitches[j % pitches.len()];
+                    notes.push(Note::new(
+                        pitch,
+                        ACCOMPANIMENT_VELOCITY,
+                        tick,
+                        step.min(end - tick),
+                    ));
+                }
+            }
+        }
+    }
+    notes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_voicings() {
+        let mut voicing = ChordVoicing {
+            quality: ChordQuality::Major7,
+            ..Default::default()
+        };
+        assert_eq!(voicing.pitches(60), vec![60, 64, 67, 71]);
+        voicing.inversion = 1;
+        assert_eq!(voicing.pitches(60), vec![64, 67, 71, 72]);
+        voicing.spread = true;
+        assert_eq!(voicing.pitches(60), vec![64, 71, 79, 84]);
+        assert_eq!(voicing.label(), "maj7, 1st inv, open");
+    }
+
+    #[test]
+    fn test_detect() {
+        let symbol = |pitches: &[u8]| Chord::detect(pitches).map(|c| c.symbol());
+        assert_eq!(symbol(&[60, 64, 67]), Some("C".to_string()));
+        assert_eq!(symbol(&[57, 60, 64, 67]), Some("Am7".to_string()));
+        assert_eq!(symbol(&[48, 57, 64, 67]), Some("C6".to_string()));
+        assert_eq!(symbol(&[52, 57, 60]), Some("Am/E".to_string()));
+        assert_eq!(symbol(&[62, 66, 69, 72]), Some("D7".to_string()));
+        assert_eq!(symbol(&[60, 67, 72]), None);
+        assert_eq!(symbol(&[60, 61, 62]), None);
+    }
+
+    #[test]
+    fn test_accompaniment_styles() {
+        let symbols = [
+            ChordSymbol {
+                tick: 0,
+                chord: Chord::new(0, ChordQuality::Major),
+            },
+            ChordSymbol {
+                tick: 1920,
+                chord: Chord::new(7, ChordQuality::Major),
+            },
+        ];
+        let block = accompaniment(&symbols, 3840, AccompanimentStyle::Block);
+        assert_eq!(block.len(), 6);
+        assert!(block[..3]
+            .iter()
+            .all(|n| n.start_tick == 0 && n.duration_ticks == 1920));
+        assert_eq!(block[3].pitch, 55);
+
+        let strum = accompaniment(&symbols, 3840, AccompanimentStyle::Strum);
+        assert_eq!(strum[2].start_tick, STRUM_TICKS * 2);
+
+        let arp = accompaniment(&symbols, 3840, AccompanimentStyle::Arpeggio);
+        assert_eq!(arp.len(), 16);
+        let first: Vec<_> = arp[..4].iter().map(|n| n.pitch).collect();
+        assert_eq!(first, vec![48, 52, 55, 48]);
+    }
+}
@@ -0,0 +1,99 @@
+//! Arrangement clips and the patterns they instance.
+//!
+//! A pattern is a named container of notes (positioned relative to the
+//! pattern start). A clip places a pattern on a track at a given tick. Several
+//! clips may share one pattern - these are linked copies, so editing the
+//! pattern changes every instance until a clip is made unique.
+
+use super::note::Note;
+use serde::{Deserialize, Serialize};
+
+/// Identifier for a pattern within a project.
+///
+/// Pattern IDs are allocated by the project (one above the highest in use) so
+/// they stay unique across save/load without a global counter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct PatternId(pub u32);
+
+/// A reusable, named container of notes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Pattern {
+    /// Unique identifier within the project.
+    pub id: PatternId,
+
+    /// Display name (shown on clip blocks).
+    pub name: String,
+
+    /// Length of the pattern in ticks (the clip block width).
+    pub length_ticks: u32,
+
+    /// Notes with start ticks relative to the pattern start, sorted by start_tick.
+    notes: Vec<Note>,
+}
+
+impl Pattern {
+    /// Creates a new pattern.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - Pattern identifier
+    /// * `name` - Display name
+    /// * `length_ticks` - Length of the pattern in ticks
+    /// * `notes` - Notes relative to the pattern start
+    pub fn new(
+        id: PatternId,
+        name: impl Into<String>,
+        length_ticks: u32,
+        notes: Vec<Note>,
+    ) -> Self {
+        let mut pattern = Self {
+            id,
+            name: name.into(),
+            length_ticks,
+            notes: Vec::new(),
+        };
+        pattern.set_notes(notes);
+        pattern
+    }
+
+    /// Returns the pattern notes (relative ticks, sorted by start_tick).
+    pub fn notes(&self) -> &[Note] {
+        &self.notes
+    }
+
+    /// Replaces the pattern notes.
+    pub fn set_notes(&mut self, mut notes: Vec<Note>) {
+        notes.sort_by_key(|n| n.start_tick);
+        self.notes = notes;
+    }
+
+    /// Returns a copy of the pattern under a new ID (with fresh note IDs).
+    pub fn duplicate(&self, id: PatternId, name: impl Into<String>) -> Self {
+        Self {
+            id,
+            name: name.into(),
+            length_ticks: self.length_ticks,
+            notes: self.notes.iter().map(|n| n.duplicate()).collect(),
+        }
+    }
+}
+
+/// A placement of a pattern on a track.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Clip {
+    /// The pattern this clip plays.
+    pub pattern_id: PatternId,
+
+    /// Position of the clip on the track in ticks.
+    pub start_tick: u32,
+}
+
+impl Clip {
+    /// Creates a new clip.
+    pub fn new(pattern_id: PatternId, start_tick: u32) -> Self {
+        Self {
+            pattern_id,
+            start_tick,
+        }
+    }
+}
@@ -0,0 +1,241 @@
+//! Drum maps and the General MIDI percussion map.
+//!
+//! On channel 10 (index 9) each pitch selects a drum instrument instead of a
+//! note. A drum map names those pitches, sets the order of the rows in the
+//! step sequencer, and can remap pitches on MIDI export so a pattern written
+//! for one kit plays the same sounds on another. Tracks without their own
+//! map use the General MIDI map.
+
+use super::note_to_name;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+/// General MIDI percussion key map (pitch, instrument name), pitches 35-81.
+pub const GM_DRUMS: [(u8, &str); 47] = [
+    (35, "Acoustic Bass Drum"),
+    (36, "Bass Drum 1"),
+    (37, "Side Stick"),
+    (38, "Acoustic Snare"),
+    (39, "Hand Clap"),
+    (40, "Electric Snare"),
+    (41, "Low Floor Tom"),
+    (42, "Closed Hi-Hat"),
+    (43, "High Floor Tom"),
+    (44, "Pedal Hi-Hat"),
+    (45, "Low Tom"),
+    (46, "Open Hi-Hat"),
+    (47, "Low-Mid Tom"),
+    (48, "Hi-Mid Tom"),
+    (49, "Crash Cymbal 1"),
+    (50, "High Tom"),
+    (51, "Ride Cymbal 1"),
+    (52, "Chinese Cymbal"),
+    (53, "Ride Bell"),
+    (54, "Tambourine"),
+    (55, "Splash Cymbal"),
+    (56, "Cowbell"),
+    (57, "Crash Cymbal 2"),
+    (58, "Vibraslap"),
+    (59, "Ride Cymbal 2"),
+    (60, "Hi Bongo"),
+    (61, "Low Bongo"),
+    (62, "Mute Hi Conga"),
+    (63, "Open Hi Conga"),
+    (64, "Low Conga"),
+    (65, "High Timbale"),
+    (66, "Low Timbale"),
+    (67, "High Agogo"),
+    (68, "Low Agogo"),
+    (69, "Cabasa"),
+    (70, "Maracas"),
+    (71, "Short Whistle"),
+    (72, "Long Whistle"),
+    (73, "Short Guiro"),
+    (74, "Long Guiro"),
+    (75, "Claves"),
+    (76, "Hi Wood Block"),
+    (77, "Low Wood Block"),
+    (78, "Mute Cuica"),
+    (79, "Open Cuica"),
+    (80, "Mute Triangle"),
+    (81, "Open Triangle"),
+];
+
+/// Returns a short label (up to 4 characters) for a drum name.
+///
+/// Multi-word names become initials plus numbers ("Closed Hi-Hat" -> "CHH",
+/// "Crash Cymbal 2" -> "CC2"); single words are cut ("Cowbell" -> "Cowb").
+pub fn drum_short_name(name: &str) -> String {
+    let words: Vec<&str> = name
+        .split(|c: char| c.is_whitespace() || c == '-')
+        .filter(|w| !w.is_empty())
+        .collect();
+    let short: String = if words.len() < 2 {
+        name.chars().take(4).collect()
+    } else {
+        words
+            .iter()
+            .flat_map(|word| {
+                if word.chars().all(|c| c.is_ascii_digit()) {
+                    word.chars().collect::<Vec<_>>()
+                } else {
+                    word.chars().take(1).flat_map(char::to_uppercase).collect()
+                }
+            })
+            .collect()
+    };
+    short.chars().take(4).collect()
+}
+
+fn default_drum_velocity() -> u8 {
+    100
+}
+
+/// One instrument of a drum map.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DrumMapEntry {
+    /// Pitch the instrument is written at.
+    pub pitch: u8,
+    /// Instrument name shown in the editor.
+    pub name: String,
+    /// Pitch written on MIDI export instead of `pitch`, if remapped.
+    #[serde(default)]
+    pub output: Option<u8>,
+    /// Velocity of steps entered in the step sequencer.
+    #[serde(default = "default_drum_velocity")]
+    pub velocity: u8,
+}
+
+impl DrumMapEntry {
+    /// Creates an entry without an output remap.
+    pub fn new(pitch: u8, name: impl Into<String>) -> Self {
+        Self {
+            pitch,
+            name: name.into(),
+            output: None,
+            velocity: default_drum_velocity(),
+        }
+    }
+}
+
+/// Named drum instruments in display order, with optional output remapping.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DrumMap {
+    /// Map name (e.g. the kit it describes).
+    pub name: String,
+    /// Instruments in display order.
+    pub entries: Vec<DrumMapEntry>,
+}
+
+impl Default for DrumMap {
+    fn default() -> Self {
+        Self::general_midi()
+    }
+}
+
+impl DrumMap {
+    /// Creates the General MIDI percussion map.
+    pub fn general_midi() -> Self {
+        Self {
+            name: "General MIDI".to_string(),
+            entries: GM_DRUMS
+                .iter()
+                .map(|&(pitch, name)| DrumMapEntry::new(pitch, name))
+                .collect(),
+        }
+    }
+
+    /// Returns the entry for a pitch.
+    pub fn entry(&self, pitch: u8) -> Option<&DrumMapEntry> {
+        self.entries.iter().find(|e| e.pitch == pitch)
+    }
+
+    /// Returns the instrument name for a pitch.
+    pub fn name_of(&self, pitch: u8) -> Option<&str> {
+        self.entry(pitch).map(|e| e.name.as_str())
+    }
+
+    /// Returns the label for a pitch: the instrument name, or the note name if unmapped.
+    pub fn label(&self, pitch: u8) -> String {
+        self.name_of(pitch)
+            .map_or_else(|| note_to_name(pitch), str::to_string)
+    }
+
+    /// Returns the pitch written on MIDI export for a pitch.
+    pub fn output_pitch(&self, pitch: u8) -> u8 {
+        self.entry(pitch).and_then(|e| e.output).unwrap_or(pitch)
+    }
+
+    /// Moves an entry up or down in the display order.
+    ///
+    /// # Returns
+    ///
+    /// The entry's new index
+    pub fn move_entry(&mut self, index: usize, delta: i32) -> usize {
+        if index >= self.entries.len() {
+            return index;
+        }
+        let target = (index as i64 + delta as i64).clamp(0, self.entries.len() as i64 - 1) as usize;
+        let entry = self.entries.remove(index);
+        self.entries.insert(target, entry);
+        target
+    }
+
+    /// Saves the drum map to a JSON file.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if serialization or file writing fails
+    pub fn save_to_file<P: AsRef<Path>>(&self, path: P) -> Result<(), std::io::Error> {
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        fs::write(path, json)
+    }
+
+    /// Loads a drum map from a JSON file.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if file reading or parsing fails
+    pub fn load_from_file<P: AsRef<Path>>(path: P) -> Result<Self, std::io::Error> {
+        let json = fs::read_to_string(path)?;
+        serde_json::from_str(&json)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gm_drum_names() {
+        let map = DrumMap::general_midi();
+        assert_eq!(map.name_of(36), Some("Bass Drum 1"));
+        assert_eq!(map.name_of(42), Some("Closed Hi-Hat"));
+        assert_eq!(map.name_of(34), None);
+        assert!(GM_DRUMS.windows(2).all(|w| w[1].0 == w[0].0 + 1));
+        assert_eq!(drum_short_name("Closed Hi-Hat"), "CHH");
+        assert_eq!(drum_short_name("Crash Cymbal 2"), "CC2");
+        assert_eq!(drum_short_name("Cowbell"), "Cowb");
+    }
+
+    #[test]
+    fn test_drum_map_remap_and_order() {
+        let mut map = DrumMap::general_midi();
+        assert_eq!(map.label(36), "Bass Drum 1");
+        assert_eq!(map.label(20), "G#0");
+        map.entries[1].output = Some(24);
+        assert_eq!(map.output_pitch(36), 24);
+        assert_eq!(map.output_pitch(38), 38);
+
+        assert_eq!(map.move_entry(1, -1), 0);
+        assert_eq!(map.entries[0].pitch, 36);
+        assert_eq!(map.move_entry(0, -1), 0);
+
+        // Missing optional fields fall back to defaults
+        let entry: DrumMapEntry = serde_json::from_str(r#"{"pitch":36,"name":"Kick"}"#).unwrap();
+        assert_eq!((entry.output, entry.velocity), (None, 100));
+    }
+}
@@ -0,0 +1,326 @@
+//! Note-generating MIDI effects: arpeggiator and echo.
+//!
+//! Effects turn a list of notes into a new list of notes. They can be
+//! rendered into a track (destructive, undoable) or kept on the track as an
+//! effect chain that playback and export evaluate on the fly.
+
+use super::groove::SplitMix64;
+use super::note::Note;
+use super::TICKS_PER_BEAT;
+use serde::{Deserialize, Serialize};
+
+/// Note order of the arpeggiator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ArpDirection {
+    /// Lowest to highest.
+    #[default]
+    Up,
+    /// Highest to lowest.
+    Down,
+    /// Up, then back down without repeating the top and bottom notes.
+    UpDown,
+    /// Random order (seeded, so playback and export agree).
+    Random,
+    /// Order in which the held notes started.
+    AsPlayed,
+}
+
+impl ArpDirection {
+    /// Returns the next direction in the cycle.
+    pub fn next(self) -> Self {
+        match self {
+            ArpDirection::Up => ArpDirection::Down,
+            ArpDirection::Down => ArpDirection::UpDown,
+            ArpDirection::UpDown => ArpDirection::Random,
+            ArpDirection::Random => ArpDirection::AsPlayed,
+            ArpDirection::AsPlayed => ArpDirection::Up,
+        }
+    }
+
+    /// Returns the previous direction in the cycle.
+    pub fn prev(self) -> Self {
+        match self {
+            ArpDirection::Up => ArpDirection::AsPlayed,
+            ArpDirection::Down => ArpDirection::Up,
+            ArpDirection::UpDown => ArpDirection::Down,
+            ArpDirection::Random => ArpDirection::UpDown,
+            ArpDirection::AsPlayed => ArpDirection::Random,
+        }
+    }
+
+    /// Returns the display name.
+    pub fn name(self) -> &'static str {
+        match self {
+            ArpDirection::Up => "up",
+            ArpDirection::Down => "down",
+            ArpDirection::UpDown => "up-down",
+            ArpDirection::Random => "random",
+            ArpDirection::AsPlayed => "as played",
+        }
+    }
+}
+
+/// Arpeggiator settings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArpSettings {
+    /// Note order.
+    pub direction: ArpDirection,
+    /// Step length as a fraction of a whole note (16 = sixteenths).
+    pub rate: u32,
+    /// Length of each step's note as a percentage of the step (1-100).
+    pub gate: u8,
+    /// Number of octaves the pattern spans (1-4).
+    pub octaves: u8,
+}
+
+impl Default for ArpSettings {
+    fn default() -> Self {
+        Self {
+            direction: ArpDirection::Up,
+            rate: 16,
+            gate: 50,
+            octaves: 1,
+        }
+    }
+}
+
+/// Echo (note repeat) settings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EchoSettings {
+    /// Number of repeats after the original note.
+    pub repeats: u8,
+    /// Delay between repeats as a fraction of a whole note (8 = eighths).
+    pub rate: u32,
+    /// Velocity of each repeat as a percentage of the previous one.
+    pub decay: u8,
+}
+
+impl Default for EchoSettings {
+    fn default() -> Self {
+        Self {
+            repeats: 3,
+            rate: 8,
+            decay: 70,
+        }
+    }
+}
+
+/// A note-generating effect.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NoteEffect {
+    /// Plays held notes one at a time in a pattern.
+    Arpeggiator(ArpSettings),
+    /// Repeats each note with decaying velocity.
+    Echo(EchoSettings),
+}
+
+impl NoteEffect {
+    /// Applies the effect to notes.
+    pub fn process(&self, notes: &[Note]) -> Vec<Note> {
+        match self {
+            NoteEffect::Arpeggiator(settings) => arpeggiate(notes, settings),
+            NoteEffect::Echo(settings) => echo(notes, settings),
+        }
+    }
+
+    /// Returns a short description, e.g. "Arp up 1/16 50% x2".
+    pub fn label(&self) -> String {
+        match self {
+            NoteEffect::Arpeggiator(s) => format!(
+                "Arp {} 1/{} {}% x{}",
+                s.direction.name(),
+                s.rate,
+                s.gate,
+                s.octaves
+            ),
+            NoteEffect::Echo(s) => format!("Echo {}x 1/{} {}%", s.repeats, s.rate, s.decay),
+        }
+    }
+}
+
+/// Returns the length in ticks of a note division (fraction of a whole note).
+fn division_ticks(division: u32) -> u32 {
+    (TICKS_PER_BEAT * 4 / division.max(1)).max(1)
+}
+
+/// Converts held notes into an arpeggio.
+///
+/// Time is divided into steps of the arpeggiator rate. At each step, the
+/// notes overlapping it are the held chord, and the next pitch of the
+/// pattern is played with the velocity of the note it came from. The
+/// pattern restarts whenever nothing is held.
+///
+/// # Returns
+///
+/// The arpeggiated notes, sorted by start tick
+pub fn arpeggiate(notes: &[Note], settings: &ArpSettings) -> Vec<Note> {
+    let (Some(first), Some(last)) = (
+        notes.iter().map(|n| n.start_tick).min(),
+        notes.iter().map(|n| n.end_tick()).max(),
+    ) else {
+        return Vec::new();
+    };
+    let step = division_ticks(settings.rate);
+    let length = (step * settings.gate.clamp(1, 100) as u32 / 100).max(1);
+    let mut rng = SplitMix64::new(first as u64);
+    let mut output = Vec::new();
+    let mut index = 0usize;
+
+    // Sweep through the notes in start order, keeping the ones still sounding
+    let mut by_start: Vec<&Note> = notes.iter().collect();
+    by_start.sort_by_key(|n| n.start_tick);
+    let mut next = 0;
+    let mut sounding: Vec<&Note> = Vec::new();
+
+    let mut tick = first / step * step;
+    while tick < last {
+        while next < by_start.len() && by_start[next].start_tick < tick + step {
+            sounding.push(by_start[next]);
+            next += 1;
+        }
+        sounding.retain(|n| n.end_tick() > tick);
+        let mut held = sounding.clone();
+        if held.is_empty() {
+            index = 0;
+            tick += step;
+            continue;
+        }
+        match settings.direction {
+            ArpDirection::AsPlayed => held.sort_by_key(|n| (n.start_tick, n.pitch)),
+            _ => held.sort_by_key(|n| n.pitch),
+        }
+
+        // Held notes repeated over the octave range, in pattern order
+        let octaves = settings.octaves.clamp(1, 4) as u16;
+        let mut pattern: Vec<(u8, u8)> = (0..octaves)
+            .flat_map(|octave| {
+                held.iter()
+                    .map(move |n| (n.pitch as u16 + octave * 12, n.velocity))
+            })
+            .filter(|&(pitch, _)| pitch <= 127)
+            .map(|(pitch, velocity)| (pitch as u8, velocity))
+            .collect();
+        match settings.direction {
+            ArpDirection::Down => pattern.reverse(),
+            ArpDirection::UpDown if pattern.len() > 2 => {
+                let down: Vec<_> = pattern[1..pattern.len() - 1]
+                    .iter()
+                    .rev()
+                    .copied()
+                    .collect();
+                pattern.extend(down);
+            }
+            _ => {}
+        }
+
+        let (pitch, velocity) = if settings.direction == ArpDirection::Random {
+            pattern[(rng.next_u64() % pattern.len() as u64) as usize]
+        } else {
+            pattern[index % pattern.len()]
+        };
+        output.push(Note::new(pitch, velocity, tick.max(first), length));
+        index += 1;
+        tick += step;
+    }
+    output
+}
+
+/// Adds decaying repeats after every note.
+///
+/// # Returns
+///
+/// The original notes plus their repeats, sorted by start tick
+pub fn echo(notes: &[Note], settings: &EchoSettings) -> Vec<Note> {
+    let delay = division_ticks(settings.rate);
+    let mut output = notes.to_vec();
+    for note in notes {
+        let mut velocity = note.velocity as u32;
+        for repeat in 1..=settings.repeats as u32 {
+            velocity = velocity * settings.decay.min(100) as u32 / 100;
+            if velocity == 0 {
+                break;
+            }
+            output.push(Note::new(
+                note.pitch,
+                velocity as u8,
+                note.start_tick + delay * repeat,
+                note.duration_ticks,
+            ));
+        }
+    }
+    output.sort_by_key(|n| n.start_tick);
+    output
+}
+
+/// Runs notes through an effect chain in order.
+pub fn apply_effects(notes: Vec<Note>, effects: &[NoteEffect]) -> Vec<Note> {
+    effects
+        .iter()
+        .fold(notes, |notes, effect| effect.process(&notes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chord(start: u32, length: u32) -> Vec<Note> {
+        [60, 64, 67]
+            .into_iter()
+            .map(|pitch| Note::new(pitch, 100, start, length))
+            .collect()
+    }
+
+    fn pitches(notes: &[Note]) -> Vec<u8> {
+        notes.iter().map(|n| n.pitch).collect()
+    }
+
+    #[test]
+    fn test_arp_directions() {
+        let held = chord(0, 960);
+        let mut settings = ArpSettings::default();
+        let up = arpeggiate(&held, &settings);
+        assert_eq!(pitches(&up), vec![60, 64, 67, 60, 64, 67, 60, 64]);
+        assert_eq!((up[1].start_tick, up[1].duration_ticks), (120, 60));
+
+        settings.direction = ArpDirection::Down;
+        assert_eq!(pitches(&arpeggiate(&held, &settings))[..3], [67, 64, 60]);
+
+        settings.direction = ArpDirection::UpDown;
+        settings.octaves = 2;
+        let up_down = arpeggiate(&held, &settings);
+        assert_eq!(pitches(&up_down), vec![60, 64, 67, 72, 76, 79, 76, 72]);
+
+        settings.direction = ArpDirection::Random;
+        let random = arpeggiate(&held, &settings);
+        assert_eq!(pitches(&random), pitches(&arpeggiate(&held, &settings)));
+        assert!(random
+            .iter()
+            .all(|n| [60, 64, 67, 72, 76, 79].contains(&n.pitch)));
+    }
+
+    #[test]
+    fn test_arp_restarts_after_gap() {
+        let mut notes = chord(0, 240);
+        notes.extend(chord(480, 240));
+        let arp = arpeggiate(&notes, &ArpSettings::default());
+        assert_eq!(pitches(&arp), vec![60, 64, 60, 64]);
+        assert_eq!(arp[2].start_tick, 480);
+    }
+
+    #[test]
+    fn test_echo_and_chain() {
+        let notes = [Note::new(60, 100, 0, 120)];
+        let echoed = echo(&notes, &EchoSettings::default());
+        let values: Vec<_> = echoed.iter().map(|n| (n.start_tick, n.velocity)).collect();
+        assert_eq!(values, vec![(0, 100), (240, 70), (480, 49), (720, 34)]);
+
+        let chain = [
+            NoteEffect::Arpeggiator(ArpSettings::default()),
+            NoteEffect::Echo(EchoSettings {
+                repeats: 1,
+                ..Default::default()
+            }),
+        ];
+        assert_eq!(apply_effects(chord(0, 240), &chain).len(), 4);
+    }
+}
@@ -0,0 +1,287 @@
+//! Formatting, parsing, sorting and filtering for the event list.
+//!
+//! Positions are shown as measure:beat:tick (measure and beat 1-based) and
+//! lengths as beats:ticks, both in the beat unit of the time signature. Only
+//! notes are listed today; the Type column leaves room for controller events.
+
+use super::note::Note;
+use super::{name_to_note, note_to_name, TICKS_PER_BEAT};
+use std::cmp::Ordering;
+use std::ops::RangeInclusive;
+
+/// A column of the event list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EventColumn {
+    /// Start position (measure:beat:tick).
+    #[default]
+    Position,
+    /// Pitch name.
+    Pitch,
+    /// Velocity (1-127).
+    Velocity,
+    /// Length (beats:ticks).
+    Length,
+}
+
+impl EventColumn {
+    /// All columns in display order.
+    pub const ALL: [EventColumn; 4] = [
+        EventColumn::Position,
+        EventColumn::Pitch,
+        EventColumn::Velocity,
+        EventColumn::Length,
+    ];
+
+    /// Returns the column header.
+    pub fn title(self) -> &'static str {
+        match self {
+            EventColumn::Position => "Position",
+            EventColumn::Pitch => "Pitch",
+            EventColumn::Velocity => "Velocity",
+            EventColumn::Length => "Length",
+        }
+    }
+
+    /// Returns the column `delta` places to the right, clamped to the table.
+    pub fn offset(self, delta: i32) -> Self {
+        let index = Self::ALL.iter().position(|&c| c == self).unwrap_or(0) as i32;
+        Self::ALL[(index + delta).clamp(0, Self::ALL.len() as i32 - 1) as usize]
+    }
+}
+
+/// Sort order of the event list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct EventSort {
+    /// Column to sort by.
+    pub column: EventColumn,
+    /// Whether the order is reversed.
+    pub descending: bool,
+}
+
+impl EventSort {
+    /// Compares two notes, falling back to start position and pitch.
+    pub fn compare(&self, a: &Note, b: &Note) -> Ordering {
+        let primary = match self.column {
+            EventColumn::Position => a.start_tick.cmp(&b.start_tick),
+            EventColumn::Pitch => a.pitch.cmp(&b.pitch),
+            EventColumn::Velocity => a.velocity.cmp(&b.velocity),
+            EventColumn::Length => a.duration_ticks.cmp(&b.duration_ticks),
+        };
+        let primary = if self.descending {
+            primary.reverse()
+        } else {
+            primary
+        };
+        primary
+            .then(a.start_tick.cmp(&b.start_tick))
+            .then(a.pitch.cmp(&b.pitch))
+    }
+}
+
+/// Converts between ticks and measure:beat:tick text for a time signature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeFormat {
+    /// Ticks per measure.
+    pub measure_ticks: u32,
+    /// Ticks per beat (of the time signature's beat unit).
+    pub beat_ticks: u32,
+}
+
+impl TimeFormat {
+    /// Creates the format for a time signature.
+    pub fn new(numerator: u8, denominator: u8) -> Self {
+        let beat_ticks = TICKS_PER_BEAT * 4 / denominator.max(1) as u32;
+        Self {
+            measure_ticks: beat_ticks * numerator.max(1) as u32,
+            beat_ticks,
+        }
+    }
+
+    /// Formats a tick as measure:beat:tick, e.g. "3:2:120".
+    pub fn position(&self, tick: u32) -> String {
+        let measure = tick / self.measure_ticks;
+        let within = tick % self.measure_ticks;
+        format!(
+            "{}:{}:{:03}",
+            measure + 1,
+            within / self.beat_ticks + 1,
+            within % self.beat_ticks
+        )
+    }
+
+    /// Parses measure:beat:tick text; beat and tick may be left out ("3", "3:2").
+    pub fn parse_position(&self, text: &str) -> Option<u32> {
+        let mut parts = text.trim().split(':');
+        let measure: u32 = parts.next()?.trim().parse().ok()?;
+        let beat: u32 = parts.next().map_or(Some(1), |p| p.trim().parse().ok())?;
+        let tick: u32 = parts.next().map_or(Some(0), |p| p.trim().parse().ok())?;
+        if parts.next().is_some() || measure == 0 || beat == 0 || tick >= self.beat_ticks {
+            return None;
+        }
+        Some((measure - 1) * self.measure_ticks + (beat - 1) * self.beat_ticks + tick)
+    }
+
+    /// Formats a length as beats:ticks, e.g. "1:240".
+    pub fn length(&self, ticks: u32) -> String {
+        format!("{}:{:03}", ticks / self.beat_ticks, ticks % self.beat_ticks)
+    }
+
+    /// Parses beats:ticks text, or a plain number of ticks.
+    pub fn parse_length(&self, text: &str) -> Option<u32> {
+        let text = text.trim();
+        let ticks = match text.split_once(':') {
+            Some((beats, ticks)) => {
+                let beats: u32 = beats.trim().parse().ok()?;
+                let ticks: u32 = ticks.trim().parse().ok()?;
+                beats * self.beat_ticks + ticks
+            }
+            None => text.parse().ok()?,
+        };
+        (ticks > 0).then_some(ticks)
+    }
+}
+
+/// Filter limiting the event list to a pitch range and/or a measure range.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct EventFilter {
+    /// Pitches to show.
+    pub pitches: Option<RangeInclusive<u8>>,
+    /// Measures to show (1-based).
+    pub measures: Option<RangeInclusive<u32>>,
+}
+
+impl EventFilter {
+    /// Parses filter text made of a pitch range and/or a measure range.
+    ///
+    /// Pitches are note names ("C4" or "C3-G4"), measures are numbers
+    /// ("5" or "5-8"). Empty text clears the filter.
+    pub fn parse(text: &str) -> Option<Self> {
+        let mut filter = Self::default();
+        for token in text.split_whitespace() {
+            let (low, high) = token.split_once('-').unwrap_or((token, token));
+            if let (Ok(low), Ok(high)) = (low.parse::<u32>(), high.parse::<u32>()) {
+                if low == 0 || filter.measures.is_some() {
+                    return None;
+                }
+                filter.measures = Some(low.min(high)..=low.max(high));
+            } else {
+                let (low, high) = split_pitch_range(token)?;
+                if filter.pitches.is_some() {
+                    return None;
+                }
+                filter.pitches = Some(low.min(high)..=low.max(high));
+            }
+        }
+        Some(filter)
+    }
+
+    /// Returns true if the filter shows every event.
+    pub fn is_empty(&self) -> bool {
+        self.pitches.is_none() && self.measures.is_none()
+    }
+
+    /// Returns true if a note passes the filter.
+    pub fn matches(&self, note: &Note, format: &TimeFormat) -> bool {
+        let measure = note.start_tick / format.measure_ticks + 1;
+        self.pitches
+            .as_ref()
+            .is_none_or(|p| p.contains(&note.pitch))
+            && self.measures.as_ref().is_none_or(|m| m.contains(&measure))
+    }
+
+    /// Returns a short description, e.g. "C3-G4, bars 5-8".
+    pub fn describe(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(pitches) = &self.pitches {
+            parts.push(if pitches.start() == pitches.end() {
+                note_to_name(*pitches.start())
+            } else {
+                format!(
+                    "{}-{}",
+                    note_to_name(*pitches.start()),
+                    note_to_name(*pitches.end())
+                )
+            });
+        }
+        if let Some(measures) = &self.measures {
+            parts.push(if measures.start() == measures.end() {
+                format!("bar {}", measures.start())
+            } else {
+                format!("bars {}-{}", measures.start(), measures.end())
+            });
+        }
+        parts.join(", ")
+    }
+}
+
+/// Splits "C3-G4" (or a single "C4") into two pitches.
+///
+/// Note names can hold a minus sign themselves ("C-1"), so every dash is
+/// tried as the separator.
+fn split_pitch_range(token: &str) -> Option<(u8, u8)> {
+    if let Some(pitch) = name_to_note(token) {
+        return Some((pitch, pitch));
+    }
+    token
+        .match_indices('-')
+        .find_map(|(i, _)| Some((name_to_note(&token[..i])?, name_to_note(&token[i + 1..])?)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_position_round_trip() {
+        let format = TimeFormat::new(4, 4);
+        assert_eq!(format.position(0), "1:1:000");
+        assert_eq!(format.position(1920 * 2 + 480 + 120), "3:2:120");
+        assert_eq!(format.parse_position("3:2:120"), Some(1920 * 2 + 480 + 120));
+        assert_eq!(format.parse_position("2"), Some(1920));
+        assert_eq!(format.parse_position("0:1"), None);
+        assert_eq!(format.parse_position("1:1:480"), None);
+
+        // 6/8 counts eighth-note beats
+        let format = TimeFormat::new(6, 8);
+        assert_eq!(format.position(1440 + 240 * 5), "2:6:000");
+        assert_eq!(format.length(360), "1:120");
+        assert_eq!(format.parse_length("1:120"), Some(360));
+        assert_eq!(format.parse_length("90"), Some(90));
+        assert_eq!(format.parse_length("0"), None);
+    }
+
+    #[test]
+    fn test_filter_parse_and_match() {
+        let filter = EventFilter::parse("C3-G4 5-8").unwrap();
+        assert_eq!(filter.pitches, Some(48..=67));
+        assert_eq!(filter.measures, Some(5..=8));
+        assert_eq!(filter.describe(), "C3-G4, bars 5-8");
+
+        let format = TimeFormat::new(4, 4);
+        assert!(filter.matches(&Note::new(60, 100, 1920 * 4, 480), &format));
+        assert!(!filter.matches(&Note::new(72, 100, 1920 * 4, 480), &format));
+        assert!(!filter.matches(&Note::new(60, 100, 0, 480), &format));
+
+        assert_eq!(EventFilter::parse("C-1-C0").unwrap().pitches, Some(0..=12));
+        assert!(EventFilter::parse("").unwrap().is_empty());
+        assert!(EventFilter::parse("H4").is_none());
+    }
+
+    #[test]
+    fn test_sort_with_tiebreak() {
+        let notes = [
+            Note::new(64, 80, 480, 240),
+            Note::new(60, 100, 0, 480),
+            Note::new(67, 80, 0, 120),
+        ];
+        let mut sorted: Vec<&Note> = notes.iter().collect();
+        let sort = EventSort {
+            column: EventColumn::Velocity,
+            descending: true,
+        };
+        sorted.sort_by(|a, b| sort.compare(a, b));
+        let pitches: Vec<u8> = sorted.iter().map(|n| n.pitch).collect();
+        assert_eq!(pitches, vec![60, 67, 64]);
+        assert_eq!(EventColumn::Pitch.offset(5), EventColumn::Length);
+    }
+}
@@ -0,0 +1,328 @@
+//! Versioned project file format.
+//!
+//! `.oxm` files start with a header (magic bytes, then the format version as
+//! a little-endian u32) followed by the bincode-encoded project. JSON files
+//! carry a top-level `format_version` field. Files written before versioning
+//! (miditui 0.1.8 and earlier) have neither and count as version 0.
+//! Unreleased development builds in between wrote headerless `.oxm` files
+//! with extra fields; those can't be told apart from version 0 and don't
+//! load (their JSON files do, as the added fields all have defaults).
+//!
+//! Older files are upgraded by running them through [`MIGRATIONS`] one
+//! version at a time. Migrations work on the JSON form of the project, so an
+//! old binary file is first decoded with its own layout and converted.
+
+use super::note::Note;
+use super::project::{new_project_id, Project};
+use super::track::TrackId;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Magic bytes at the start of every versioned `.oxm` file.
+pub const OXM_MAGIC: [u8; 4] = *b"OXM\x1a";
+
+/// Format version written by this build.
+pub const FORMAT_VERSION: u32 = 1;
+
+/// Length of the `.oxm` header (magic bytes and version).
+const HEADER_LEN: usize = OXM_MAGIC.len() + 4;
+
+/// Name of the version field in JSON project files.
+const VERSION_FIELD: &str = "format_version";
+
+/// Upgrades the JSON form of a project by one version.
+type Migration = fn(Value) -> Value;
+
+/// Migrations in order; entry `n` upgrades version `n` to `n + 1`.
+const MIGRATIONS: [Migration; FORMAT_VERSION as usize] = [migrate_v0_to_v1];
+
+/// Errors reading a project file.
+#[derive(Debug)]
+pub enum FormatError {
+    /// The file was written by a newer version of the app.
+    NewerVersion {
+        /// Version found in the file.
+        found: u32,
+    },
+    /// The file could not be decoded.
+    Invalid(String),
+}
+
+impl std::fmt::Display for FormatError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FormatError::NewerVersion { found } => write!(
+                f,
+                "file format version {} is newer than this version of miditui supports ({}); \
+                 update miditui to open it",
+                found, FORMAT_VERSION
+            ),
+            FormatError::Invalid(e) => write!(f, "Invalid project file: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for FormatError {}
+
+impl From<FormatError> for std::io::Error {
+    fn from(e: FormatError) -> Self {
+        std::io::Error::new(std::io::ErrorKind::InvalidData, e)
+    }
+}
+
+/// Project layout of version 0 binary files: no project id, and none of the
+/// fields added since (markers, patterns, grooves, chords, scale).
+///
+/// bincode stores fields by position, so old files must be read with the
+/// exact layout they were written with. The SoundFont path came last and was
+/// only written when set, so [`decode_binary_v0`] reads it separately.
+#[derive(Serialize, Deserialize)]
+struct ProjectV0 {
+    name: String,
+    tempo: u32,
+    time_sig_numerator: u8,
+    time_sig_denominator: u8,
+    tracks: Vec<TrackV0>,
+    next_channel: u8,
+    #[serde(skip_deserializing, skip_serializing_if = "Option::is_none")]
+    soundfont_path: Option<String>,
+}
+
+/// Track layout of version 0 binary files, before lyrics, clips, effects
+/// and drum maps.
+#[derive(Serialize, Deserialize)]
+struct TrackV0 {
+    id: TrackId,
+    name: String,
+    channel: u8,
+    program: u8,
+    volume: u8,
+    pan: u8,
+    muted: bool,
+    solo: bool,
+    notes: Vec<Note>,
+}
+
+/// Version 0 to 1: projects get a unique id.
+fn migrate_v0_to_v1(mut value: Value) -> Value {
+    if let Value::Object(fields) = &mut value {
+        fields
+            .entry("id")
+            .or_insert_with(|| Value::String(new_project_id()));
+    }
+    value
+}
+
+/// Upgrades the JSON form of a project from `version` to [`FORMAT_VERSION`].
+fn migrate(mut value: Value, version: u32) -> Value {
+    for migration in &MIGRATIONS[version as usize..] {
+        value = migration(value);
+    }
+    value
+}
+
+/// Returns an error if `version` is newer than this build can read.
+fn check_version(version: u32) -> Result<(), FormatError> {
+    if version > FORMAT_VERSION {
+        return Err(FormatError::NewerVersion { found: version });
+    }
+    Ok(())
+}
+
+/// Encodes a project as a versioned `.oxm` file.
+pub fn encode_binary(project: &Project) -> Result<Vec<u8>, FormatError> {
+    let payload = bincode::serialize(project).map_err(|e| FormatError::Invalid(e.to_string()))?;
+    let mut data = Vec::with_capacity(HEADER_LEN + payload.len());
+    data.extend_from_slice(&OXM_MAGIC);
+    data.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+    data.extend_from_slice(&payload);
+    Ok(data)
+}
+
+/// Decodes a `.oxm` file of any supported version.
+pub fn decode_binary(data: &[u8]) -> Result<Project, FormatError> {
+    let invalid = |e: bincode::Error| FormatError::Invalid(e.to_string());
+    let Some(header) = data.get(..HEADER_LEN).filter(|h| h.starts_with(&OXM_MAGIC)) else {
+        return decode_binary_v0(data);
+    };
+    let version = u32::from_le_bytes([header[4], header[5], header[6], header[7]]);
+    check_version(version)?;
+
+    let payload = &data[HEADER_LEN..];
+    if version == FORMAT_VERSION {
+        let mut project: Project = bincode::deserialize(payload).map_err(invalid)?;
+        project.ensure_valid_id();
+        return Ok(project);
+    }
+    // No headered version has been superseded yet; later layouts go here
+    Err(FormatError::Invalid(format!(
+        "unknown binary format version {}",
+        version
+    )))
+}
+
+/// Decodes a headerless (version 0) `.oxm` file.
+fn decode_binary_v0(mut data: &[u8]) -> Result<Project, FormatError> {
+    let invalid = |e: bincode::Error| FormatError::Invalid(e.to_string());
+    let mut legacy: ProjectV0 = bincode::deserialize_from(&mut data).map_err(invalid)?;
+    // Version 0 left out the trailing SoundFont path when it was unset
+    if !data.is_empty() {
+        legacy.soundfont_path = bincode::deserialize(data).map_err(invalid)?;
+    }
+    let value = serde_json::to_value(legacy).map_err(|e| FormatError::Invalid(e.to_string()))?;
+    from_value(migrate(value, 0))
+}
+
+/// Encodes a project as a versioned JSON file.
+pub fn encode_json(project: &Project) -> Result<String, FormatError> {
+    /// A project with the format version in front of its fields.
+    #[derive(Serialize)]
+    struct Versioned<'a> {
+        format_version: u32,
+        #[serde(flatten)]
+        project: &'a Project,
+    }
+
+    serde_json::to_string_pretty(&Versioned {
+        format_version: FORMAT_VERSION,
+        project,
+    })
+    .map_err(|e| FormatError::Invalid(e.to_string()))
+}
+
+/// Decodes a JSON project file of any supported version.
+pub fn decode_json(text: &str) -> Result<Project, FormatError> {
+    let mut value: Value =
+        serde_json::from_str(text).map_err(|e| FormatError::Invalid(e.to_string()))?;
+    let version = match value.as_object_mut().and_then(|o| o.remove(VERSION_FIELD)) {
+        Some(version) => version
+            .as_u64()
+            .and_then(|v| u32::try_from(v).ok())
+            .ok_or_else(|| FormatError::Invalid(format!("bad {}", VERSION_FIELD)))?,
+        None => 0,
+    };
+    check_version(version)?;
+    from_value(migrate(value, version))
+}
+
+/// Builds a project from its current JSON form.
+fn from_value(value: Value) -> Result<Project, FormatError> {
+    let mut project: Project =
+        serde_json::from_value(value).map_err(|e| FormatError::Invalid(e.to_string()))?;
+    project.ensure_valid_id();
+    Ok(project)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The v0 fixtures were written by miditui 0.1.8, before versioning
+    const V0_OXM: &[u8] = include_bytes!("../../tests/fixtures/v0.oxm");
+    const V0_AUTOSAVE_OXM: &[u8] = include_bytes!("../../tests/fixtures/v0_autosave.oxm");
+    const V0_JSON: &str = include_str!("../../tests/fixtures/v0.json");
+    const V1_OXM: &[u8] = include_bytes!("../../tests/fixtures/v1.oxm");
+    const V1_JSON: &str = include_str!("../../tests/fixtures/v1.json");
+    const FUTURE_OXM: &[u8] = include_bytes!("../../tests/fixtures/future.oxm");
+    const FUTURE_JSON: &str = include_str!("../../tests/fixtures/future.json");
+
+    /// Checks the contents shared by every fixture.
+    fn assert_fixture(project: &Project) {
+        assert_eq!(project.name, "Fixture");
+        assert_eq!(project.tempo, 96);
+        assert_eq!(project.time_sig_numerator, 3);
+        assert_eq!(project.track_count(), 2);
+        assert_eq!(project.tracks()[0].name, "Piano");
+        assert_eq!(project.tracks()[0].note_count(), 3);
+        assert_eq!(project.tracks()[1].notes()[0].duration_ticks, 1440);
+        assert!(!project.id().is_empty());
+    }
+
+    #[test]
+    fn test_fixtures_of_every_version_load() {
+        for project in [
+            decode_binary(V0_OXM).unwrap(),
+            decode_binary(V0_AUTOSAVE_OXM).unwrap(),
+            decode_json(V0_JSON).unwrap(),
+            decode_binary(V1_OXM).unwrap(),
+            decode_json(V1_JSON).unwrap(),
+        ] {
+            assert_fixture(&project);
+        }
+        assert_eq!(
+            decode_binary(V1_OXM).unwrap().id(),
+            decode_json(V1_JSON).unwrap().id()
+        );
+        assert_eq!(decode_binary(V1_OXM).unwrap().markers()[0].name, "Verse");
+    }
+
+    #[test]
+    fn test_v0_fields_survive_migration() {
+        let project = decode_binary(V0_OXM).unwrap();
+        assert_eq!(project.soundfont_path, None);
+        assert_eq!(project.tracks()[0].volume, 90);
+        assert!(project.tracks()[1].muted);
+        assert_eq!(project.tracks()[1].program, 33);
+        assert!(project.markers().is_empty());
+
+        let autosave = decode_binary(V0_AUTOSAVE_OXM).unwrap();
+        assert_eq!(
+            autosave.soundfont_path.as_deref(),
+            Some("/usr/share/sounds/sf2/FluidR3_GM.sf2")
+        );
+        assert_eq!(
+            decode_json(V0_JSON).unwrap().tracks()[1].notes()[0].pitch,
+            36
+        );
+    }
+
+    #[test]
+    fn test_newer_versions_are_rejected() {
+        for error in [
+            decode_binary(FUTURE_OXM).unwrap_err(),
+            decode_json(FUTURE_JSON).unwrap_err(),
+        ] {
+            assert!(matches!(error, FormatError::NewerVersion { found: 2 }));
+            assert!(error.to_string().contains("newer"));
+        }
+    }
+
+    #[test]
+    fn test_ids_that_are_not_uuids_are_replaced() {
+        let valid = "86ae3b73-0fbb-4352-a842-5ced0168773a";
+        assert_eq!(decode_json(V1_JSON).unwrap().id(), valid);
+        for id in [
+            "../../x",
+            "",
+            "/tmp/x",
+            "{86ae3b73-0fbb-4352-a842-5ced0168773a}",
+        ] {
+            let text = V1_JSON.replace(valid, id);
+            let unchecked: Project = serde_json::from_str(&text).unwrap();
+            for project in [
+                decode_json(&text).unwrap(),
+                decode_binary(&encode_binary(&unchecked).unwrap()).unwrap(),
+            ] {
+                assert_ne!(project.id(), id);
+                assert!(uuid::Uuid::try_parse(project.id()).is_ok());
+            }
+        }
+    }
+
+    #[test]
+    fn test_round_trip_writes_current_version() {
+        let project = decode_json(V1_JSON).unwrap();
+
+        let data = encode_binary(&project).unwrap();
+        assert!(data.starts_with(&OXM_MAGIC));
+        assert_eq!(data[4..8], FORMAT_VERSION.to_le_bytes());
+        assert_fixture(&decode_binary(&data).unwrap());
+
+        let text = encode_json(&project).unwrap();
+        let value: Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(value[VERSION_FIELD], FORMAT_VERSION);
+        let reloaded = decode_json(&text).unwrap();
+        assert_fixture(&reloaded);
+        assert_eq!(reloaded.id(), project.id());
+    }
+}
@@ -0,0 +1,310 @@
+//! Humanize and groove templates.
+//!
+//! Humanize adds small random timing, velocity and length offsets. The
+//! randomness is seeded so the same seed always gives the same result.
+//!
+//! A groove template stores per-step timing and velocity deviations over a
+//! repeating cycle of grid steps (usually one measure of sixteenths). Templates
+//! come from built-in MPC-style swing presets or are extracted from the notes
+//! of an existing track, and can then be applied to other notes.
+
+use super::note::Note;
+use super::TICKS_PER_BEAT;
+use serde::{Deserialize, Serialize};
+
+/// Small, fast, seedable pseudo-random generator (SplitMix64).
+#[derive(Debug, Clone)]
+pub struct SplitMix64(u64);
+
+impl SplitMix64 {
+    /// Creates a generator from a seed.
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    /// Returns the next 64-bit value.
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a value in `-amount..=amount`.
+    pub fn offset(&mut self, amount: u32) -> i64 {
+        if amount == 0 {
+            return 0;
+        }
+        let span = amount as u64 * 2 + 1;
+        (self.next_u64() % span) as i64 - amount as i64
+    }
+}
+
+/// Settings for humanizing notes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HumanizeSettings {
+    /// Maximum start offset in ticks (either direction).
+    pub timing: u32,
+    /// Maximum velocity change (either direction).
+    pub velocity: u32,
+    /// Maximum length change in ticks (either direction).
+    pub length: u32,
+    /// Seed for reproducible results.
+    pub seed: u64,
+}
+
+impl Default for HumanizeSettings {
+    fn default() -> Self {
+        Self {
+            timing: 10,
+            velocity: 10,
+            length: 0,
+            seed: 1,
+        }
+    }
+}
+
+/// Applies random offsets to notes.
+///
+/// Notes are visited in the given order, so the same notes and seed always
+/// produce the same result.
+///
+/// # Returns
+///
+/// The number of notes processed
+pub fn humanize_notes<'a>(
+    notes: impl IntoIterator<Item = &'a mut Note>,
+    settings: &HumanizeSettings,
+) -> usize {
+    let mut rng = SplitMix64::new(settings.seed);
+    let mut count = 0;
+    for note in notes {
+        let start = note.start_tick as i64 + rng.offset(settings.timing);
+        let velocity = note.velocity as i64 + rng.offset(settings.velocity);
+        let length = note.duration_ticks as i64 + rng.offset(settings.length);
+
+        note.start_tick = start.max(0) as u32;
+        note.velocity = velocity.clamp(1, 127) as u8;
+        note.duration_ticks = length.max(1) as u32;
+        count += 1;
+    }
+    count
+}
+
+/// A repeating pattern of timing and velocity deviations from a grid.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GrooveTemplate {
+    /// Display name.
+    pub name: String,
+    /// Grid step in ticks.
+    pub grid_ticks: u32,
+    /// Start offset in ticks for each step of the cycle.
+    pub timing: Vec<i32>,
+    /// Velocity change for each step of the cycle.
+    pub velocity: Vec<i32>,
+}
+
+impl GrooveTemplate {
+    /// Creates an MPC-style swing template.
+    ///
+    /// Swing is the position of the off-beat note as a percentage of the pair
+    /// length: 50% is straight, 66% is close to a triplet shuffle.
+    ///
+    /// # Arguments
+    ///
+    /// * `grid_ticks` - Length of one step (120 for 16ths, 240 for 8ths)
+    /// * `swing` - Swing percentage (50-75)
+    pub fn swing(grid_ticks: u32, swing: u32) -> Self {
+        let pair = grid_ticks as i64 * 2;
+        let delay = (pair * swing as i64 / 100 - grid_ticks as i64) as i32;
+        let label = if grid_ticks == TICKS_PER_BEAT / 4 {
+            "16"
+        } else {
+            "8"
+        };
+        Self {
+            name: format!("Swing {} {}%", label, swing),
+            grid_ticks,
+            timing: vec![0, delay],
+            velocity: vec![0, 0],
+        }
+    }
+
+    /// Returns the built-in swing presets.
+    pub fn presets() -> Vec<Self> {
+        let sixteenth = TICKS_PER_BEAT / 4;
+        let eighth = TICKS_PER_BEAT / 2;
+        [54, 58, 62, 66, 71]
+            .into_iter()
+            .map(|swing| Self::swing(sixteenth, swing))
+            .chain([58, 66].into_iter().map(|swing| Self::swing(eighth, swing)))
+            .collect()
+    }
+
+    /// Extracts a groove from notes by averaging their deviation from the grid.
+    ///
+    /// Each note is assigned to its nearest grid step within the cycle. Steps
+    /// without notes get no offset. Velocity changes are relative to the
+    /// average velocity of all notes.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Display name for the template
+    /// * `notes` - Source notes (e.g. a played-in drum track)
+    /// * `grid_ticks` - Length of one step
+    /// * `steps` - Number of steps in the cycle (16 for a measure of 16ths)
+    ///
+    /// # Returns
+    ///
+    /// The template, or None if there are no notes
+    pub fn extract(
+        name: impl Into<String>,
+        notes: &[Note],
+        grid_ticks: u32,
+        steps: usize,
+    ) -> Option<Self> {
+        if notes.is_empty() || grid_ticks == 0 || steps == 0 {
+            return None;
+        }
+        let mut timing_sum = vec![0i64; steps];
+        let mut velocity_sum = vec![0i64; steps];
+        let mut counts = vec![0i64; steps];
+
+        for note in notes {
+            let index = (note.start_tick + grid_ticks / 2) / grid_ticks;
+            let step = index as usize % steps;
+            timing_sum[step] += note.start_tick as i64 - (index * grid_ticks) as i64;
+            velocity_sum[step] += note.velocity as i64;
+            counts[step] += 1;
+        }
+
+        let average_velocity =
+            notes.iter().map(|n| n.velocity as i64).sum::<i64>() / notes.len() as i64;
+        let per_step = |sums: &[i64], base: i64| -> Vec<i32> {
+            sums.iter()
+                .zip(&counts)
+                .map(|(&sum, &count)| {
+                    if count == 0 {
+                        0
+                    } else {
+                        (sum / count - base) as i32
+                    }
+                })
+                .collect()
+        };
+
+        Some(Self {
+            name: name.into(),
+            grid_ticks,
+            timing: per_step(&timing_sum, 0),
+            velocity: per_step(&velocity_sum, average_velocity),
+        })
+    }
+
+    /// Applies the groove to a single note.
+    ///
+    /// The note is moved to its nearest grid step plus the step's offset
+    /// (scaled by strength), and its velocity is changed by the step's amount.
+    pub fn apply(&self, note: &mut Note, strength: u32) {
+        let steps = self.timing.len().min(self.velocity.len());
+        if steps == 0 || self.grid_ticks == 0 {
+            return;
+        }
+        let index = (note.start_tick + self.grid_ticks / 2) / self.grid_ticks;
+        let step = index as usize % steps;
+        let target = (index * self.grid_ticks) as i64 + self.timing[step] as i64;
+
+        let start = note.start_tick as i64;
+        note.start_tick = (start + (target - start) * strength as i64 / 100).max(0) as u32;
+        let velocity = note.velocity as i64 + self.velocity[step] as i64 * strength as i64 / 100;
+        note.velocity = velocity.clamp(1, 127) as u8;
+    }
+}
+
+/// Applies a groove template to notes.
+///
+/// # Arguments
+///
+/// * `notes` - Notes to change
+/// * `template` - The groove to apply
+/// * `strength` - How strongly to apply it (0-100%)
+///
+/// # Returns
+///
+/// The number of notes processed
+pub fn apply_groove<'a>(
+    notes: impl IntoIterator<Item = &'a mut Note>,
+    template: &GrooveTemplate,
+    strength: u32,
+) -> usize {
+    notes
+        .into_iter()
+        .map(|note| template.apply(note, strength))
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_humanize_is_reproducible() {
+        let make = || {
+            (0..8)
+                .map(|i| Note::new(60, 100, i * 120, 120))
+                .collect::<Vec<_>>()
+        };
+        let settings = HumanizeSettings::default();
+        let mut a = make();
+        let mut b = make();
+        humanize_notes(a.iter_mut(), &settings);
+        humanize_notes(b.iter_mut(), &settings);
+
+        let values = |notes: &[Note]| {
+            notes
+                .iter()
+                .map(|n| (n.start_tick, n.velocity, n.duration_ticks))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(values(&a), values(&b));
+        assert_ne!(values(&a), values(&make()));
+        for (note, original) in a.iter().zip(make()) {
+            assert!(note.start_tick.abs_diff(original.start_tick) <= settings.timing);
+            assert!(note.velocity.abs_diff(original.velocity) as u32 <= settings.velocity);
+        }
+    }
+
+    #[test]
+    fn test_swing_preset() {
+        let groove = GrooveTemplate::swing(120, 66);
+        assert_eq!(groove.timing, vec![0, 38]);
+
+        let mut on_beat = Note::new(36, 100, 480, 60);
+        let mut off_beat = Note::new(36, 100, 600, 60);
+        groove.apply(&mut on_beat, 100);
+        groove.apply(&mut off_beat, 100);
+        assert_eq!(on_beat.start_tick, 480);
+        assert_eq!(off_beat.start_tick, 638);
+    }
+
+    #[test]
+    fn test_extract_and_apply() {
+        // Played part: every second 8th late and soft
+        let played: Vec<Note> = (0..8)
+            .map(|i| {
+                let late = i % 2 == 1;
+                let start = i * 240 + if late { 30 } else { 0 };
+                Note::new(42, if late { 80 } else { 120 }, start, 120)
+            })
+            .collect();
+        let groove = GrooveTemplate::extract("Played", &played, 240, 2).unwrap();
+        assert_eq!(groove.timing, vec![0, 30]);
+        assert_eq!(groove.velocity, vec![20, -20]);
+
+        let mut target = [Note::new(38, 100, 0, 120), Note::new(38, 100, 240, 120)];
+        apply_groove(target.iter_mut(), &groove, 50);
+        assert_eq!((target[1].start_tick, target[1].velocity), (255, 90));
+        assert_eq!((target[0].start_tick, target[0].velocity), (0, 110));
+    }
+}
@@ -0,0 +1,125 @@
+//! Lyric syllables attached to track positions.
+//!
+//! Lyrics are stored per track as a tick-sorted list of syllables, following
+//! the usual karaoke conventions: a syllable ending in `-` joins the next
+//! syllable without a space, and a syllable flagged with `line_start` begins
+//! a new display line.
+
+use serde::{Deserialize, Serialize};
+
+/// A single lyric syllable anchored to a tick position.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Lyric {
+    /// Tick at which the syllable is sung (usually a note start).
+    pub tick: u32,
+
+    /// Syllable text. A trailing `-` joins it to the following syllable.
+    pub text: String,
+
+    /// Whether this syllable begins a new lyric line.
+    #[serde(default)]
+    pub line_start: bool,
+}
+
+impl Lyric {
+    /// Creates a new lyric syllable.
+    ///
+    /// # Arguments
+    ///
+    /// * `tick` - Position of the syllable in ticks
+    /// * `text` - Syllable text
+    /// * `line_start` - Whether the syllable begins a new line
+    pub fn new(tick: u32, text: impl Into<String>, line_start: bool) -> Self {
+        Self {
+            tick,
+            text: text.into(),
+            line_start,
+        }
+    }
+
+    /// Returns the text as displayed, without the joining hyphen.
+    pub fn display_text(&self) -> &str {
+        self.text.strip_suffix('-').unwrap_or(&self.text)
+    }
+
+    /// Returns true if this syllable is joined to the next one (mid-word).
+    pub fn joins_next(&self) -> bool {
+        self.text.ends_with('-')
+    }
+}
+
+/// The lyric line containing a given tick.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LyricLine {
+    /// Index of the first syllable of the line.
+    pub start: usize,
+    /// Index one past the last syllable of the line.
+    pub end: usize,
+    /// Index of the syllable currently being sung, if the line has started.
+    pub current: Option<usize>,
+}
+
+/// Finds the lyric line to display at a tick position.
+///
+/// Before the first syllable the first line is returned with no current
+/// syllable, so the upcoming words can be shown ahead of time.
+///
+/// # Arguments
+///
+/// * `lyrics` - Tick-sorted syllables
+/// * `tick` - Current playback or cursor position
+///
+/// # Returns
+///
+/// The line to display, or None if there are no lyrics
+pub fn line_at(lyrics: &[Lyric], tick: u32) -> Option<LyricLine> {
+    if lyrics.is_empty() {
+        return None;
+    }
+
+    // Index of the last syllable at or before the tick
+    let current = lyrics.partition_point(|l| l.tick <= tick).checked_sub(1);
+    let anchor = current.unwrap_or(0);
+
+    let start = (1..=anchor)
+        .rev()
+        .find(|&i| lyrics[i].line_start)
+        .unwrap_or(0);
+    let end = (anchor + 1..lyrics.len())
+        .find(|&i| lyrics[i].line_start)
+        .unwrap_or(lyrics.len());
+
+    Some(LyricLine {
+        start,
+        end,
+        current,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Vec<Lyric> {
+        vec![
+            Lyric::new(0, "Hel-", true),
+            Lyric::new(480, "lo", false),
+            Lyric::new(960, "world", false),
+            Lyric::new(1920, "Good-", true),
+            Lyric::new(2400, "bye", false),
+        ]
+    }
+
+    #[test]
+    fn test_line_at() {
+        let lyrics = sample();
+
+        let line = line_at(&lyrics, 500).unwrap();
+        assert_eq!((line.start, line.end, line.current), (0, 3, Some(1)));
+
+        let line = line_at(&lyrics, 2000).unwrap();
+        assert_eq!((line.start, line.end, line.current), (3, 5, Some(3)));
+
+        assert!(line_at(&[], 0).is_none());
+    }
+}
@@ -0,0 +1,40 @@
+//! Named timeline markers.
+//!
+//! Markers label positions in the song ("Verse", "Chorus", ...). Each marker
+//! starts a section that runs until the next marker (or the end of the song).
+
+use serde::{Deserialize, Serialize};
+
+/// Number of distinct marker colors (indices into the UI palette).
+pub const MARKER_COLOR_COUNT: u8 = 6;
+
+/// A named position on the project timeline.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Marker {
+    /// Position of the marker in ticks.
+    pub tick: u32,
+
+    /// Display name (e.g. "Chorus").
+    pub name: String,
+
+    /// Color index (0 to MARKER_COLOR_COUNT - 1).
+    #[serde(default)]
+    pub color: u8,
+}
+
+impl Marker {
+    /// Creates a new marker.
+    ///
+    /// # Arguments
+    ///
+    /// * `tick` - Position in ticks
+    /// * `name` - Display name
+    /// * `color` - Color index (wrapped into the palette range)
+    pub fn new(tick: u32, name: impl Into<String>, color: u8) -> Self {
+        Self {
+            tick,
+            name: name.into(),
+            color: color % MARKER_COLOR_COUNT,
+        }
+    }
+}
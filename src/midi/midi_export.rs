@@ -13,8 +13,8 @@
 //! # Format Details
 //!
 //! Exports as SMF Format 1 (multi-track) with:
-//! - Track 0: Tempo and time signature meta events
-//! - Tracks 1-N: MIDI note data with program changes
+//! - Track 0: Tempo, time signature, key signature and marker meta events
+//! - Tracks 1-N: MIDI note data with program changes and lyric meta events
 
 use super::{Project, TICKS_PER_BEAT};
 use std::fs::File;
@@ -85,8 +85,14 @@ enum MidiEvent {
         numerator: u8,
         denominator_power: u8,
     },
+    /// Key signature: sharps (positive) or flats (negative), minor flag
+    KeySignature { sharps: i8, minor: bool },
     /// Track name (meta event)
     TrackName { name: String },
+    /// Lyric syllable (meta event)
+    Lyric { text: String },
+    /// Marker / section name (meta event)
+    Marker { name: String },
     /// End of track (meta event)
     EndOfTrack,
 }
@@ -173,6 +179,14 @@ fn write_event(event: &MidiEvent, buffer: &mut Vec<u8>) {
             buffer.push(24); // Clocks per click
             buffer.push(8); // 32nd notes per quarter
         }
+        MidiEvent::KeySignature { sharps, minor } => {
+            // Meta event: FF 59 02 sf mi
+            buffer.push(0xFF);
+            buffer.push(0x59);
+            buffer.push(0x02);
+            buffer.push(*sharps as u8);
+            buffer.push(*minor as u8);
+        }
         MidiEvent::TrackName { name } => {
             // Meta event: FF 03 len text
             buffer.push(0xFF);
@@ -181,6 +195,22 @@ fn write_event(event: &MidiEvent, buffer: &mut Vec<u8>) {
             write_vlq(name_bytes.len() as u32, buffer);
             buffer.extend_from_slice(name_bytes);
         }
+        MidiEvent::Lyric { text } => {
+            // Meta event: FF 05 len text
+            buffer.push(0xFF);
+            buffer.push(0x05);
+            let text_bytes = text.as_bytes();
+            write_vlq(text_bytes.len() as u32, buffer);
+            buffer.extend_from_slice(text_bytes);
+        }
+        MidiEvent::Marker { name } => {
+            // Meta event: FF 06 len text
+            buffer.push(0xFF);
+            buffer.push(0x06);
+            let name_bytes = name.as_bytes();
+            write_vlq(name_bytes.len() as u32, buffer);
+            buffer.extend_from_slice(name_bytes);
+        }
         MidiEvent::EndOfTrack => {
             // Meta event: FF 2F 00
             buffer.push(0xFF);
@@ -304,9 +334,30 @@ pub fn export_to_midi<P: AsRef<Path>>(project: &Project, path: P) -> std::io::Re
             2,
         ));
 
+        // Key signature at tick 0 (only major and minor keys have one)
+        if let Some((sharps, minor)) = project.scale.and_then(|s| s.key_signature()) {
+            events.push(TimedEvent::new(
+                0,
+                MidiEvent::KeySignature { sharps, minor },
+                2,
+            ));
+        }
+
+        // Section markers
+        for marker in project.markers() {
+            events.push(TimedEvent::new(
+                marker.tick,
+                MidiEvent::Marker {
+                    name: marker.name.clone(),
+                },
+                3,
+            ));
+        }
+
         // End of track
+        let last_marker_tick = project.markers().last().map(|m| m.tick).unwrap_or(0);
         events.push(TimedEvent::new(
-            project.duration_ticks(),
+            project.duration_ticks().max(last_marker_tick),
             MidiEvent::EndOfTrack,
             255,
         ));
@@ -360,14 +411,19 @@ pub fn export_to_midi<P: AsRef<Path>>(project: &Project, path: P) -> std::io::Re
             3,
         ));
 
-        // Note events
-        for note in track.notes() {
+        // Note events (loose notes plus clip instances), retargeted by the drum map
+        for note in project.playback_notes(track) {
+            let pitch = track
+                .drum_map
+                .as_ref()
+                .map_or(note.pitch, |map| map.output_pitch(note.pitch));
+
             // Note on
             events.push(TimedEvent::new(
                 note.start_tick,
                 MidiEvent::NoteOn {
                     channel: track.channel,
-                    pitch: note.pitch,
+                    pitch,
                     velocity: note.velocity,
                 },
                 10, // Notes after setup events
@@ -378,15 +434,27 @@ pub fn export_to_midi<P: AsRef<Path>>(project: &Project, path: P) -> std::io::Re
                 note.end_tick(),
                 MidiEvent::NoteOff {
                     channel: track.channel,
-                    pitch: note.pitch,
+                    pitch,
                     velocity: 0,
                 },
                 11, // Note offs slightly after note ons at same tick
             ));
         }
 
+        // Lyrics, before any note starting at the same tick.
+        // A carriage return ends the syllable preceding a new line.
+        let lyrics = track.lyrics();
+        for (i, lyric) in lyrics.iter().enumerate() {
+            let mut text = lyric.text.clone();
+            if lyrics.get(i + 1).is_some_and(|next| next.line_start) {
+                text.push('\r');
+            }
+            events.push(TimedEvent::new(lyric.tick, MidiEvent::Lyric { text }, 9));
+        }
+
         // End of track (at the end of all notes or duration)
-        let track_end = track.duration_ticks().max(1);
+        let last_lyric_tick = lyrics.last().map(|l| l.tick).unwrap_or(0);
+        let track_end = project.track_end_tick(track).max(last_lyric_tick).max(1);
         events.push(TimedEvent::new(track_end, MidiEvent::EndOfTrack, 255));
 
         let track_data = build_track_data(&mut events);
@@ -436,4 +504,93 @@ mod tests {
         assert_eq!(denominator_to_power(2), 1);
         assert_eq!(denominator_to_power(16), 4);
     }
+
+    #[test]
+    fn test_lyrics_round_trip() {
+        use crate::midi::{import_from_midi, Lyric};
+
+        let mut project = Project::with_default_track("Lyrics");
+        let track = project.track_at_mut(0).unwrap();
+        track.create_note(60, 100, 0, 480);
+        track.create_note(62, 100, 480, 480);
+        track.create_note(64, 100, 960, 480);
+        track.set_lyric(Lyric::new(0, "Hel-", true));
+        track.set_lyric(Lyric::new(480, "lo", false));
+        track.set_lyric(Lyric::new(960, "again", true));
+
+        let path = std::env::temp_dir().join(format!("miditui_lyrics_{}.mid", std::process::id()));
+        export_to_midi(&project, &path).unwrap();
+        let imported = import_from_midi(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        let lyrics = imported.track_at(0).unwrap().lyrics();
+        assert_eq!(lyrics, project.track_at(0).unwrap().lyrics());
+    }
+
+    #[test]
+    fn test_markers_round_trip() {
+        use crate::midi::{import_from_midi, Marker};
+
+        let mut project = Project::with_default_track("Markers");
+        project
+            .track_at_mut(0)
+            .unwrap()
+            .create_note(60, 100, 0, 480);
+        project.add_marker(Marker::new(0, "Verse", 0));
+        project.add_marker(Marker::new(3840, "Chorus", 1));
+
+        let path = std::env::temp_dir().join(format!("miditui_markers_{}.mid", std::process::id()));
+        export_to_midi(&project, &path).unwrap();
+        let imported = import_from_midi(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        let markers: Vec<_> = imported
+            .markers()
+            .iter()
+            .map(|m| (m.tick, m.name.as_str()))
+            .collect();
+        assert_eq!(markers, vec![(0, "Verse"), (3840, "Chorus")]);
+    }
+
+    #[test]
+    fn test_key_signature_round_trip() {
+        use crate::midi::{import_from_midi, Scale};
+
+        let mut project = Project::with_default_track("Key");
+        project
+            .track_at_mut(0)
+            .unwrap()
+            .create_note(62, 100, 0, 480);
+        project.scale = Some(Scale::from_key_signature(-3, true));
+
+        let path = std::env::temp_dir().join(format!("miditui_key_{}.mid", std::process::id()));
+        export_to_midi(&project, &path).unwrap();
+        let imported = import_from_midi(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(imported.scale, project.scale);
+    }
+
+    #[test]
+    fn test_drum_map_remaps_export() {
+        use crate::midi::{import_from_midi, DrumMap};
+
+        let mut project = Project::new("Drums");
+        project.create_drum_track("Kit");
+        let track = project.track_at_mut(0).unwrap();
+        track.create_note(36, 100, 0, 120);
+        track.create_note(38, 100, 480, 120);
+        let mut map = DrumMap::general_midi();
+        map.entries[1].output = Some(24); // Bass Drum 1 -> kit's kick
+        track.drum_map = Some(map);
+
+        let path = std::env::temp_dir().join(format!("miditui_drums_{}.mid", std::process::id()));
+        export_to_midi(&project, &path).unwrap();
+        let imported = import_from_midi(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        let track = imported.tracks().iter().find(|t| t.is_drum()).unwrap();
+        let pitches: Vec<u8> = track.notes().iter().map(|n| n.pitch).collect();
+        assert_eq!(pitches, vec![24, 38]);
+    }
 }
@@ -9,9 +9,12 @@
 //! - Tempo and time signature are read from the first track (or global events)
 //! - Program changes set the track instrument
 //! - Volume (CC7) and Pan (CC10) are imported
+//! - Lyric meta events (and text events in `.kar` karaoke files) become track lyrics
+//! - Marker meta events become project markers
+//! - The first key signature meta event sets the project key
 //! - Other MIDI events (pitch bend, aftertouch, etc.) are ignored
 
-use super::{Note, Project, Track, TICKS_PER_BEAT};
+use super::{Lyric, Marker, Note, Project, Scale, Track, TICKS_PER_BEAT};
 use midly::{Format, Smf, Timing, TrackEventKind};
 use std::collections::HashMap;
 use std::fs;
@@ -50,9 +53,90 @@ impl From<std::io::Error> for MidiImportError {
 /// Key is (channel, pitch), value is (start_tick, velocity).
 type ActiveNotes = HashMap<(u8, u8), (u32, u8)>;
 
+/// Data parsed from a single MIDI track chunk.
+struct ParsedTrack {
+    /// Tracks split by channel.
+    tracks: Vec<Track>,
+    /// Tempo in BPM, if a tempo event was found.
+    tempo: Option<u32>,
+    /// Time signature (numerator, denominator), if found.
+    time_sig: Option<(u8, u8)>,
+    /// Key signature (sharps/flats, is_minor), if found.
+    key_sig: Option<(i8, bool)>,
+    /// Lyric syllables found in the chunk.
+    lyrics: Vec<Lyric>,
+    /// Markers found in the chunk (tick, name).
+    markers: Vec<(u32, String)>,
+}
+
 /// Result type for parsing a single MIDI track.
-/// Contains: (Vec of Tracks split by channel, optional tempo, optional time signature).
-type ParseTrackResult = Result<(Vec<Track>, Option<u32>, Option<(u8, u8)>), MidiImportError>;
+type ParseTrackResult = Result<ParsedTrack, MidiImportError>;
+
+/// Accumulates lyric syllables while parsing a track.
+///
+/// Lyric meta events use the common `Hel-` / `lo` hyphenation style with
+/// carriage returns ending a line. Karaoke (`.kar`) text events instead mark
+/// new words with a leading space and new lines with a leading `/` or `\`;
+/// these are normalized to the hyphenation style on import.
+#[derive(Default)]
+struct LyricCollector {
+    lyrics: Vec<Lyric>,
+    pending_line_start: bool,
+}
+
+impl LyricCollector {
+    /// Adds a syllable from a Lyric meta event.
+    fn push_lyric(&mut self, tick: u32, raw: &str) {
+        let mut line_start = self.pending_line_start || self.lyrics.is_empty();
+        let mut text = raw;
+
+        if let Some(rest) = text.strip_prefix(['\r', '\n', '/', '\\']) {
+            line_start = true;
+            text = rest;
+        }
+        self.pending_line_start = text.ends_with(['\r', '\n']);
+        let text = text.trim();
+
+        if !text.is_empty() {
+            self.lyrics.push(Lyric::new(tick, text, line_start));
+        } else if line_start {
+            self.pending_line_start = true;
+        }
+    }
+
+    /// Adds a syllable from a `.kar` text event.
+    fn push_karaoke_text(&mut self, tick: u32, raw: &str) {
+        // "@" events are karaoke header fields (title, language, ...)
+        if raw.starts_with('@') {
+            return;
+        }
+
+        let mut line_start = self.pending_line_start || self.lyrics.is_empty();
+        let mut text = raw;
+        if let Some(rest) = text.strip_prefix(['/', '\\']) {
+            line_start = true;
+            text = rest;
+        }
+        self.pending_line_start = false;
+
+        let new_word = line_start || text.starts_with(' ');
+        let text = text.trim();
+        if text.is_empty() {
+            self.pending_line_start = line_start;
+            return;
+        }
+
+        // A syllable without a leading space continues the previous word
+        if !new_word {
+            if let Some(prev) = self.lyrics.last_mut() {
+                if !prev.joins_next() {
+                    prev.text.push('-');
+                }
+            }
+        }
+        self.lyrics.push(Lyric::new(tick, text, line_start));
+    }
+}
 
 /// Imports a MIDI file and creates a Project.
 ///
@@ -71,6 +155,12 @@ pub fn import_from_midi<P: AsRef<Path>>(path: P) -> Result<Project, MidiImportEr
     let path = path.as_ref();
     let data = fs::read(path)?;
 
+    // Karaoke files carry their lyrics in plain text events
+    let is_kar = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e.eq_ignore_ascii_case("kar"));
+
     let smf = Smf::parse(&data).map_err(|e| MidiImportError::ParseError(e.to_string()))?;
 
     // Get ticks per beat from header
@@ -108,6 +198,9 @@ pub fn import_from_midi<P: AsRef<Path>>(path: P) -> Result<Project, MidiImportEr
     let mut time_sig_num: u8 = 4;
     let mut time_sig_denom: u8 = 4;
 
+    // Lyrics from chunks without notes (e.g. a .kar "Words" track)
+    let mut orphan_lyrics: Vec<Lyric> = Vec::new();
+
     // Process tracks based on format
     match smf.header.format {
         Format::SingleTrack | Format::Parallel => {
@@ -120,17 +213,45 @@ pub fn import_from_midi<P: AsRef<Path>>(path: P) -> Result<Project, MidiImportEr
                 let is_tempo_track = is_format_1 && track_idx == 0;
 
                 // Parse the track
-                let (track_data, track_tempo, track_time_sig) =
-                    parse_track(track, track_idx, source_ticks_per_beat, is_tempo_track)?;
+                let parsed = parse_track(
+                    track,
+                    track_idx,
+                    source_ticks_per_beat,
+                    is_tempo_track,
+                    is_kar,
+                )?;
+                let mut track_data = parsed.tracks;
+
+                for (tick, name) in parsed.markers {
+                    let color = project.markers().len() as u8;
+                    project.add_marker(Marker::new(tick, name, color));
+                }
 
                 // Update global tempo/time sig from tempo track or first occurrence
-                if let Some(t) = track_tempo {
+                if let Some(t) = parsed.tempo {
                     tempo = t;
                 }
-                if let Some((num, denom)) = track_time_sig {
+                if let Some((num, denom)) = parsed.time_sig {
                     time_sig_num = num;
                     time_sig_denom = denom;
                 }
+                if let Some((sharps, minor)) = parsed.key_sig {
+                    if project.scale.is_none() {
+                        project.scale = Some(Scale::from_key_signature(sharps, minor));
+                    }
+                }
+
+                // Attach lyrics to the first track of the chunk that has notes
+                if !parsed.lyrics.is_empty() {
+                    match track_data.iter_mut().find(|t| !t.notes().is_empty()) {
+                        Some(target) => {
+                            for lyric in parsed.lyrics {
+                                target.set_lyric(lyric);
+                            }
+                        }
+                        None => orphan_lyrics.extend(parsed.lyrics),
+                    }
+                }
 
                 if !is_tempo_track || !track_data.is_empty() {
                     for imported_track in track_data {
@@ -155,6 +276,20 @@ pub fn import_from_midi<P: AsRef<Path>>(path: P) -> Result<Project, MidiImportEr
         project.add_track(Track::new("Track 1", 0));
     }
 
+    // Lyrics stored in their own chunk go to the first melodic track
+    if !orphan_lyrics.is_empty() {
+        let target_idx = project
+            .tracks()
+            .iter()
+            .position(|t| t.channel != 9 && !t.has_lyrics())
+            .unwrap_or(0);
+        if let Some(target) = project.track_at_mut(target_idx) {
+            for lyric in orphan_lyrics {
+                target.set_lyric(lyric);
+            }
+        }
+    }
+
     Ok(project)
 }
 
@@ -164,13 +299,17 @@ fn parse_track(
     track_idx: usize,
     source_ticks_per_beat: u32,
     is_tempo_track: bool,
+    is_kar: bool,
 ) -> ParseTrackResult {
     // Track state per channel
     let mut channel_tracks: HashMap<u8, Track> = HashMap::new();
     let mut active_notes: ActiveNotes = HashMap::new();
     let mut tempo: Option<u32> = None;
     let mut time_sig: Option<(u8, u8)> = None;
+    let mut key_sig: Option<(i8, bool)> = None;
     let mut track_name: Option<String> = None;
+    let mut lyrics = LyricCollector::default();
+    let mut markers: Vec<(u32, String)> = Vec::new();
 
     // Current absolute tick position
     let mut current_tick: u32 = 0;
@@ -191,8 +330,8 @@ fn parse_track(
                     midly::MetaMessage::Tempo(tempo_val) => {
                         // tempo_val is microseconds per beat
                         let usec_per_beat = tempo_val.as_int();
-                        if usec_per_beat > 0 {
-                            tempo = Some(60_000_000 / usec_per_beat);
+                        if let Some(bpm) = 60_000_000u32.checked_div(usec_per_beat) {
+                            tempo = Some(bpm);
                         }
                     }
                     midly::MetaMessage::TimeSignature(num, denom_power, _, _) => {
@@ -200,6 +339,20 @@ fn parse_track(
                         let denom = 1u8 << denom_power;
                         time_sig = Some((num, denom));
                     }
+                    midly::MetaMessage::KeySignature(sharps, minor) => {
+                        key_sig.get_or_insert((sharps, minor));
+                    }
+                    midly::MetaMessage::Marker(text_bytes) => {
+                        let name = String::from_utf8_lossy(text_bytes).trim().to_string();
+                        markers.push((current_tick, name));
+                    }
+                    midly::MetaMessage::Lyric(text_bytes) => {
+                        lyrics.push_lyric(current_tick, &String::from_utf8_lossy(text_bytes));
+                    }
+                    midly::MetaMessage::Text(text_bytes) if is_kar => {
+                        lyrics
+                            .push_karaoke_text(current_tick, &String::from_utf8_lossy(text_bytes));
+                    }
                     _ => {} // Ignore other meta events
                 }
             }
@@ -288,7 +441,14 @@ fn parse_track(
         tracks.clear();
     }
 
-    Ok((tracks, tempo, time_sig))
+    Ok(ParsedTrack {
+        tracks,
+        tempo,
+        time_sig,
+        key_sig,
+        lyrics: lyrics.lyrics,
+        markers,
+    })
 }
 
 /// Scales ticks from source resolution to our internal resolution (TICKS_PER_BEAT).
@@ -320,4 +480,37 @@ mod tests {
         // Different resolution
         assert_eq!(scale_ticks(120, 120), 480);
     }
+
+    #[test]
+    fn test_lyric_meta_line_breaks() {
+        let mut collector = LyricCollector::default();
+        collector.push_lyric(0, "Hel-");
+        collector.push_lyric(480, "lo\r");
+        collector.push_lyric(960, "world");
+
+        let lyrics = collector.lyrics;
+        assert_eq!(lyrics.len(), 3);
+        assert!(lyrics[0].line_start);
+        assert!(!lyrics[1].line_start);
+        assert_eq!(lyrics[1].text, "lo");
+        assert!(lyrics[2].line_start);
+    }
+
+    #[test]
+    fn test_karaoke_text_normalized() {
+        let mut collector = LyricCollector::default();
+        collector.push_karaoke_text(0, "@TSong title");
+        collector.push_karaoke_text(0, "/Hel");
+        collector.push_karaoke_text(240, "lo");
+        collector.push_karaoke_text(480, " world");
+        collector.push_karaoke_text(960, "\\Bye");
+
+        let lyrics = collector.lyrics;
+        assert_eq!(lyrics.len(), 4);
+        assert_eq!(lyrics[0].text, "Hel-");
+        assert_eq!(lyrics[1].text, "lo");
+        assert_eq!(lyrics[2].text, "world");
+        assert!(!lyrics[2].line_start);
+        assert!(lyrics[3].line_start);
+    }
 }
@@ -3,19 +3,45 @@
 //! This module provides the core types for representing MIDI notes, tracks,
 //! and projects. The design supports unlimited tracks with efficient memory usage.
 
+mod chord;
+mod clip;
+mod drums;
+mod effects;
+mod event_list;
+mod format;
+mod groove;
+mod lyrics;
+mod marker;
 mod midi_export;
 mod midi_import;
+mod notation;
 mod note;
 mod project;
+mod quantize;
+mod scale;
 mod track;
 
+pub use chord::{AccompanimentStyle, Chord, ChordQuality, ChordVoicing};
+pub use drums::{drum_short_name, DrumMap, DrumMapEntry};
+pub use effects::{ArpSettings, EchoSettings, NoteEffect};
+pub use event_list::{EventColumn, EventFilter, EventSort, TimeFormat};
+pub use format::FORMAT_VERSION;
+pub use groove::{GrooveTemplate, HumanizeSettings};
+pub use lyrics::{line_at as lyric_line_at, Lyric};
+pub use marker::Marker;
 pub use midi_export::export_to_midi;
 pub use midi_import::import_from_midi;
 // MidiImportError is available for external error handling if needed
 #[allow(unused_imports)]
 pub use midi_import::MidiImportError;
+pub use notation::{
+    Accidental, Beam, Clef, Meter, NoteValue, Score, StaffEvent, FLAT_ORDER, SHARP_ORDER,
+    SLOT_TICKS,
+};
 pub use note::{Note, NoteId};
 pub use project::Project;
+pub use quantize::{GridFeel, QuantizeSettings, GRID_DIVISIONS};
+pub use scale::{Scale, ScaleKind};
 #[allow(unused_imports)]
 pub use track::{Track, TrackId};
 
@@ -58,7 +84,6 @@ pub fn note_to_name(note: u8) -> String {
 /// # Returns
 ///
 /// MIDI note number (0-127) or None if invalid
-#[allow(dead_code)]
 pub fn name_to_note(name: &str) -> Option<u8> {
     let name = name.trim();
     if name.is_empty() {
@@ -0,0 +1,692 @@
+//! Staff notation model for the score view.
+//!
+//! Notes are quantized to a sixteenth grid and turned into measures of
+//! staff events: chords and rests with drawable durations, ties where a note
+//! crosses a barline or needs more than one note value, accidentals relative
+//! to the key signature and beam groups for eighths and sixteenths.
+
+use super::note::Note;
+use super::TICKS_PER_BEAT;
+use std::collections::{BTreeMap, HashMap};
+use std::ops::Range;
+
+/// Length of one notation slot (a sixteenth note) in ticks.
+pub const SLOT_TICKS: u32 = TICKS_PER_BEAT / 4;
+
+/// Lowest pitch drawn on the treble staff of a grand staff (middle C).
+pub const GRAND_STAFF_SPLIT: u8 = 60;
+
+/// Letter (0 = C .. 6 = B) and alteration of each pitch class, spelled with sharps.
+const SHARP_SPELLING: [(i32, i8); 12] = [
+    (0, 0),
+    (0, 1),
+    (1, 0),
+    (1, 1),
+    (2, 0),
+    (3, 0),
+    (3, 1),
+    (4, 0),
+    (4, 1),
+    (5, 0),
+    (5, 1),
+    (6, 0),
+];
+
+/// Letter and alteration of each pitch class, spelled with flats.
+const FLAT_SPELLING: [(i32, i8); 12] = [
+    (0, 0),
+    (1, -1),
+    (1, 0),
+    (2, -1),
+    (2, 0),
+    (3, 0),
+    (4, -1),
+    (4, 0),
+    (5, -1),
+    (5, 0),
+    (6, -1),
+    (6, 0),
+];
+
+/// Letters sharpened by a key signature, in order (F C G D A E B).
+pub const SHARP_ORDER: [i32; 7] = [3, 0, 4, 1, 5, 2, 6];
+
+/// Letters flattened by a key signature, in order (B E A D G C F).
+pub const FLAT_ORDER: [i32; 7] = [6, 2, 5, 1, 4, 0, 3];
+
+/// A staff clef.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Clef {
+    /// G clef, bottom line E4.
+    Treble,
+    /// F clef, bottom line G2.
+    Bass,
+}
+
+impl Clef {
+    /// Returns the diatonic index (octave * 7 + letter) of the bottom staff line.
+    pub fn bottom_line(self) -> i32 {
+        match self {
+            Clef::Treble => 4 * 7 + 2,
+            Clef::Bass => 2 * 7 + 4,
+        }
+    }
+
+    /// Returns the staff step of a key signature accidental for a letter.
+    ///
+    /// Steps count lines and spaces up from the bottom line (0) to the top
+    /// line (8), placed where key signatures are usually written.
+    pub fn key_step(self, letter: i32, sharp: bool) -> i32 {
+        // Treble positions; sharps sit higher than flats for F and G
+        let treble = match (letter, sharp) {
+            (3, true) => 8,  // F5
+            (3, false) => 1, // F4
+            (0, _) => 5,     // C5
+            (4, true) => 9,  // G5
+            (4, false) => 2, // G4
+            (1, _) => 6,     // D5
+            (5, _) => 3,     // A4
+            (2, _) => 7,     // E5
+            _ => 4,          // B4
+        };
+        match self {
+            Clef::Treble => treble,
+            Clef::Bass => treble - 2,
+        }
+    }
+
+    /// Returns the clef for a single staff holding the given pitches.
+    pub fn for_pitches(pitches: impl Iterator<Item = u8>) -> Self {
+        let (sum, count) = pitches.fold((0u32, 0u32), |(s, c), p| (s + p as u32, c + 1));
+        if count > 0 && sum / count < GRAND_STAFF_SPLIT as u32 {
+            Clef::Bass
+        } else {
+            Clef::Treble
+        }
+    }
+}
+
+/// A time signature as used for notation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Meter {
+    /// Beats per measure.
+    pub numerator: u8,
+    /// Beat unit (4 = quarter, 8 = eighth).
+    pub denominator: u8,
+}
+
+impl Meter {
+    /// Returns the number of sixteenth slots in a measure (at least 1).
+    pub fn slots_per_measure(&self) -> u32 {
+        let beat_ticks = TICKS_PER_BEAT * 4 / self.denominator.max(1) as u32;
+        (beat_ticks * self.numerator as u32 / SLOT_TICKS).max(1)
+    }
+
+    /// Returns the length of a beam group in slots.
+    ///
+    /// Compound meters (6/8, 9/8, 12/8) beam by dotted quarters, everything
+    /// else by quarters.
+    pub fn beam_slots(&self) -> u32 {
+        if self.denominator == 8 && self.numerator.is_multiple_of(3) && self.numerator > 3 {
+            6
+        } else {
+            4
+        }
+    }
+}
+
+/// A drawable note or rest value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NoteValue {
+    Whole,
+    Half,
+    Quarter,
+    Eighth,
+    Sixteenth,
+}
+
+impl NoteValue {
+    /// Returns the length in sixteenth slots.
+    pub fn slots(self) -> u32 {
+        match self {
+            NoteValue::Whole => 16,
+            NoteValue::Half => 8,
+            NoteValue::Quarter => 4,
+            NoteValue::Eighth => 2,
+            NoteValue::Sixteenth => 1,
+        }
+    }
+
+    /// Returns true for values drawn with flags or beams.
+    pub fn is_beamable(self) -> bool {
+        matches!(self, NoteValue::Eighth | NoteValue::Sixteenth)
+    }
+}
+
+/// A note value, possibly dotted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NoteDuration {
+    /// Base value.
+    pub value: NoteValue,
+    /// Whether the value is dotted (one and a half times as long).
+    pub dotted: bool,
+}
+
+impl NoteDuration {
+    /// Durations tried when splitting a length, longest first.
+    const CANDIDATES: [NoteDuration; 9] = [
+        NoteDuration::dotted(NoteValue::Whole),
+        NoteDuration::plain(NoteValue::Whole),
+        NoteDuration::dotted(NoteValue::Half),
+        NoteDuration::plain(NoteValue::Half),
+        NoteDuration::dotted(NoteValue::Quarter),
+        NoteDuration::plain(NoteValue::Quarter),
+        NoteDuration::dotted(NoteValue::Eighth),
+        NoteDuration::plain(NoteValue::Eighth),
+        NoteDuration::plain(NoteValue::Sixteenth),
+    ];
+
+    const fn plain(value: NoteValue) -> Self {
+        Self {
+            value,
+            dotted: false,
+        }
+    }
+
+    const fn dotted(value: NoteValue) -> Self {
+        Self {
+            value,
+            dotted: true,
+        }
+    }
+
+    /// Returns the length in sixteenth slots.
+    pub fn slots(self) -> u32 {
+        let base = self.value.slots();
+        if self.dotted {
+            base * 3 / 2
+        } else {
+            base
+        }
+    }
+
+    /// Splits a length in slots into drawable durations, longest first.
+    pub fn split(mut slots: u32) -> Vec<NoteDuration> {
+        let mut durations = Vec::new();
+        while slots > 0 {
+            let duration = Self::CANDIDATES
+                .into_iter()
+                .find(|d| d.slots() <= slots)
+                .unwrap_or(Self::plain(NoteValue::Sixteenth));
+            slots -= duration.slots();
+            durations.push(duration);
+        }
+        durations
+    }
+}
+
+/// An accidental drawn before a notehead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Accidental {
+    Sharp,
+    Flat,
+    Natural,
+}
+
+impl Accidental {
+    /// Returns the accidental for an alteration in semitones.
+    fn from_alteration(alteration: i8) -> Self {
+        match alteration {
+            1.. => Accidental::Sharp,
+            0 => Accidental::Natural,
+            _ => Accidental::Flat,
+        }
+    }
+
+    /// Returns the display glyph.
+    pub fn glyph(self) -> char {
+        match self {
+            Accidental::Sharp => '♯',
+            Accidental::Flat => '♭',
+            Accidental::Natural => '♮',
+        }
+    }
+}
+
+/// Position of an event within a beam group.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Beam {
+    /// Not beamed (flagged if shorter than a quarter).
+    #[default]
+    None,
+    /// First note of a group.
+    Start,
+    /// Inner note of a group.
+    Continue,
+    /// Last note of a group.
+    End,
+}
+
+/// One notehead of a chord.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NoteHead {
+    /// Staff step (0 = bottom line, 8 = top line, may lie outside the staff).
+    pub step: i32,
+    /// Accidental drawn before the head, if any.
+    pub accidental: Option<Accidental>,
+}
+
+/// A chord or rest within a measure.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StaffEvent {
+    /// Slot within the measure where the event starts.
+    pub slot: u32,
+    /// Drawn duration.
+    pub duration: NoteDuration,
+    /// Noteheads from low to high (empty for a rest).
+    pub heads: Vec<NoteHead>,
+    /// Whether the notes are tied to the next event.
+    pub tied: bool,
+    /// Position within a beam group.
+    pub beam: Beam,
+}
+
+impl StaffEvent {
+    /// Returns true if the event is a rest.
+    pub fn is_rest(&self) -> bool {
+        self.heads.is_empty()
+    }
+}
+
+/// One staff of a score.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Staff {
+    /// Clef of the staff.
+    pub clef: Clef,
+    /// Events of each notated measure.
+    pub measures: Vec<Vec<StaffEvent>>,
+}
+
+/// Notation of a track over a range of measures.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Score {
+    /// Key signature (sharps positive, flats negative).
+    pub key_sharps: i8,
+    /// Time signature.
+    pub meter: Meter,
+    /// First notated measure (0-based).
+    pub first_measure: u32,
+    /// One staff, or treble and bass for a grand staff.
+    pub staves: Vec<Staff>,
+}
+
+impl Score {
+    /// Notates notes over a range of measures.
+    ///
+    /// # Arguments
+    ///
+    /// * `notes` - Notes of the track
+    /// * `meter` - Time signature
+    /// * `key_sharps` - Key signature (sharps positive, flats negative)
+    /// * `grand_staff` - Split notes at middle C over a treble and a bass staff
+    /// * `measures` - Measures to notate (0-based)
+    pub fn new(
+        notes: &[Note],
+        meter: Meter,
+        key_sharps: i8,
+        grand_staff: bool,
+        measures: Range<u32>,
+    ) -> Self {
+        let staves = if grand_staff {
+            let (treble, bass): (Vec<&Note>, Vec<&Note>) =
+                notes.iter().partition(|n| n.pitch >= GRAND_STAFF_SPLIT);
+            vec![
+                notate_staff(&treble, Clef::Treble, meter, key_sharps, measures.clone()),
+                notate_staff(&bass, Clef::Bass, meter, key_sharps, measures.clone()),
+            ]
+        } else {
+            let notes: Vec<&Note> = notes.iter().collect();
+            let clef = Clef::for_pitches(notes.iter().map(|n| n.pitch));
+            vec![notate_staff(
+                &notes,
+                clef,
+                meter,
+                key_sharps,
+                measures.clone(),
+            )]
+        };
+        Self {
+            key_sharps,
+            meter,
+            first_measure: measures.start,
+            staves,
+        }
+    }
+}
+
+/// Returns the diatonic index and alteration of a pitch, spelled for the key.
+fn spell(pitch: u8, key_sharps: i8) -> (i32, i8) {
+    let table = if key_sharps < 0 {
+        &FLAT_SPELLING
+    } else {
+        &SHARP_SPELLING
+    };
+    let (letter, alteration) = table[pitch as usize % 12];
+    let octave = pitch as i32 / 12 - 1;
+    (octave * 7 + letter, alteration)
+}
+
+/// Returns the alteration the key signature applies to a letter.
+fn key_alteration(letter: i32, key_sharps: i8) -> i8 {
+    let count = key_sharps.unsigned_abs() as usize;
+    if key_sharps > 0 && SHARP_ORDER[..count.min(7)].contains(&letter) {
+        1
+    } else if key_sharps < 0 && FLAT_ORDER[..count.min(7)].contains(&letter) {
+        -1
+    } else {
+        0
+    }
+}
+
+/// Rounds a tick to the nearest slot.
+fn to_slot(tick: u32) -> u32 {
+    (tick + SLOT_TICKS / 2) / SLOT_TICKS
+}
+
+/// Builds the events of one staff.
+fn notate_staff(
+    notes: &[&Note],
+    clef: Clef,
+    meter: Meter,
+    key_sharps: i8,
+    measures: Range<u32>,
+) -> Staff {
+    let per_measure = meter.slots_per_measure();
+    let range_start = measures.start * per_measure;
+    let range_end = measures.end * per_measure;
+
+    // Group notes by quantized start; each chord lasts until its longest note
+    // ends or the next chord starts
+    let mut onsets: BTreeMap<u32, (u32, Vec<u8>)> = BTreeMap::new();
+    for note in notes {
+        let start = to_slot(note.start_tick);
+        let end = to_slot(note.end_tick()).max(start + 1);
+        let entry = onsets.entry(start).or_insert((end, Vec::new()));
+        entry.0 = entry.0.max(end);
+        entry.1.push(note.pitch);
+    }
+    let starts: Vec<u32> = onsets.keys().copied().collect();
+
+    let mut builder = StaffBuilder {
+        clef,
+        key_sharps,
+        per_measure,
+        first_measure: measures.start,
+        measures: vec![Vec::new(); measures.len()],
+        carried: HashMap::new(),
+        carried_measure: None,
+    };
+    let mut position = range_start;
+    for (index, (&start, (end, pitches))) in onsets.iter().enumerate() {
+        let end = starts.get(index + 1).map_or(*end, |&next| (*end).min(next));
+        if end <= range_start || start >= range_end {
+            continue;
+        }
+        if start > position {
+            builder.push(position, start, None);
+        }
+        let clipped_start = start.max(range_start);
+        let clipped_end = end.min(range_end);
+        let mut pitches = pitches.clone();
+        pitches.sort_unstable();
+        pitches.dedup();
+        builder.push(
+            clipped_start,
+            clipped_end,
+            Some((pitches.as_slice(), clipped_start > start)),
+        );
+        position = clipped_end;
+    }
+    if position < range_end {
+        builder.push(position, range_end, None);
+    }
+
+    let beam_slots = meter.beam_slots();
+    for events in &mut builder.measures {
+        assign_beams(events, beam_slots);
+    }
+    Staff {
+        clef,
+        measures: builder.measures,
+    }
+}
+
+/// Accumulates events measure by measure, tracking accidentals.
+struct StaffBuilder {
+    clef: Clef,
+    key_sharps: i8,
+    per_measure: u32,
+    first_measure: u32,
+    measures: Vec<Vec<StaffEvent>>,
+    /// Alterations set by accidentals earlier in the current measure.
+    carried: HashMap<i32, i8>,
+    carried_measure: Option<u32>,
+}
+
+impl StaffBuilder {
+    /// Adds a chord (pitches and whether it continues an earlier note) or a
+    /// rest spanning `start..end` slots, split at barlines and into drawable
+    /// durations.
+    fn push(&mut self, start: u32, end: u32, chord: Option<(&[u8], bool)>) {
+        let mut slot = start;
+        let mut continued = chord.is_some_and(|(_, continued)| continued);
+        while slot < end {
+            let measure = slot / self.per_measure;
+            let bar_end = (measure + 1) * self.per_measure;
+            let piece_end = end.min(bar_end);
+            for duration in NoteDuration::split(piece_end - slot) {
+                let heads = match chord {
+                    Some((pitches, _)) => self.heads(measure, pitches, continued),
+                    None => Vec::new(),
+                };
+                let index = (measure - self.first_measure) as usize;
+                self.measures[index].push(StaffEvent {
+                    slot: slot - measure * self.per_measure,
+                    duration,
+                    tied: chord.is_some() && slot + duration.slots() < end,
+                    heads,
+                    beam: Beam::None,
+                });
+                slot += duration.slots();
+                continued = true;
+            }
+        }
+    }
+
+    /// Returns the noteheads of a chord, with accidentals where the key
+    /// signature or an earlier accidental in the measure does not apply.
+    fn heads(&mut self, measure: u32, pitches: &[u8], tied_from_previous: bool) -> Vec<NoteHead> {
+        if self.carried_measure != Some(measure) {
+            self.carried.clear();
+            self.carried_measure = Some(measure);
+        }
+        pitches
+            .iter()
+            .map(|&pitch| {
+                let (diatonic, alteration) = spell(pitch, self.key_sharps);
+                let current = self
+                    .carried
+                    .get(&diatonic)
+                    .copied()
+                    .unwrap_or_else(|| key_alteration(diatonic.rem_euclid(7), self.key_sharps));
+                self.carried.insert(diatonic, alteration);
+                let accidental = (alteration != current && !tied_from_previous)
+                    .then(|| Accidental::from_alteration(alteration));
+                NoteHead {
+                    step: diatonic - self.clef.bottom_line(),
+                    accidental,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Beams runs of eighths and sixteenths that fall in the same beam group.
+fn assign_beams(events: &mut [StaffEvent], beam_slots: u32) {
+    let mut index = 0;
+    while index < events.len() {
+        let beamable = |e: &StaffEvent| !e.is_rest() && e.duration.value.is_beamable();
+        if !beamable(&events[index]) {
+            index += 1;
+            continue;
+        }
+        let group = events[index].slot / beam_slots;
+        let mut last = index;
+        while last + 1 < events.len()
+            && beamable(&events[last + 1])
+            && events[last + 1].slot / beam_slots == group
+        {
+            last += 1;
+        }
+        if last > index {
+            events[index].beam = Beam::Start;
+            for event in &mut events[index + 1..last] {
+                event.beam = Beam::Continue;
+            }
+            events[last].beam = Beam::End;
+        }
+        index = last + 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FOUR_FOUR: Meter = Meter {
+        numerator: 4,
+        denominator: 4,
+    };
+
+    fn note(pitch: u8, start_slot: u32, slots: u32) -> Note {
+        Note::new(pitch, 100, start_slot * SLOT_TICKS, slots * SLOT_TICKS)
+    }
+
+    #[test]
+    fn test_split_durations() {
+        let split = NoteDuration::split(5);
+        assert_eq!(
+            split,
+            vec![
+                NoteDuration::plain(NoteValue::Quarter),
+                NoteDuration::plain(NoteValue::Sixteenth)
+            ]
+        );
+        assert_eq!(
+            NoteDuration::split(12),
+            vec![NoteDuration::dotted(NoteValue::Half)]
+        );
+        assert_eq!(FOUR_FOUR.slots_per_measure(), 16);
+        let six_eight = Meter {
+            numerator: 6,
+            denominator: 8,
+        };
+        assert_eq!(six_eight.slots_per_measure(), 12);
+        assert_eq!(six_eight.beam_slots(), 6);
+    }
+
+    #[test]
+    fn test_empty_measure_is_whole_rest() {
+        let score = Score::new(&[], FOUR_FOUR, 0, false, 0..2);
+        let staff = &score.staves[0];
+        assert_eq!(staff.clef, Clef::Treble);
+        for measure in &staff.measures {
+            assert_eq!(measure.len(), 1);
+            assert!(measure[0].is_rest());
+            assert_eq!(measure[0].duration, NoteDuration::plain(NoteValue::Whole));
+        }
+    }
+
+    #[test]
+    fn test_tie_across_barline() {
+        // Half note starting on beat 4
+        let notes = [note(67, 12, 8)];
+        let score = Score::new(&notes, FOUR_FOUR, 0, false, 0..2);
+        let measures = &score.staves[0].measures;
+        let last = measures[0].last().unwrap();
+        assert_eq!(last.slot, 12);
+        assert_eq!(last.duration, NoteDuration::plain(NoteValue::Quarter));
+        assert!(last.tied);
+        let next = &measures[1][0];
+        assert_eq!(next.heads[0].step, last.heads[0].step);
+        assert!(!next.tied);
+        // G4 is the second line of the treble staff
+        assert_eq!(next.heads[0].step, 2);
+    }
+
+    #[test]
+    fn test_accidentals_follow_key_and_measure() {
+        // G major: F# needs no sign, F natural does; a repeated C# in the same
+        // measure carries its sharp
+        let notes = [
+            note(66, 0, 4),
+            note(65, 4, 4),
+            note(73, 8, 4),
+            note(73, 12, 4),
+        ];
+        let score = Score::new(&notes, FOUR_FOUR, 1, false, 0..1);
+        let events = &score.staves[0].measures[0];
+        let accidentals: Vec<_> = events.iter().map(|e| e.heads[0].accidental).collect();
+        assert_eq!(
+            accidentals,
+            vec![
+                None,
+                Some(Accidental::Natural),
+                Some(Accidental::Sharp),
+                None
+            ]
+        );
+
+        // Flat keys spell black keys with flats
+        let score = Score::new(&[note(70, 0, 16)], FOUR_FOUR, -1, false, 0..1);
+        assert_eq!(score.staves[0].measures[0][0].heads[0].accidental, None);
+    }
+
+    #[test]
+    fn test_beams_group_by_beat() {
+        let notes: Vec<Note> = (0..4)
+            .map(|i| note(72, i * 2, 2))
+            .chain((0..4).map(|i| note(72, 8 + i, 1)))
+            .collect();
+        let score = Score::new(&notes, FOUR_FOUR, 0, false, 0..1);
+        let beams: Vec<Beam> = score.staves[0].measures[0].iter().map(|e| e.beam).collect();
+        assert_eq!(
+            beams,
+            vec![
+                Beam::Start,
+                Beam::End,
+                Beam::Start,
+                Beam::End,
+                Beam::Start,
+                Beam::Continue,
+                Beam::Continue,
+                Beam::End,
+                Beam::None
+            ]
+        );
+    }
+
+    #[test]
+    fn test_grand_staff_splits_at_middle_c() {
+        let notes = [note(60, 0, 16), note(48, 0, 16)];
+        let score = Score::new(&notes, FOUR_FOUR, 0, true, 0..1);
+        assert_eq!(score.staves.len(), 2);
+        let treble = &score.staves[0].measures[0][0];
+        let bass = &score.staves[1].measures[0][0];
+        // Middle C: first ledger line below the treble staff
+        assert_eq!(treble.heads[0].step, -2);
+        // C3 in the second space of the bass staff
+        assert_eq!(bass.heads[0].step, 3);
+        assert_eq!(score.staves[1].clef, Clef::Bass);
+    }
+}
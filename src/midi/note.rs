@@ -126,7 +126,6 @@ impl Note {
 
     /// Creates a copy of this note with a new unique ID.
     /// Useful for copy/paste operations.
-    #[allow(dead_code)]
     pub fn duplicate(&self) -> Self {
         Self {
             id: NoteId::new(),
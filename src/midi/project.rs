@@ -3,8 +3,17 @@
 //! A project represents a complete musical composition with multiple tracks,
 //! tempo settings, and time signature information.
 
-use super::note::NoteId;
+use super::chord::{accompaniment, AccompanimentStyle, Chord, ChordSymbol};
+use super::clip::{Clip, Pattern, PatternId};
+use super::effects::apply_effects;
+use super::format;
+use super::groove::GrooveTemplate;
+use super::marker::{Marker, MARKER_COLOR_COUNT};
+use super::note::{Note, NoteId};
+use super::scale::Scale;
 use super::track::{Track, TrackId};
+use std::collections::HashSet;
+
 use super::{ticks_to_seconds, DEFAULT_TEMPO, TICKS_PER_BEAT};
 use serde::{Deserialize, Serialize};
 use std::fs;
@@ -14,6 +23,9 @@ use std::path::Path;
 ///
 /// The project maintains a list of tracks and global settings like tempo.
 /// Supports unlimited tracks - memory is the only constraint.
+///
+/// As with [`Track`], a change to the fields needs a new format version in
+/// `format.rs` so existing `.oxm` files still load.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Project {
     /// Project name.
@@ -36,13 +48,53 @@ pub struct Project {
     /// Skips channel 9 (drums) for melodic tracks.
     next_channel: u8,
 
+    /// Named markers (song sections), sorted by tick.
+    #[serde(default)]
+    markers: Vec<Marker>,
+
+    /// Note patterns instanced by track clips.
+    #[serde(default)]
+    patterns: Vec<Pattern>,
+
+    /// Groove templates extracted from tracks.
+    #[serde(default)]
+    grooves: Vec<GrooveTemplate>,
+
+    /// Chord track: chord symbols sorted by tick.
+    #[serde(default)]
+    chords: Vec<ChordSymbol>,
+
+    /// Key and scale of the project, if set.
+    #[serde(default)]
+    pub scale: Option<Scale>,
+
+    /// Unique id of the project, kept across saves.
+    ///
+    /// Autosaves are grouped by this id. Files written before it existed,
+    /// or holding anything but a UUID, get a fresh one when loaded.
+    #[serde(default = "new_project_id")]
+    id: String,
+
     /// Path to the SoundFont file used for playback.
     /// Stored as a string for cross-platform serialization compatibility.
     /// None means no SoundFont is explicitly associated (use default).
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
     pub soundfont_path: Option<String>,
 }
 
+/// Generates a new random project id.
+pub(super) fn new_project_id() -> String {
+    uuid::Uuid::new_v4().to_string()
+}
+
+/// Returns true if `id` is a UUID in the form [`new_project_id`] writes.
+///
+/// The id names the project's autosave directory, so anything else (such
+/// as a path) is replaced on load.
+fn is_project_id(id: &str) -> bool {
+    uuid::Uuid::try_parse(id).is_ok_and(|uuid| uuid.to_string() == id)
+}
+
 impl Project {
     /// Creates a new empty project with default settings.
     ///
@@ -61,10 +113,65 @@ impl Project {
             time_sig_denominator: 4,
             tracks: Vec::new(),
             next_channel: 0,
+            markers: Vec::new(),
+            patterns: Vec::new(),
+            grooves: Vec::new(),
+            chords: Vec::new(),
+            scale: None,
+            id: new_project_id(),
             soundfont_path: None,
         }
     }
 
+    /// Returns the unique id of the project.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Gives the project a fresh id if the one it was loaded with isn't valid.
+    pub(super) fn ensure_valid_id(&mut self) {
+        if !is_project_id(&self.id) {
+            self.id = new_project_id();
+        }
+    }
+
+    /// Returns the total number of notes across all tracks.
+    pub fn note_count(&self) -> usize {
+        self.tracks.iter().map(|t| t.note_count()).sum()
+    }
+
+    /// Returns a copy of the project without its tracks.
+    ///
+    /// Lets project-wide settings be copied and compared without copying
+    /// every note.
+    pub fn without_tracks(&self) -> Project {
+        Project {
+            name: self.name.clone(),
+            tempo: self.tempo,
+            time_sig_numerator: self.time_sig_numerator,
+            time_sig_denominator: self.time_sig_denominator,
+            tracks: Vec::new(),
+            next_channel: self.next_channel,
+            markers: self.markers.clone(),
+            patterns: self.patterns.clone(),
+            grooves: self.grooves.clone(),
+            chords: self.chords.clone(),
+            scale: self.scale,
+            id: self.id.clone(),
+            soundfont_path: self.soundfont_path.clone(),
+        }
+    }
+
+    /// Removes and returns every track, leaving the rest of the project as is.
+    pub fn take_tracks(&mut self) -> Vec<Track> {
+        std::mem::take(&mut self.tracks)
+    }
+
+    /// Replaces every track.
+    pub fn set_tracks(&mut self, tracks: Vec<Track>) {
+        self.tracks = tracks;
+    }
+
     /// Creates a new project with a single default track.
     pub fn with_default_track(name: impl Into<String>) -> Self {
         let mut project = Self::new(name);
@@ -96,11 +203,11 @@ impl Project {
     }
 
     /// Returns the total duration of the project in ticks.
-    /// This is the maximum duration across all tracks.
+    /// This is the maximum duration across all tracks (including clips).
     pub fn duration_ticks(&self) -> u32 {
         self.tracks
             .iter()
-            .map(|t| t.duration_ticks())
+            .map(|t| self.track_end_tick(t))
             .max()
             .unwrap_or(0)
     }
@@ -176,7 +283,9 @@ impl Project {
     /// The removed track, or None if not found
     pub fn remove_track(&mut self, id: TrackId) -> Option<Track> {
         let pos = self.tracks.iter().position(|t| t.id == id)?;
-        Some(self.tracks.remove(pos))
+        let track = self.tracks.remove(pos);
+        self.drop_unused_patterns();
+        Some(track)
     }
 
     /// Returns a reference to a track by its ID.
@@ -272,6 +381,691 @@ impl Project {
         None
     }
 
+    // ==================== Markers & Sections ====================
+
+    /// Returns all markers (sorted by tick).
+    pub fn markers(&self) -> &[Marker] {
+        &self.markers
+    }
+
+    /// Adds a marker, replacing any existing marker at the same tick.
+    ///
+    /// # Returns
+    ///
+    /// The index of the marker in the sorted marker list
+    pub fn add_marker(&mut self, marker: Marker) -> usize {
+        match self.markers.binary_search_by_key(&marker.tick, |m| m.tick) {
+            Ok(pos) => {
+                self.markers[pos] = marker;
+                pos
+            }
+            Err(pos) => {
+                self.markers.insert(pos, marker);
+                pos
+            }
+        }
+    }
+
+    /// Removes a marker by index.
+    pub fn remove_marker(&mut self, index: usize) -> Option<Marker> {
+        (index < self.markers.len()).then(|| self.markers.remove(index))
+    }
+
+    /// Renames a marker.
+    pub fn rename_marker(&mut self, index: usize, name: impl Into<String>) {
+        if let Some(marker) = self.markers.get_mut(index) {
+            marker.name = name.into();
+        }
+    }
+
+    /// Advances a marker to the next palette color.
+    pub fn cycle_marker_color(&mut self, index: usize) {
+        if let Some(marker) = self.markers.get_mut(index) {
+            marker.color = (marker.color + 1) % MARKER_COLOR_COUNT;
+        }
+    }
+
+    /// Returns the index of the first marker after a tick.
+    pub fn next_marker_after(&self, tick: u32) -> Option<usize> {
+        let idx = self.markers.partition_point(|m| m.tick <= tick);
+        (idx < self.markers.len()).then_some(idx)
+    }
+
+    /// Returns the index of the last marker before a tick.
+    pub fn prev_marker_before(&self, tick: u32) -> Option<usize> {
+        self.markers
+            .partition_point(|m| m.tick < tick)
+            .checked_sub(1)
+    }
+
+    /// Returns the tick range covered by a marker's section.
+    ///
+    /// A section runs from its marker to the next marker. The last section
+    /// runs to the end of the song, rounded up to a whole measure.
+    ///
+    /// # Returns
+    ///
+    /// (start, end) ticks, or None if the index is invalid
+    pub fn section_range(&self, index: usize) -> Option<(u32, u32)> {
+        let start = self.markers.get(index)?.tick;
+        let end = match self.markers.get(index + 1) {
+            Some(next) => next.tick,
+            None => {
+                let measure = self.ticks_per_measure();
+                let song_end = self.duration_ticks().max(start + 1);
+                song_end.div_ceil(measure) * measure
+            }
+        };
+        Some((start, end))
+    }
+
+    /// Shifts notes, lyrics and markers that start within a tick range on every track.
+    fn shift_range_all(&mut self, start: u32, end: u32, delta: i64) {
+        for track in &mut self.tracks {
+            track.shift_range(start, end, delta);
+        }
+        for marker in &mut self.markers {
+            if (start..end).contains(&marker.tick) {
+                marker.tick = (marker.tick as i64 + delta).clamp(0, u32::MAX as i64) as u32;
+            }
+        }
+        self.markers.sort_by_key(|m| m.tick);
+        for symbol in &mut self.chords {
+            if (start..end).contains(&symbol.tick) {
+                symbol.tick = (symbol.tick as i64 + delta).clamp(0, u32::MAX as i64) as u32;
+            }
+        }
+        self.chords.sort_by_key(|c| c.tick);
+    }
+
+    /// Inserts empty time on every track, pushing notes, lyrics, clips and
+    /// markers at or after the tick later.
+    ///
+    /// Notes sounding across the insertion point are split.
+    ///
+    /// # Arguments
+    ///
+    /// * `at` - Tick where the time is inserted
+    /// * `length` - Number of ticks to insert
+    pub fn insert_time(&mut self, at: u32, length: u32) {
+        for track in &mut self.tracks {
+            track.insert_time(at, length);
+        }
+        for marker in &mut self.markers {
+            if marker.tick >= at {
+                marker.tick = marker.tick.saturating_add(length);
+            }
+        }
+        for symbol in &mut self.chords {
+            if symbol.tick >= at {
+                symbol.tick = symbol.tick.saturating_add(length);
+            }
+        }
+    }
+
+    /// Removes a span of time on every track, pulling everything after it earlier.
+    ///
+    /// Notes straddling the span are trimmed, and markers inside it are removed.
+    ///
+    /// # Arguments
+    ///
+    /// * `start` - Start tick (inclusive)
+    /// * `end` - End tick (exclusive)
+    pub fn delete_time(&mut self, start: u32, end: u32) {
+        if end <= start {
+            return;
+        }
+        for track in &mut self.tracks {
+            track.delete_time(start, end);
+        }
+        self.drop_unused_patterns();
+        self.markers.retain(|m| !(start..end).contains(&m.tick));
+        for marker in &mut self.markers {
+            if marker.tick >= end {
+                marker.tick -= end - start;
+            }
+        }
+        self.chords.retain(|c| !(start..end).contains(&c.tick));
+        for symbol in &mut self.chords {
+            if symbol.tick >= end {
+                symbol.tick -= end - start;
+            }
+        }
+    }
+
+    /// Duplicates a section across all tracks, inserting the copy right after it.
+    ///
+    /// Everything after the section is pushed later by the section length.
+    ///
+    /// # Returns
+    ///
+    /// true if the section exists and was duplicated
+    pub fn duplicate_section(&mut self, index: usize) -> bool {
+        let Some((start, end)) = self.section_range(index) else {
+            return false;
+        };
+        let len = end - start;
+        let marker = self.markers[index].clone();
+
+        let copies: Vec<_> = self
+            .tracks
+            .iter()
+            .map(|t| t.copy_range(start, end))
+            .collect();
+
+        let chords: Vec<_> = self
+            .chords
+            .iter()
+            .filter(|c| (start..end).contains(&c.tick))
+            .copied()
+            .collect();
+
+        self.shift_range_all(end, u32::MAX, len as i64);
+
+        for (track, copy) in self.tracks.iter_mut().zip(copies) {
+            copy.paste_into(track, len);
+        }
+        for symbol in chords {
+            self.set_chord(symbol.tick + len, symbol.chord);
+        }
+        self.add_marker(Marker::new(end, marker.name, marker.color));
+        true
+    }
+
+    /// Deletes a section across all tracks, closing the gap it leaves.
+    ///
+    /// Notes that start inside the section are removed along with its marker.
+    ///
+    /// # Returns
+    ///
+    /// true if the section exists and was deleted
+    pub fn delete_section(&mut self, index: usize) -> bool {
+        let Some((start, end)) = self.section_range(index) else {
+            return false;
+        };
+
+        for track in &mut self.tracks {
+            track.remove_range(start, end);
+        }
+        self.drop_unused_patterns();
+        self.markers.retain(|m| !(start..end).contains(&m.tick));
+        self.chords.retain(|c| !(start..end).contains(&c.tick));
+        self.shift_range_all(end, u32::MAX, -((end - start) as i64));
+        true
+    }
+
+    /// Swaps a section with the one that follows it across all tracks.
+    ///
+    /// Moving a section earlier is a swap with the preceding section.
+    ///
+    /// # Returns
+    ///
+    /// true if both sections exist and were swapped
+    pub fn swap_sections(&mut self, index: usize) -> bool {
+        let (Some((a_start, a_end)), Some((_, b_end))) =
+            (self.section_range(index), self.section_range(index + 1))
+        else {
+            return false;
+        };
+        let a_len = a_end - a_start;
+        let b_len = b_end - a_end;
+
+        // Move the first section out of the way, pull the second one back,
+        // then drop the first section in after it
+        let parking = u32::MAX / 2;
+        self.shift_range_all(a_start, a_end, (parking - a_start) as i64);
+        self.shift_range_all(a_end, b_end, -(a_len as i64));
+        self.shift_range_all(
+            parking,
+            parking + a_len,
+            -((parking - a_start - b_len) as i64),
+        );
+        true
+    }
+
+    // ==================== Chords ====================
+
+    /// Returns the chord track (sorted by tick).
+    pub fn chords(&self) -> &[ChordSymbol] {
+        &self.chords
+    }
+
+    /// Sets the chord at a tick, replacing any chord symbol already there.
+    pub fn set_chord(&mut self, tick: u32, chord: Chord) {
+        let symbol = ChordSymbol { tick, chord };
+        match self.chords.binary_search_by_key(&tick, |c| c.tick) {
+            Ok(pos) => self.chords[pos] = symbol,
+            Err(pos) => self.chords.insert(pos, symbol),
+        }
+    }
+
+    /// Returns the index of the chord symbol in effect at a tick.
+    pub fn chord_index_at(&self, tick: u32) -> Option<usize> {
+        self.chords
+            .partition_point(|c| c.tick <= tick)
+            .checked_sub(1)
+    }
+
+    /// Removes a chord symbol by index.
+    pub fn remove_chord(&mut self, index: usize) -> Option<ChordSymbol> {
+        (index < self.chords.len()).then(|| self.chords.remove(index))
+    }
+
+    /// Names the chord sounding at a tick across all non-drum tracks.
+    ///
+    /// Notes inside clips are included.
+    pub fn detect_chord_at(&self, tick: u32) -> Option<Chord> {
+        let mut pitches = Vec::new();
+        for track in self.tracks.iter().filter(|t| !t.is_drum()) {
+            pitches.extend(
+                track
+                    .notes()
+                    .iter()
+                    .filter(|n| n.is_active_at(tick))
+                    .map(|n| n.pitch),
+            );
+            for clip in track.clips() {
+                let Some(pattern) = self.pattern(clip.pattern_id) else {
+                    continue;
+                };
+                let Some(offset) = tick.checked_sub(clip.start_tick) else {
+                    continue;
+                };
+                pitches.extend(
+                    pattern
+                        .notes()
+                        .iter()
+                        .filter(|n| n.is_active_at(offset))
+                        .map(|n| n.pitch),
+                );
+            }
+        }
+        Chord::detect(&pitches)
+    }
+
+    /// Replaces a track's notes under the chord track with generated accompaniment.
+    ///
+    /// Only notes starting within the chord track are replaced. The last chord lasts one measure, or to the end of the song if that is later.
+    ///
+    /// # Returns
+    ///
+    /// The number of notes generated
+    pub fn generate_accompaniment(
+        &mut self,
+        track_index: usize,
+        style: AccompanimentStyle,
+    ) -> usize {
+        let (Some(first), Some(last)) = (self.chords.first(), self.chords.last()) else {
+            return 0;
+        };
+        let start = first.tick;
+        let measure = self.ticks_per_measure();
+        let end = (last.tick + measure).max(self.duration_ticks().div_ceil(measure) * measure);
+        let notes = accompaniment(&self.chords, end, style);
+
+        let Some(track) = self.tracks.get_mut(track_index) else {
+            return 0;
+        };
+        let replaced: Vec<NoteId> = track
+            .notes()
+            .iter()
+            .filter(|n| (start..end).contains(&n.start_tick))
+            .map(|n| n.id)
+            .collect();
+        for id in replaced {
+            track.remove_note(id);
+        }
+        for note in &notes {
+            track.create_note(
+                note.pitch,
+                note.velocity,
+                note.start_tick,
+                note.duration_ticks,
+            );
+        }
+        notes.len()
+    }
+
+    // ==================== Grooves ====================
+
+    /// Returns the groove templates saved in the project.
+    pub fn grooves(&self) -> &[GrooveTemplate] {
+        &self.grooves
+    }
+
+    /// Extracts a groove template from a track and saves it in the project.
+    ///
+    /// The groove covers one measure of sixteenth notes.
+    ///
+    /// # Returns
+    ///
+    /// The index of the new groove, or None if the track has no notes
+    pub fn extract_groove(&mut self, track_index: usize) -> Option<usize> {
+        let track = self.tracks.get(track_index)?;
+        let grid = TICKS_PER_BEAT / 4;
+        let steps = (self.ticks_per_measure() / grid).max(1) as usize;
+        let name = format!("Groove: {}", track.name);
+        let groove = GrooveTemplate::extract(name, track.notes(), grid, steps)?;
+        self.grooves.push(groove);
+        Some(self.grooves.len() - 1)
+    }
+
+    // ==================== Patterns & Clips ====================
+
+    /// Returns a pattern by ID.
+    pub fn pattern(&self, id: PatternId) -> Option<&Pattern> {
+        self.patterns.iter().find(|p| p.id == id)
+    }
+
+    /// Returns the next free pattern ID.
+    fn next_pattern_id(&self) -> PatternId {
+        PatternId(self.patterns.iter().map(|p| p.id.0 + 1).max().unwrap_or(1))
+    }
+
+    /// Drops the patterns no clip uses any more.
+    pub fn drop_unused_patterns(&mut self) {
+        let used: HashSet<PatternId> = self
+            .tracks
+            .iter()
+            .flat_map(|t| t.clips().iter().chain(t.open_clip.as_ref()))
+            .map(|c| c.pattern_id)
+            .collect();
+        self.patterns.retain(|p| used.contains(&p.id));
+    }
+
+    /// Returns how many clips (across all tracks, including open ones) use a pattern.
+    pub fn pattern_use_count(&self, id: PatternId) -> usize {
+        self.tracks
+            .iter()
+            .flat_map(|t| t.clips().iter().chain(t.open_clip.as_ref()))
+            .filter(|c| c.pattern_id == id)
+            .count()
+    }
+
+    /// Returns the end tick of a clip (start plus pattern length).
+    pub fn clip_end_tick(&self, clip: &Clip) -> u32 {
+        let length = self.pattern(clip.pattern_id).map_or(0, |p| p.length_ticks);
+        clip.start_tick + length
+    }
+
+    /// Returns the end tick of a track, including its clips and effect tails.
+    pub fn track_end_tick(&self, track: &Track) -> u32 {
+        if !track.effects.is_empty() {
+            return self
+                .playback_notes(track)
+                .iter()
+                .map(|n| n.end_tick())
+                .max()
+                .unwrap_or(0);
+        }
+        track
+            .clips()
+            .iter()
+            .map(|c| self.clip_end_tick(c))
+            .fold(track.duration_ticks(), u32::max)
+    }
+
+    /// Finds the clip covering a tick on a track.
+    ///
+    /// # Returns
+    ///
+    /// The index of the clip in the track's clip list
+    pub fn clip_at(&self, track_index: usize, tick: u32) -> Option<usize> {
+        let track = self.tracks.get(track_index)?;
+        track
+            .clips()
+            .iter()
+            .rposition(|c| c.start_tick <= tick && tick < self.clip_end_tick(c))
+    }
+
+    /// Returns the notes a track plays: its own notes plus every clip instance,
+    /// run through the track's effect chain and sorted by start tick.
+    ///
+    /// This is the flattened view used by the sequencer and by exports.
+    pub fn playback_notes(&self, track: &Track) -> Vec<Note> {
+        let mut notes = track.notes().to_vec();
+        for clip in track.clips() {
+            if let Some(pattern) = self.pattern(clip.pattern_id) {
+                notes.extend(pattern.notes().iter().map(|n| {
+                    let mut note = n.clone();
+                    note.start_tick += clip.start_tick;
+                    note
+                }));
+            }
+        }
+        notes.sort_by_key(|n| n.start_tick);
+        if track.effects.is_empty() {
+            notes
+        } else {
+            apply_effects(notes, &track.effects)
+        }
+    }
+
+    /// Turns notes of a track into a new pattern with a single clip in their place.
+    ///
+    /// The clip starts at the measure containing the earliest note and spans
+    /// whole measures.
+    ///
+    /// # Returns
+    ///
+    /// The index of the new clip, or None if no notes matched
+    pub fn create_clip_from_notes(
+        &mut self,
+        track_index: usize,
+        note_ids: &std::collections::HashSet<NoteId>,
+    ) -> Option<usize> {
+        let measure = self.ticks_per_measure();
+        let id = self.next_pattern_id();
+        let track = self.tracks.get_mut(track_index)?;
+
+        let notes: Vec<Note> = note_ids
+            .iter()
+            .filter_map(|&n| track.remove_note(n))
+            .collect();
+        let first = notes.iter().map(|n| n.start_tick).min()?;
+        let last = notes.iter().map(|n| n.end_tick()).max().unwrap_or(first);
+        let start = first / measure * measure;
+        let length = (last - start).div_ceil(measure).max(1) * measure;
+
+        let relative = notes
+            .into_iter()
+            .map(|mut n| {
+                n.start_tick -= start;
+                n
+            })
+            .collect();
+        let index = track.add_clip(Clip::new(id, start));
+        self.patterns.push(Pattern::new(
+            id,
+            format!("Pattern {}", id.0),
+            length,
+            relative,
+        ));
+        Some(index)
+    }
+
+    /// Places a copy of a clip right after it.
+    ///
+    /// # Arguments
+    ///
+    /// * `linked` - Share the pattern (edits affect both) instead of copying it
+    ///
+    /// # Returns
+    ///
+    /// The index of the new clip
+    pub fn duplicate_clip(
+        &mut self,
+        track_index: usize,
+        clip_index: usize,
+        linked: bool,
+    ) -> Option<usize> {
+        let clip = self
+            .tracks
+            .get(track_index)?
+            .clips()
+            .get(clip_index)?
+            .clone();
+        let start = self.clip_end_tick(&clip);
+
+        let pattern_id = if linked {
+            clip.pattern_id
+        } else {
+            self.copy_pattern(clip.pattern_id)?
+        };
+        Some(self.tracks[track_index].add_clip(Clip::new(pattern_id, start)))
+    }
+
+    /// Gives a linked clip its own copy of the pattern.
+    ///
+    /// # Returns
+    ///
+    /// true if the clip was linked and is now unique
+    pub fn make_clip_unique(&mut self, track_index: usize, clip_index: usize) -> bool {
+        let Some(pattern_id) = self
+            .tracks
+            .get(track_index)
+            .and_then(|t| t.clips().get(clip_index))
+            .map(|c| c.pattern_id)
+        else {
+            return false;
+        };
+        if self.pattern_use_count(pattern_id) < 2 {
+            return false;
+        }
+        let Some(new_id) = self.copy_pattern(pattern_id) else {
+            return false;
+        };
+        let track = &mut self.tracks[track_index];
+        if let Some(clip) = track.remove_clip(clip_index) {
+            track.add_clip(Clip::new(new_id, clip.start_tick));
+        }
+        true
+    }
+
+    /// Copies a pattern under a new ID.
+    fn copy_pattern(&mut self, id: PatternId) -> Option<PatternId> {
+        let new_id = self.next_pattern_id();
+        let source = self.pattern(id)?;
+        let copy = source.duplicate(new_id, format!("{} ({})", source.name, new_id.0));
+        self.patterns.push(copy);
+        Some(new_id)
+    }
+
+    /// Removes a clip from a track, dropping its pattern if nothing else uses it.
+    pub fn remove_clip(&mut self, track_index: usize, clip_index: usize) -> bool {
+        if self
+            .tracks
+            .get_mut(track_index)
+            .and_then(|t| t.remove_clip(clip_index))
+            .is_none()
+        {
+            return false;
+        }
+        self.drop_unused_patterns();
+        true
+    }
+
+    /// Moves a clip along its track.
+    ///
+    /// # Returns
+    ///
+    /// The new index of the clip
+    pub fn move_clip(
+        &mut self,
+        track_index: usize,
+        clip_index: usize,
+        delta: i64,
+    ) -> Option<usize> {
+        let track = self.tracks.get_mut(track_index)?;
+        let mut clip = track.remove_clip(clip_index)?;
+        clip.start_tick = (clip.start_tick as i64 + delta).clamp(0, u32::MAX as i64) as u32;
+        Some(track.add_clip(clip))
+    }
+
+    /// Opens a clip for editing by unpacking its pattern notes into the track.
+    ///
+    /// Only one clip per track can be open. Linked copies keep playing the
+    /// previous pattern contents until the clip is closed.
+    ///
+    /// # Returns
+    ///
+    /// true if the clip was opened
+    pub fn open_clip(&mut self, track_index: usize, clip_index: usize) -> bool {
+        let Some(track) = self.tracks.get(track_index) else {
+            return false;
+        };
+        if track.open_clip.is_some() {
+            return false;
+        }
+        let Some(clip) = track.clips().get(clip_index).cloned() else {
+            return false;
+        };
+        let notes: Vec<Note> = self
+            .pattern(clip.pattern_id)
+            .map(|p| p.notes().to_vec())
+            .unwrap_or_default();
+
+        let track = &mut self.tracks[track_index];
+        track.remove_clip(clip_index);
+        track.open_clip = Some(clip.clone());
+        for mut note in notes {
+            note.start_tick += clip.start_tick;
+            track.add_note(note);
+        }
+        true
+    }
+
+    /// Closes the open clip of a track, storing its notes back into the pattern.
+    ///
+    /// Only notes unpacked from the pattern or added while the clip was open
+    /// are taken, and only those still starting within the clip. Other notes
+    /// in its range stay on the track. The pattern grows (by whole measures) if notes were added past its end,
+    /// and every linked copy picks up the edit.
+    ///
+    /// # Returns
+    ///
+    /// true if a clip was open and has been closed
+    pub fn close_clip(&mut self, track_index: usize) -> bool {
+        let measure = self.ticks_per_measure();
+        let Some((clip, clip_notes)) = self
+            .tracks
+            .get_mut(track_index)
+            .and_then(|t| t.take_open_clip())
+        else {
+            return false;
+        };
+        let old_length = self
+            .pattern(clip.pattern_id)
+            .map_or(measure, |p| p.length_ticks);
+        let end = clip.start_tick + old_length;
+
+        let track = &mut self.tracks[track_index];
+        let ids: Vec<NoteId> = clip_notes
+            .into_iter()
+            .filter(|&id| {
+                track
+                    .get_note(id)
+                    .is_some_and(|n| (clip.start_tick..end).contains(&n.start_tick))
+            })
+            .collect();
+        let notes: Vec<Note> = ids
+            .into_iter()
+            .filter_map(|id| track.remove_note(id))
+            .map(|mut n| {
+                n.start_tick -= clip.start_tick;
+                n
+            })
+            .collect();
+        let extent = notes.iter().map(|n| n.end_tick()).max().unwrap_or(0);
+        track.add_clip(clip.clone());
+
+        if let Some(pattern) = self.patterns.iter_mut().find(|p| p.id == clip.pattern_id) {
+            pattern.length_ticks = old_length.max(extent.div_ceil(measure) * measure);
+            pattern.set_notes(notes);
+        }
+        true
+    }
+
     /// Calculates the measure and beat for a given tick position.
     ///
     /// # Arguments
@@ -351,9 +1145,7 @@ impl Project {
     ///
     /// Returns error if serialization or file writing fails
     pub fn save_to_file<P: AsRef<Path>>(&self, path: P) -> Result<(), std::io::Error> {
-        let json = serde_json::to_string_pretty(self)
-            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
-        fs::write(path, json)
+        fs::write(path, format::encode_json(self)?)
     }
 
     /// Loads a project from a JSON file.
@@ -368,16 +1160,17 @@ impl Project {
     ///
     /// # Errors
     ///
-    /// Returns error if file reading or parsing fails
+    /// Returns error if file reading or parsing fails, or if the file was
+    /// written by a newer version of miditui
     pub fn load_from_file<P: AsRef<Path>>(path: P) -> Result<Self, std::io::Error> {
         let json = fs::read_to_string(path)?;
-        serde_json::from_str(&json)
-            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+        Ok(format::decode_json(&json)?)
     }
 
     /// Saves the project to binary format (.oxm).
     ///
-    /// Uses bincode for efficient serialization of numeric data.
+    /// Uses bincode for efficient serialization of numeric data, behind a
+    /// header carrying the file format version.
     ///
     /// # Arguments
     ///
@@ -387,9 +1180,7 @@ impl Project {
     ///
     /// Returns error if serialization or file writing fails
     pub fn save_to_binary<P: AsRef<Path>>(&self, path: P) -> Result<(), std::io::Error> {
-        let data = bincode::serialize(self)
-            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
-        fs::write(path, data)
+        fs::write(path, format::encode_binary(self)?)
     }
 
     /// Loads a project from binary format (.oxm).
@@ -404,11 +1195,29 @@ impl Project {
     ///
     /// # Errors
     ///
-    /// Returns error if file reading or parsing fails
+    /// Returns error if file reading or parsing fails, or if the file was
+    /// written by a newer version of miditui
     pub fn load_from_binary<P: AsRef<Path>>(path: P) -> Result<Self, std::io::Error> {
         let data = fs::read(path)?;
-        bincode::deserialize(&data)
-            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+        Ok(format::decode_binary(&data)?)
+    }
+
+    /// Loads a project file (.oxm or .json) to start a new project from.
+    ///
+    /// The copy gets a fresh id so its autosaves don't mix with the
+    /// template's own.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the file can't be loaded
+    pub fn load_template<P: AsRef<Path>>(path: P) -> Result<Self, std::io::Error> {
+        let path = path.as_ref();
+        let mut project = match path.extension().and_then(|e| e.to_str()) {
+            Some("oxm") => Self::load_from_binary(path)?,
+            _ => Self::load_from_file(path)?,
+        };
+        project.id = new_project_id();
+        Ok(project)
     }
 
     /// Exports the project to a Standard MIDI File (.mid).
@@ -439,6 +1248,231 @@ impl Default for Project {
 mod tests {
     use super::*;
 
+    /// Builds a project with three one-measure sections, one note each.
+    fn sectioned_project() -> Project {
+        let mut project = Project::with_default_track("Sections");
+        let track = project.track_at_mut(0).unwrap();
+        track.create_note(60, 100, 0, 480);
+        track.create_note(62, 100, 1920, 480);
+        track.create_note(64, 100, 3840, 480);
+        project.add_marker(Marker::new(0, "Verse", 0));
+        project.add_marker(Marker::new(1920, "Chorus", 1));
+        project.add_marker(Marker::new(3840, "Outro", 2));
+        project
+    }
+
+    fn pitches(project: &Project) -> Vec<(u32, u8)> {
+        project.tracks()[0]
+            .notes()
+            .iter()
+            .map(|n| (n.start_tick, n.pitch))
+            .collect()
+    }
+
+    #[test]
+    fn test_insert_and_delete_time() {
+        let mut project = sectioned_project();
+
+        project.insert_time(1920, 3840);
+        assert_eq!(pitches(&project), vec![(0, 60), (5760, 62), (7680, 64)]);
+        let ticks: Vec<_> = project.markers().iter().map(|m| m.tick).collect();
+        assert_eq!(ticks, vec![0, 5760, 7680]);
+
+        // Deleting the inserted bars plus the chorus restores the outro position
+        project.delete_time(1920, 7680);
+        assert_eq!(pitches(&project), vec![(0, 60), (1920, 64)]);
+        let names: Vec<_> = project.markers().iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(names, vec!["Verse", "Outro"]);
+    }
+
+    #[test]
+    fn test_clip_linked_copies() {
+        let mut project = Project::with_default_track("Clips");
+        let track = project.track_at_mut(0).unwrap();
+        let a = track.create_note(36, 100, 0, 240);
+        let b = track.create_note(38, 100, 960, 240);
+        let ids: std::collections::HashSet<_> = [a, b].into_iter().collect();
+
+        let clip = project.create_clip_from_notes(0, &ids).unwrap();
+        assert!(project.tracks()[0].notes().is_empty());
+        assert_eq!(project.patterns[0].length_ticks, 1920);
+
+        // Linked copy plays the same notes one measure later
+        project.duplicate_clip(0, clip, true).unwrap();
+        let starts: Vec<_> = project
+            .playback_notes(&project.tracks()[0])
+            .iter()
+            .map(|n| n.start_tick)
+            .collect();
+        assert_eq!(starts, vec![0, 960, 1920, 2880]);
+        assert_eq!(project.duration_ticks(), 3840);
+
+        // Editing the first clip changes both instances
+        assert!(project.open_clip(0, 0));
+        project
+            .track_at_mut(0)
+            .unwrap()
+            .create_note(42, 100, 480, 240);
+        assert!(project.close_clip(0));
+        assert_eq!(project.playback_notes(&project.tracks()[0]).len(), 6);
+
+        // Unique copies stop following the shared pattern
+        assert!(project.make_clip_unique(0, 1));
+        assert_eq!(project.patterns.len(), 2);
+        assert!(!project.make_clip_unique(0, 1));
+    }
+
+    #[test]
+    fn test_open_clip_keeps_loose_notes_out_of_pattern() {
+        let mut project = Project::with_default_track("Clips");
+        let track = project.track_at_mut(0).unwrap();
+        let id = track.create_note(36, 100, 0, 240);
+        let clip = project
+            .create_clip_from_notes(0, &[id].into_iter().collect())
+            .unwrap();
+        project.duplicate_clip(0, clip, true).unwrap();
+
+        // A loose note inside the clip's range stays loose
+        let loose = project
+            .track_at_mut(0)
+            .unwrap()
+            .create_note(60, 100, 480, 240);
+        assert!(project.open_clip(0, 0));
+        project.insert_time(0, 960);
+        assert_eq!(
+            project.tracks()[0].open_clip.as_ref().unwrap().start_tick,
+            960
+        );
+        project
+            .track_at_mut(0)
+            .unwrap()
+            .create_note(38, 100, 1200, 240);
+        assert!(project.close_clip(0));
+
+        assert_eq!(project.patterns[0].notes().len(), 2);
+        assert_eq!(project.tracks()[0].notes()[0].id, loose);
+        assert_eq!(project.playback_notes(&project.tracks()[0]).len(), 5);
+    }
+
+    #[test]
+    fn test_deleting_clips_drops_unused_patterns() {
+        let mut project = Project::with_default_track("Clips");
+        project.add_marker(Marker::new(0, "Intro", 0));
+        project.add_marker(Marker::new(1920, "Verse", 0));
+        let track = project.track_at_mut(0).unwrap();
+        let a = track.create_note(36, 100, 0, 240);
+        let b = track.create_note(36, 100, 1920, 240);
+        project.create_clip_from_notes(0, &[a].into_iter().collect());
+        project.create_clip_from_notes(0, &[b].into_iter().collect());
+        assert!(project.open_clip(0, 1));
+
+        assert!(project.delete_section(1));
+        assert_eq!(project.patterns.len(), 1);
+        assert!(project.tracks()[0].open_clip.is_none());
+        assert!(project.remove_clip(0, 0));
+        assert!(project.patterns.is_empty());
+    }
+
+    #[test]
+    fn test_chord_track() {
+        use crate::midi::ChordQuality;
+
+        let mut project = Project::with_default_track("Chords");
+        let track = project.track_at_mut(0).unwrap();
+        for pitch in [57, 60, 64] {
+            track.create_note(pitch, 100, 0, 960);
+        }
+        assert_eq!(project.detect_chord_at(480).unwrap().symbol(), "Am");
+        assert!(project.detect_chord_at(960).is_none());
+
+        project.set_chord(0, Chord::new(9, ChordQuality::Minor));
+        project.set_chord(1920, Chord::new(5, ChordQuality::Major));
+        project.insert_time(960, 960);
+        let ticks: Vec<_> = project.chords().iter().map(|c| c.tick).collect();
+        assert_eq!(ticks, vec![0, 2880]);
+        assert_eq!(project.chord_index_at(2000), Some(0));
+
+        let accompaniment = project.create_track("Keys");
+        let index = project
+            .tracks()
+            .iter()
+            .position(|t| t.id == accompaniment)
+            .unwrap();
+        assert_eq!(
+            project.generate_accompaniment(index, AccompanimentStyle::Block),
+            6
+        );
+        // Regenerating replaces the previous accompaniment
+        project.generate_accompaniment(index, AccompanimentStyle::Block);
+        assert_eq!(project.tracks()[index].notes().len(), 6);
+    }
+
+    #[test]
+    fn test_track_effects_in_playback() {
+        use crate::midi::{EchoSettings, NoteEffect};
+
+        let mut project = Project::with_default_track("Echo");
+        let track = project.track_at_mut(0).unwrap();
+        track.create_note(60, 100, 0, 240);
+        track
+            .effects
+            .push(NoteEffect::Echo(EchoSettings::default()));
+
+        // Playback and length see the repeats, the stored notes do not change
+        let track = &project.tracks()[0];
+        assert_eq!(project.playback_notes(track).len(), 4);
+        assert_eq!(track.notes().len(), 1);
+        assert_eq!(project.duration_ticks(), 960);
+
+        let effect = track.effects[0];
+        let track = project.track_at_mut(0).unwrap();
+        let generated = track.render_effect(None, &effect);
+        assert_eq!(generated.len(), 4);
+        assert_eq!(track.notes().len(), 4);
+    }
+
+    #[test]
+    fn test_marker_navigation() {
+        let project = sectioned_project();
+        assert_eq!(project.next_marker_after(0), Some(1));
+        assert_eq!(project.next_marker_after(3840), None);
+        assert_eq!(project.prev_marker_before(1920), Some(0));
+        assert_eq!(project.prev_marker_before(0), None);
+        assert_eq!(project.section_range(1), Some((1920, 3840)));
+        assert_eq!(project.section_range(2), Some((3840, 5760)));
+    }
+
+    #[test]
+    fn test_duplicate_section() {
+        let mut project = sectioned_project();
+        assert!(project.duplicate_section(1));
+        assert_eq!(
+            pitches(&project),
+            vec![(0, 60), (1920, 62), (3840, 62), (5760, 64)]
+        );
+        let names: Vec<_> = project.markers().iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(names, vec!["Verse", "Chorus", "Chorus", "Outro"]);
+    }
+
+    #[test]
+    fn test_delete_section() {
+        let mut project = sectioned_project();
+        assert!(project.delete_section(1));
+        assert_eq!(pitches(&project), vec![(0, 60), (1920, 64)]);
+        assert_eq!(project.markers().len(), 2);
+        assert_eq!(project.markers()[1].tick, 1920);
+    }
+
+    #[test]
+    fn test_swap_sections() {
+        let mut project = sectioned_project();
+        assert!(project.swap_sections(0));
+        assert_eq!(pitches(&project), vec![(0, 62), (1920, 60), (3840, 64)]);
+        assert_eq!(project.markers()[0].name, "Chorus");
+        assert_eq!(project.markers()[1].name, "Verse");
+        assert!(!project.swap_sections(2));
+    }
+
     #[test]
     fn test_project_creation() {
         let project = Project::new("Test");
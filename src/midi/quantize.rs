@@ -0,0 +1,246 @@
+//! Note quantization with strength, swing and window controls.
+//!
+//! Quantization moves note starts (and optionally ends) toward the nearest
+//! line of a grid. Strength blends between the original and the grid
+//! position, swing delays every second grid line, and the window limits the
+//! operation to notes already close to the grid.
+
+use super::note::Note;
+use super::TICKS_PER_BEAT;
+
+/// Grid divisions offered by the quantize dialog (fractions of a whole note).
+pub const GRID_DIVISIONS: [u32; 6] = [1, 2, 4, 8, 16, 32];
+
+/// Rhythmic feel of the quantize grid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GridFeel {
+    /// Plain division (e.g. 1/16).
+    #[default]
+    Straight,
+    /// Three in the space of two (2/3 of the straight length).
+    Triplet,
+    /// One and a half times the straight length.
+    Dotted,
+}
+
+impl GridFeel {
+    /// Returns the next feel in the cycle.
+    pub fn next(self) -> Self {
+        match self {
+            GridFeel::Straight => GridFeel::Triplet,
+            GridFeel::Triplet => GridFeel::Dotted,
+            GridFeel::Dotted => GridFeel::Straight,
+        }
+    }
+
+    /// Returns the previous feel in the cycle.
+    pub fn prev(self) -> Self {
+        match self {
+            GridFeel::Straight => GridFeel::Dotted,
+            GridFeel::Triplet => GridFeel::Straight,
+            GridFeel::Dotted => GridFeel::Triplet,
+        }
+    }
+
+    /// Returns the display suffix ("", "T" or ".").
+    pub fn suffix(self) -> &'static str {
+        match self {
+            GridFeel::Straight => "",
+            GridFeel::Triplet => "T",
+            GridFeel::Dotted => ".",
+        }
+    }
+}
+
+/// Settings for a quantize operation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuantizeSettings {
+    /// Grid division as a fraction of a whole note (4 = quarter, 16 = sixteenth).
+    pub division: u32,
+    /// Straight, triplet or dotted grid.
+    pub feel: GridFeel,
+    /// How far notes move toward the grid (0-100%).
+    pub strength: u8,
+    /// Delay applied to every second grid line (0-100% of half a grid step).
+    pub swing: u8,
+    /// Only notes within this share of half a grid step are moved (0-100%).
+    pub window: u8,
+    /// Quantize note starts.
+    pub starts: bool,
+    /// Quantize note ends.
+    pub ends: bool,
+}
+
+impl Default for QuantizeSettings {
+    fn default() -> Self {
+        Self {
+            division: 16,
+            feel: GridFeel::Straight,
+            strength: 100,
+            swing: 0,
+            window: 100,
+            starts: true,
+            ends: false,
+        }
+    }
+}
+
+impl QuantizeSettings {
+    /// Returns the grid step in ticks.
+    pub fn grid_ticks(&self) -> u32 {
+        let straight = TICKS_PER_BEAT * 4 / self.division.max(1);
+        match self.feel {
+            GridFeel::Straight => straight,
+            GridFeel::Triplet => straight * 2 / 3,
+            GridFeel::Dotted => straight * 3 / 2,
+        }
+        .max(1)
+    }
+
+    /// Returns the grid label, e.g. "1/16", "1/8T" or "1/4.".
+    pub fn grid_label(&self) -> String {
+        format!("1/{}{}", self.division, self.feel.suffix())
+    }
+
+    /// Returns the position of a grid line, including swing.
+    fn grid_line(&self, index: u32) -> u32 {
+        let grid = self.grid_ticks();
+        let swing = if index % 2 == 1 {
+            grid / 2 * self.swing as u32 / 100
+        } else {
+            0
+        };
+        index * grid + swing
+    }
+
+    /// Finds the grid line nearest to a tick.
+    fn nearest_line(&self, tick: u32) -> u32 {
+        let index = tick / self.grid_ticks();
+        [index.saturating_sub(1), index, index + 1]
+            .into_iter()
+            .map(|i| self.grid_line(i))
+            .min_by_key(|&line| line.abs_diff(tick))
+            .unwrap_or(tick)
+    }
+
+    /// Moves a tick toward its nearest grid line, honoring window and strength.
+    fn snap(&self, tick: u32) -> u32 {
+        let target = self.nearest_line(tick);
+        let window = self.grid_ticks() / 2 * self.window as u32 / 100;
+        if target.abs_diff(tick) > window {
+            return tick;
+        }
+        let offset = (target as i64 - tick as i64) * self.strength as i64 / 100;
+        (tick as i64 + offset).max(0) as u32
+    }
+
+    /// Quantizes a single note.
+    ///
+    /// When only starts are quantized the note keeps its length. When ends are
+    /// quantized the note is never shortened below one grid step.
+    ///
+    /// # Returns
+    ///
+    /// true if the note changed
+    pub fn apply(&self, note: &mut Note) -> bool {
+        let start = if self.starts {
+            self.snap(note.start_tick)
+        } else {
+            note.start_tick
+        };
+        let end = if self.ends {
+            let end = self.snap(note.end_tick());
+            if end <= start {
+                start + self.grid_ticks()
+            } else {
+                end
+            }
+        } else {
+            start + note.duration_ticks
+        };
+
+        let changed = start != note.start_tick || end != note.end_tick();
+        note.start_tick = start;
+        note.duration_ticks = end - start;
+        changed
+    }
+}
+
+/// Quantizes a set of notes.
+///
+/// # Returns
+///
+/// The number of notes that moved or changed length
+pub fn quantize_notes<'a>(
+    notes: impl IntoIterator<Item = &'a mut Note>,
+    settings: &QuantizeSettings,
+) -> usize {
+    notes
+        .into_iter()
+        .map(|note| settings.apply(note))
+        .filter(|&changed| changed)
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn note(start: u32, duration: u32) -> Note {
+        Note::new(60, 100, start, duration)
+    }
+
+    #[test]
+    fn test_grid_ticks() {
+        let mut settings = QuantizeSettings::default();
+        assert_eq!(settings.grid_ticks(), 120);
+        settings.feel = GridFeel::Triplet;
+        assert_eq!(settings.grid_ticks(), 80);
+        settings.division = 4;
+        settings.feel = GridFeel::Dotted;
+        assert_eq!(settings.grid_ticks(), 720);
+        assert_eq!(settings.grid_label(), "1/4.");
+    }
+
+    #[test]
+    fn test_strength_and_window() {
+        let mut settings = QuantizeSettings::default();
+        let mut n = note(130, 100);
+        assert!(settings.apply(&mut n));
+        assert_eq!((n.start_tick, n.duration_ticks), (120, 100));
+
+        // Half strength moves halfway
+        settings.strength = 50;
+        let mut n = note(140, 100);
+        settings.apply(&mut n);
+        assert_eq!(n.start_tick, 130);
+
+        // Notes outside the window are left alone
+        settings.strength = 100;
+        settings.window = 25;
+        let mut n = note(170, 100);
+        assert!(!settings.apply(&mut n));
+        assert_eq!(n.start_tick, 170);
+    }
+
+    #[test]
+    fn test_swing_and_ends() {
+        let settings = QuantizeSettings {
+            division: 8,
+            swing: 50,
+            ends: true,
+            ..Default::default()
+        };
+        // Second eighth is delayed by a quarter of the grid (60 ticks)
+        let mut n = note(250, 100);
+        settings.apply(&mut n);
+        assert_eq!(n.start_tick, 300);
+        // An end snapping onto the start extends the note to one grid step
+        assert_eq!(n.end_tick(), 540);
+
+        // The second note already sits on (swung) grid lines
+        let mut notes = [note(5, 10), note(480, 300)];
+        assert_eq!(quantize_notes(notes.iter_mut(), &settings), 1);
+        assert_eq!((notes[0].start_tick, notes[0].duration_ticks), (0, 240));
+    }
+}
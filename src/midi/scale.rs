@@ -0,0 +1,264 @@
+//! Musical keys and scales.
+//!
+//! A scale is a root pitch class plus a set of allowed pitch classes (stored
+//! as a 12-bit mask, bit 0 = root). It is used to shade the piano roll, to
+//! snap editing to scale degrees, and maps to the MIDI KeySignature meta event
+//! for major and minor keys.
+
+use super::NOTE_NAMES;
+use serde::{Deserialize, Serialize};
+
+/// Scale types, with their interval masks relative to the root.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ScaleKind {
+    /// Ionian: W W H W W W H.
+    #[default]
+    Major,
+    /// Aeolian.
+    NaturalMinor,
+    /// Natural minor with a raised 7th.
+    HarmonicMinor,
+    /// Ascending melodic minor (raised 6th and 7th).
+    MelodicMinor,
+    /// Minor with a raised 6th.
+    Dorian,
+    /// Minor with a lowered 2nd.
+    Phrygian,
+    /// Major with a raised 4th.
+    Lydian,
+    /// Major with a lowered 7th.
+    Mixolydian,
+    /// Diminished mode (lowered 2nd and 5th).
+    Locrian,
+    /// Five-note major scale.
+    MajorPentatonic,
+    /// Five-note minor scale.
+    MinorPentatonic,
+    /// Minor pentatonic plus the flat 5th.
+    Blues,
+    /// User-defined pitch classes (see `Scale::custom_mask`).
+    Custom,
+}
+
+impl ScaleKind {
+    /// All scale kinds, in dialog order.
+    pub const ALL: [ScaleKind; 13] = [
+        ScaleKind::Major,
+        ScaleKind::NaturalMinor,
+        ScaleKind::HarmonicMinor,
+        ScaleKind::MelodicMinor,
+        ScaleKind::Dorian,
+        ScaleKind::Phrygian,
+        ScaleKind::Lydian,
+        ScaleKind::Mixolydian,
+        ScaleKind::Locrian,
+        ScaleKind::MajorPentatonic,
+        ScaleKind::MinorPentatonic,
+        ScaleKind::Blues,
+        ScaleKind::Custom,
+    ];
+
+    /// Returns the display name.
+    pub fn name(self) -> &'static str {
+        match self {
+            ScaleKind::Major => "major",
+            ScaleKind::NaturalMinor => "minor",
+            ScaleKind::HarmonicMinor => "harmonic minor",
+            ScaleKind::MelodicMinor => "melodic minor",
+            ScaleKind::Dorian => "dorian",
+            ScaleKind::Phrygian => "phrygian",
+            ScaleKind::Lydian => "lydian",
+            ScaleKind::Mixolydian => "mixolydian",
+            ScaleKind::Locrian => "locrian",
+            ScaleKind::MajorPentatonic => "major pentatonic",
+            ScaleKind::MinorPentatonic => "minor pentatonic",
+            ScaleKind::Blues => "blues",
+            ScaleKind::Custom => "custom",
+        }
+    }
+
+    /// Returns the interval mask (bit n = n semitones above the root), or
+    /// None for custom scales.
+    fn intervals(self) -> Option<&'static [u8]> {
+        Some(match self {
+            ScaleKind::Major => &[0, 2, 4, 5, 7, 9, 11],
+            ScaleKind::NaturalMinor => &[0, 2, 3, 5, 7, 8, 10],
+            ScaleKind::HarmonicMinor => &[0, 2, 3, 5, 7, 8, 11],
+            ScaleKind::MelodicMinor => &[0, 2, 3, 5, 7, 9, 11],
+            ScaleKind::Dorian => &[0, 2, 3, 5, 7, 9, 10],
+            ScaleKind::Phrygian => &[0, 1, 3, 5, 7, 8, 10],
+            ScaleKind::Lydian => &[0, 2, 4, 6, 7, 9, 11],
+            ScaleKind::Mixolydian => &[0, 2, 4, 5, 7, 9, 10],
+            ScaleKind::Locrian => &[0, 1, 3, 5, 6, 8, 10],
+            ScaleKind::MajorPentatonic => &[0, 2, 4, 7, 9],
+            ScaleKind::MinorPentatonic => &[0, 3, 5, 7, 10],
+            ScaleKind::Blues => &[0, 3, 5, 6, 7, 10],
+            ScaleKind::Custom => return None,
+        })
+    }
+}
+
+/// A key: root pitch class and scale, plus whether editing snaps to it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Scale {
+    /// Root pitch class (0 = C, 11 = B).
+    pub root: u8,
+    /// Scale type.
+    pub kind: ScaleKind,
+    /// Intervals for `ScaleKind::Custom` (bit n = n semitones above the root).
+    #[serde(default)]
+    pub custom_mask: u16,
+    /// Snap cursor movement, note placement, transposition and keyboard input to the scale.
+    #[serde(default)]
+    pub snap: bool,
+}
+
+impl Default for Scale {
+    fn default() -> Self {
+        Self::new(0, ScaleKind::Major)
+    }
+}
+
+impl Scale {
+    /// Creates a scale without snapping.
+    pub fn new(root: u8, kind: ScaleKind) -> Self {
+        Self {
+            root: root % 12,
+            kind,
+            custom_mask: 0b1010_1011_0101, // Major until edited
+            snap: false,
+        }
+    }
+
+    /// Returns the interval mask relative to the root (the root is always included).
+    pub fn mask(&self) -> u16 {
+        let mask = match self.kind.intervals() {
+            Some(intervals) => intervals.iter().fold(0u16, |m, &i| m | 1 << i),
+            None => self.custom_mask & 0x0FFF,
+        };
+        mask | 1
+    }
+
+    /// Returns true if a pitch is in the scale.
+    pub fn contains(&self, pitch: u8) -> bool {
+        let interval = (pitch as i16 - self.root as i16).rem_euclid(12);
+        self.mask() & (1 << interval) != 0
+    }
+
+    /// Returns true if a pitch is the root of the scale.
+    pub fn is_root(&self, pitch: u8) -> bool {
+        pitch % 12 == self.root
+    }
+
+    /// Snaps a pitch to the nearest scale pitch (ties go down).
+    pub fn snap(&self, pitch: u8) -> u8 {
+        (0..=6i16)
+            .flat_map(|d| [pitch as i16 - d, pitch as i16 + d])
+            .filter(|p| (0..=127).contains(p))
+            .find(|&p| self.contains(p as u8))
+            .unwrap_or(pitch as i16) as u8
+    }
+
+    /// Moves a pitch by scale degrees.
+    ///
+    /// A pitch outside the scale counts its nearest scale pitch in the
+    /// direction of travel as the first step.
+    ///
+    /// # Returns
+    ///
+    /// The new pitch, clamped to the MIDI range
+    pub fn step(&self, pitch: u8, degrees: i32) -> u8 {
+        let direction = degrees.signum() as i16;
+        let mut current = pitch as i16;
+        for _ in 0..degrees.unsigned_abs() {
+            let next = (1..=12)
+                .map(|d| current + d * direction)
+                .take_while(|p| (0..=127).contains(p))
+                .find(|&p| self.contains(p as u8));
+            match next {
+                Some(p) => current = p,
+                None => break,
+            }
+        }
+        current as u8
+    }
+
+    /// Returns the display name, e.g. "D dorian".
+    pub fn name(&self) -> String {
+        format!(
+            "{} {}",
+            NOTE_NAMES[self.root as usize % 12],
+            self.kind.name()
+        )
+    }
+
+    /// Returns the MIDI key signature (sharps/flats, is_minor) for major and
+    /// natural minor keys.
+    pub fn key_signature(&self) -> Option<(i8, bool)> {
+        // Position of each major key on the circle of fifths, preferring the
+        // usual spelling (e.g. Bb rather than A#)
+        const MAJOR_FIFTHS: [i8; 12] = [0, -5, 2, -3, 4, -1, 6, 1, -4, 3, -2, 5];
+        match self.kind {
+            ScaleKind::Major => Some((MAJOR_FIFTHS[self.root as usize], false)),
+            ScaleKind::NaturalMinor => {
+                let relative_major = (self.root + 3) % 12;
+                Some((MAJOR_FIFTHS[relative_major as usize], true))
+            }
+            _ => None,
+        }
+    }
+
+    /// Creates a scale from a MIDI key signature.
+    ///
+    /// # Arguments
+    ///
+    /// * `sharps` - Number of sharps (positive) or flats (negative), -7 to 7
+    /// * `minor` - Whether the key is minor
+    pub fn from_key_signature(sharps: i8, minor: bool) -> Self {
+        let major_root = (sharps as i16 * 7).rem_euclid(12) as u8;
+        if minor {
+            Self::new((major_root + 9) % 12, ScaleKind::NaturalMinor)
+        } else {
+            Self::new(major_root, ScaleKind::Major)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_contains_and_snap() {
+        let c_major = Scale::new(0, ScaleKind::Major);
+        assert!(c_major.contains(60));
+        assert!(!c_major.contains(61));
+        assert_eq!(c_major.snap(61), 60);
+        assert_eq!(c_major.snap(66), 65);
+
+        let a_blues = Scale::new(9, ScaleKind::Blues);
+        assert!(a_blues.contains(63)); // Eb, the blue note
+        assert!(!a_blues.contains(61));
+    }
+
+    #[test]
+    fn test_step_by_degrees() {
+        let c_major = Scale::new(0, ScaleKind::Major);
+        assert_eq!(c_major.step(60, 1), 62);
+        assert_eq!(c_major.step(64, 1), 65);
+        assert_eq!(c_major.step(60, -2), 57);
+        assert_eq!(c_major.step(61, 1), 62);
+        assert_eq!(c_major.step(127, 3), 127);
+    }
+
+    #[test]
+    fn test_key_signature_round_trip() {
+        let cases = [(0, false, 0), (-2, false, 10), (3, true, 6), (-1, true, 2)];
+        for (sharps, minor, root) in cases {
+            let scale = Scale::from_key_signature(sharps, minor);
+            assert_eq!(scale.root, root);
+            assert_eq!(scale.key_signature(), Some((sharps, minor)));
+        }
+        assert_eq!(Scale::new(2, ScaleKind::Dorian).key_signature(), None);
+    }
+}
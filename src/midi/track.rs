@@ -3,8 +3,16 @@
 //! A track contains a collection of notes assigned to a specific MIDI channel
 //! and instrument (program). Tracks can be muted, soloed, and have adjustable volume.
 
+use super::clip::Clip;
+use super::drums::DrumMap;
+use super::effects::NoteEffect;
+use super::groove::{apply_groove, humanize_notes, GrooveTemplate, HumanizeSettings};
+use super::lyrics::Lyric;
 use super::note::{Note, NoteId};
+use super::quantize::{quantize_notes, QuantizeSettings};
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::HashSet;
 use std::sync::atomic::{AtomicU64, Ordering};
 
 /// Global counter for generating unique track IDs.
@@ -37,6 +45,11 @@ impl Default for TrackId {
 ///
 /// Each track has its own instrument (program), channel, and mixing settings.
 /// Notes within a track are sorted by start time for efficient playback.
+///
+/// Adding, removing or reordering fields changes the `.oxm` layout:
+/// `#[serde(default)]` only helps JSON files, since bincode reads fields by
+/// position. Such changes need a new format version and a migration in
+/// `format.rs`.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Track {
     /// Unique identifier for this track.
@@ -65,6 +78,61 @@ pub struct Track {
 
     /// Collection of notes in this track, sorted by start_tick.
     notes: Vec<Note>,
+
+    /// Lyric syllables attached to this track, sorted by tick.
+    #[serde(default)]
+    lyrics: Vec<Lyric>,
+
+    /// Pattern clips placed on this track, sorted by start_tick.
+    #[serde(default)]
+    clips: Vec<Clip>,
+
+    /// Clip currently opened for editing. Its pattern notes are unpacked into
+    /// the track's own notes until the clip is closed again.
+    #[serde(default)]
+    pub open_clip: Option<Clip>,
+
+    /// Notes unpacked from the open clip's pattern or added while it is open.
+    /// Only these are stored back into the pattern when the clip is closed.
+    #[serde(default)]
+    open_clip_notes: Vec<NoteId>,
+
+    /// Note effects applied in order during playback and export.
+    #[serde(default)]
+    pub effects: Vec<NoteEffect>,
+
+    /// Drum map naming this track's pitches (None uses General MIDI on channel 10).
+    #[serde(default)]
+    pub drum_map: Option<DrumMap>,
+}
+
+/// Content copied out of a track range (see [`Track::copy_range`]).
+#[derive(Debug, Clone, Default)]
+pub struct TrackSlice {
+    /// Copied notes (with fresh IDs).
+    pub notes: Vec<Note>,
+    /// Copied lyric syllables.
+    pub lyrics: Vec<Lyric>,
+    /// Copied clips (still linked to the same patterns).
+    pub clips: Vec<Clip>,
+}
+
+impl TrackSlice {
+    /// Adds the copied content to a track, offset by a number of ticks.
+    pub fn paste_into(self, track: &mut Track, offset: u32) {
+        for mut note in self.notes {
+            note.start_tick += offset;
+            track.add_note(note);
+        }
+        for mut lyric in self.lyrics {
+            lyric.tick += offset;
+            track.set_lyric(lyric);
+        }
+        for mut clip in self.clips {
+            clip.start_tick += offset;
+            track.add_clip(clip);
+        }
+    }
 }
 
 impl Track {
@@ -89,6 +157,12 @@ impl Track {
             muted: false,
             solo: false,
             notes: Vec::new(),
+            lyrics: Vec::new(),
+            clips: Vec::new(),
+            open_clip: None,
+            open_clip_notes: Vec::new(),
+            effects: Vec::new(),
+            drum_map: None,
         }
     }
 
@@ -109,6 +183,36 @@ impl Track {
             muted: false,
             solo: false,
             notes: Vec::new(),
+            lyrics: Vec::new(),
+            clips: Vec::new(),
+            open_clip: None,
+            open_clip_notes: Vec::new(),
+            effects: Vec::new(),
+            drum_map: None,
+        }
+    }
+
+    /// Returns true if the track plays on the General MIDI drum channel.
+    pub fn is_drum(&self) -> bool {
+        self.channel == 9
+    }
+
+    /// Returns true if the track is percussion: on the drum channel or with its own drum map.
+    pub fn is_percussion(&self) -> bool {
+        self.is_drum() || self.drum_map.is_some()
+    }
+
+    /// Returns the drum map of a percussion track.
+    ///
+    /// # Returns
+    ///
+    /// The track's own map, the General MIDI map for drum tracks without one,
+    /// or None for melodic tracks
+    pub fn effective_drum_map(&self) -> Option<Cow<'_, DrumMap>> {
+        match &self.drum_map {
+            Some(map) => Some(Cow::Borrowed(map)),
+            None if self.is_drum() => Some(Cow::Owned(DrumMap::general_midi())),
+            None => None,
         }
     }
 
@@ -130,6 +234,9 @@ impl Track {
             .binary_search_by_key(&note.start_tick, |n| n.start_tick)
             .unwrap_or_else(|pos| pos);
         self.notes.insert(pos, note);
+        if self.open_clip.is_some() && !self.open_clip_notes.contains(&id) {
+            self.open_clip_notes.push(id);
+        }
         id
     }
 
@@ -173,7 +280,6 @@ impl Track {
     }
 
     /// Returns a reference to a note by its ID.
-    #[allow(dead_code)]
     pub fn get_note(&self, id: NoteId) -> Option<&Note> {
         self.notes.iter().find(|n| n.id == id)
     }
@@ -184,6 +290,11 @@ impl Track {
         self.notes.iter_mut().find(|n| n.id == id)
     }
 
+    /// Restores start order after note positions were edited in place.
+    pub fn sort_notes(&mut self) {
+        self.notes.sort_by_key(|n| n.start_tick);
+    }
+
     /// Returns all notes in the track (sorted by start_tick).
     pub fn notes(&self) -> &[Note] {
         &self.notes
@@ -218,6 +329,23 @@ impl Track {
             .filter(move |n| n.overlaps_range(start, end))
     }
 
+    /// Returns the first note of a pitch that starts within a tick range.
+    ///
+    /// Used by the step sequencer, where a step is on if a note starts in it.
+    ///
+    /// # Arguments
+    ///
+    /// * `pitch` - MIDI pitch (drum instrument)
+    /// * `start` - Start tick of the step (inclusive)
+    /// * `end` - End tick of the step (exclusive)
+    pub fn note_starting_in(&self, pitch: u8, start: u32, end: u32) -> Option<&Note> {
+        let first_idx = self.notes.partition_point(|n| n.start_tick < start);
+        self.notes[first_idx..]
+            .iter()
+            .take_while(|n| n.start_tick < end)
+            .find(|n| n.pitch == pitch)
+    }
+
     /// Returns notes that are active at a specific tick.
     #[allow(dead_code)]
     pub fn notes_at_tick(&self, tick: u32) -> impl Iterator<Item = &Note> {
@@ -231,7 +359,6 @@ impl Track {
     }
 
     /// Returns the number of notes in the track.
-    #[allow(dead_code)]
     pub fn note_count(&self) -> usize {
         self.notes.len()
     }
@@ -265,6 +392,150 @@ impl Track {
         self.notes.sort_by_key(|n| n.start_tick);
     }
 
+    /// Quantizes notes with full quantize settings.
+    ///
+    /// # Arguments
+    ///
+    /// * `ids` - Notes to quantize, or None for every note in the track
+    /// * `settings` - Grid, strength, swing, window and start/end options
+    ///
+    /// # Returns
+    ///
+    /// The number of notes that changed
+    pub fn quantize_with(
+        &mut self,
+        ids: Option<&HashSet<NoteId>>,
+        settings: &QuantizeSettings,
+    ) -> usize {
+        let changed = quantize_notes(
+            self.notes
+                .iter_mut()
+                .filter(|n| ids.is_none_or(|ids| ids.contains(&n.id))),
+            settings,
+        );
+        self.notes.sort_by_key(|n| n.start_tick);
+        changed
+    }
+
+    /// Adds random timing, velocity and length offsets to notes.
+    ///
+    /// # Arguments
+    ///
+    /// * `ids` - Notes to humanize, or None for every note in the track
+    /// * `settings` - Offset ranges and seed
+    ///
+    /// # Returns
+    ///
+    /// The number of notes changed
+    pub fn humanize(
+        &mut self,
+        ids: Option<&HashSet<NoteId>>,
+        settings: &HumanizeSettings,
+    ) -> usize {
+        let count = humanize_notes(
+            self.notes
+                .iter_mut()
+                .filter(|n| ids.is_none_or(|ids| ids.contains(&n.id))),
+            settings,
+        );
+        self.notes.sort_by_key(|n| n.start_tick);
+        count
+    }
+
+    /// Applies a groove template to notes.
+    ///
+    /// # Arguments
+    ///
+    /// * `ids` - Notes to change, or None for every note in the track
+    /// * `template` - The groove to apply
+    /// * `strength` - How strongly to apply it (0-100%)
+    ///
+    /// # Returns
+    ///
+    /// The number of notes changed
+    pub fn apply_groove(
+        &mut self,
+        ids: Option<&HashSet<NoteId>>,
+        template: &GrooveTemplate,
+        strength: u32,
+    ) -> usize {
+        let count = apply_groove(
+            self.notes
+                .iter_mut()
+                .filter(|n| ids.is_none_or(|ids| ids.contains(&n.id))),
+            template,
+            strength,
+        );
+        self.notes.sort_by_key(|n| n.start_tick);
+        count
+    }
+
+    /// Replaces notes with the output of a note effect (destructive).
+    ///
+    /// # Arguments
+    ///
+    /// * `ids` - Notes fed to the effect, or None for every note in the track
+    /// * `effect` - The arpeggiator or echo to render
+    ///
+    /// # Returns
+    ///
+    /// The IDs of the generated notes
+    pub fn render_effect(
+        &mut self,
+        ids: Option<&HashSet<NoteId>>,
+        effect: &NoteEffect,
+    ) -> Vec<NoteId> {
+        let (source, kept): (Vec<Note>, Vec<Note>) = std::mem::take(&mut self.notes)
+            .into_iter()
+            .partition(|n| ids.is_none_or(|ids| ids.contains(&n.id)));
+        let generated = effect.process(&source);
+        let new_ids = generated.iter().map(|n| n.id).collect();
+        self.notes = kept;
+        self.notes.extend(generated);
+        self.notes.sort_by_key(|n| n.start_tick);
+        new_ids
+    }
+
+    /// Removes the notes with the given IDs and adds others, keeping notes sorted.
+    pub fn patch_notes(&mut self, remove: &HashSet<NoteId>, add: &[Note]) {
+        self.notes.retain(|n| !remove.contains(&n.id));
+        self.notes.extend_from_slice(add);
+        self.notes.sort_by_key(|n| n.start_tick);
+    }
+
+    /// Returns a copy of the track's settings, lyrics and clips without its notes.
+    pub fn without_notes(&self) -> Track {
+        Track {
+            id: self.id,
+            name: self.name.clone(),
+            channel: self.channel,
+            program: self.program,
+            volume: self.volume,
+            pan: self.pan,
+            muted: self.muted,
+            solo: self.solo,
+            notes: Vec::new(),
+            lyrics: self.lyrics.clone(),
+            clips: self.clips.clone(),
+            open_clip: self.open_clip.clone(),
+            open_clip_notes: self.open_clip_notes.clone(),
+            effects: self.effects.clone(),
+            drum_map: self.drum_map.clone(),
+        }
+    }
+
+    /// Overwrites notes with the given versions (matched by ID).
+    ///
+    /// Used to restore notes after a preview. Notes that no longer exist are ignored.
+    pub fn replace_notes(&mut self, notes: &[Note]) {
+        for note in notes {
+            if let Some(existing) = self.notes.iter_mut().find(|n| n.id == note.id) {
+                *existing = note.clone();
+            }
+        }
+        self.notes.sort_by_key(|n| n.start_tick);
+    }
+
     /// Transposes all notes by a number of semitones.
     ///
     /// # Arguments
@@ -284,6 +555,234 @@ impl Track {
         }
         failed
     }
+
+    /// Returns the lyric syllables of the track (sorted by tick).
+    pub fn lyrics(&self) -> &[Lyric] {
+        &self.lyrics
+    }
+
+    /// Returns true if the track has any lyrics.
+    pub fn has_lyrics(&self) -> bool {
+        !self.lyrics.is_empty()
+    }
+
+    /// Sets the lyric syllable at a tick, replacing any existing one there.
+    ///
+    /// # Arguments
+    ///
+    /// * `lyric` - The syllable to store
+    pub fn set_lyric(&mut self, lyric: Lyric) {
+        match self.lyrics.binary_search_by_key(&lyric.tick, |l| l.tick) {
+            Ok(pos) => self.lyrics[pos] = lyric,
+            Err(pos) => self.lyrics.insert(pos, lyric),
+        }
+    }
+
+    /// Returns the lyric syllable at an exact tick.
+    pub fn lyric_at(&self, tick: u32) -> Option<&Lyric> {
+        self.lyrics
+            .binary_search_by_key(&tick, |l| l.tick)
+            .ok()
+            .map(|pos| &self.lyrics[pos])
+    }
+
+    /// Removes the lyric syllable at an exact tick.
+    ///
+    /// # Returns
+    ///
+    /// The removed syllable, or None if there was none at that tick
+    pub fn remove_lyric_at(&mut self, tick: u32) -> Option<Lyric> {
+        let pos = self.lyrics.binary_search_by_key(&tick, |l| l.tick).ok()?;
+        Some(self.lyrics.remove(pos))
+    }
+
+    /// Shifts the notes, lyrics and clips that start within a tick range.
+    ///
+    /// # Arguments
+    ///
+    /// * `start` - Start tick (inclusive)
+    /// * `end` - End tick (exclusive)
+    /// * `delta` - Number of ticks to shift by (clamped at tick 0)
+    pub fn shift_range(&mut self, start: u32, end: u32, delta: i64) {
+        let shift = |tick: u32| (tick as i64 + delta).clamp(0, u32::MAX as i64) as u32;
+        for note in &mut self.notes {
+            if (start..end).contains(&note.start_tick) {
+                note.start_tick = shift(note.start_tick);
+            }
+        }
+        for lyric in &mut self.lyrics {
+            if (start..end).contains(&lyric.tick) {
+                lyric.tick = shift(lyric.tick);
+            }
+        }
+        for clip in self.clips.iter_mut().chain(&mut self.open_clip) {
+            if (start..end).contains(&clip.start_tick) {
+                clip.start_tick = shift(clip.start_tick);
+            }
+        }
+        self.notes.sort_by_key(|n| n.start_tick);
+        self.lyrics.sort_by_key(|l| l.tick);
+        self.clips.sort_by_key(|c| c.start_tick);
+    }
+
+    /// Removes the notes, lyrics and clips that start within a tick range.
+    ///
+    /// # Returns
+    ///
+    /// The number of notes removed
+    pub fn remove_range(&mut self, start: u32, end: u32) -> usize {
+        let before = self.notes.len();
+        self.notes.retain(|n| !(start..end).contains(&n.start_tick));
+        self.lyrics.retain(|l| !(start..end).contains(&l.tick));
+        self.clips.retain(|c| !(start..end).contains(&c.start_tick));
+        self.drop_open_clip_in(start, end);
+        before - self.notes.len()
+    }
+
+    /// Inserts empty time at a tick, pushing everything from there on later.
+    ///
+    /// Notes sounding across the insertion point are split: the part after
+    /// the point moves with the rest of the track.
+    ///
+    /// # Arguments
+    ///
+    /// * `at` - Tick where the time is inserted
+    /// * `length` - Number of ticks to insert
+    pub fn insert_time(&mut self, at: u32, length: u32) {
+        let mut tails = Vec::new();
+        for note in &mut self.notes {
+            if note.start_tick < at && note.end_tick() > at {
+                let mut tail = note.duplicate();
+                tail.start_tick = at;
+                tail.duration_ticks = note.end_tick() - at;
+                note.duration_ticks = at - note.start_tick;
+                tails.push(tail);
+            }
+        }
+        self.notes.extend(tails);
+        self.shift_range(at, u32::MAX, length as i64);
+    }
+
+    /// Removes a span of time, pulling everything after it earlier.
+    ///
+    /// Notes inside the span are removed and notes straddling its edges are
+    /// trimmed so only the parts outside the span remain. Lyrics and clips
+    /// starting inside the span are removed.
+    ///
+    /// # Arguments
+    ///
+    /// * `start` - Start tick (inclusive)
+    /// * `end` - End tick (exclusive)
+    pub fn delete_time(&mut self, start: u32, end: u32) {
+        if end <= start {
+            return;
+        }
+        let length = end - start;
+        self.notes.retain_mut(|note| {
+            let note_end = note.end_tick();
+            if note_end <= start || note.start_tick >= end {
+                return true;
+            }
+            if note.start_tick >= start && note_end <= end {
+                return false;
+            }
+            if note.start_tick < start {
+                // Keep the head (and the tail, if the note spans the whole range)
+                note.duration_ticks -= note_end.min(end) - start;
+            } else {
+                // Keep the tail, which lands at the start of the range
+                note.start_tick = end;
+                note.duration_ticks = note_end - end;
+            }
+            true
+        });
+        self.lyrics.retain(|l| !(start..end).contains(&l.tick));
+        self.clips.retain(|c| !(start..end).contains(&c.start_tick));
+        self.drop_open_clip_in(start, end);
+        self.shift_range(end, u32::MAX, -(length as i64));
+    }
+
+    /// Copies the notes, lyrics and clips that start within a tick range.
+    ///
+    /// Copied notes get fresh IDs so they can be added back to the track.
+    pub fn copy_range(&self, start: u32, end: u32) -> TrackSlice {
+        let in_range = |tick: u32| (start..end).contains(&tick);
+        TrackSlice {
+            notes: self
+                .notes
+                .iter()
+                .filter(|n| in_range(n.start_tick))
+                .map(|n| n.duplicate())
+                .collect(),
+            lyrics: self
+                .lyrics
+                .iter()
+                .filter(|l| in_range(l.tick))
+                .cloned()
+                .collect(),
+            clips: self
+                .clips
+                .iter()
+                .filter(|c| in_range(c.start_tick))
+                .cloned()
+                .collect(),
+        }
+    }
+
+    /// Returns the clips placed on the track (sorted by start_tick).
+    pub fn clips(&self) -> &[Clip] {
+        &self.clips
+    }
+
+    /// Places a clip on the track.
+    ///
+    /// # Returns
+    ///
+    /// The index of the clip in the sorted clip list
+    pub fn add_clip(&mut self, clip: Clip) -> usize {
+        let pos = self
+            .clips
+            .partition_point(|c| c.start_tick <= clip.start_tick);
+        self.clips.insert(pos, clip);
+        pos
+    }
+
+    /// Removes a clip by index.
+    pub fn remove_clip(&mut self, index: usize) -> Option<Clip> {
+        (index < self.clips.len()).then(|| self.clips.remove(index))
+    }
+
+    /// Takes the open clip along with the IDs of the notes that belong to it.
+    pub fn take_open_clip(&mut self) -> Option<(Clip, Vec<NoteId>)> {
+        let clip = self.open_clip.take()?;
+        Some((clip, std::mem::take(&mut self.open_clip_notes)))
+    }
+
+    /// Drops the open clip if it starts within a tick range that was removed.
+    fn drop_open_clip_in(&mut self, start: u32, end: u32) {
+        if self
+            .open_clip
+            .as_ref()
+            .is_some_and(|c| (start..end).contains(&c.start_tick))
+        {
+            self.open_clip = None;
+            self.open_clip_notes.clear();
+        }
+    }
+
+    /// Returns the first note start tick after a position.
+    ///
+    /// Used by lyric entry to advance one syllable per note (chords count once).
+    pub fn next_note_start_after(&self, tick: u32) -> Option<u32> {
+        let idx = self.notes.partition_point(|n| n.start_tick <= tick);
+        self.notes.get(idx).map(|n| n.start_tick)
+    }
+
+    /// Returns the closest note start tick before a position.
+    pub fn prev_note_start_before(&self, tick: u32) -> Option<u32> {
+        let idx = self.notes.partition_point(|n| n.start_tick < tick);
+        idx.checked_sub(1).map(|i| self.notes[i].start_tick)
+    }
 }
 
 impl Default for Track {
@@ -330,6 +829,18 @@ mod tests {
         assert_eq!(in_range.len(), 2); // First two notes overlap
     }
 
+    #[test]
+    fn test_note_starting_in() {
+        let mut track = Track::new_drum_track("Drums");
+        track.create_note(36, 100, 0, 120);
+        track.create_note(42, 100, 120, 120);
+
+        assert_eq!(track.note_starting_in(42, 120, 240).unwrap().pitch, 42);
+        assert!(track.note_starting_in(36, 120, 240).is_none());
+        // A note sounding into the step but starting earlier doesn't count
+        assert!(track.note_starting_in(36, 60, 120).is_none());
+    }
+
     #[test]
     fn test_duration() {
         let mut track = Track::new("Test", 0);
@@ -341,4 +852,43 @@ mod tests {
         track.create_note(62, 100, 960, 480);
         assert_eq!(track.duration_ticks(), 1440);
     }
+
+    #[test]
+    fn test_lyrics_sorted_and_replaced() {
+        let mut track = Track::new("Vocals", 0);
+        track.set_lyric(Lyric::new(480, "lo", false));
+        track.set_lyric(Lyric::new(0, "Hel-", true));
+        track.set_lyric(Lyric::new(480, "low", false));
+
+        assert_eq!(track.lyrics().len(), 2);
+        assert_eq!(track.lyrics()[0].tick, 0);
+        assert_eq!(track.lyric_at(480).unwrap().text, "low");
+
+        assert!(track.remove_lyric_at(480).is_some());
+        assert!(track.lyric_at(480).is_none());
+    }
+
+    #[test]
+    fn test_insert_and_delete_time() {
+        let mut track = Track::new("Test", 0);
+        track.create_note(60, 100, 0, 960); // straddles 480
+        track.create_note(62, 100, 960, 480);
+
+        track.insert_time(480, 1920);
+        let spans: Vec<_> = track
+            .notes()
+            .iter()
+            .map(|n| (n.start_tick, n.duration_ticks))
+            .collect();
+        assert_eq!(spans, vec![(0, 480), (2400, 480), (2880, 480)]);
+
+        // Delete from the middle of the first note into the second
+        track.delete_time(240, 2640);
+        let spans: Vec<_> = track
+            .notes()
+            .iter()
+            .map(|n| (n.start_tick, n.duration_ticks))
+            .collect();
+        assert_eq!(spans, vec![(0, 240), (240, 240), (480, 480)]);
+    }
 }
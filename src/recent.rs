@@ -0,0 +1,194 @@
+//! Recently opened files.
+//!
+//! The list is kept in `recent.json` in the user's config directory
+//! (`$XDG_CONFIG_HOME/miditui`, falling back to `~/.config/miditui`), newest
+//! first, and survives restarts so projects can be reopened without the
+//! file browser. It also remembers the last SoundFont loaded, which is
+//! offered again on the next start.
+
+use crate::config::{config_dir, unix_now};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Maximum number of files remembered.
+pub const MAX_RECENT_FILES: usize = 20;
+
+/// File name of the recent-files list inside the config directory.
+const RECENT_FILE_NAME: &str = "recent.json";
+
+/// A file in the recent-files list.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecentFile {
+    /// Absolute path of the file.
+    pub path: PathBuf,
+    /// When the file was last opened or saved (seconds since the Unix epoch).
+    pub opened_at: u64,
+}
+
+impl RecentFile {
+    /// Returns a short label for the file type, based on the extension.
+    pub fn kind(&self) -> &'static str {
+        match self.path.extension().and_then(|e| e.to_str()) {
+            Some("oxm") => "Project",
+            Some("json") => "JSON",
+            Some("mid") | Some("midi") => "MIDI",
+            Some("kar") => "Karaoke",
+            _ => "File",
+        }
+    }
+
+    /// Returns true if the file is still on disk.
+    pub fn exists(&self) -> bool {
+        self.path.is_file()
+    }
+}
+
+/// The recent-files list, newest first.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecentFiles {
+    /// Remembered files, newest first.
+    pub files: Vec<RecentFile>,
+    /// SoundFont loaded most recently.
+    #[serde(default)]
+    pub soundfont: Option<PathBuf>,
+    /// Where the list is stored (None keeps it in memory only).
+    #[serde(skip)]
+    store: Option<PathBuf>,
+}
+
+impl RecentFiles {
+    /// Loads the list from the config directory.
+    ///
+    /// A missing or unreadable list starts empty; it is still saved back to
+    /// the same place when files are added.
+    pub fn load() -> Self {
+        let Some(store) = config_dir().map(|dir| dir.join(RECENT_FILE_NAME)) else {
+            return Self::default();
+        };
+        let mut recent: Self = std::fs::read_to_string(&store)
+            .ok()
+            .and_then(|text| serde_json::from_str(&text).ok())
+            .unwrap_or_default();
+        recent.store = Some(store);
+        recent
+    }
+
+    /// Records that `path` was opened now and saves the list.
+    pub fn add(&mut self, path: &Path) {
+        self.add_at(path, unix_now());
+        if let Err(e) = self.save() {
+            tracing::warn!("Failed to save recent files: {}", e);
+        }
+    }
+
+    /// Records that `path` was opened at `opened_at`, moving it to the front.
+    fn add_at(&mut self, path: &Path, opened_at: u64) {
+        let path = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        self.files.retain(|file| file.path != path);
+        self.files.insert(0, RecentFile { path, opened_at });
+        self.files.truncate(MAX_RECENT_FILES);
+    }
+
+    /// Records the SoundFont in use and saves the list if it changed.
+    pub fn set_soundfont(&mut self, path: &Path) {
+        let path = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        if self.soundfont.as_ref() == Some(&path) {
+            return;
+        }
+        self.soundfont = Some(path);
+        if let Err(e) = self.save() {
+            tracing::warn!("Failed to save recent files: {}", e);
+        }
+    }
+
+    /// Forgets the file at `index` and saves the list.
+    pub fn remove(&mut self, index: usize) {
+        if index < self.files.len() {
+            self.files.remove(index);
+            if let Err(e) = self.save() {
+                tracing::warn!("Failed to save recent files: {}", e);
+            }
+        }
+    }
+
+    /// Writes the list to its store, creating the config directory if needed.
+    fn save(&self) -> std::io::Result<()> {
+        let Some(store) = &self.store else {
+            return Ok(());
+        };
+        if let Some(dir) = store.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let json = serde_json::to_string_pretty(self).map_err(std::io::Error::other)?;
+        std::fs::write(store, json)
+    }
+}
+
+/// Formats how long ago a Unix timestamp was, e.g. "5 min ago" or "3 days ago".
+pub fn format_age(then: u64, now: u64) -> String {
+    let secs = now.saturating_sub(then);
+    match secs {
+        0..60 => "just now".to_string(),
+        60..3600 => format!("{} min ago", secs / 60),
+        3600..86400 => format!("{} h ago", secs / 3600),
+        86400..172800 => "yesterday".to_string(),
+        _ => format!("{} days ago", secs / 86400),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add_moves_to_front_and_caps() {
+        let mut recent = RecentFiles::default();
+        for i in 0..MAX_RECENT_FILES + 5 {
+            recent.add_at(Path::new(&format!("/no/such/song{}.mid", i)), i as u64);
+        }
+        assert_eq!(recent.files.len(), MAX_RECENT_FILES);
+        assert_eq!(
+            recent.files[0].path,
+            PathBuf::from(format!("/no/such/song{}.mid", MAX_RECENT_FILES + 4))
+        );
+
+        // Reopening moves the entry to the front without duplicating it
+        recent.add_at(Path::new("/no/such/song10.mid"), 100);
+        assert_eq!(recent.files.len(), MAX_RECENT_FILES);
+        assert_eq!(recent.files[0].path, PathBuf::from("/no/such/song10.mid"));
+        assert_eq!(recent.files[0].opened_at, 100);
+        assert_eq!(
+            recent
+                .files
+                .iter()
+                .filter(|f| f.path == Path::new("/no/such/song10.mid"))
+                .count(),
+            1
+        );
+        assert_eq!(recent.files[0].kind(), "MIDI");
+        assert!(!recent.files[0].exists());
+    }
+
+    #[test]
+    fn test_soundfont_is_remembered() {
+        let mut recent = RecentFiles::default();
+        recent.set_soundfont(Path::new("/no/such/font.sf2"));
+        let json = serde_json::to_string(&recent).unwrap();
+        let reloaded: RecentFiles = serde_json::from_str(&json).unwrap();
+        assert_eq!(reloaded.soundfont, Some(PathBuf::from("/no/such/font.sf2")));
+
+        // Lists saved before the field existed still load
+        let old: RecentFiles = serde_json::from_str(r#"{"files":[]}"#).unwrap();
+        assert_eq!(old.soundfont, None);
+    }
+
+    #[test]
+    fn test_format_age() {
+        assert_eq!(format_age(1000, 1010), "just now");
+        assert_eq!(format_age(0, 300), "5 min ago");
+        assert_eq!(format_age(0, 7200), "2 h ago");
+        assert_eq!(format_age(0, 90000), "yesterday");
+        assert_eq!(format_age(0, 86400 * 3), "3 days ago");
+        assert_eq!(format_age(500, 100), "just now");
+    }
+}
@@ -0,0 +1,717 @@
+//! Color themes.
+//!
+//! Every color the editor views and dialogs draw with is a named role in
+//! [`Theme`] (grid, beat and measure lines, notes, selection, playhead,
+//! ruler, track colors, text, ...). There are three built-in themes: `dark` (the default),
+//! `light`, and `ansi`, which only uses the 16 terminal colors and so
+//! follows the terminal's own palette. A user theme is a JSON file in the
+//! `themes` folder of the config directory, named after the theme; it
+//! starts from a built-in theme and overrides any roles it lists:
+//!
+//! ```json
+//! { "base": "light", "playhead": "#d03030", "note_selected": "magenta" }
+//! ```
+//!
+//! Colors are names (`"dark gray"`), `#rrggbb`, or a 256-color index. When
+//! the terminal doesn't announce truecolor support, RGB colors are mapped to
+//! the nearest of the 256 indexed colors.
+
+use ratatui::style::Color;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::path::PathBuf;
+
+/// Name of the theme used when none is configured.
+pub const DEFAULT_THEME: &str = "dark";
+
+/// Names of the built-in themes.
+pub const BUILTIN_THEMES: [&str; 3] = ["dark", "light", "ansi"];
+
+/// Named colors for everything the editor views draw.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Theme {
+    /// Border of an unfocused panel.
+    #[serde(with = "color")]
+    pub border: Color,
+    /// Border of the focused panel.
+    #[serde(with = "color")]
+    pub border_focused: Color,
+    /// Background of the ruler's label column and the velocity lane labels.
+    #[serde(with = "color")]
+    pub panel_bg: Color,
+    /// Measure numbers on the time ruler.
+    #[serde(with = "color")]
+    pub ruler_measure: Color,
+    /// Beat ticks on the time ruler.
+    #[serde(with = "color")]
+    pub ruler_beat: Color,
+    /// Piano roll background on white-key rows.
+    #[serde(with = "color")]
+    pub grid_bg: Color,
+    /// Piano roll background on black-key rows.
+    #[serde(with = "color")]
+    pub grid_shaded_bg: Color,
+    /// Piano roll background on rows outside the project scale.
+    #[serde(with = "color")]
+    pub grid_out_of_scale_bg: Color,
+    /// Piano roll background on rows of the scale's root note.
+    #[serde(with = "color")]
+    pub grid_root_bg: Color,
+    /// Dots between beats in the piano roll, and faint timeline marks.
+    #[serde(with = "color")]
+    pub grid_dot: Color,
+    /// Beat lines.
+    #[serde(with = "color")]
+    pub beat_line: Color,
+    /// Measure lines.
+    #[serde(with = "color")]
+    pub measure_line: Color,
+    /// White piano keys.
+    #[serde(with = "color")]
+    pub key_white: Color,
+    /// The C keys, which stand out among the white keys.
+    #[serde(with = "color")]
+    pub key_c: Color,
+    /// Black piano keys.
+    #[serde(with = "color")]
+    pub key_black: Color,
+    /// Labels on white piano keys.
+    #[serde(with = "color")]
+    pub key_white_text: Color,
+    /// Labels on black piano keys.
+    #[serde(with = "color")]
+    pub key_black_text: Color,
+    /// Text drawn on notes.
+    #[serde(with = "color")]
+    pub note_text: Color,
+    /// Notes at the lowest velocity; louder notes blend towards `note_loud`.
+    #[serde(with = "color")]
+    pub note_soft: Color,
+    /// Notes at the highest velocity.
+    #[serde(with = "color")]
+    pub note_loud: Color,
+    /// Selected notes.
+    #[serde(with = "color")]
+    pub note_selected: Color,
+    /// Notes sounding during playback.
+    #[serde(with = "color")]
+    pub note_playing: Color,
+    /// The note just added.
+    #[serde(with = "color")]
+    pub note_recent: Color,
+    /// The edit cursor.
+    #[serde(with = "color")]
+    pub cursor: Color,
+    /// Background of the cursor cell.
+    #[serde(with = "color")]
+    pub cursor_bg: Color,
+    /// Background of ghost notes from other tracks.
+    #[serde(with = "color")]
+    pub ghost_bg: Color,
+    /// The playhead and Insert mode recording line.
+    #[serde(with = "color")]
+    pub playhead: Color,
+    /// Background of the step or slot under the playhead.
+    #[serde(with = "color")]
+    pub playhead_bg: Color,
+    /// Arrows pointing at notes outside the view.
+    #[serde(with = "color")]
+    pub indicator: Color,
+    /// Background of the off-screen note arrows.
+    #[serde(with = "color")]
+    pub indicator_bg: Color,
+    /// Background of the current row in lists.
+    #[serde(with = "color")]
+    pub current_row_bg: Color,
+    /// Background of every other beat group in the step sequencer.
+    #[serde(with = "color")]
+    pub alternate_bg: Color,
+    /// Background of the velocity lane.
+    #[serde(with = "color")]
+    pub lane_bg: Color,
+    /// Background of the velocity lane column under the cursor.
+    #[serde(with = "color")]
+    pub lane_cursor_bg: Color,
+    /// Clips in the project timeline.
+    #[serde(with = "color")]
+    pub clip_bg: Color,
+    /// Clips on muted tracks.
+    #[serde(with = "color")]
+    pub clip_muted_bg: Color,
+    /// Text: values, names and noteheads.
+    #[serde(with = "color")]
+    pub text: Color,
+    /// Less important text, such as unselected names and staff lines.
+    #[serde(with = "color")]
+    pub text_secondary: Color,
+    /// Labels, key hint descriptions and placeholders.
+    #[serde(with = "color")]
+    pub text_dim: Color,
+    /// Text drawn on a colored background, such as a button.
+    #[serde(with = "color")]
+    pub text_inverse: Color,
+    /// Key hints, status messages and other highlights.
+    #[serde(with = "color")]
+    pub accent: Color,
+    /// Playing, sounding, solo-active and confirm states.
+    #[serde(with = "color")]
+    pub active: Color,
+    /// Mute, stop, loud volumes and destructive actions.
+    #[serde(with = "color")]
+    pub alert: Color,
+    /// Secondary values such as pan, the chord and paths.
+    #[serde(with = "color")]
+    pub info: Color,
+    /// Folders in the file browsers and the normal-mode indicator.
+    #[serde(with = "color")]
+    pub link: Color,
+    /// Colors that tell tracks apart, by track position.
+    #[serde(with = "colors")]
+    pub track_colors: Vec<Color>,
+    /// Marker colors, by the marker's color index.
+    #[serde(with = "colors")]
+    pub marker_colors: Vec<Color>,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::dark()
+    }
+}
+
+impl Theme {
+    /// The default theme, for dark terminals.
+    pub fn dark() -> Self {
+        Self {
+            border: Color::Gray,
+            border_focused: Color::Cyan,
+            panel_bg: Color::Rgb(20, 20, 20),
+            ruler_measure: Color::Yellow,
+            ruler_beat: Color::DarkGray,
+            grid_bg: Color::Rgb(40, 40, 40),
+            grid_shaded_bg: Color::Rgb(30, 30, 30),
+            grid_out_of_scale_bg: Color::Rgb(22, 22, 22),
+            grid_root_bg: Color::Rgb(40, 45, 60),
+            grid_dot: Color::Rgb(60, 60, 60),
+            beat_line: Color::DarkGray,
+            measure_line: Color::White,
+            key_white: Color::Gray,
+            key_c: Color::White,
+            key_black: Color::DarkGray,
+            key_white_text: Color::Black,
+            key_black_text: Color::White,
+            note_text: Color::Black,
+            note_soft: Color::Rgb(30, 110, 60),
+            note_loud: Color::Rgb(250, 170, 30),
+            note_selected: Color::Magenta,
+            note_playing: Color::White,
+            note_recent: Color::Blue,
+            cursor: Color::Cyan,
+            cursor_bg: Color::DarkGray,
+            ghost_bg: Color::Rgb(30, 30, 30),
+            playhead: Color::Red,
+            playhead_bg: Color::Rgb(20, 60, 20),
+            indicator: Color::Yellow,
+            indicator_bg: Color::Rgb(60, 50, 0),
+            current_row_bg: Color::Rgb(40, 40, 60),
+            alternate_bg: Color::Rgb(30, 30, 36),
+            lane_bg: Color::Rgb(25, 25, 25),
+            lane_cursor_bg: Color::Rgb(35, 35, 50),
+            clip_bg: Color::Rgb(45, 45, 80),
+            clip_muted_bg: Color::Rgb(50, 50, 50),
+            text: Color::White,
+            text_secondary: Color::Gray,
+            text_dim: Color::DarkGray,
+            text_inverse: Color::Black,
+            accent: Color::Yellow,
+            active: Color::Green,
+            alert: Color::Red,
+            info: Color::Cyan,
+            link: Color::Blue,
+            track_colors: vec![
+                Color::Blue,
+                Color::Green,
+                Color::Yellow,
+                Color::Magenta,
+                Color::Cyan,
+                Color::Red,
+                Color::LightBlue,
+                Color::LightGreen,
+            ],
+            marker_colors: vec![
+                Color::LightYellow,
+                Color::LightCyan,
+                Color::LightMagenta,
+                Color::LightGreen,
+                Color::LightRed,
+                Color::LightBlue,
+            ],
+        }
+    }
+
+    /// A theme for light terminal backgrounds.
+    pub fn light() -> Self {
+        Self {
+            border: Color::DarkGray,
+            border_focused: Color::Blue,
+            panel_bg: Color::Rgb(225, 225, 225),
+            ruler_measure: Color::Rgb(150, 100, 0),
+            ruler_beat: Color::Rgb(150, 150, 150),
+            grid_bg: Color::Rgb(245, 245, 245),
+            grid_shaded_bg: Color::Rgb(228, 228, 228),
+            grid_out_of_scale_bg: Color::Rgb(212, 212, 212),
+            grid_root_bg: Color::Rgb(215, 225, 245),
+            grid_dot: Color::Rgb(200, 200, 200),
+            beat_line: Color::Rgb(160, 160, 160),
+            measure_line: Color::Rgb(60, 60, 60),
+            key_white: Color::Rgb(235, 235, 235),
+            key_c: Color::Rgb(255, 255, 255),
+            key_black: Color::Rgb(70, 70, 70),
+            key_white_text: Color::Rgb(0, 0, 0),
+            key_black_text: Color::Rgb(255, 255, 255),
+            note_text: Color::Rgb(0, 0, 0),
+            note_soft: Color::Rgb(120, 200, 140),
+            note_loud: Color::Rgb(240, 140, 20),
+            note_selected: Color::Rgb(210, 100, 210),
+            note_playing: Color::Rgb(255, 225, 80),
+            note_recent: Color::Rgb(100, 150, 255),
+            cursor: Color::Rgb(0, 130, 170),
+            cursor_bg: Color::Rgb(190, 190, 190),
+            ghost_bg: Color::Rgb(232, 232, 232),
+            playhead: Color::Rgb(210, 30, 30),
+            playhead_bg: Color::Rgb(190, 235, 190),
+            indicator: Color::Rgb(130, 90, 0),
+            indicator_bg: Color::Rgb(255, 235, 160),
+            current_row_bg: Color::Rgb(210, 215, 235),
+            alternate_bg: Color::Rgb(232, 232, 238),
+            lane_bg: Color::Rgb(238, 238, 238),
+            lane_cursor_bg: Color::Rgb(215, 215, 235),
+            clip_bg: Color::Rgb(190, 190, 235),
+            clip_muted_bg: Color::Rgb(210, 210, 210),
+            text: Color::Rgb(20, 20, 20),
+            text_secondary: Color::Rgb(70, 70, 70),
+            text_dim: Color::Rgb(120, 120, 120),
+            text_inverse: Color::Rgb(255, 255, 255),
+            accent: Color::Rgb(150, 100, 0),
+            active: Color::Rgb(30, 130, 50),
+            alert: Color::Rgb(200, 40, 40),
+            info: Color::Rgb(0, 120, 140),
+            link: Color::Rgb(40, 90, 200),
+            track_colors: vec![
+                Color::Rgb(40, 90, 200),
+                Color::Rgb(30, 140, 60),
+                Color::Rgb(170, 120, 0),
+                Color::Rgb(160, 50, 160),
+                Color::Rgb(0, 140, 150),
+                Color::Rgb(200, 40, 40),
+                Color::Rgb(90, 110, 230),
+                Color::Rgb(60, 170, 90),
+            ],
+            marker_colors: vec![
+                Color::Rgb(170, 120, 0),
+                Color::Rgb(0, 140, 150),
+                Color::Rgb(160, 50, 160),
+                Color::Rgb(30, 140, 60),
+                Color::Rgb(200, 40, 40),
+                Color::Rgb(40, 90, 200),
+            ],
+        }
+    }
+
+    /// A theme using only the 16 terminal colors.
+    pub fn ansi() -> Self {
+        Self {
+            border: Color::Gray,
+            border_focused: Color::Cyan,
+            panel_bg: Color::Reset,
+            ruler_measure: Color::Yellow,
+            ruler_beat: Color::DarkGray,
+            grid_bg: Color::Reset,
+            grid_shaded_bg: Color::Black,
+            grid_out_of_scale_bg: Color::Black,
+            grid_root_bg: Color::Reset,
+            grid_dot: Color::DarkGray,
+            beat_line: Color::Gray,
+            measure_line: Color::White,
+            key_white: Color::Gray,
+            key_c: Color::White,
+            key_black: Color::DarkGray,
+            key_white_text: Color::Black,
+            key_black_text: Color::White,
+            note_text: Color::Black,
+            note_soft: Color::Green,
+            note_loud: Color::Yellow,
+            note_selected: Color::Magenta,
+            note_playing: Color::White,
+            note_recent: Color::Blue,
+            cursor: Color::Cyan,
+            cursor_bg: Color::DarkGray,
+            ghost_bg: Color::Black,
+            playhead: Color::Red,
+            playhead_bg: Color::DarkGray,
+            indicator: Color::Yellow,
+            indicator_bg: Color::Black,
+            current_row_bg: Color::DarkGray,
+            alternate_bg: Color::Black,
+            lane_bg: Color::Reset,
+            lane_cursor_bg: Color::Black,
+            clip_bg: Color::Blue,
+            clip_muted_bg: Color::DarkGray,
+            ..Self::dark()
+        }
+    }
+
+    /// Returns the built-in theme called `name`.
+    pub fn builtin(name: &str) -> Option<Self> {
+        match name {
+            "dark" => Some(Self::dark()),
+            "light" => Some(Self::light()),
+            "ansi" => Some(Self::ansi()),
+            _ => None,
+        }
+    }
+
+    /// Loads the theme called `name`: a built-in one, or a file in the
+    /// themes folder. Without truecolor, RGB colors are mapped to the 256
+    /// indexed colors.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there's no such theme, or its file can't be read
+    /// or names an unknown role or color
+    pub fn load(name: &str, truecolor: bool) -> Result<Self, String> {
+        let theme = match Self::builtin(name) {
+            Some(theme) => theme,
+            None => {
+                let path = themes_dir()
+                    .map(|dir| dir.join(format!("{}.json", name)))
+                    .ok_or_else(|| format!("Unknown theme '{}'", name))?;
+                let text = std::fs::read_to_string(&path)
+                    .map_err(|e| format!("Theme {}: {}", path.display(), e))?;
+                Self::parse(&text).map_err(|e| format!("Theme {}: {}", path.display(), e))?
+            }
+        };
+        Ok(if truecolor { theme } else { theme.indexed() })
+    }
+
+    /// Parses a theme file: a built-in `base` theme (dark if not given)
+    /// with the listed roles replaced.
+    fn parse(text: &str) -> Result<Self, String> {
+        let serde_json::Value::Object(mut overrides) =
+            serde_json::from_str(text).map_err(|e| e.to_string())?
+        else {
+            return Err("expected a JSON object".to_string());
+        };
+        let base = match overrides.remove("base") {
+            Some(serde_json::Value::String(name)) => {
+                Self::builtin(&name).ok_or_else(|| format!("unknown base theme '{}'", name))?
+            }
+            Some(_) => return Err("base must be a theme name".to_string()),
+            None => Self::dark(),
+        };
+        let serde_json::Value::Object(mut roles) =
+            serde_json::to_value(base).map_err(|e| e.to_string())?
+        else {
+            unreachable!("themes serialize to objects");
+        };
+        roles.extend(overrides);
+        let theme: Self =
+            serde_json::from_value(serde_json::Value::Object(roles)).map_err(|e| e.to_string())?;
+        if theme.track_colors.is_empty() || theme.marker_colors.is_empty() {
+            return Err("track_colors and marker_colors need at least one color".to_string());
+        }
+        Ok(theme)
+    }
+
+    /// Returns the theme with every RGB color replaced by the nearest
+    /// 256-color index.
+    pub fn indexed(mut self) -> Self {
+        for color in [
+            &mut self.border,
+            &mut self.border_focused,
+            &mut self.panel_bg,
+            &mut self.ruler_measure,
+            &mut self.ruler_beat,
+            &mut self.grid_bg,
+            &mut self.grid_shaded_bg,
+            &mut self.grid_out_of_scale_bg,
+            &mut self.grid_root_bg,
+            &mut self.grid_dot,
+            &mut self.beat_line,
+            &mut self.measure_line,
+            &mut self.key_white,
+            &mut self.key_c,
+            &mut self.key_black,
+            &mut self.key_white_text,
+            &mut self.key_black_text,
+            &mut self.note_text,
+            &mut self.note_soft,
+            &mut self.note_loud,
+            &mut self.note_selected,
+            &mut self.note_playing,
+            &mut self.note_recent,
+            &mut self.cursor,
+            &mut self.cursor_bg,
+            &mut self.ghost_bg,
+            &mut self.playhead,
+            &mut self.playhead_bg,
+            &mut self.indicator,
+            &mut self.indicator_bg,
+            &mut self.current_row_bg,
+            &mut self.alternate_bg,
+            &mut self.lane_bg,
+            &mut self.lane_cursor_bg,
+            &mut self.clip_bg,
+            &mut self.clip_muted_bg,
+            &mut self.text,
+            &mut self.text_secondary,
+            &mut self.text_dim,
+            &mut self.text_inverse,
+            &mut self.accent,
+            &mut self.active,
+            &mut self.alert,
+            &mut self.info,
+            &mut self.link,
+        ] {
+            *color = to_indexed(*color);
+        }
+        for color in self
+            .track_colors
+            .iter_mut()
+            .chain(self.marker_colors.iter_mut())
+        {
+            *color = to_indexed(*color);
+        }
+        self
+    }
+
+    /// Returns the border color for a panel.
+    pub fn border(&self, focused: bool) -> Color {
+        if focused {
+            self.border_focused
+        } else {
+            self.border
+        }
+    }
+
+    /// Returns the color of the track at `index`.
+    pub fn track_color(&self, index: usize) -> Color {
+        self.track_colors[index % self.track_colors.len()]
+    }
+
+    /// Returns the color for a marker color index.
+    pub fn marker_color(&self, index: u8) -> Color {
+        self.marker_colors[index as usize % self.marker_colors.len()]
+    }
+
+    /// Returns the note color for a velocity, blending from `note_soft` to
+    /// `note_loud`. Named colors can't blend, so they switch halfway.
+    pub fn velocity_color(&self, velocity: u8) -> Color {
+        let t = velocity.min(127) as u32;
+        match (self.note_soft, self.note_loud) {
+            (Color::Rgb(sr, sg, sb), Color::Rgb(lr, lg, lb)) => {
+                let mix =
+                    |soft: u8, loud: u8| ((soft as u32 * (127 - t) + loud as u32 * t) / 127) as u8;
+                Color::Rgb(mix(sr, lr), mix(sg, lg), mix(sb, lb))
+            }
+            (soft, _) if t < 64 => soft,
+            (_, loud) => loud,
+        }
+    }
+}
+
+/// Returns the names of the built-in themes followed by the user's.
+pub fn available_themes() -> Vec<String> {
+    let mut names: Vec<String> = BUILTIN_THEMES.iter().map(|s| s.to_string()).collect();
+    let mut user: Vec<String> = themes_dir()
+        .and_then(|dir| std::fs::read_dir(dir).ok())
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            if path.extension()? != "json" {
+                return None;
+            }
+            Some(path.file_stem()?.to_str()?.to_string())
+        })
+        .filter(|name| !names.contains(name))
+        .collect();
+    user.sort();
+    names.append(&mut user);
+    names
+}
+
+/// Returns whether the terminal announces 24-bit color in `COLORTERM`.
+pub fn detect_truecolor() -> bool {
+    std::env::var("COLORTERM")
+        .map(|v| matches!(v.to_ascii_lowercase().as_str(), "truecolor" | "24bit"))
+        .unwrap_or(false)
+}
+
+/// Returns the folder user themes are read from.
+fn themes_dir() -> Option<PathBuf> {
+    crate::config::config_dir().map(|dir| dir.join("themes"))
+}
+
+/// Maps an RGB color to the nearest color of the xterm 256-color palette
+/// (the 6x6x6 cube or the gray ramp). Other colors are kept.
+fn to_indexed(color: Color) -> Color {
+    let Color::Rgb(r, g, b) = color else {
+        return color;
+    };
+    // Cube levels are 0, 95, 135, 175, 215, 255
+    let level = |v: u8| -> u8 {
+        if v < 48 {
+            0
+        } else if v < 115 {
+            1
+        } else {
+            (v - 35) / 40
+        }
+    };
+    let level_value = |l: u8| if l == 0 { 0 } else { 55 + l * 40 };
+    let (lr, lg, lb) = (level(r), level(g), level(b));
+    let cube = (level_value(lr), level_value(lg), level_value(lb));
+
+    // Gray ramp: 24 steps from 8 to 238
+    let average = (r as u32 + g as u32 + b as u32) / 3;
+    let gray_step = ((average.saturating_sub(3)) / 10).min(23) as u8;
+    let gray_value = 8 + gray_step * 10;
+
+    let distance = |(cr, cg, cb): (u8, u8, u8)| {
+        let d = |a: u8, b: u8| (a as i32 - b as i32).pow(2);
+        d(r, cr) + d(g, cg) + d(b, cb)
+    };
+    if distance((gray_value, gray_value, gray_value)) < distance(cube) {
+        Color::Indexed(232 + gray_step)
+    } else {
+        Color::Indexed(16 + 36 * lr + 6 * lg + lb)
+    }
+}
+
+/// Serializes a color as its name, `#rrggbb` or index.
+mod color {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(color: &Color, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&color.to_string())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Color, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        text.parse()
+            .map_err(|_| serde::de::Error::custom(format!("unknown color '{}'", text)))
+    }
+}
+
+/// Serializes a list of colors like [`color`].
+mod colors {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(colors: &[Color], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(colors.iter().map(|c| c.to_string()))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<Color>, D::Error> {
+        Vec::<String>::deserialize(deserializer)?
+            .into_iter()
+            .map(|text| {
+                text.parse()
+                    .map_err(|_| serde::de::Error::custom(format!("unknown color '{}'", text)))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_user_theme_overrides_base() {
+        let theme =
+            Theme::parse(r##"{ "base": "light", "playhead": "#d03030", "cursor": "dark gray" }"##)
+                .unwrap();
+        assert_eq!(theme.playhead, Color::Rgb(0xd0, 0x30, 0x30));
+        assert_eq!(theme.cursor, Color::DarkGray);
+        assert_eq!(theme.grid_bg, Theme::light().grid_bg);
+
+        assert!(Theme::parse(r#"{ "grid": "red" }"#).is_err());
+        assert!(Theme::parse(r#"{ "playhead": "reddish" }"#).is_err());
+        assert!(Theme::parse(r#"{ "base": "solarized" }"#).is_err());
+        assert!(Theme::parse(r#"{ "track_colors": [] }"#).is_err());
+    }
+
+    #[test]
+    fn test_ansi_theme_uses_only_terminal_colors() {
+        let theme = Theme::ansi();
+        let value = serde_json::to_value(&theme).unwrap();
+        let text = value.to_string();
+        assert!(!text.contains('#'), "{}", text);
+        // Named colors pass through the 256-color fallback untouched
+        assert_eq!(theme.clone().indexed(), theme);
+        assert_eq!(theme.velocity_color(10), Color::Green);
+        assert_eq!(theme.velocity_color(120), Color::Yellow);
+    }
+
+    #[test]
+    fn test_rgb_falls_back_to_nearest_indexed_color() {
+        assert_eq!(to_indexed(Color::Rgb(255, 0, 0)), Color::Indexed(196));
+        assert_eq!(to_indexed(Color::Rgb(0, 0, 0)), Color::Indexed(16));
+        assert_eq!(to_indexed(Color::Rgb(40, 40, 40)), Color::Indexed(235));
+        assert_eq!(to_indexed(Color::Blue), Color::Blue);
+        let theme = Theme::dark().indexed();
+        assert!(!serde_json::to_value(&theme)
+            .unwrap()
+            .to_string()
+            .contains('#'));
+    }
+
+    #[test]
+    fn test_views_draw_only_theme_colors() {
+        let sources = [
+            ("main.rs", include_str!("main.rs")),
+            ("combined.rs", include_str!("ui/combined.rs")),
+            ("dialogs.rs", include_str!("ui/dialogs.rs")),
+            ("event_list.rs", include_str!("ui/event_list.rs")),
+            ("help.rs", include_str!("ui/help.rs")),
+            ("keyboard.rs", include_str!("ui/keyboard.rs")),
+            ("lyrics.rs", include_str!("ui/lyrics.rs")),
+            ("mod.rs", include_str!("ui/mod.rs")),
+            ("piano_roll.rs", include_str!("ui/piano_roll.rs")),
+            (
+                "project_timeline.rs",
+                include_str!("ui/project_timeline.rs"),
+            ),
+            ("staff.rs", include_str!("ui/staff.rs")),
+            ("step_sequencer.rs", include_str!("ui/step_sequencer.rs")),
+            ("timeline.rs", include_str!("ui/timeline.rs")),
+            ("tracks.rs", include_str!("ui/tracks.rs")),
+            ("velocity.rs", include_str!("ui/velocity.rs")),
+        ];
+        for (name, source) in sources {
+            assert!(!source.contains("Color::"), "{} hardcodes a color", name);
+        }
+    }
+
+    #[test]
+    fn test_light_theme_text_is_dark() {
+        let theme = Theme::light();
+        for color in [
+            theme.text,
+            theme.text_secondary,
+            theme.text_dim,
+            theme.accent,
+            theme.active,
+            theme.alert,
+            theme.info,
+            theme.link,
+        ] {
+            let Color::Rgb(r, g, b) = color else {
+                panic!("{} is not an RGB color", color);
+            };
+            let luma = (299 * r as u32 + 587 * g as u32 + 114 * b as u32) / 1000;
+            assert!(luma < 140, "{} is too light for a light background", color);
+        }
+    }
+}
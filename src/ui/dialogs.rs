@@ -3,7 +3,9 @@
 //! Provides modal dialogs for saving projects with filename/format selection,
 //! browsing files for loading, and selecting SoundFont.
 
-use crate::app::{App, SaveFormat};
+use crate::app::{App, DrumMapInput, GuardedAction, SaveFormat, SCALE_DIALOG_CUSTOM_ROW};
+use crate::midi::Chord;
+use crate::midi::{GridFeel, NOTE_NAMES, TICKS_PER_BEAT};
 use ratatui::layout::{Constraint, Direction, Layout};
 use ratatui::style::{Color, Modifier, Style};
 use ratatui::text::{Line, Span};
@@ -52,7 +54,7 @@ pub fn render_save_dialog(frame: &mut Frame, app: &App) {
     let block = Block::default()
         .title(" Save Project ")
         .borders(Borders::ALL)
-        .border_style(Style::default().fg(Color::Cyan));
+        .border_style(Style::default().fg(app.theme.border_focused));
 
     let inner = block.inner(area);
     frame.render_widget(block, area);
@@ -73,7 +75,10 @@ pub fn render_save_dialog(frame: &mut Frame, app: &App) {
 
     // Filename label
     frame.render_widget(
-        Paragraph::new(Span::styled("Filename:", Style::default().fg(Color::White))),
+        Paragraph::new(Span::styled(
+            "Filename:",
+            Style::default().fg(app.theme.text),
+        )),
         chunks[0],
     );
 
@@ -88,23 +93,23 @@ pub fn render_save_dialog(frame: &mut Frame, app: &App) {
             Span::styled(
                 &app.save_dialog.filename,
                 Style::default()
-                    .fg(Color::Yellow)
+                    .fg(app.theme.accent)
                     .add_modifier(Modifier::BOLD),
             ),
             Span::styled(
                 "_",
                 Style::default()
-                    .fg(Color::White)
+                    .fg(app.theme.text)
                     .add_modifier(Modifier::RAPID_BLINK),
             ),
-            Span::styled(extension, Style::default().fg(Color::DarkGray)),
+            Span::styled(extension, Style::default().fg(app.theme.text_dim)),
         ])),
         chunks[1],
     );
 
     // Format label
     frame.render_widget(
-        Paragraph::new(Span::styled("Format:", Style::default().fg(Color::White))),
+        Paragraph::new(Span::styled("Format:", Style::default().fg(app.theme.text))),
         chunks[3],
     );
 
@@ -112,10 +117,10 @@ pub fn render_save_dialog(frame: &mut Frame, app: &App) {
     let format_style = |selected: bool| {
         if selected {
             Style::default()
-                .fg(Color::Green)
+                .fg(app.theme.active)
                 .add_modifier(Modifier::BOLD)
         } else {
-            Style::default().fg(Color::DarkGray)
+            Style::default().fg(app.theme.text_dim)
         }
     };
 
@@ -125,15 +130,15 @@ pub fn render_save_dialog(frame: &mut Frame, app: &App) {
 
     frame.render_widget(
         Paragraph::new(Line::from(vec![
-            Span::styled("[", Style::default().fg(Color::DarkGray)),
+            Span::styled("[", Style::default().fg(app.theme.text_dim)),
             Span::styled(if is_json { "X" } else { " " }, format_style(is_json)),
-            Span::styled("] JSON  ", Style::default().fg(Color::DarkGray)),
-            Span::styled("[", Style::default().fg(Color::DarkGray)),
+            Span::styled("] JSON  ", Style::default().fg(app.theme.text_dim)),
+            Span::styled("[", Style::default().fg(app.theme.text_dim)),
             Span::styled(if is_oxm { "X" } else { " " }, format_style(is_oxm)),
-            Span::styled("] OXM  ", Style::default().fg(Color::DarkGray)),
-            Span::styled("[", Style::default().fg(Color::DarkGray)),
+            Span::styled("] OXM  ", Style::default().fg(app.theme.text_dim)),
+            Span::styled("[", Style::default().fg(app.theme.text_dim)),
             Span::styled(if is_midi { "X" } else { " " }, format_style(is_midi)),
-            Span::styled("] MIDI", Style::default().fg(Color::DarkGray)),
+            Span::styled("] MIDI", Style::default().fg(app.theme.text_dim)),
         ])),
         chunks[4],
     );
@@ -141,12 +146,12 @@ pub fn render_save_dialog(frame: &mut Frame, app: &App) {
     // Instructions
     frame.render_widget(
         Paragraph::new(Line::from(vec![
-            Span::styled("[Tab]", Style::default().fg(Color::Yellow)),
-            Span::styled(" Toggle format  ", Style::default().fg(Color::DarkGray)),
-            Span::styled("[Enter]", Style::default().fg(Color::Yellow)),
-            Span::styled(" Save  ", Style::default().fg(Color::DarkGray)),
-            Span::styled("[Esc]", Style::default().fg(Color::Yellow)),
-            Span::styled(" Cancel", Style::default().fg(Color::DarkGray)),
+            Span::styled("[Tab]", Style::default().fg(app.theme.accent)),
+            Span::styled(" Toggle format  ", Style::default().fg(app.theme.text_dim)),
+            Span::styled("[Enter]", Style::default().fg(app.theme.accent)),
+            Span::styled(" Save  ", Style::default().fg(app.theme.text_dim)),
+            Span::styled("[Esc]", Style::default().fg(app.theme.accent)),
+            Span::styled(" Cancel", Style::default().fg(app.theme.text_dim)),
         ])),
         chunks[6],
     );
@@ -169,7 +174,7 @@ pub fn render_file_browser(frame: &mut Frame, app: &App) {
     let block = Block::default()
         .title(" Open Project ")
         .borders(Borders::ALL)
-        .border_style(Style::default().fg(Color::Cyan));
+        .border_style(Style::default().fg(app.theme.border_focused));
 
     let inner = block.inner(area);
     frame.render_widget(block, area);
@@ -191,7 +196,10 @@ pub fn render_file_browser(frame: &mut Frame, app: &App) {
     let display_path = truncate_path(&path_str, max_width);
 
     frame.render_widget(
-        Paragraph::new(Span::styled(display_path, Style::default().fg(Color::Cyan))),
+        Paragraph::new(Span::styled(
+            display_path,
+            Style::default().fg(app.theme.info),
+        )),
         chunks[0],
     );
 
@@ -211,20 +219,20 @@ pub fn render_file_browser(frame: &mut Frame, app: &App) {
                 (
                     "[..]",
                     "Parent Directory".to_string(),
-                    Style::default().fg(Color::Blue),
+                    Style::default().fg(app.theme.link),
                 )
             } else if path.is_dir() {
                 (
                     "[D]",
                     path_display_name(path),
-                    Style::default().fg(Color::Blue),
+                    Style::default().fg(app.theme.link),
                 )
             } else {
                 let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
                 let (icon, color) = match ext {
-                    "oxm" => ("[B]", Color::White),
-                    "mid" | "midi" => ("[M]", Color::Magenta),
-                    _ => ("[J]", Color::White),
+                    "oxm" => ("[B]", app.theme.text),
+                    "mid" | "midi" => ("[M]", app.theme.info),
+                    _ => ("[J]", app.theme.text),
                 };
                 (icon, path_display_name(path), Style::default().fg(color))
             };
@@ -236,7 +244,10 @@ pub fn render_file_browser(frame: &mut Frame, app: &App) {
             };
 
             ListItem::new(Line::from(vec![
-                Span::styled(format!("{} ", icon), Style::default().fg(Color::DarkGray)),
+                Span::styled(
+                    format!("{} ", icon),
+                    Style::default().fg(app.theme.text_dim),
+                ),
                 Span::styled(name, display_style),
             ]))
         })
@@ -248,17 +259,479 @@ pub fn render_file_browser(frame: &mut Frame, app: &App) {
     // Instructions
     frame.render_widget(
         Paragraph::new(Line::from(vec![
-            Span::styled("[Up/Down]", Style::default().fg(Color::Yellow)),
-            Span::styled(" Navigate  ", Style::default().fg(Color::DarkGray)),
-            Span::styled("[Enter]", Style::default().fg(Color::Yellow)),
-            Span::styled(" Open  ", Style::default().fg(Color::DarkGray)),
-            Span::styled("[Esc]", Style::default().fg(Color::Yellow)),
-            Span::styled(" Cancel", Style::default().fg(Color::DarkGray)),
+            Span::styled("[Up/Down]", Style::default().fg(app.theme.accent)),
+            Span::styled(" Navigate  ", Style::default().fg(app.theme.text_dim)),
+            Span::styled("[Enter]", Style::default().fg(app.theme.accent)),
+            Span::styled(" Open  ", Style::default().fg(app.theme.text_dim)),
+            Span::styled("[Esc]", Style::default().fg(app.theme.accent)),
+            Span::styled(" Cancel", Style::default().fg(app.theme.text_dim)),
         ])),
         chunks[3],
     );
 }
 
+/// Renders the recent files dialog overlay.
+///
+/// Each row shows the file name, its type, when it was last opened and
+/// its folder. Files that no longer exist are dimmed.
+///
+/// # Arguments
+///
+/// * `frame` - The frame to render to
+/// * `app` - Application state
+pub fn render_recent_dialog(frame: &mut Frame, app: &App) {
+    if !app.recent_dialog.open {
+        return;
+    }
+
+    let area = centered_rect(70, 60, frame.area());
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(" Recent Files ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(app.theme.border_focused));
+
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Min(3),    // File list
+            Constraint::Length(1), // Instructions
+        ])
+        .split(inner);
+
+    let files = &app.recent_files.files;
+    let now = crate::config::unix_now();
+    let visible_height = chunks[0].height as usize;
+    let start_idx = (app.recent_dialog.selected + 1).saturating_sub(visible_height);
+
+    let items: Vec<ListItem> = files
+        .iter()
+        .enumerate()
+        .skip(start_idx)
+        .take(visible_height)
+        .map(|(idx, file)| {
+            let exists = file.exists();
+            let name_style = if exists {
+                Style::default().fg(app.theme.text)
+            } else {
+                Style::default().fg(app.theme.text_dim)
+            };
+            let name_style = if idx == app.recent_dialog.selected {
+                name_style.add_modifier(Modifier::REVERSED)
+            } else {
+                name_style
+            };
+            let age = if exists {
+                crate::recent::format_age(file.opened_at, now)
+            } else {
+                "missing".to_string()
+            };
+            let folder = file
+                .path
+                .parent()
+                .map(|p| p.display().to_string())
+                .unwrap_or_default();
+            let folder_width = (chunks[0].width as usize).saturating_sub(51);
+
+            ListItem::new(Line::from(vec![
+                Span::styled(
+                    format!(
+                        "{:<24} ",
+                        path_display_name(&file.path)
+                            .chars()
+                            .take(24)
+                            .collect::<String>()
+                    ),
+                    name_style,
+                ),
+                Span::styled(
+                    format!("{:<8} ", file.kind()),
+                    Style::default().fg(app.theme.info),
+                ),
+                Span::styled(
+                    format!("{:<14} ", age),
+                    Style::default().fg(if exists {
+                        app.theme.active
+                    } else {
+                        app.theme.alert
+                    }),
+                ),
+                Span::styled(
+                    truncate_path(&folder, folder_width.max(4)),
+                    Style::default().fg(app.theme.text_dim),
+                ),
+            ]))
+        })
+        .collect();
+
+    frame.render_widget(List::new(items), chunks[0]);
+
+    frame.render_widget(
+        Paragraph::new(Line::from(vec![
+            Span::styled("[Up/Down]", Style::default().fg(app.theme.accent)),
+            Span::styled(" Navigate  ", Style::default().fg(app.theme.text_dim)),
+            Span::styled("[Enter]", Style::default().fg(app.theme.accent)),
+            Span::styled(" Open  ", Style::default().fg(app.theme.text_dim)),
+            Span::styled("[x]", Style::default().fg(app.theme.accent)),
+            Span::styled(" Forget  ", Style::default().fg(app.theme.text_dim)),
+            Span::styled("[Esc]", Style::default().fg(app.theme.accent)),
+            Span::styled(" Cancel", Style::default().fg(app.theme.text_dim)),
+        ])),
+        chunks[1],
+    );
+}
+
+/// Renders the settings dialog overlay.
+///
+/// Shows the adjustable settings, then the paths that are only set in the
+/// config file and where that file lives.
+///
+/// # Arguments
+///
+/// * `frame` - The frame to render to
+/// * `app` - Application state
+pub fn render_settings_dialog(frame: &mut Frame, app: &App) {
+    let dialog = &app.settings_dialog;
+    if !dialog.open {
+        return;
+    }
+
+    let area = centered_rect(55, 60, frame.area());
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(" Settings ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(app.theme.accent));
+
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(10), // Settings
+            Constraint::Min(4),     // Paths
+            Constraint::Length(1),  // Instructions
+        ])
+        .split(inner);
+
+    let draft = &dialog.draft;
+    let note_length = match draft.default_note_duration {
+        d if d == TICKS_PER_BEAT * 4 => "Whole".to_string(),
+        d if d == TICKS_PER_BEAT * 2 => "1/2".to_string(),
+        d if d == TICKS_PER_BEAT => "1/4".to_string(),
+        d if d == TICKS_PER_BEAT / 2 => "1/8".to_string(),
+        d if d == TICKS_PER_BEAT / 4 => "1/16".to_string(),
+        d => format!("{} ticks", d),
+    };
+    let rows = [
+        ("Velocity", draft.default_velocity.to_string()),
+        ("Note length", note_length),
+        ("Zoom", format!("{} ticks/col", draft.default_zoom)),
+        ("Highlight", draft.highlight_mode.label().to_string()),
+        ("Theme", draft.theme.clone()),
+        (
+            "Autosave",
+            format!("{} s after edit", draft.autosave_delay_secs),
+        ),
+        ("Input poll", format!("{} ms", draft.poll_interval_ms)),
+        (
+            "History",
+            if draft.persist_history {
+                "Saved with autosave"
+            } else {
+                "Not saved"
+            }
+            .to_string(),
+        ),
+        (
+            "Sample rate",
+            format!("{} Hz", draft.export.wav.sample_rate),
+        ),
+        (
+            "WAV tail",
+            format!("{:.1} s", draft.export.wav.tail_seconds),
+        ),
+    ];
+
+    let items: Vec<ListItem> = rows
+        .iter()
+        .enumerate()
+        .map(|(idx, (label, value))| {
+            let value_style = if idx == dialog.selected {
+                Style::default()
+                    .fg(app.theme.text)
+                    .bg(app.theme.current_row_bg)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(app.theme.text)
+            };
+            ListItem::new(Line::from(vec![
+                Span::styled(
+                    format!("  {:<12}", label),
+                    Style::default().fg(app.theme.text_secondary),
+                ),
+                Span::styled(format!(" {} ", value), value_style),
+            ]))
+        })
+        .collect();
+    frame.render_widget(List::new(items), chunks[0]);
+
+    let path_width = (chunks[1].width as usize).saturating_sub(16).max(8);
+    let show = |path: Option<&Path>| {
+        path.map_or_else(
+            || "(none)".to_string(),
+            |p| truncate_path(&p.display().to_string(), path_width),
+        )
+    };
+    let label_style = Style::default().fg(app.theme.text_dim);
+    let path_lines: Vec<Line> = [
+        ("SoundFont", show(draft.soundfont.as_deref())),
+        ("Template", show(draft.template.as_deref())),
+        ("Export to", show(Some(&draft.export.dir))),
+        ("Config file", show(draft.path())),
+    ]
+    .into_iter()
+    .map(|(label, value)| {
+        Line::from(vec![
+            Span::styled(format!("  {:<12} ", label), label_style),
+            Span::styled(value, Style::default().fg(app.theme.info)),
+        ])
+    })
+    .collect();
+    frame.render_widget(Paragraph::new(path_lines), chunks[1]);
+
+    let key_style = Style::default().fg(app.theme.accent);
+    let desc_style = Style::default().fg(app.theme.text_dim);
+    frame.render_widget(
+        Paragraph::new(Line::from(vec![
+            Span::styled("[Up/Down]", key_style),
+            Span::styled(" Select  ", desc_style),
+            Span::styled("[Left/Right]", key_style),
+            Span::styled(" Change  ", desc_style),
+            Span::styled("[Enter]", key_style),
+            Span::styled(" Save  ", desc_style),
+            Span::styled("[Esc]", key_style),
+            Span::styled(" Cancel", desc_style),
+        ])),
+        chunks[2],
+    );
+}
+
+/// Renders the undo history panel.
+///
+/// Lists every edit, oldest first, with how long ago it was made. Edits
+/// after the current point are undone and shown dimmed; jumping to one
+/// redoes everything up to it.
+///
+/// # Arguments
+///
+/// * `frame` - The frame to render to
+/// * `app` - Application state
+pub fn render_history_dialog(frame: &mut Frame, app: &App) {
+    let dialog = &app.history_dialog;
+    if !dialog.open {
+        return;
+    }
+
+    let area = centered_rect(60, 60, frame.area());
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(" Undo History ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(app.theme.border_focused));
+
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Min(3),    // Edits
+            Constraint::Length(1), // Instructions
+        ])
+        .split(inner);
+
+    let entries = app.history.entries();
+    let position = app.history.position();
+    let now = crate::config::unix_now();
+    let visible_height = chunks[0].height as usize;
+    let start_idx = (dialog.selected + 1).saturating_sub(visible_height);
+
+    // Row 0 is the state before the first edit; row N is after edit N
+    let items: Vec<ListItem> = (0..=entries.len())
+        .skip(start_idx)
+        .take(visible_height)
+        .map(|idx| {
+            let (label, age) = match idx.checked_sub(1).map(|i| &entries[i]) {
+                Some(entry) => {
+                    let secs = now.saturating_sub(entry.timestamp);
+                    let age = if secs < 60 {
+                        format!("{} s ago", secs)
+                    } else {
+                        crate::recent::format_age(entry.timestamp, now)
+                    };
+                    (entry.description.as_str(), age)
+                }
+                None => ("(start)", String::new()),
+            };
+            let marker = if idx == position { "> " } else { "  " };
+            let style = if idx > position {
+                Style::default().fg(app.theme.text_dim)
+            } else {
+                Style::default().fg(app.theme.text)
+            };
+            let style = if idx == dialog.selected {
+                style.add_modifier(Modifier::REVERSED)
+            } else {
+                style
+            };
+            let label_width = (chunks[0].width as usize).saturating_sub(16).max(8);
+            ListItem::new(Line::from(vec![
+                Span::styled(marker, Style::default().fg(app.theme.accent)),
+                Span::styled(
+                    format!(
+                        "{:<width$} ",
+                        label.chars().take(label_width).collect::<String>(),
+                        width = label_width
+                    ),
+                    style,
+                ),
+                Span::styled(
+                    format!("{:>12}", age),
+                    Style::default().fg(app.theme.active),
+                ),
+            ]))
+        })
+        .collect();
+    frame.render_widget(List::new(items), chunks[0]);
+
+    let persist = if app.config.persist_history {
+        "on"
+    } else {
+        "off"
+    };
+    frame.render_widget(
+        Paragraph::new(Line::from(vec![
+            Span::styled("[Up/Down]", Style::default().fg(app.theme.accent)),
+            Span::styled(" Navigate  ", Style::default().fg(app.theme.text_dim)),
+            Span::styled("[Enter]", Style::default().fg(app.theme.accent)),
+            Span::styled(" Jump here  ", Style::default().fg(app.theme.text_dim)),
+            Span::styled("[p]", Style::default().fg(app.theme.accent)),
+            Span::styled(
+                format!(" Save with autosave: {}  ", persist),
+                Style::default().fg(app.theme.text_dim),
+            ),
+            Span::styled("[Esc]", Style::default().fg(app.theme.accent)),
+            Span::styled(" Close", Style::default().fg(app.theme.text_dim)),
+        ])),
+        chunks[1],
+    );
+}
+
+/// Renders the crash-recovery dialog overlay.
+///
+/// Lists every autosave version, newest first, with its project, track
+/// and note counts and when it was written.
+///
+/// # Arguments
+///
+/// * `frame` - The frame to render to
+/// * `app` - Application state
+pub fn render_recovery_dialog(frame: &mut Frame, app: &App) {
+    let dialog = &app.recovery_dialog;
+    if !dialog.open {
+        return;
+    }
+
+    let area = centered_rect(70, 60, frame.area());
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(" Recover Autosave ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(app.theme.accent));
+
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(1), // Header
+            Constraint::Min(3),    // Versions
+            Constraint::Length(1), // Instructions
+        ])
+        .split(inner);
+
+    frame.render_widget(
+        Paragraph::new(Span::styled(
+            format!(
+                "{:<24} {:<18} {:>6} {:>6}  {}",
+                "Project", "File", "Tracks", "Notes", "Saved"
+            ),
+            Style::default()
+                .fg(app.theme.accent)
+                .add_modifier(Modifier::BOLD),
+        )),
+        chunks[0],
+    );
+
+    let now = crate::config::unix_now();
+    let visible_height = chunks[1].height as usize;
+    let start_idx = (dialog.selected + 1).saturating_sub(visible_height);
+    let items: Vec<ListItem> = dialog
+        .entries
+        .iter()
+        .enumerate()
+        .skip(start_idx)
+        .take(visible_height)
+        .map(|(idx, entry)| {
+            let file = entry
+                .source
+                .as_deref()
+                .map_or_else(|| "(unsaved)".to_string(), path_display_name);
+            let text = format!(
+                "{:<24} {:<18} {:>6} {:>6}  {}",
+                entry.name.chars().take(24).collect::<String>(),
+                file.chars().take(18).collect::<String>(),
+                entry.tracks,
+                entry.notes,
+                crate::recent::format_age(entry.saved_at, now)
+            );
+            let style = if idx == dialog.selected {
+                Style::default()
+                    .fg(app.theme.text)
+                    .add_modifier(Modifier::REVERSED)
+            } else {
+                Style::default().fg(app.theme.text_secondary)
+            };
+            ListItem::new(Line::from(Span::styled(text, style)))
+        })
+        .collect();
+    frame.render_widget(List::new(items), chunks[1]);
+
+    frame.render_widget(
+        Paragraph::new(Line::from(vec![
+            Span::styled("[Up/Down]", Style::default().fg(app.theme.accent)),
+            Span::styled(" Navigate  ", Style::default().fg(app.theme.text_dim)),
+            Span::styled("[Enter]", Style::default().fg(app.theme.accent)),
+            Span::styled(" Recover  ", Style::default().fg(app.theme.text_dim)),
+            Span::styled("[x]", Style::default().fg(app.theme.accent)),
+            Span::styled(" Delete  ", Style::default().fg(app.theme.text_dim)),
+            Span::styled("[Esc]", Style::default().fg(app.theme.accent)),
+            Span::styled(" Keep current", Style::default().fg(app.theme.text_dim)),
+        ])),
+        chunks[2],
+    );
+}
+
 /// Renders the new project confirmation dialog overlay.
 ///
 /// # Arguments
@@ -276,7 +749,7 @@ pub fn render_new_project_dialog(frame: &mut Frame, app: &App) {
     let block = Block::default()
         .title(" New Project ")
         .borders(Borders::ALL)
-        .border_style(Style::default().fg(Color::Yellow));
+        .border_style(Style::default().fg(app.theme.accent));
 
     let inner = block.inner(area);
     frame.render_widget(block, area);
@@ -300,12 +773,12 @@ pub fn render_new_project_dialog(frame: &mut Frame, app: &App) {
             Line::from(Span::styled(
                 "Create a new project?",
                 Style::default()
-                    .fg(Color::White)
+                    .fg(app.theme.text)
                     .add_modifier(Modifier::BOLD),
             )),
             Line::from(Span::styled(
-                "Unsaved changes will be lost.",
-                Style::default().fg(Color::Red),
+                "The current project will be closed.",
+                Style::default().fg(app.theme.text_dim),
             )),
         ]),
         chunks[1],
@@ -314,20 +787,20 @@ pub fn render_new_project_dialog(frame: &mut Frame, app: &App) {
     // Button styles
     let yes_style = if app.new_project_dialog.selected == 0 {
         Style::default()
-            .fg(Color::Black)
-            .bg(Color::Green)
+            .fg(app.theme.text_inverse)
+            .bg(app.theme.active)
             .add_modifier(Modifier::BOLD)
     } else {
-        Style::default().fg(Color::Green)
+        Style::default().fg(app.theme.active)
     };
 
     let no_style = if app.new_project_dialog.selected == 1 {
         Style::default()
-            .fg(Color::Black)
-            .bg(Color::Red)
+            .fg(app.theme.text_inverse)
+            .bg(app.theme.alert)
             .add_modifier(Modifier::BOLD)
     } else {
-        Style::default().fg(Color::Red)
+        Style::default().fg(app.theme.alert)
     };
 
     // Buttons - center them
@@ -344,12 +817,108 @@ pub fn render_new_project_dialog(frame: &mut Frame, app: &App) {
     // Instructions
     frame.render_widget(
         Paragraph::new(Line::from(vec![
-            Span::styled("[Left/Right]", Style::default().fg(Color::Yellow)),
-            Span::styled(" Select  ", Style::default().fg(Color::DarkGray)),
-            Span::styled("[Enter]", Style::default().fg(Color::Yellow)),
-            Span::styled(" Confirm  ", Style::default().fg(Color::DarkGray)),
-            Span::styled("[Esc]", Style::default().fg(Color::Yellow)),
-            Span::styled(" Cancel", Style::default().fg(Color::DarkGray)),
+            Span::styled("[Left/Right]", Style::default().fg(app.theme.accent)),
+            Span::styled(" Select  ", Style::default().fg(app.theme.text_dim)),
+            Span::styled("[Enter]", Style::default().fg(app.theme.accent)),
+            Span::styled(" Confirm  ", Style::default().fg(app.theme.text_dim)),
+            Span::styled("[Esc]", Style::default().fg(app.theme.accent)),
+            Span::styled(" Cancel", Style::default().fg(app.theme.text_dim)),
+        ])),
+        chunks[5],
+    );
+}
+
+/// Renders the unsaved changes prompt (Save / Discard / Cancel).
+///
+/// # Arguments
+///
+/// * `frame` - The frame to render to
+/// * `app` - Application state
+pub fn render_unsaved_dialog(frame: &mut Frame, app: &App) {
+    let dialog = &app.unsaved_dialog;
+    let Some(action) = dialog.action.as_ref().filter(|_| dialog.open) else {
+        return;
+    };
+
+    let area = centered_rect(50, 25, frame.area());
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(" Unsaved Changes ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(app.theme.accent));
+
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(1), // Spacer
+            Constraint::Length(2), // Message
+            Constraint::Length(1), // Spacer
+            Constraint::Length(1), // Buttons
+            Constraint::Length(1), // Spacer
+            Constraint::Min(1),    // Instructions
+        ])
+        .split(inner);
+
+    let name = app
+        .project_path
+        .as_deref()
+        .map_or_else(|| "The new project".to_string(), path_display_name);
+    let question = match action {
+        GuardedAction::Quit => "Save before quitting?".to_string(),
+        GuardedAction::NewProject => "Save before creating a new project?".to_string(),
+        GuardedAction::Load(path) => format!("Save before opening {}?", path_display_name(path)),
+        GuardedAction::Recover(_) => "Save before recovering the autosave?".to_string(),
+    };
+    frame.render_widget(
+        Paragraph::new(vec![
+            Line::from(Span::styled(
+                format!("{} has unsaved changes.", name),
+                Style::default()
+                    .fg(app.theme.text)
+                    .add_modifier(Modifier::BOLD),
+            )),
+            Line::from(Span::styled(
+                question,
+                Style::default().fg(app.theme.text_secondary),
+            )),
+        ]),
+        chunks[1],
+    );
+
+    let buttons = [
+        (" Save ", app.theme.active),
+        (" Discard ", app.theme.alert),
+        (" Cancel ", app.theme.text_secondary),
+    ];
+    let mut spans = vec![Span::raw("   ")];
+    for (index, (label, color)) in buttons.into_iter().enumerate() {
+        let style = if index == dialog.selected {
+            Style::default()
+                .fg(app.theme.text_inverse)
+                .bg(color)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(color)
+        };
+        spans.push(Span::styled(label, style));
+        spans.push(Span::raw("   "));
+    }
+    frame.render_widget(Paragraph::new(Line::from(spans)), chunks[3]);
+
+    frame.render_widget(
+        Paragraph::new(Line::from(vec![
+            Span::styled("[s]", Style::default().fg(app.theme.accent)),
+            Span::styled(" Save  ", Style::default().fg(app.theme.text_dim)),
+            Span::styled("[d]", Style::default().fg(app.theme.accent)),
+            Span::styled(" Discard  ", Style::default().fg(app.theme.text_dim)),
+            Span::styled("[Enter]", Style::default().fg(app.theme.accent)),
+            Span::styled(" Confirm  ", Style::default().fg(app.theme.text_dim)),
+            Span::styled("[Esc]", Style::default().fg(app.theme.accent)),
+            Span::styled(" Cancel", Style::default().fg(app.theme.text_dim)),
         ])),
         chunks[5],
     );
@@ -371,9 +940,9 @@ pub fn render_soundfont_dialog(frame: &mut Frame, app: &App) {
 
     // Use different title/style for first-load modal
     let (title, border_color) = if app.soundfont_dialog.is_first_load {
-        (" Select a SoundFont to Continue ", Color::Yellow)
+        (" Select a SoundFont to Continue ", app.theme.accent)
     } else {
-        (" Load SoundFont ", Color::Cyan)
+        (" Load SoundFont ", app.theme.info)
     };
 
     let block = Block::default()
@@ -406,11 +975,11 @@ pub fn render_soundfont_dialog(frame: &mut Frame, app: &App) {
             Paragraph::new(vec![
                 Line::from(Span::styled(
                     "A SoundFont (.sf2) is required for audio playback.",
-                    Style::default().fg(Color::White),
+                    Style::default().fg(app.theme.text),
                 )),
                 Line::from(Span::styled(
                     "Browse to select a SoundFont file.",
-                    Style::default().fg(Color::DarkGray),
+                    Style::default().fg(app.theme.text_dim),
                 )),
             ]),
             chunks[0],
@@ -423,7 +992,10 @@ pub fn render_soundfont_dialog(frame: &mut Frame, app: &App) {
     let display_path = truncate_path(&path_str, max_width);
 
     frame.render_widget(
-        Paragraph::new(Span::styled(display_path, Style::default().fg(Color::Cyan))),
+        Paragraph::new(Span::styled(
+            display_path,
+            Style::default().fg(app.theme.info),
+        )),
         chunks[1],
     );
 
@@ -436,7 +1008,7 @@ pub fn render_soundfont_dialog(frame: &mut Frame, app: &App) {
         vec![ListItem::new(Line::from(Span::styled(
             "No SoundFont files found in this directory",
             Style::default()
-                .fg(Color::DarkGray)
+                .fg(app.theme.text_dim)
                 .add_modifier(Modifier::ITALIC),
         )))]
     } else {
@@ -451,19 +1023,19 @@ pub fn render_soundfont_dialog(frame: &mut Frame, app: &App) {
                     (
                         "[..]",
                         "Parent Directory".to_string(),
-                        Style::default().fg(Color::Blue),
+                        Style::default().fg(app.theme.link),
                     )
                 } else if path.is_dir() {
                     (
                         "[D]",
                         path_display_name(path),
-                        Style::default().fg(Color::Blue),
+                        Style::default().fg(app.theme.link),
                     )
                 } else {
                     (
                         "[SF2]",
                         path_display_name(path),
-                        Style::default().fg(Color::Green),
+                        Style::default().fg(app.theme.active),
                     )
                 };
 
@@ -474,7 +1046,10 @@ pub fn render_soundfont_dialog(frame: &mut Frame, app: &App) {
                 };
 
                 ListItem::new(Line::from(vec![
-                    Span::styled(format!("{} ", icon), Style::default().fg(Color::DarkGray)),
+                    Span::styled(
+                        format!("{} ", icon),
+                        Style::default().fg(app.theme.text_dim),
+                    ),
                     Span::styled(name, display_style),
                 ]))
             })
@@ -487,21 +1062,955 @@ pub fn render_soundfont_dialog(frame: &mut Frame, app: &App) {
     // Instructions - show different message for first-load modal
     let instructions = if app.soundfont_dialog.is_first_load {
         Line::from(vec![
-            Span::styled("[Up/Down]", Style::default().fg(Color::Yellow)),
-            Span::styled(" Navigate  ", Style::default().fg(Color::DarkGray)),
-            Span::styled("[Enter]", Style::default().fg(Color::Yellow)),
-            Span::styled(" Select", Style::default().fg(Color::DarkGray)),
+            Span::styled("[Up/Down]", Style::default().fg(app.theme.accent)),
+            Span::styled(" Navigate  ", Style::default().fg(app.theme.text_dim)),
+            Span::styled("[Enter]", Style::default().fg(app.theme.accent)),
+            Span::styled(" Select", Style::default().fg(app.theme.text_dim)),
         ])
     } else {
         Line::from(vec![
-            Span::styled("[Up/Down]", Style::default().fg(Color::Yellow)),
-            Span::styled(" Navigate  ", Style::default().fg(Color::DarkGray)),
-            Span::styled("[Enter]", Style::default().fg(Color::Yellow)),
-            Span::styled(" Select  ", Style::default().fg(Color::DarkGray)),
-            Span::styled("[Esc]", Style::default().fg(Color::Yellow)),
-            Span::styled(" Cancel", Style::default().fg(Color::DarkGray)),
+            Span::styled("[Up/Down]", Style::default().fg(app.theme.accent)),
+            Span::styled(" Navigate  ", Style::default().fg(app.theme.text_dim)),
+            Span::styled("[Enter]", Style::default().fg(app.theme.accent)),
+            Span::styled(" Select  ", Style::default().fg(app.theme.text_dim)),
+            Span::styled("[Esc]", Style::default().fg(app.theme.accent)),
+            Span::styled(" Cancel", Style::default().fg(app.theme.text_dim)),
         ])
     };
 
     frame.render_widget(Paragraph::new(instructions), chunks[4]);
 }
+
+/// Renders the marker list dialog overlay.
+///
+/// # Arguments
+///
+/// * `frame` - The frame to render to
+/// * `app` - Application state
+pub fn render_marker_dialog(frame: &mut Frame, app: &App) {
+    if !app.marker_dialog.open {
+        return;
+    }
+
+    let area = centered_rect(55, 60, frame.area());
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(" Markers ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(app.theme.border_focused));
+
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Min(3),    // Marker list
+            Constraint::Length(1), // Instructions (navigation)
+            Constraint::Length(1), // Instructions (sections)
+        ])
+        .split(inner);
+
+    let project = app.project();
+    let markers = project.markers();
+
+    let items: Vec<ListItem> = if markers.is_empty() {
+        vec![ListItem::new(Line::from(Span::styled(
+            "No markers - press M in Normal mode to add one",
+            Style::default().fg(app.theme.text_dim),
+        )))]
+    } else {
+        // Keep the selection visible
+        let visible_height = chunks[0].height as usize;
+        let start_idx = app
+            .marker_dialog
+            .selected
+            .saturating_sub(visible_height.saturating_sub(1));
+        let end_idx = (start_idx + visible_height).min(markers.len());
+
+        (start_idx..end_idx)
+            .map(|idx| {
+                let marker = &markers[idx];
+                let is_selected = idx == app.marker_dialog.selected;
+                let (measure, beat, tick) = project.tick_to_position(marker.tick);
+                let color = app.theme.marker_color(marker.color);
+
+                let name = if is_selected && app.marker_dialog.renaming {
+                    format!("{}_", app.marker_dialog.rename_buffer)
+                } else {
+                    marker.name.clone()
+                };
+                let name_style = if is_selected {
+                    Style::default()
+                        .fg(color)
+                        .add_modifier(Modifier::BOLD | Modifier::REVERSED)
+                } else {
+                    Style::default().fg(color)
+                };
+
+                ListItem::new(Line::from(vec![
+                    Span::styled("\u{25BC} ", Style::default().fg(color)),
+                    Span::styled(
+                        format!("{:>4}:{:02}:{:03}  ", measure, beat, tick),
+                        Style::default().fg(app.theme.text_dim),
+                    ),
+                    Span::styled(name, name_style),
+                ]))
+            })
+            .collect()
+    };
+
+    frame.render_widget(List::new(items), chunks[0]);
+
+    let key_style = Style::default().fg(app.theme.accent);
+    let desc_style = Style::default().fg(app.theme.text_dim);
+    let instructions = if app.marker_dialog.renaming {
+        Line::from(vec![
+            Span::styled("[Enter]", key_style),
+            Span::styled(" Confirm name  ", desc_style),
+            Span::styled("[Esc]", key_style),
+            Span::styled(" Cancel", desc_style),
+        ])
+    } else {
+        Line::from(vec![
+            Span::styled("[Enter]", key_style),
+            Span::styled(" Jump  ", desc_style),
+            Span::styled("[r]", key_style),
+            Span::styled(" Rename  ", desc_style),
+            Span::styled("[c]", key_style),
+            Span::styled(" Color  ", desc_style),
+            Span::styled("[x]", key_style),
+            Span::styled(" Remove marker  ", desc_style),
+            Span::styled("[Esc]", key_style),
+            Span::styled(" Close", desc_style),
+        ])
+    };
+    frame.render_widget(Paragraph::new(instructions), chunks[1]);
+
+    frame.render_widget(
+        Paragraph::new(Line::from(vec![
+            Span::styled("Section: ", desc_style),
+            Span::styled("[D]", key_style),
+            Span::styled(" Duplicate  ", desc_style),
+            Span::styled("[X]", key_style),
+            Span::styled(" Delete  ", desc_style),
+            Span::styled("[K/J]", key_style),
+            Span::styled(" Move earlier/later", desc_style),
+        ])),
+        chunks[2],
+    );
+}
+
+/// Renders the insert/delete time dialog overlay.
+///
+/// # Arguments
+///
+/// * `frame` - The frame to render to
+/// * `app` - Application state
+pub fn render_time_dialog(frame: &mut Frame, app: &App) {
+    if !app.time_dialog.open {
+        return;
+    }
+
+    let area = centered_rect(45, 25, frame.area());
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(" Insert / Delete Time ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(app.theme.accent));
+
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(1), // Spacer
+            Constraint::Length(1), // Operation
+            Constraint::Length(1), // Length
+            Constraint::Length(1), // Range
+            Constraint::Length(1), // Spacer
+            Constraint::Min(1),    // Instructions
+        ])
+        .split(inner);
+
+    let dialog = &app.time_dialog;
+    let project = app.project();
+    let label_style = Style::default().fg(app.theme.text_secondary);
+    let selected_style = |active: bool, color: Color| {
+        if active {
+            Style::default()
+                .fg(app.theme.text_inverse)
+                .bg(color)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(color)
+        }
+    };
+
+    frame.render_widget(
+        Paragraph::new(Line::from(vec![
+            Span::styled("  Operation: ", label_style),
+            Span::styled(" Insert ", selected_style(!dialog.delete, app.theme.active)),
+            Span::raw("  "),
+            Span::styled(" Delete ", selected_style(dialog.delete, app.theme.alert)),
+        ])),
+        chunks[1],
+    );
+
+    let numerator = project.time_sig_numerator.max(1) as u32;
+    let measures = dialog.length_beats / numerator;
+    let beats = dialog.length_beats % numerator;
+    frame.render_widget(
+        Paragraph::new(Line::from(vec![
+            Span::styled("  Length:    ", label_style),
+            Span::styled(
+                format!("{} beats", dialog.length_beats),
+                Style::default()
+                    .fg(app.theme.text)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(
+                format!("  ({} bars + {} beats)", measures, beats),
+                Style::default().fg(app.theme.text_dim),
+            ),
+        ])),
+        chunks[2],
+    );
+
+    let (measure, beat, tick) = project.tick_to_position(app.cursor_tick);
+    frame.render_widget(
+        Paragraph::new(Line::from(vec![
+            Span::styled("  At:        ", label_style),
+            Span::styled(
+                format!("{}:{:02}:{:03} (all tracks)", measure, beat, tick),
+                Style::default().fg(app.theme.text),
+            ),
+        ])),
+        chunks[3],
+    );
+
+    let key_style = Style::default().fg(app.theme.accent);
+    let desc_style = Style::default().fg(app.theme.text_dim);
+    frame.render_widget(
+        Paragraph::new(Line::from(vec![
+            Span::styled("[Left/Right]", key_style),
+            Span::styled(" Op  ", desc_style),
+            Span::styled("[Up/Down]", key_style),
+            Span::styled(" Beat  ", desc_style),
+            Span::styled("[K/J]", key_style),
+            Span::styled(" Bar  ", desc_style),
+            Span::styled("[Enter]", key_style),
+            Span::styled(" Apply  ", desc_style),
+            Span::styled("[Esc]", key_style),
+            Span::styled(" Cancel", desc_style),
+        ])),
+        chunks[5],
+    );
+}
+
+/// Renders the quantize dialog overlay.
+///
+/// # Arguments
+///
+/// * `frame` - The frame to render to
+/// * `app` - Application state
+pub fn render_quantize_dialog(frame: &mut Frame, app: &App) {
+    if !app.quantize_dialog.open {
+        return;
+    }
+
+    let area = centered_rect(45, 45, frame.area());
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(format!(
+            " Quantize ({} notes) ",
+            app.quantize_target_count()
+        ))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(app.theme.accent));
+
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Min(7),    // Settings
+            Constraint::Length(1), // Preview state
+            Constraint::Length(1), // Instructions
+        ])
+        .split(inner);
+
+    let dialog = &app.quantize_dialog;
+    let settings = &dialog.settings;
+    let on_off = |on: bool| if on { "On" } else { "Off" };
+    let rows = [
+        ("Grid", settings.grid_label()),
+        (
+            "Feel",
+            match settings.feel {
+                GridFeel::Straight => "Straight",
+                GridFeel::Triplet => "Triplet",
+                GridFeel::Dotted => "Dotted",
+            }
+            .to_string(),
+        ),
+        ("Strength", format!("{}%", settings.strength)),
+        ("Swing", format!("{}%", settings.swing)),
+        ("Window", format!("{}%", settings.window)),
+        ("Starts", on_off(settings.starts).to_string()),
+        ("Ends", on_off(settings.ends).to_string()),
+    ];
+
+    let items: Vec<ListItem> = rows
+        .iter()
+        .enumerate()
+        .map(|(idx, (label, value))| {
+            let value_style = if idx == dialog.selected {
+                Style::default()
+                    .fg(app.theme.text)
+                    .bg(app.theme.current_row_bg)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(app.theme.text)
+            };
+            ListItem::new(Line::from(vec![
+                Span::styled(
+                    format!("  {:<10}", label),
+                    Style::default().fg(app.theme.text_secondary),
+                ),
+                Span::styled(format!(" {} ", value), value_style),
+            ]))
+        })
+        .collect();
+    frame.render_widget(List::new(items), chunks[0]);
+
+    let preview = if dialog.preview {
+        Span::styled(
+            "  Preview on - piano roll shows the result",
+            Style::default().fg(app.theme.active),
+        )
+    } else {
+        Span::styled("  Preview off", Style::default().fg(app.theme.text_dim))
+    };
+    frame.render_widget(Paragraph::new(Line::from(preview)), chunks[1]);
+
+    let key_style = Style::default().fg(app.theme.accent);
+    let desc_style = Style::default().fg(app.theme.text_dim);
+    frame.render_widget(
+        Paragraph::new(Line::from(vec![
+            Span::styled("[Up/Down]", key_style),
+            Span::styled(" Select  ", desc_style),
+            Span::styled("[Left/Right]", key_style),
+            Span::styled(" Change  ", desc_style),
+            Span::styled("[p]", key_style),
+            Span::styled(" Preview  ", desc_style),
+            Span::styled("[Enter]", key_style),
+            Span::styled(" Apply  ", desc_style),
+            Span::styled("[Esc]", key_style),
+            Span::styled(" Cancel", desc_style),
+        ])),
+        chunks[2],
+    );
+}
+
+/// Renders the humanize/groove dialog overlay.
+///
+/// # Arguments
+///
+/// * `frame` - The frame to render to
+/// * `app` - Application state
+pub fn render_groove_dialog(frame: &mut Frame, app: &App) {
+    if !app.groove_dialog.open {
+        return;
+    }
+
+    let area = centered_rect(50, 50, frame.area());
+    frame.render_widget(Clear, area);
+
+    let target = if app.selected_notes.is_empty() {
+        "whole track".to_string()
+    } else {
+        format!("{} selected notes", app.selected_notes.len())
+    };
+    let block = Block::default()
+        .title(format!(" Humanize / Groove ({}) ", target))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(app.theme.accent));
+
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Min(8),    // Settings
+            Constraint::Length(1), // Instructions
+        ])
+        .split(inner);
+
+    let dialog = &app.groove_dialog;
+    let templates = app.groove_templates();
+    let template_name = templates
+        .get(dialog.template)
+        .map_or("-", |t| t.name.as_str());
+    let section_style = Style::default()
+        .fg(app.theme.info)
+        .add_modifier(Modifier::BOLD);
+
+    let row = |idx: usize, label: &str, value: String| {
+        let value_style = if idx == dialog.selected {
+            Style::default()
+                .fg(app.theme.text)
+                .bg(app.theme.current_row_bg)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(app.theme.text)
+        };
+        Line::from(vec![
+            Span::styled(
+                format!("  {:<10}", label),
+                Style::default().fg(app.theme.text_secondary),
+            ),
+            Span::styled(format!(" {} ", value), value_style),
+        ])
+    };
+
+    let humanize = &dialog.humanize;
+    let lines = vec![
+        Line::from(Span::styled(" Humanize", section_style)),
+        row(0, "Timing", format!("+/- {} ticks", humanize.timing)),
+        row(1, "Velocity", format!("+/- {}", humanize.velocity)),
+        row(2, "Length", format!("+/- {} ticks", humanize.length)),
+        row(3, "Seed", humanize.seed.to_string()),
+        Line::from(""),
+        Line::from(Span::styled(" Groove", section_style)),
+        row(4, "Template", template_name.to_string()),
+        row(5, "Strength", format!("{}%", dialog.strength)),
+    ];
+    frame.render_widget(Paragraph::new(lines), chunks[0]);
+
+    let key_style = Style::default().fg(app.theme.accent);
+    let desc_style = Style::default().fg(app.theme.text_dim);
+    frame.render_widget(
+        Paragraph::new(Line::from(vec![
+            Span::styled("[Left/Right]", key_style),
+            Span::styled(" Change  ", desc_style),
+            Span::styled("[Enter]", key_style),
+            Span::styled(" Apply section  ", desc_style),
+            Span::styled("[x]", key_style),
+            Span::styled(" Extract from track  ", desc_style),
+            Span::styled("[Esc]", key_style),
+            Span::styled(" Close", desc_style),
+        ])),
+        chunks[1],
+    );
+}
+
+/// Renders the key/scale dialog overlay.
+///
+/// Shows the root, scale type and snap setting, plus the twelve pitch classes
+/// above the root, which can be toggled to build a custom scale.
+///
+/// # Arguments
+///
+/// * `frame` - The frame to render to
+/// * `app` - Application state
+pub fn render_scale_dialog(frame: &mut Frame, app: &App) {
+    if !app.scale_dialog.open {
+        return;
+    }
+
+    let area = centered_rect(50, 40, frame.area());
+    frame.render_widget(Clear, area);
+
+    let current = app
+        .project()
+        .scale
+        .map_or_else(|| "none".to_string(), |scale| scale.name());
+    let block = Block::default()
+        .title(format!(" Key / Scale (current: {}) ", current))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(app.theme.accent));
+
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Min(5),    // Settings
+            Constraint::Length(1), // Instructions
+        ])
+        .split(inner);
+
+    let dialog = &app.scale_dialog;
+    let scale = &dialog.scale;
+    let highlight = Style::default()
+        .fg(app.theme.text)
+        .bg(app.theme.current_row_bg)
+        .add_modifier(Modifier::BOLD);
+
+    let row = |idx: usize, label: &str, value: String| {
+        let value_style = if idx == dialog.selected {
+            highlight
+        } else {
+            Style::default().fg(app.theme.text)
+        };
+        Line::from(vec![
+            Span::styled(
+                format!("  {:<10}", label),
+                Style::default().fg(app.theme.text_secondary),
+            ),
+            Span::styled(format!(" {} ", value), value_style),
+        ])
+    };
+
+    // Pitch classes from the root up, in-scale ones lit
+    let mut pitch_spans = vec![Span::styled(
+        format!("  {:<10} ", "Notes"),
+        Style::default().fg(app.theme.text_secondary),
+    )];
+    for interval in 0..12u8 {
+        let pitch = scale.root + interval;
+        let in_scale = scale.contains(pitch);
+        let style =
+            if dialog.selected == SCALE_DIALOG_CUSTOM_ROW && interval == dialog.custom_cursor {
+                highlight
+            } else if in_scale {
+                Style::default()
+                    .fg(app.theme.info)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(app.theme.text_dim)
+            };
+        pitch_spans.push(Span::styled(
+            format!("{:<3}", NOTE_NAMES[pitch as usize % 12]),
+            style,
+        ));
+    }
+
+    let lines = vec![
+        row(0, "Root", NOTE_NAMES[scale.root as usize].to_string()),
+        row(1, "Scale", scale.kind.name().to_string()),
+        row(2, "Snap", if scale.snap { "on" } else { "off" }.to_string()),
+        Line::from(pitch_spans),
+    ];
+    frame.render_widget(Paragraph::new(lines), chunks[0]);
+
+    let key_style = Style::default().fg(app.theme.accent);
+    let desc_style = Style::default().fg(app.theme.text_dim);
+    frame.render_widget(
+        Paragraph::new(Line::from(vec![
+            Span::styled("[Left/Right]", key_style),
+            Span::styled(" Change  ", desc_style),
+            Span::styled("[Space]", key_style),
+            Span::styled(" Toggle note  ", desc_style),
+            Span::styled("[Enter]", key_style),
+            Span::styled(" Set  ", desc_style),
+            Span::styled("[x]", key_style),
+            Span::styled(" Clear  ", desc_style),
+            Span::styled("[Esc]", key_style),
+            Span::styled(" Cancel", desc_style),
+        ])),
+        chunks[1],
+    );
+}
+
+/// Renders the chord dialog overlay.
+///
+/// The top section sets up chord entry (quality, inversion, voicing), the
+/// bottom one edits the chord track and generates accompaniment from it.
+///
+/// # Arguments
+///
+/// * `frame` - The frame to render to
+/// * `app` - Application state
+pub fn render_chord_dialog(frame: &mut Frame, app: &App) {
+    if !app.chord_dialog.open {
+        return;
+    }
+
+    let area = centered_rect(55, 55, frame.area());
+    frame.render_widget(Clear, area);
+
+    let detected = app
+        .detected_chord()
+        .map_or_else(|| "-".to_string(), |chord| chord.symbol());
+    let block = Block::default()
+        .title(format!(" Chords (at cursor: {}) ", detected))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(app.theme.accent));
+
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Min(9),    // Settings
+            Constraint::Length(2), // Instructions
+        ])
+        .split(inner);
+
+    let dialog = &app.chord_dialog;
+    let voicing = &app.chord_voicing;
+    let section_style = Style::default()
+        .fg(app.theme.info)
+        .add_modifier(Modifier::BOLD);
+
+    let row = |idx: usize, label: &str, value: String| {
+        let value_style = if idx == dialog.selected {
+            Style::default()
+                .fg(app.theme.text)
+                .bg(app.theme.current_row_bg)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(app.theme.text)
+        };
+        Line::from(vec![
+            Span::styled(
+                format!("  {:<10}", label),
+                Style::default().fg(app.theme.text_secondary),
+            ),
+            Span::styled(format!(" {} ", value), value_style),
+        ])
+    };
+
+    let quality = Chord::new(app.cursor_pitch, voicing.quality).symbol();
+    let target = app
+        .project()
+        .track_at(dialog.target_track)
+        .map_or("-", |t| t.name.as_str());
+    let symbols = app.project().chords().len();
+
+    let lines = vec![
+        Line::from(Span::styled(" Chord entry", section_style)),
+        row(
+            0,
+            "Entry",
+            if app.chord_entry { "on" } else { "off" }.to_string(),
+        ),
+        row(1, "Chord", quality),
+        row(2, "Inversion", voicing.inversion_name()),
+        row(
+            3,
+            "Voicing",
+            if voicing.spread { "open" } else { "close" }.to_string(),
+        ),
+        Line::from(""),
+        Line::from(Span::styled(
+            format!(" Chord track ({} symbols)", symbols),
+            section_style,
+        )),
+        row(4, "Style", dialog.style.name().to_string()),
+        row(5, "Track", target.to_string()),
+    ];
+    frame.render_widget(Paragraph::new(lines), chunks[0]);
+
+    let key_style = Style::default().fg(app.theme.accent);
+    let desc_style = Style::default().fg(app.theme.text_dim);
+    frame.render_widget(
+        Paragraph::new(vec![
+            Line::from(vec![
+                Span::styled("[Left/Right]", key_style),
+                Span::styled(" Change  ", desc_style),
+                Span::styled("[a]", key_style),
+                Span::styled(" Add chord  ", desc_style),
+                Span::styled("[c]", key_style),
+                Span::styled(" Add detected  ", desc_style),
+                Span::styled("[x]", key_style),
+                Span::styled(" Remove", desc_style),
+            ]),
+            Line::from(vec![
+                Span::styled("[g]", key_style),
+                Span::styled(" Generate accompaniment  ", desc_style),
+                Span::styled("[Esc]", key_style),
+                Span::styled(" Close", desc_style),
+            ]),
+        ]),
+        chunks[1],
+    );
+}
+
+/// Renders the note effects dialog (arpeggiator and echo).
+///
+/// # Arguments
+///
+/// * `frame` - The frame to render to
+/// * `app` - Application state
+pub fn render_effects_dialog(frame: &mut Frame, app: &App) {
+    if !app.effects_dialog.open {
+        return;
+    }
+
+    let area = centered_rect(55, 55, frame.area());
+    frame.render_widget(Clear, area);
+
+    let target = if app.selected_notes.is_empty() {
+        "track".to_string()
+    } else {
+        format!("{} notes", app.selected_notes.len())
+    };
+    let block = Block::default()
+        .title(format!(" Note Effects ({}) ", target))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(app.theme.accent));
+
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Min(9),    // Settings and chain
+            Constraint::Length(2), // Instructions
+        ])
+        .split(inner);
+
+    let dialog = &app.effects_dialog;
+    let section_style = Style::default()
+        .fg(app.theme.info)
+        .add_modifier(Modifier::BOLD);
+
+    let row = |idx: usize, label: &str, value: String| {
+        let value_style = if idx == dialog.selected {
+            Style::default()
+                .fg(app.theme.text)
+                .bg(app.theme.current_row_bg)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(app.theme.text)
+        };
+        Line::from(vec![
+            Span::styled(
+                format!("  {:<10}", label),
+                Style::default().fg(app.theme.text_secondary),
+            ),
+            Span::styled(format!(" {} ", value), value_style),
+        ])
+    };
+
+    let mut lines = vec![Line::from(Span::styled(" Effect", section_style))];
+    if dialog.echo {
+        let echo = &dialog.echo_settings;
+        lines.push(row(0, "Type", "echo".to_string()));
+        lines.push(row(1, "Repeats", echo.repeats.to_string()));
+        lines.push(row(2, "Rate", format!("1/{}", echo.rate)));
+        lines.push(row(3, "Decay", format!("{}%", echo.decay)));
+    } else {
+        let arp = &dialog.arp_settings;
+        lines.push(row(0, "Type", "arpeggiator".to_string()));
+        lines.push(row(1, "Direction", arp.direction.name().to_string()));
+        lines.push(row(2, "Rate", format!("1/{}", arp.rate)));
+        lines.push(row(3, "Gate", format!("{}%", arp.gate)));
+        lines.push(row(4, "Octaves", arp.octaves.to_string()));
+    }
+
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        " Track effect chain",
+        section_style,
+    )));
+    let chain = app
+        .selected_track()
+        .map(|t| t.effects.as_slice())
+        .unwrap_or(&[]);
+    if chain.is_empty() {
+        lines.push(Line::from(Span::styled(
+            "  (none)",
+            Style::default().fg(app.theme.text_dim),
+        )));
+    }
+    for (i, effect) in chain.iter().enumerate() {
+        lines.push(Line::from(Span::styled(
+            format!("  {}. {}", i + 1, effect.label()),
+            Style::default().fg(app.theme.text),
+        )));
+    }
+    frame.render_widget(Paragraph::new(lines), chunks[0]);
+
+    let key_style = Style::default().fg(app.theme.accent);
+    let desc_style = Style::default().fg(app.theme.text_dim);
+    frame.render_widget(
+        Paragraph::new(vec![
+            Line::from(vec![
+                Span::styled("[Left/Right]", key_style),
+                Span::styled(" Change  ", desc_style),
+                Span::styled("[Enter]", key_style),
+                Span::styled(" Render into notes  ", desc_style),
+                Span::styled("[Esc]", key_style),
+                Span::styled(" Close", desc_style),
+            ]),
+            Line::from(vec![
+                Span::styled("[a]", key_style),
+                Span::styled(" Add to track  ", desc_style),
+                Span::styled("[x]", key_style),
+                Span::styled(" Remove last  ", desc_style),
+                Span::styled("[b]", key_style),
+                Span::styled(" Bake chain", desc_style),
+            ]),
+        ]),
+        chunks[1],
+    );
+}
+
+/// Renders the drum map dialog for the selected track.
+///
+/// Lists the instruments in display order with their pitch, output remap
+/// and step sequencer velocity.
+///
+/// # Arguments
+///
+/// * `frame` - The frame to render to
+/// * `app` - Application state
+pub fn render_drum_map_dialog(frame: &mut Frame, app: &App) {
+    if !app.drum_map_dialog.open {
+        return;
+    }
+
+    let area = centered_rect(60, 70, frame.area());
+    frame.render_widget(Clear, area);
+
+    let map = app.drum_map();
+    let track_name = app.selected_track().map_or("-", |t| t.name.as_str());
+    let block = Block::default()
+        .title(format!(" Drum Map - {} ({}) ", map.name, track_name))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(app.theme.border_focused));
+
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(1), // Column headers
+            Constraint::Min(3),    // Instruments
+            Constraint::Length(1), // Input line
+            Constraint::Length(2), // Instructions
+        ])
+        .split(inner);
+
+    let dialog = &app.drum_map_dialog;
+    let header_style = Style::default()
+        .fg(app.theme.text_dim)
+        .add_modifier(Modifier::BOLD);
+    frame.render_widget(
+        Paragraph::new(Line::from(Span::styled(
+            format!(
+                "  {:<20} {:>6} {:>8} {:>9}",
+                "Instrument", "Pitch", "Output", "Velocity"
+            ),
+            header_style,
+        ))),
+        chunks[0],
+    );
+
+    // Keep the selection visible
+    let visible_height = chunks[1].height as usize;
+    let start_idx = dialog
+        .selected
+        .saturating_sub(visible_height.saturating_sub(1));
+    let items: Vec<ListItem> = map
+        .entries
+        .iter()
+        .enumerate()
+        .skip(start_idx)
+        .take(visible_height)
+        .map(|(idx, entry)| {
+            let is_selected = idx == dialog.selected;
+            let value_style = |column: usize| {
+                if is_selected && column == dialog.column {
+                    Style::default()
+                        .fg(app.theme.text)
+                        .bg(app.theme.current_row_bg)
+                        .add_modifier(Modifier::BOLD)
+                } else if is_selected {
+                    Style::default().fg(app.theme.text)
+                } else {
+                    Style::default().fg(app.theme.text_secondary)
+                }
+            };
+            let name = match &dialog.input {
+                Some((DrumMapInput::Rename, buffer)) if is_selected => format!("{}_", buffer),
+                _ => entry.name.clone(),
+            };
+            let name_style = if is_selected {
+                Style::default()
+                    .fg(app.theme.info)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(app.theme.text)
+            };
+            let output = entry
+                .output
+                .map_or_else(|| "-".to_string(), |pitch| pitch.to_string());
+            ListItem::new(Line::from(vec![
+                Span::styled(format!("  {:<20.20} ", name), name_style),
+                Span::styled(format!("{:>6}", entry.pitch), value_style(0)),
+                Span::raw(" "),
+                Span::styled(format!("{:>8}", output), value_style(1)),
+                Span::raw(" "),
+                Span::styled(format!("{:>9}", entry.velocity), value_style(2)),
+            ]))
+        })
+        .collect();
+    frame.render_widget(List::new(items), chunks[1]);
+
+    let key_style = Style::default().fg(app.theme.accent);
+    let desc_style = Style::default().fg(app.theme.text_dim);
+    if let Some((kind @ (DrumMapInput::Load | DrumMapInput::Save), buffer)) = &dialog.input {
+        let label = if *kind == DrumMapInput::Load {
+            "Load from: "
+        } else {
+            "Save to: "
+        };
+        frame.render_widget(
+            Paragraph::new(Line::from(vec![
+                Span::styled(label, key_style),
+                Span::styled(format!("{}_", buffer), Style::default().fg(app.theme.text)),
+            ])),
+            chunks[2],
+        );
+    }
+
+    let instructions = if dialog.input.is_some() {
+        vec![Line::from(vec![
+            Span::styled("[Enter]", key_style),
+            Span::styled(" Confirm  ", desc_style),
+            Span::styled("[Esc]", key_style),
+            Span::styled(" Cancel", desc_style),
+        ])]
+    } else {
+        vec![
+            Line::from(vec![
+                Span::styled("[Left/Right]", key_style),
+                Span::styled(" Column  ", desc_style),
+                Span::styled("[+/-]", key_style),
+                Span::styled(" Change  ", desc_style),
+                Span::styled("[d]", key_style),
+                Span::styled(" No remap  ", desc_style),
+                Span::styled("[K/J]", key_style),
+                Span::styled(" Move  ", desc_style),
+                Span::styled("[r]", key_style),
+                Span::styled(" Rename", desc_style),
+            ]),
+            Line::from(vec![
+                Span::styled("[a/x]", key_style),
+                Span::styled(" Add/remove  ", desc_style),
+                Span::styled("[g]", key_style),
+                Span::styled(" GM map  ", desc_style),
+                Span::styled("[o/w]", key_style),
+                Span::styled(" Load/save file  ", desc_style),
+                Span::styled("[Esc]", key_style),
+                Span::styled(" Close", desc_style),
+            ]),
+        ]
+    };
+    frame.render_widget(Paragraph::new(instructions), chunks[3]);
+}
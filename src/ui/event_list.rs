@@ -0,0 +1,203 @@
+//! Event list rendering.
+//!
+//! Shows every note of the selected track as a table row with its exact
+//! position, pitch, velocity and length. Selected rows share the piano
+//! roll's note selection.
+
+use crate::app::{App, EventListInput};
+use crate::midi::EventColumn;
+use ratatui::layout::Rect;
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Paragraph};
+use ratatui::Frame;
+
+/// Width of the selection marker column.
+const MARK_WIDTH: usize = 2;
+
+/// Width of the Type column.
+const TYPE_WIDTH: usize = 6;
+
+/// Returns the display width of a column.
+fn column_width(column: EventColumn) -> usize {
+    match column {
+        EventColumn::Position => 13,
+        EventColumn::Pitch => 7,
+        EventColumn::Velocity => 10,
+        EventColumn::Length => 10,
+    }
+}
+
+/// Renders the event list for the selected track.
+///
+/// # Arguments
+///
+/// * `frame` - The frame to render to
+/// * `area` - The area to render in
+/// * `app` - Application state
+/// * `focused` - Whether this panel is focused
+///
+/// # Returns
+///
+/// The area of the note rows and the index of the first shown row, for
+/// mouse hit testing
+pub fn render_event_list(frame: &mut Frame, area: Rect, app: &App, focused: bool) -> (Rect, usize) {
+    let list = &app.event_list;
+    let rows = app.event_rows();
+    let total = app.selected_track().map_or(0, |t| t.notes().len());
+    let filter = if list.filter.is_empty() {
+        String::new()
+    } else {
+        format!(" | Filter: {}", list.filter.describe())
+    };
+    let title = match app.selected_track() {
+        Some(track) => format!(
+            " Event List - {} | {} of {} notes{} ",
+            track.name,
+            rows.len(),
+            total,
+            filter
+        ),
+        None => " Event List ".to_string(),
+    };
+    let block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(app.theme.border(focused)));
+
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+    if inner.height < 3 {
+        return (Rect::default(), 0);
+    }
+
+    // One line for the header and one for hints or the filter field
+    let visible_rows = (inner.height - 2) as usize;
+    let first_row = (list.row + 1).saturating_sub(visible_rows);
+    let playing = app.audio.is_playing();
+
+    let header_style = Style::default()
+        .fg(app.theme.accent)
+        .add_modifier(Modifier::BOLD);
+    let mut header = vec![
+        Span::raw(" ".repeat(MARK_WIDTH)),
+        Span::styled(
+            format!("{:<width$}", "Type", width = TYPE_WIDTH),
+            header_style,
+        ),
+    ];
+    for column in EventColumn::ALL {
+        let arrow = match (list.sort.column == column, list.sort.descending) {
+            (true, false) => " ▲",
+            (true, true) => " ▼",
+            (false, _) => "",
+        };
+        let mut style = header_style;
+        if column == list.column {
+            style = style.add_modifier(Modifier::UNDERLINED);
+        }
+        header.push(Span::styled(
+            format!(
+                "{:<width$}",
+                format!("{}{}", column.title(), arrow),
+                width = column_width(column)
+            ),
+            style,
+        ));
+    }
+    let mut lines = vec![Line::from(header)];
+
+    if rows.is_empty() {
+        lines.push(Line::from(Span::styled(
+            if total == 0 {
+                " No notes in this track"
+            } else {
+                " No notes match the filter"
+            },
+            Style::default().fg(app.theme.text_dim),
+        )));
+    }
+
+    for (index, note) in rows.iter().enumerate().skip(first_row).take(visible_rows) {
+        let current = index == list.row;
+        let selected = app.selected_notes.contains(&note.id);
+        let sounding = playing && note.is_active_at(app.cursor_tick);
+        let mut row_style = if sounding {
+            Style::default().fg(app.theme.active)
+        } else if selected {
+            Style::default().fg(app.theme.info)
+        } else {
+            Style::default().fg(app.theme.text)
+        };
+        if current {
+            row_style = row_style.bg(app.theme.current_row_bg);
+        }
+
+        let mut spans = vec![
+            Span::styled(if selected { "● " } else { "  " }, row_style),
+            Span::styled(format!("{:<width$}", "Note", width = TYPE_WIDTH), row_style),
+        ];
+        for column in EventColumn::ALL {
+            let editing = current && column == list.column;
+            let text = match &list.input {
+                Some((EventListInput::Cell, buffer)) if editing => format!("{}_", buffer),
+                _ => app.event_cell_text(note, column),
+            };
+            let style = if editing && focused {
+                Style::default()
+                    .fg(app.theme.text)
+                    .bg(app.theme.current_row_bg)
+            } else {
+                row_style
+            };
+            let width = column_width(column);
+            spans.push(Span::styled(format!("{:<width$}", text), style));
+        }
+        lines.push(Line::from(spans));
+    }
+    frame.render_widget(Paragraph::new(lines), inner);
+
+    let key_style = Style::default().fg(app.theme.accent);
+    let desc_style = Style::default().fg(app.theme.text_dim);
+    let footer = match &list.input {
+        Some((EventListInput::Filter, buffer)) => Line::from(vec![
+            Span::styled("Filter: ", key_style),
+            Span::raw(format!("{}_", buffer)),
+            Span::styled("  (C3-G4 pitches, 5-8 bars, empty clears)", desc_style),
+        ]),
+        _ => Line::from(vec![
+            Span::styled("Enter", key_style),
+            Span::styled(" edit  ", desc_style),
+            Span::styled("+/-", key_style),
+            Span::styled(" nudge  ", desc_style),
+            Span::styled("v", key_style),
+            Span::styled(" select  ", desc_style),
+            Span::styled("J/K", key_style),
+            Span::styled(" extend  ", desc_style),
+            Span::styled("a", key_style),
+            Span::styled(" all  ", desc_style),
+            Span::styled("o", key_style),
+            Span::styled(" sort  ", desc_style),
+            Span::styled("/", key_style),
+            Span::styled(" filter  ", desc_style),
+            Span::styled("x", key_style),
+            Span::styled(" delete", desc_style),
+        ]),
+    };
+    let footer_area = Rect {
+        y: inner.y + inner.height - 1,
+        height: 1,
+        ..inner
+    };
+    frame.render_widget(Paragraph::new(footer), footer_area);
+
+    let shown = rows.len().saturating_sub(first_row).min(visible_rows);
+    (
+        Rect {
+            y: inner.y + 1,
+            height: shown as u16,
+            ..inner
+        },
+        first_row,
+    )
+}
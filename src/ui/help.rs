@@ -1,14 +1,18 @@
 //! Help overlay rendering.
 //!
-//! Displays keyboard shortcuts and commands in a modal overlay.
+//! Displays keyboard shortcuts and commands in a modal overlay. The bindings
+//! of the remappable modes come from the active keymap.
 
 use ratatui::layout::{Constraint, Direction, Layout};
-use ratatui::style::{Color, Modifier, Style};
+use ratatui::style::{Modifier, Style};
 use ratatui::text::{Line, Span};
 use ratatui::widgets::{Block, Borders, Clear, Paragraph};
 use ratatui::Frame;
 
 use super::centered_rect;
+use crate::command::COMMANDS;
+use crate::keymap::{Action, HelpSection, KeyContext, Keymap};
+use crate::theme::Theme;
 
 /// Key binding entry for the help display.
 struct KeyBinding {
@@ -16,215 +20,145 @@ struct KeyBinding {
     description: &'static str,
 }
 
-const GENERAL_BINDINGS: &[KeyBinding] = &[
+/// Keys inside the marker list, listed after the marker actions.
+const MARKER_LIST_BINDINGS: &[KeyBinding] = &[
     KeyBinding {
-        key: "?",
-        description: "Toggle this help",
+        key: "D / X",
+        description: "Duplicate/delete section (in marker list)",
     },
     KeyBinding {
-        key: "q / Esc",
-        description: "Quit",
-    },
-    KeyBinding {
-        key: "Ctrl+C",
-        description: "Force quit",
-    },
-    KeyBinding {
-        key: "Tab",
-        description: "Cycle focus between panels",
-    },
-    KeyBinding {
-        key: "Space",
-        description: "Play / Pause",
-    },
-    KeyBinding {
-        key: "Shift+Space",
-        description: "Restart playback from beginning",
-    },
-    KeyBinding {
-        key: ".",
-        description: "Stop (reset to start)",
+        key: "K / J",
+        description: "Move section earlier/later (in marker list)",
     },
 ];
 
-const MODE_BINDINGS: &[KeyBinding] = &[
-    KeyBinding {
-        key: "i",
-        description: "Enter INSERT mode",
-    },
-    KeyBinding {
-        key: "v",
-        description: "Enter SELECT mode",
-    },
+/// Count keys, listed before the navigation actions.
+const COUNT_BINDINGS: &[KeyBinding] = &[KeyBinding {
+    key: "1-9 (0-9)",
+    description: "Count: repeat the next motion or edit (8l, 3p, 4.)",
+}];
+
+const STEP_BINDINGS: &[KeyBinding] = &[
     KeyBinding {
-        key: "Esc",
-        description: "Return to NORMAL mode",
+        key: "g",
+        description: "Open from the timeline view (drum tracks)",
     },
-];
-
-const NAVIGATION_BINDINGS: &[KeyBinding] = &[
     KeyBinding {
-        key: "h / Left",
-        description: "Move cursor left",
+        key: "Enter / Click",
+        description: "Toggle step",
     },
     KeyBinding {
-        key: "l / Right",
-        description: "Move cursor right",
+        key: "a",
+        description: "Toggle accent on step",
     },
     KeyBinding {
-        key: "k / Up",
-        description: "Move cursor up (higher pitch)",
+        key: "+ / -",
+        description: "Step velocity up/down",
     },
     KeyBinding {
-        key: "j / Down",
-        description: "Move cursor down (lower pitch)",
+        key: "H / L",
+        description: "Previous/next pattern",
     },
     KeyBinding {
-        key: "H",
-        description: "Jump left by measure",
+        key: ", / /",
+        description: "Shorter/longer pattern (1-32 steps)",
     },
     KeyBinding {
-        key: "L",
-        description: "Jump right by measure",
+        key: "z",
+        description: "Cycle step length (1/4 to 1/32)",
     },
     KeyBinding {
-        key: "0",
-        description: "Go to start",
+        key: "c",
+        description: "Copy pattern to the next bar",
     },
     KeyBinding {
-        key: "$",
-        description: "Go to end",
+        key: "o",
+        description: "Loop pattern during playback",
     },
 ];
 
-const EDIT_BINDINGS: &[KeyBinding] = &[
+const STAFF_BINDINGS: &[KeyBinding] = &[
     KeyBinding {
-        key: "Enter / n",
-        description: "Place note at cursor",
+        key: "g",
+        description: "Open from the timeline view (read-only)",
     },
     KeyBinding {
-        key: "Delete",
-        description: "Delete note at cursor",
+        key: "Click",
+        description: "Seek to the clicked sixteenth",
     },
     KeyBinding {
-        key: "W/A/S/D",
-        description: "Move selected notes (up/left/down/right)",
+        key: "h / l",
+        description: "Move cursor by one beat",
     },
     KeyBinding {
-        key: "Shift+A/D",
-        description: "Shrink/expand note duration",
+        key: "H / L",
+        description: "Move cursor by one bar",
     },
 ];
 
-const TRACK_BINDINGS: &[KeyBinding] = &[
+const EVENT_BINDINGS: &[KeyBinding] = &[
     KeyBinding {
-        key: "a",
-        description: "Add new track",
+        key: "j / k",
+        description: "Move between notes (cursor follows)",
     },
     KeyBinding {
-        key: "x / d",
-        description: "Delete selected track",
+        key: "h / l",
+        description: "Move between columns",
     },
     KeyBinding {
-        key: "r",
-        description: "Rename selected track",
+        key: "Enter",
+        description: "Edit cell (Enter applies, Esc cancels)",
     },
     KeyBinding {
-        key: "m",
-        description: "Toggle mute on selected track",
+        key: "+ / -",
+        description: "Nudge cell by one tick/semitone/step",
     },
     KeyBinding {
-        key: "s",
-        description: "Toggle solo on selected track",
+        key: "v / Click",
+        description: "Toggle selection / highlight row",
     },
     KeyBinding {
         key: "J / K",
-        description: "Select next/previous track",
-    },
-    KeyBinding {
-        key: "< / >",
-        description: "Change instrument (GM)",
+        description: "Extend selection down/up",
     },
     KeyBinding {
-        key: "; / '",
-        description: "Decrease/increase volume",
-    },
-    KeyBinding {
-        key: "( / )",
-        description: "Pan left/right",
-    },
-];
-
-const KEYBOARD_BINDINGS: &[KeyBinding] = &[
-    KeyBinding {
-        key: "Z-M / Q-I",
-        description: "Play notes (piano layout)",
+        key: "a",
+        description: "Select all listed notes / clear",
     },
     KeyBinding {
-        key: ",",
-        description: "Octave down",
+        key: "o",
+        description: "Sort by column (again to reverse)",
     },
     KeyBinding {
         key: "/",
-        description: "Octave up",
-    },
-];
-
-const VIEW_BINDINGS: &[KeyBinding] = &[
-    KeyBinding {
-        key: "g",
-        description: "Cycle views (Combined/Piano/Timeline)",
-    },
-    KeyBinding {
-        key: "t",
-        description: "Toggle track list view (compact/expanded)",
+        description: "Filter (C3-G4 pitches, 5-8 bars)",
     },
     KeyBinding {
-        key: "W",
-        description: "Toggle active track highlighting",
-    },
-    KeyBinding {
-        key: "= / -",
-        description: "Zoom in/out",
-    },
-    KeyBinding {
-        key: "[ / ]",
-        description: "Decrease/increase tempo (BPM)",
-    },
-    KeyBinding {
-        key: "{ / }",
-        description: "Decrease/increase time sig numerator",
-    },
-    KeyBinding {
-        key: "|",
-        description: "Cycle time sig denominator (2/4/8/16)",
+        key: "x / Del",
+        description: "Delete note(s)",
     },
 ];
 
-const FILE_BINDINGS: &[KeyBinding] = &[
+const LYRIC_BINDINGS: &[KeyBinding] = &[
     KeyBinding {
-        key: "Ctrl+n",
-        description: "New project (with confirmation)",
-    },
-    KeyBinding {
-        key: "Ctrl+s",
-        description: "Save project",
+        key: "Space",
+        description: "End word, go to next note",
     },
     KeyBinding {
-        key: "Ctrl+o",
-        description: "Open project",
+        key: "-",
+        description: "End syllable mid-word, go to next note",
     },
     KeyBinding {
-        key: "Ctrl+l",
-        description: "Load SoundFont (.sf2)",
+        key: "Enter",
+        description: "Start a new lyric line at the next note",
     },
     KeyBinding {
-        key: "e / Ctrl+e",
-        description: "Export to WAV",
+        key: "Left / Right",
+        description: "Previous/next note",
     },
     KeyBinding {
-        key: "Ctrl+m",
-        description: "Export to MIDI (.mid)",
+        key: "Backspace",
+        description: "Delete character / previous note",
     },
 ];
 
@@ -263,13 +197,68 @@ const MOUSE_BINDINGS: &[KeyBinding] = &[
     },
 ];
 
+/// Returns the help rows for the actions of `section`, with every key
+/// bound to each action in the keymap. Unbound actions are left out.
+fn keymap_rows(keymap: &Keymap, section: HelpSection) -> Vec<(String, &'static str)> {
+    let mut rows = Vec::new();
+    if section == HelpSection::Keyboard {
+        rows.push((keymap.piano_label(), "Play notes (piano layout)"));
+    }
+    if section == HelpSection::Navigation {
+        rows.extend(
+            COUNT_BINDINGS
+                .iter()
+                .map(|b| (b.key.to_string(), b.description)),
+        );
+    }
+    for &action in Action::ALL.iter().filter(|a| a.section() == section) {
+        let bindings: Vec<_> = keymap
+            .bindings()
+            .iter()
+            .filter(|b| b.action == action)
+            .collect();
+        if bindings.is_empty() {
+            continue;
+        }
+        let mut keys: Vec<String> = Vec::new();
+        for binding in &bindings {
+            let label = binding.chord.to_string();
+            if !keys.contains(&label) {
+                keys.push(label);
+            }
+        }
+        let mut key = keys.join(" / ");
+        // Name the mode when the action only exists in one
+        let only_in = |context| bindings.iter().all(|b| b.context == context);
+        if only_in(KeyContext::Select) {
+            key.push_str(" (select)");
+        } else if only_in(KeyContext::Insert) {
+            key.push_str(" (insert)");
+        }
+        rows.push((key, action.description()));
+    }
+    if section == HelpSection::Markers {
+        rows.extend(
+            MARKER_LIST_BINDINGS
+                .iter()
+                .map(|b| (b.key.to_string(), b.description)),
+        );
+    }
+    rows
+}
+
 /// Renders the help overlay.
 ///
+/// The sections for the remappable modes are built from the active keymap;
+/// the view-specific, lyric and mouse sections are fixed.
+///
 /// # Arguments
 ///
 /// * `frame` - The frame to render to
+/// * `keymap` - The active keymap
+/// * `theme` - The active color theme
 /// * `scroll` - Vertical scroll offset
-pub fn render_help(frame: &mut Frame, scroll: u16) {
+pub fn render_help(frame: &mut Frame, keymap: &Keymap, theme: &Theme, scroll: u16) {
     let area = centered_rect(70, 80, frame.area());
 
     // Clear the area behind the popup
@@ -278,7 +267,7 @@ pub fn render_help(frame: &mut Frame, scroll: u16) {
     let block = Block::default()
         .title(" Help - Keyboard Shortcuts ")
         .borders(Borders::ALL)
-        .border_style(Style::default().fg(Color::Cyan));
+        .border_style(Style::default().fg(theme.border_focused));
 
     let inner = block.inner(area);
     frame.render_widget(block, area);
@@ -296,92 +285,84 @@ pub fn render_help(frame: &mut Frame, scroll: u16) {
     let mut lines: Vec<Line<'static>> = Vec::new();
 
     let section_style = Style::default()
-        .fg(Color::Yellow)
+        .fg(theme.accent)
         .add_modifier(Modifier::BOLD | Modifier::UNDERLINED);
-    let key_style = Style::default()
-        .fg(Color::Cyan)
-        .add_modifier(Modifier::BOLD);
-    let desc_style = Style::default().fg(Color::White);
+    let key_style = Style::default().fg(theme.info).add_modifier(Modifier::BOLD);
+    let desc_style = Style::default().fg(theme.text);
 
     // Helper to add a section
     fn add_section(
         lines: &mut Vec<Line<'static>>,
         title: &'static str,
-        bindings: &[KeyBinding],
+        rows: Vec<(String, &'static str)>,
         section_style: Style,
         key_style: Style,
         desc_style: Style,
     ) {
         lines.push(Line::from(Span::styled(title, section_style)));
-        for binding in bindings {
+        for (key, description) in rows {
             lines.push(Line::from(vec![
-                Span::styled(format!("{:15}", binding.key), key_style),
-                Span::styled(binding.description, desc_style),
+                Span::styled(format!("{:15}", key), key_style),
+                Span::styled(description, desc_style),
             ]));
         }
         lines.push(Line::from(""));
     }
+    fn fixed(bindings: &[KeyBinding]) -> Vec<(String, &'static str)> {
+        bindings
+            .iter()
+            .map(|b| (b.key.to_string(), b.description))
+            .collect()
+    }
 
+    // Problems in the keymap file come first so they aren't missed
+    if !keymap.conflicts().is_empty() {
+        lines.push(Line::from(Span::styled("Keymap Conflicts", section_style)));
+        for conflict in keymap.conflicts() {
+            lines.push(Line::from(Span::styled(
+                conflict.clone(),
+                Style::default().fg(theme.alert),
+            )));
+        }
+        lines.push(Line::from(""));
+    }
+
+    for section in HelpSection::ALL {
+        add_section(
+            &mut lines,
+            section.title(),
+            keymap_rows(keymap, section),
+            section_style,
+            key_style,
+            desc_style,
+        );
+        // The view sections follow the track keys they build on
+        if section == HelpSection::Tracks {
+            for (title, bindings) in [
+                ("Step Sequencer", STEP_BINDINGS),
+                ("Staff View", STAFF_BINDINGS),
+                ("Event List", EVENT_BINDINGS),
+                ("Lyric Mode", LYRIC_BINDINGS),
+            ] {
+                add_section(
+                    &mut lines,
+                    title,
+                    fixed(bindings),
+                    section_style,
+                    key_style,
+                    desc_style,
+                );
+            }
+        }
+    }
+    let commands = COMMANDS
+        .iter()
+        .map(|c| (format!(":{} {}", c.name, c.args), c.description))
+        .collect();
     add_section(
         &mut lines,
-        "General",
-        GENERAL_BINDINGS,
-        section_style,
-        key_style,
-        desc_style,
-    );
-    add_section(
-        &mut lines,
-        "Modes",
-        MODE_BINDINGS,
-        section_style,
-        key_style,
-        desc_style,
-    );
-    add_section(
-        &mut lines,
-        "Navigation",
-        NAVIGATION_BINDINGS,
-        section_style,
-        key_style,
-        desc_style,
-    );
-    add_section(
-        &mut lines,
-        "Editing",
-        EDIT_BINDINGS,
-        section_style,
-        key_style,
-        desc_style,
-    );
-    add_section(
-        &mut lines,
-        "Tracks",
-        TRACK_BINDINGS,
-        section_style,
-        key_style,
-        desc_style,
-    );
-    add_section(
-        &mut lines,
-        "Keyboard",
-        KEYBOARD_BINDINGS,
-        section_style,
-        key_style,
-        desc_style,
-    );
-    add_section(
-        &mut lines,
-        "View",
-        VIEW_BINDINGS,
-        section_style,
-        key_style,
-        desc_style,
-    );
-    add_section(
-        &mut lines,
-        "File & Export",
-        FILE_BINDINGS,
+        "Command Line (Tab completes, Up/Down for history)",
+        commands,
         section_style,
         key_style,
         desc_style,
@@ -389,7 +370,7 @@ pub fn render_help(frame: &mut Frame, scroll: u16) {
     add_section(
         &mut lines,
         "Mouse Controls",
-        MOUSE_BINDINGS,
+        fixed(MOUSE_BINDINGS),
         section_style,
         key_style,
         desc_style,
@@ -403,7 +384,7 @@ pub fn render_help(frame: &mut Frame, scroll: u16) {
     let footer = Paragraph::new(Line::from(Span::styled(
         "Scroll: Up/Down/j/k/Mouse  |  Close: ?/Esc/Click",
         Style::default()
-            .fg(Color::DarkGray)
+            .fg(theme.text_dim)
             .add_modifier(Modifier::ITALIC),
     )));
     frame.render_widget(footer, chunks[1]);
@@ -3,24 +3,24 @@
 //! Shows the computer keyboard to MIDI note mapping and currently held keys.
 //! Also displays contextual key bindings based on the current edit mode.
 
-use crate::app::{App, EditMode, KEYBOARD_MAP};
+use crate::app::{App, EditMode};
+use crate::keymap::{Action, KeyContext, Keymap};
+use crate::theme::Theme;
 use ratatui::layout::Rect;
-use ratatui::style::{Color, Modifier, Style};
+use ratatui::style::{Modifier, Style};
 use ratatui::text::{Line, Span};
 use ratatui::widgets::{Block, Borders, Paragraph};
 use ratatui::Frame;
 
-/// Builds a keyboard row from a slice of key characters.
+/// Builds a keyboard row from a slice of piano key characters.
 ///
 /// Maps each key to its corresponding MIDI note and applies appropriate styling
 /// based on whether the note is a black key or was recently added.
 fn build_keyboard_row(keys: &[char], app: &App) -> Vec<Span<'static>> {
     keys.iter()
         .map(|&key| {
-            let base_note = KEYBOARD_MAP
-                .iter()
-                .find(|(k, _)| k.to_ascii_uppercase() == key)
-                .map(|(_, n)| *n);
+            let base_note = app.keymap.piano_note(key);
+            let key = key.to_uppercase();
 
             if let Some(base) = base_note {
                 let note = (base as i16 + app.octave_offset as i16 * 12) as u8;
@@ -29,18 +29,18 @@ fn build_keyboard_row(keys: &[char], app: &App) -> Vec<Span<'static>> {
 
                 let style = if is_recently_added {
                     Style::default()
-                        .fg(Color::White)
-                        .bg(Color::Blue)
+                        .fg(app.theme.key_black_text)
+                        .bg(app.theme.note_recent)
                         .add_modifier(Modifier::BOLD)
                 } else if is_black {
                     Style::default()
-                        .fg(Color::White)
-                        .bg(Color::DarkGray)
+                        .fg(app.theme.key_black_text)
+                        .bg(app.theme.key_black)
                         .add_modifier(Modifier::BOLD)
                 } else {
                     Style::default()
-                        .fg(Color::Black)
-                        .bg(Color::White)
+                        .fg(app.theme.key_white_text)
+                        .bg(app.theme.key_c)
                         .add_modifier(Modifier::BOLD)
                 };
 
@@ -72,7 +72,7 @@ pub fn render_keyboard(frame: &mut Frame, area: Rect, app: &App, focused: bool)
     let block = Block::default()
         .title(format!(" Keyboard (Octave: {}) ", octave_str))
         .borders(Borders::ALL)
-        .border_style(Style::default().fg(if focused { Color::Cyan } else { Color::Gray }));
+        .border_style(Style::default().fg(app.theme.border(focused)));
 
     let inner = block.inner(area);
     frame.render_widget(block, area);
@@ -81,17 +81,13 @@ pub fn render_keyboard(frame: &mut Frame, area: Rect, app: &App, focused: bool)
         return;
     }
 
-    // Keyboard layout: upper row (Q-I) and lower row (Z-M)
-    const UPPER_KEYS: &[char] = &[
-        'Q', '2', 'W', '3', 'E', 'R', '5', 'T', '6', 'Y', '7', 'U', 'I',
-    ];
-    const LOWER_KEYS: &[char] = &['Z', 'S', 'X', 'D', 'C', 'V', 'G', 'B', 'H', 'N', 'J', 'M'];
-
-    let upper_row = build_keyboard_row(UPPER_KEYS, app);
-    let lower_row = build_keyboard_row(LOWER_KEYS, app);
+    // Keyboard layout: upper row (C4-C5) and lower row (C3-B3)
+    let (lower_keys, upper_keys) = app.keymap.piano_keys().split_at(12);
+    let upper_row = build_keyboard_row(upper_keys, app);
+    let lower_row = build_keyboard_row(lower_keys, app);
 
     // Contextual help text based on current mode
-    let help_line = build_contextual_help(app.edit_mode);
+    let help_line = build_contextual_help(app.edit_mode, &app.keymap, &app.theme);
 
     // Render rows
     if inner.height >= 1 {
@@ -116,33 +112,35 @@ pub fn render_keyboard(frame: &mut Frame, area: Rect, app: &App, focused: bool)
 
 /// Builds the contextual help line based on the current edit mode.
 ///
-/// Different modes show different relevant key bindings to guide the user.
-fn build_contextual_help(mode: EditMode) -> Line<'static> {
-    let key_style = Style::default().fg(Color::Yellow);
-    let bracket_style = Style::default().fg(Color::DarkGray);
-    let desc_style = Style::default().fg(Color::DarkGray);
+/// Different modes show different relevant key bindings to guide the user,
+/// labelled with the keys from the active keymap.
+fn build_contextual_help(mode: EditMode, keymap: &Keymap, theme: &Theme) -> Line<'static> {
+    let key_style = Style::default().fg(theme.accent);
+    let bracket_style = Style::default().fg(theme.text_dim);
+    let desc_style = Style::default().fg(theme.text_dim);
+    let key = |context, action| keymap.key_label(context, action).unwrap_or_default();
 
     match mode {
         EditMode::Normal => {
             // Normal mode: show mode switching, file ops, and quit hint
             Line::from(vec![
                 Span::styled("[", bracket_style),
-                Span::styled("i", key_style),
+                Span::styled(key(KeyContext::Normal, Action::InsertMode), key_style),
                 Span::styled("]Ins ", desc_style),
                 Span::styled("[", bracket_style),
-                Span::styled("v", key_style),
+                Span::styled(key(KeyContext::Normal, Action::SelectMode), key_style),
                 Span::styled("]Sel ", desc_style),
                 Span::styled("[", bracket_style),
-                Span::styled("Space", key_style),
+                Span::styled(key(KeyContext::Normal, Action::TogglePlayback), key_style),
                 Span::styled("]Play ", desc_style),
                 Span::styled("[", bracket_style),
-                Span::styled("^S", key_style),
+                Span::styled(key(KeyContext::Normal, Action::Save), key_style),
                 Span::styled("]Save ", desc_style),
                 Span::styled("[", bracket_style),
-                Span::styled("?", key_style),
+                Span::styled(key(KeyContext::Normal, Action::ToggleHelp), key_style),
                 Span::styled("]Help ", desc_style),
                 Span::styled("[", bracket_style),
-                Span::styled("q", key_style),
+                Span::styled(key(KeyContext::Normal, Action::Quit), key_style),
                 Span::styled("]Quit", desc_style),
             ])
         }
@@ -152,20 +150,24 @@ fn build_contextual_help(mode: EditMode) -> Line<'static> {
                 Span::styled(
                     "INSERT MODE  ",
                     Style::default()
-                        .fg(Color::Green)
+                        .fg(theme.active)
                         .add_modifier(Modifier::BOLD),
                 ),
                 Span::styled("[", bracket_style),
-                Span::styled("Z-M", key_style),
+                Span::styled(keymap.piano_label(), key_style),
                 Span::styled("] Play+Add  ", desc_style),
                 Span::styled("[", bracket_style),
-                Span::styled(",/", key_style),
+                Span::styled(
+                    key(KeyContext::Insert, Action::OctaveDown)
+                        + &key(KeyContext::Insert, Action::OctaveUp),
+                    key_style,
+                ),
                 Span::styled("] Octave  ", desc_style),
                 Span::styled("[", bracket_style),
                 Span::styled("Arrows", key_style),
                 Span::styled("] Move  ", desc_style),
                 Span::styled("[", bracket_style),
-                Span::styled("Esc", key_style),
+                Span::styled(key(KeyContext::Insert, Action::NormalMode), key_style),
                 Span::styled("] Exit", desc_style),
             ])
         }
@@ -175,19 +177,53 @@ fn build_contextual_help(mode: EditMode) -> Line<'static> {
                 Span::styled(
                     "SELECT MODE  ",
                     Style::default()
-                        .fg(Color::Magenta)
+                        .fg(theme.note_selected)
                         .add_modifier(Modifier::BOLD),
                 ),
                 Span::styled("[", bracket_style),
-                Span::styled("Enter", key_style),
+                Span::styled(
+                    key(KeyContext::Select, Action::ToggleNoteSelection),
+                    key_style,
+                ),
                 Span::styled("] Toggle  ", desc_style),
                 Span::styled("[", bracket_style),
-                Span::styled("x", key_style),
+                Span::styled(key(KeyContext::Select, Action::DeleteSelection), key_style),
                 Span::styled("] Delete  ", desc_style),
                 Span::styled("[", bracket_style),
                 Span::styled("hjkl", key_style),
                 Span::styled("] Move  ", desc_style),
                 Span::styled("[", bracket_style),
+                Span::styled(
+                    key(KeyContext::Select, Action::VelocityUp)
+                        + "/"
+                        + &key(KeyContext::Select, Action::VelocityDown),
+                    key_style,
+                ),
+                Span::styled("] Velocity  ", desc_style),
+                Span::styled("[", bracket_style),
+                Span::styled(key(KeyContext::Select, Action::NormalMode), key_style),
+                Span::styled("] Exit", desc_style),
+            ])
+        }
+        EditMode::Lyric => {
+            // Lyric mode: show syllable entry hints
+            Line::from(vec![
+                Span::styled(
+                    "LYRIC MODE  ",
+                    Style::default()
+                        .fg(theme.accent)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::styled("[", bracket_style),
+                Span::styled("Space", key_style),
+                Span::styled("] Next word  ", desc_style),
+                Span::styled("[", bracket_style),
+                Span::styled("-", key_style),
+                Span::styled("] Next syllable  ", desc_style),
+                Span::styled("[", bracket_style),
+                Span::styled("Enter", key_style),
+                Span::styled("] New line  ", desc_style),
+                Span::styled("[", bracket_style),
                 Span::styled("Esc", key_style),
                 Span::styled("] Exit", desc_style),
             ])
@@ -0,0 +1,100 @@
+//! Karaoke-style lyric strip rendering.
+//!
+//! Shows the lyric line at the playhead (or cursor when stopped), with sung
+//! syllables highlighted. In Lyric mode the syllable being typed is shown in
+//! place of the stored one.
+
+use crate::app::{App, EditMode};
+use crate::midi::lyric_line_at;
+use ratatui::layout::{Alignment, Rect};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Paragraph};
+use ratatui::Frame;
+
+/// Height of the lyric strip including borders.
+pub const LYRICS_STRIP_HEIGHT: u16 = 3;
+
+/// Renders the karaoke lyric strip.
+///
+/// # Arguments
+///
+/// * `frame` - The frame to render to
+/// * `area` - The area to render in
+/// * `app` - Application state
+pub fn render_lyrics_strip(frame: &mut Frame, area: Rect, app: &App) {
+    let Some(track) = app
+        .lyrics_track_index()
+        .and_then(|idx| app.project().track_at(idx))
+    else {
+        return;
+    };
+
+    let block = Block::default()
+        .title(format!(" Lyrics - {} ", track.name))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(app.theme.border));
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let editing = app.edit_mode == EditMode::Lyric;
+    let tick = if editing || !app.audio.is_playing() {
+        app.cursor_tick
+    } else {
+        app.display_position_ticks()
+    };
+
+    let sung_style = Style::default()
+        .fg(app.theme.accent)
+        .add_modifier(Modifier::BOLD);
+    let current_style = sung_style.add_modifier(Modifier::REVERSED);
+    let upcoming_style = Style::default().fg(app.theme.text);
+    let edit_style = Style::default()
+        .fg(app.theme.text)
+        .bg(app.theme.current_row_bg)
+        .add_modifier(Modifier::BOLD);
+
+    let lyrics = track.lyrics();
+    let mut spans: Vec<Span> = Vec::new();
+    let mut edit_shown = false;
+
+    if let Some(line) = lyric_line_at(lyrics, tick) {
+        for (i, lyric) in lyrics[line.start..line.end].iter().enumerate() {
+            let idx = line.start + i;
+
+            // While typing, the edited syllable replaces the stored one
+            if editing && lyric.tick == app.cursor_tick {
+                spans.push(Span::styled(format!("{}_", app.lyric_buffer), edit_style));
+                spans.push(Span::raw(" "));
+                edit_shown = true;
+                continue;
+            }
+
+            // Typed syllable for a note that has no lyric yet
+            if editing && !edit_shown && lyric.tick > app.cursor_tick {
+                spans.push(Span::styled(format!("{}_", app.lyric_buffer), edit_style));
+                spans.push(Span::raw(" "));
+                edit_shown = true;
+            }
+
+            let style = match line.current {
+                Some(current) if idx == current => current_style,
+                Some(current) if idx < current => sung_style,
+                _ => upcoming_style,
+            };
+            spans.push(Span::styled(lyric.display_text().to_string(), style));
+            if !lyric.joins_next() {
+                spans.push(Span::raw(" "));
+            }
+        }
+    }
+
+    if editing && !edit_shown {
+        spans.push(Span::styled(format!("{}_", app.lyric_buffer), edit_style));
+    }
+
+    frame.render_widget(
+        Paragraph::new(Line::from(spans)).alignment(Alignment::Center),
+        inner,
+    );
+}
@@ -1,33 +1,47 @@
 //! Terminal user interface components.
 //!
 //! This module provides the visual components for the MIDI composer,
-//! including the track list, piano roll, timeline, project view, and keyboard display.
+//! including the track list, piano roll, timeline, project view, staff view, and keyboard display.
 
 mod combined;
 mod dialogs;
+mod event_list;
 mod help;
 mod keyboard;
+mod lyrics;
 mod piano_roll;
 mod project_timeline;
+mod staff;
+mod step_sequencer;
 mod timeline;
 mod tracks;
+mod velocity;
 
 use crate::app::{App, FocusedPanel, LayoutRegions, ViewMode, PIANO_KEY_WIDTH};
 use crate::midi::{contains_beat, contains_measure, TICKS_PER_BEAT};
+use crate::theme::Theme;
 use ratatui::layout::{Constraint, Direction, Layout, Rect};
-use ratatui::style::{Color, Modifier, Style};
+use ratatui::style::{Modifier, Style};
 use ratatui::text::{Line, Span};
 use ratatui::widgets::Paragraph;
 use ratatui::Frame;
 
 pub use combined::render_combined;
 pub use dialogs::{
-    render_file_browser, render_new_project_dialog, render_save_dialog, render_soundfont_dialog,
+    render_chord_dialog, render_drum_map_dialog, render_effects_dialog, render_file_browser,
+    render_groove_dialog, render_history_dialog, render_marker_dialog, render_new_project_dialog,
+    render_quantize_dialog, render_recent_dialog, render_recovery_dialog, render_save_dialog,
+    render_scale_dialog, render_settings_dialog, render_soundfont_dialog, render_time_dialog,
+    render_unsaved_dialog,
 };
+pub use event_list::render_event_list;
 pub use help::render_help;
 pub use keyboard::render_keyboard;
+pub use lyrics::render_lyrics_strip;
 pub use piano_roll::render_piano_roll;
 pub use project_timeline::{render_project_timeline, render_project_timeline_compact};
+pub use staff::render_staff;
+pub use step_sequencer::render_step_sequencer;
 pub use timeline::render_timeline;
 pub use tracks::render_track_list;
 
@@ -39,9 +53,10 @@ pub use tracks::render_track_list;
 ///
 /// * `frame` - The frame to render to
 /// * `area` - The area to render the ruler in (should be 1 row high)
+/// * `theme` - Colors to draw with
 /// * `scroll_x` - Horizontal scroll position in ticks
 /// * `zoom` - Number of ticks per display column
-pub fn render_time_ruler(frame: &mut Frame, area: Rect, scroll_x: u32, zoom: u32) {
+pub fn render_time_ruler(frame: &mut Frame, area: Rect, theme: &Theme, scroll_x: u32, zoom: u32) {
     let mut ruler_spans: Vec<Span> = Vec::with_capacity(area.width as usize);
     let mut col = 0u16;
 
@@ -65,18 +80,18 @@ pub fn render_time_ruler(frame: &mut Frame, area: Rect, scroll_x: u32, zoom: u32
                 ruler_spans.push(Span::styled(
                     measure_str.clone(),
                     Style::default()
-                        .fg(Color::Yellow)
+                        .fg(theme.ruler_measure)
                         .add_modifier(Modifier::BOLD),
                 ));
                 col += measure_str.len() as u16;
                 continue;
             } else {
-                ruler_spans.push(Span::styled("|", Style::default().fg(Color::Yellow)));
+                ruler_spans.push(Span::styled("|", Style::default().fg(theme.ruler_measure)));
             }
         } else if is_beat {
-            ruler_spans.push(Span::styled(".", Style::default().fg(Color::DarkGray)));
+            ruler_spans.push(Span::styled(".", Style::default().fg(theme.ruler_beat)));
         } else {
-            ruler_spans.push(Span::styled(" ", Style::default().fg(Color::DarkGray)));
+            ruler_spans.push(Span::styled(" ", Style::default().fg(theme.ruler_beat)));
         }
         col += 1;
     }
@@ -88,14 +103,20 @@ pub fn render_time_ruler(frame: &mut Frame, area: Rect, scroll_x: u32, zoom: u32
 ///
 /// This is called during rendering to update the layout regions used
 /// for mouse hit testing and auto-scroll calculations.
-fn calculate_layout(size: Rect, view_mode: ViewMode) -> (LayoutRegions, [Rect; 3], [Rect; 2]) {
-    // Main vertical layout: timeline, content, keyboard
+fn calculate_layout(
+    size: Rect,
+    view_mode: ViewMode,
+    lyrics_height: u16,
+    show_velocity_lane: bool,
+) -> (LayoutRegions, [Rect; 4], [Rect; 2]) {
+    // Main vertical layout: timeline, content, lyrics (optional), keyboard
     let main_chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Length(3), // Timeline/transport
-            Constraint::Min(10),   // Content area
-            Constraint::Length(5), // Keyboard
+            Constraint::Length(3),             // Timeline/transport
+            Constraint::Min(10),               // Content area
+            Constraint::Length(lyrics_height), // Karaoke lyric strip
+            Constraint::Length(5),             // Keyboard
         ])
         .split(size);
 
@@ -117,6 +138,7 @@ fn calculate_layout(size: Rect, view_mode: ViewMode) -> (LayoutRegions, [Rect; 3
     let left_content_width = match view_mode {
         ViewMode::PianoRoll | ViewMode::Combined => PIANO_KEY_WIDTH,
         ViewMode::ProjectTimeline => 12, // DEFAULT_LABEL_WIDTH from project_timeline
+        ViewMode::StepSequencer | ViewMode::Staff | ViewMode::EventList => 0,
     };
 
     // For Combined view, the piano roll only takes 55% of the content area height
@@ -129,12 +151,45 @@ fn calculate_layout(size: Rect, view_mode: ViewMode) -> (LayoutRegions, [Rect; 3
         _ => piano_roll.height,
     };
 
-    let piano_roll_grid = Rect {
-        x: piano_roll.x + 1 + left_content_width,
-        y: piano_roll.y + 1,
-        width: piano_roll.width.saturating_sub(2 + left_content_width),
-        // Use actual piano roll height (accounts for Combined view's 55% split)
-        height: actual_piano_roll_height.saturating_sub(2),
+    // The velocity lane (if shown) takes the bottom rows of the piano roll
+    let piano_roll_inner_height = actual_piano_roll_height.saturating_sub(2);
+    let lane_height = match view_mode {
+        ViewMode::PianoRoll | ViewMode::Combined => {
+            velocity::lane_height(show_velocity_lane, piano_roll_inner_height)
+        }
+        ViewMode::ProjectTimeline
+        | ViewMode::StepSequencer
+        | ViewMode::Staff
+        | ViewMode::EventList => 0,
+    };
+
+    // The step sequencer, staff view and event list have their own geometry
+    // (see `StepGridLayout`, `StaffLayout` and `LayoutRegions::event_rows`)
+    let piano_roll_grid = if matches!(
+        view_mode,
+        ViewMode::StepSequencer | ViewMode::Staff | ViewMode::EventList
+    ) {
+        Rect::default()
+    } else {
+        Rect {
+            x: piano_roll.x + 1 + left_content_width,
+            y: piano_roll.y + 1,
+            width: piano_roll.width.saturating_sub(2 + left_content_width),
+            // Use actual piano roll height (accounts for Combined view's 55% split)
+            height: piano_roll_inner_height - lane_height,
+        }
+    };
+
+    // Lane bars start below the lane's header row
+    let velocity_lane = if lane_height > 0 {
+        Rect {
+            x: piano_roll_grid.x,
+            y: piano_roll_grid.y + piano_roll_grid.height + 1,
+            width: piano_roll_grid.width,
+            height: lane_height - 1,
+        }
+    } else {
+        Rect::default()
     };
 
     // Calculate visible pitches based on available grid height.
@@ -146,15 +201,25 @@ fn calculate_layout(size: Rect, view_mode: ViewMode) -> (LayoutRegions, [Rect; 3
         track_list: content_chunks[0],
         piano_roll,
         piano_roll_grid,
-        keyboard: main_chunks[2],
+        keyboard: main_chunks[3],
         // Ruler regions are set during rendering
         piano_roll_ruler: Rect::default(),
         project_timeline_ruler: Rect::default(),
         visible_pitches,
+        velocity_lane,
+        step_grid: Default::default(),
+        staff: Default::default(),
+        event_rows: Rect::default(),
+        event_first_row: 0,
     };
 
     // Convert to arrays for returning
-    let main_arr = [main_chunks[0], main_chunks[1], main_chunks[2]];
+    let main_arr = [
+        main_chunks[0],
+        main_chunks[1],
+        main_chunks[2],
+        main_chunks[3],
+    ];
     let content_arr = [content_chunks[0], content_chunks[1]];
 
     (layout, main_arr, content_arr)
@@ -166,10 +231,17 @@ fn calculate_layout(size: Rect, view_mode: ViewMode) -> (LayoutRegions, [Rect; 3
 /// - Top: Timeline with transport controls and position display
 /// - Left: Track list with mute/solo controls
 /// - Center: Piano roll editor OR project timeline (based on view mode)
+/// - Lyric strip (only when a track has lyrics or in Lyric mode)
 /// - Bottom: Piano keyboard for live input
 pub fn render(frame: &mut Frame, app: &mut App) {
     let size = frame.area();
-    let (layout, main_chunks, content_chunks) = calculate_layout(size, app.view_mode);
+    let lyrics_height = if app.lyrics_track_index().is_some() {
+        lyrics::LYRICS_STRIP_HEIGHT
+    } else {
+        0
+    };
+    let (layout, main_chunks, content_chunks) =
+        calculate_layout(size, app.view_mode, lyrics_height, app.show_velocity_lane);
 
     // Update app's layout regions for mouse hit testing
     app.update_layout(layout);
@@ -201,15 +273,33 @@ pub fn render(frame: &mut Frame, app: &mut App) {
             let ruler = render_project_timeline(frame, content_chunks[1], app, is_focused);
             (None, ruler)
         }
+        ViewMode::StepSequencer => {
+            app.layout.step_grid = render_step_sequencer(frame, content_chunks[1], app, is_focused);
+            (None, None)
+        }
+        ViewMode::Staff => {
+            app.layout.staff = render_staff(frame, content_chunks[1], app, is_focused);
+            (None, None)
+        }
+        ViewMode::EventList => {
+            let (rows, first_row) = render_event_list(frame, content_chunks[1], app, is_focused);
+            app.layout.event_rows = rows;
+            app.layout.event_first_row = first_row;
+            (None, None)
+        }
     };
 
     // Update ruler regions in layout for mouse hit testing
     app.layout.piano_roll_ruler = piano_roll_ruler.unwrap_or_default();
     app.layout.project_timeline_ruler = project_timeline_ruler.unwrap_or_default();
 
+    if lyrics_height > 0 {
+        render_lyrics_strip(frame, main_chunks[2], app);
+    }
+
     render_keyboard(
         frame,
-        main_chunks[2],
+        main_chunks[3],
         app,
         app.focused_panel == FocusedPanel::Keyboard,
     );
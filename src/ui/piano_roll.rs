@@ -4,10 +4,11 @@
 //! Similar to a DAW piano roll interface. Includes visual indicators for
 //! notes that are scrolled off-screen.
 
-use crate::app::{App, EditMode};
-use crate::midi::{contains_beat, contains_measure, note_to_name, Note};
+use super::velocity::render_velocity_lane;
+use crate::app::{App, EditMode, GhostMode};
+use crate::midi::{contains_beat, contains_measure, drum_short_name, note_to_name, Note};
 use ratatui::layout::Rect;
-use ratatui::style::{Color, Modifier, Style};
+use ratatui::style::{Modifier, Style};
 use ratatui::text::{Line, Span};
 use ratatui::widgets::{Block, Borders, Paragraph};
 use ratatui::Frame;
@@ -170,15 +171,44 @@ pub fn render_piano_roll(frame: &mut Frame, area: Rect, app: &App, focused: bool
     // Build indicator suffix for title (shows which edges have off-screen notes)
     let indicator_suffix = build_title_indicator(&indicators);
 
+    // Project key, with a marker when editing snaps to it
+    let key_label = app.project().scale.map_or_else(String::new, |scale| {
+        format!(
+            "[{}{}] ",
+            scale.name(),
+            if scale.snap { " snap" } else { "" }
+        )
+    });
+
+    // Percussion tracks label rows with drum names; the title names the cursor row
+    let drum_map = app.selected_track().and_then(|t| t.effective_drum_map());
+    let drum_label = drum_map
+        .as_ref()
+        .and_then(|map| map.name_of(app.cursor_pitch))
+        .map_or_else(String::new, |name| format!("[{}] ", name));
+
+    // Notes of other tracks drawn dim behind the edited track
+    let ghost_tracks: Vec<(usize, &[Note])> = app
+        .ghost_track_indices()
+        .into_iter()
+        .filter_map(|i| app.project().track_at(i).map(|t| (i, t.notes())))
+        .collect();
+    let ghost_label = match app.ghost_mode {
+        GhostMode::Off => "",
+        GhostMode::Above => "[ghost: above] ",
+        GhostMode::All => "[ghost: all] ",
+        GhostMode::Chosen => "[ghost: chosen] ",
+    };
+
     let title = format!(
-        " Piano Roll - {} ({}) {}",
-        track_name, instrument_name, indicator_suffix
+        " Piano Roll - {} ({}) {}{}{}{}",
+        track_name, instrument_name, key_label, drum_label, ghost_label, indicator_suffix
     );
 
     let block = Block::default()
         .title(title)
         .borders(Borders::ALL)
-        .border_style(Style::default().fg(if focused { Color::Cyan } else { Color::Gray }));
+        .border_style(Style::default().fg(app.theme.border(focused)));
 
     let inner = block.inner(area);
     frame.render_widget(block, area);
@@ -193,7 +223,9 @@ pub fn render_piano_roll(frame: &mut Frame, area: Rect, app: &App, focused: bool
     let piano_width = 5u16; // Width for note labels
     let grid_width = inner.width.saturating_sub(piano_width);
     let ruler_height = 1u16; // Time ruler takes 1 row
-    let grid_height = inner.height.saturating_sub(ruler_height);
+                             // Velocity lane (if shown) takes the bottom rows
+    let lane_height = super::velocity::lane_height(app.show_velocity_lane, inner.height);
+    let grid_height = inner.height.saturating_sub(ruler_height + lane_height);
     // Calculate visible pitches for pitch calculations (capped at 127 max MIDI pitch)
     let visible_pitches = grid_height.min(127) as u8;
 
@@ -201,11 +233,12 @@ pub fn render_piano_roll(frame: &mut Frame, area: Rect, app: &App, focused: bool
 
     // Render the time ruler at the top (above the grid, aligned with grid columns)
     let ruler_rect = Rect::new(inner.x + piano_width, inner.y, grid_width, ruler_height);
-    super::render_time_ruler(frame, ruler_rect, app.scroll_x, app.zoom);
+    super::render_time_ruler(frame, ruler_rect, &app.theme, app.scroll_x, app.zoom);
+    render_chord_symbols(frame, ruler_rect, app);
 
     // Render ruler label area (empty space above piano keys for alignment)
     frame.render_widget(
-        Paragraph::new("     ").style(Style::default().bg(Color::Rgb(20, 20, 20))),
+        Paragraph::new("     ").style(Style::default().bg(app.theme.panel_bg)),
         Rect::new(inner.x, inner.y, piano_width, ruler_height),
     );
 
@@ -218,10 +251,10 @@ pub fn render_piano_roll(frame: &mut Frame, area: Rect, app: &App, focused: bool
         visible_pitches,
     );
 
-    // Style for off-screen indicators (yellow on dark background for high visibility)
+    // Style for off-screen indicators
     let indicator_style = Style::default()
-        .fg(Color::Yellow)
-        .bg(Color::Rgb(60, 50, 0))
+        .fg(app.theme.indicator)
+        .bg(app.theme.indicator_bg)
         .add_modifier(Modifier::BOLD);
 
     // Render each row (pitch), starting below the ruler
@@ -233,10 +266,17 @@ pub fn render_piano_roll(frame: &mut Frame, area: Rect, app: &App, focused: bool
         let is_top_row = row == 0;
         let is_bottom_row = row == grid_height - 1;
 
-        // Note name label (piano key column)
-        let note_name = note_to_name(pitch);
+        // Note name label (piano key column), or the short drum name
+        let note_name = drum_map
+            .as_ref()
+            .and_then(|map| map.name_of(pitch))
+            .map_or_else(|| note_to_name(pitch), drum_short_name);
         let is_black_key = matches!(pitch % 12, 1 | 3 | 6 | 8 | 10);
         let is_c = pitch.is_multiple_of(12);
+        // With a project key, out-of-scale rows are shaded instead of black keys
+        let scale = app.project().scale;
+        let is_shaded = scale.map_or(is_black_key, |s| !s.contains(pitch));
+        let is_root = scale.is_some_and(|s| s.is_root(pitch));
 
         let show_key_indicator =
             (is_top_row && indicators.above) || (is_bottom_row && indicators.below);
@@ -246,15 +286,21 @@ pub fn render_piano_roll(frame: &mut Frame, area: Rect, app: &App, focused: bool
             indicator_style
         } else if pitch == app.cursor_pitch {
             Style::default()
-                .bg(Color::Blue)
-                .fg(Color::White)
+                .bg(app.theme.note_recent)
+                .fg(app.theme.key_black_text)
                 .add_modifier(Modifier::BOLD)
         } else if is_black_key {
-            Style::default().bg(Color::DarkGray).fg(Color::White)
+            Style::default()
+                .bg(app.theme.key_black)
+                .fg(app.theme.key_black_text)
         } else if is_c {
-            Style::default().bg(Color::White).fg(Color::Black)
+            Style::default()
+                .bg(app.theme.key_c)
+                .fg(app.theme.key_white_text)
         } else {
-            Style::default().bg(Color::Gray).fg(Color::Black)
+            Style::default()
+                .bg(app.theme.key_white)
+                .fg(app.theme.key_white_text)
         };
 
         // Build the key label with optional off-screen indicator
@@ -314,6 +360,18 @@ pub fn render_piano_roll(frame: &mut Frame, area: Rect, app: &App, focused: bool
                 .map(|n| n.start_tick <= display_pos && n.end_tick() > display_pos)
                 .unwrap_or(false);
 
+            // Ghost notes only show where the edited track has no note
+            let ghost_here = if note_here.is_none() {
+                ghost_tracks.iter().find_map(|(index, notes)| {
+                    notes
+                        .iter()
+                        .find(|n| n.pitch == pitch && n.is_active_at(tick))
+                        .map(|n| (*index, n))
+                })
+            } else {
+                None
+            };
+
             let show_left_indicator = is_left_col && indicators.left;
             let show_right_indicator = is_right_col && indicators.right;
             let show_top_indicator = is_top_row && indicators.above;
@@ -333,35 +391,60 @@ pub fn render_piano_roll(frame: &mut Frame, area: Rect, app: &App, focused: bool
                 // Determine note background color based on state
                 // Priority: insert indicator > playback highlight > recently added > selected > cursor > default
                 let bg = if is_insert_indicator {
-                    Color::Red // Note at insert indicator position
+                    app.theme.playhead // Note at insert indicator position
                 } else if should_highlight {
-                    Color::White // Active note highlighting during playback
+                    app.theme.note_playing // Active note highlighting during playback
                 } else if is_recently_added {
-                    Color::Blue // Recently added note (blue highlight)
+                    app.theme.note_recent
                 } else if is_selected {
-                    Color::Magenta
+                    app.theme.note_selected
                 } else if is_cursor {
-                    Color::Cyan
+                    app.theme.cursor
                 } else {
-                    Color::Green
+                    app.theme.velocity_color(note.velocity)
                 };
 
                 let ch = if is_start { '[' } else { '=' };
-                (ch, Style::default().fg(Color::Black).bg(bg))
+                (ch, Style::default().fg(app.theme.note_text).bg(bg))
             } else if is_insert_indicator {
-                // Insert Mode indicator - red vertical line
+                // Insert Mode indicator - vertical line
                 (
                     '|',
-                    Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+                    Style::default()
+                        .fg(app.theme.playhead)
+                        .add_modifier(Modifier::BOLD),
+                )
+            } else if let Some((index, ghost)) = ghost_here {
+                // Ghost note from another track - dim, in the track's color
+                let is_start = ghost.start_tick + app.zoom > tick;
+                let ch = if is_start { '[' } else { '-' };
+                let bg = if is_cursor {
+                    app.theme.cursor_bg
+                } else {
+                    app.theme.ghost_bg
+                };
+                (
+                    ch,
+                    Style::default()
+                        .fg(app.theme.track_color(index))
+                        .bg(bg)
+                        .add_modifier(Modifier::DIM),
                 )
             } else if is_cursor {
                 // Cursor position
-                ('_', Style::default().fg(Color::Cyan).bg(Color::DarkGray))
+                (
+                    '_',
+                    Style::default()
+                        .fg(app.theme.cursor)
+                        .bg(app.theme.cursor_bg),
+                )
             } else if is_playhead {
                 // Playhead position (during regular playback, not Insert Mode)
                 (
                     '|',
-                    Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+                    Style::default()
+                        .fg(app.theme.playhead)
+                        .add_modifier(Modifier::BOLD),
                 )
             } else if show_left_indicator {
                 // Left edge indicator - note extends from left
@@ -375,10 +458,14 @@ pub fn render_piano_roll(frame: &mut Frame, area: Rect, app: &App, focused: bool
                 (ch, indicator_style)
             } else {
                 // Grid background
-                let bg = if is_black_key {
-                    Color::Rgb(30, 30, 30)
+                let bg = if is_root {
+                    app.theme.grid_root_bg
+                } else if is_shaded && scale.is_some() {
+                    app.theme.grid_out_of_scale_bg
+                } else if is_shaded {
+                    app.theme.grid_shaded_bg
                 } else {
-                    Color::Rgb(40, 40, 40)
+                    app.theme.grid_bg
                 };
 
                 let ch = if is_measure {
@@ -390,11 +477,11 @@ pub fn render_piano_roll(frame: &mut Frame, area: Rect, app: &App, focused: bool
                 };
 
                 let fg = if is_measure {
-                    Color::White
+                    app.theme.measure_line
                 } else if is_beat {
-                    Color::DarkGray
+                    app.theme.beat_line
                 } else {
-                    Color::Rgb(60, 60, 60)
+                    app.theme.grid_dot
                 };
 
                 (ch, Style::default().fg(fg).bg(bg))
@@ -409,5 +496,53 @@ pub fn render_piano_roll(frame: &mut Frame, area: Rect, app: &App, focused: bool
         );
     }
 
+    if lane_height > 0 {
+        let lane_area = Rect::new(
+            inner.x,
+            inner.y + inner.height - lane_height,
+            inner.width,
+            lane_height,
+        );
+        render_velocity_lane(frame, lane_area, app, piano_width);
+    }
+
     Some(ruler_rect)
 }
+
+/// Draws chord track symbols over the time ruler.
+///
+/// Each symbol is clipped so it doesn't run into the next one.
+fn render_chord_symbols(frame: &mut Frame, ruler: Rect, app: &App) {
+    let start_col = app.scroll_x / app.zoom;
+    let chords = app.project().chords();
+
+    for (idx, symbol) in chords.iter().enumerate() {
+        let chord_col = symbol.tick / app.zoom;
+        if chord_col < start_col {
+            continue;
+        }
+        let screen_col = chord_col - start_col;
+        if screen_col >= ruler.width as u32 {
+            break;
+        }
+
+        let next_col = chords
+            .get(idx + 1)
+            .map(|c| (c.tick / app.zoom).saturating_sub(start_col))
+            .unwrap_or(ruler.width as u32)
+            .min(ruler.width as u32);
+        let width = next_col.saturating_sub(screen_col).max(1) as usize;
+
+        let label: String = symbol.chord.symbol().chars().take(width).collect();
+        let label_width = label.chars().count() as u16;
+        frame.render_widget(
+            Paragraph::new(label).style(
+                Style::default()
+                    .fg(app.theme.cursor)
+                    .bg(app.theme.panel_bg)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Rect::new(ruler.x + screen_col as u16, ruler.y, label_width, 1),
+        );
+    }
+}
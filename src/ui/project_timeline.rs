@@ -6,7 +6,7 @@
 use crate::app::App;
 use crate::midi::{contains_beat, contains_measure};
 use ratatui::layout::Rect;
-use ratatui::style::{Color, Modifier, Style};
+use ratatui::style::{Modifier, Style};
 use ratatui::text::{Line, Span};
 use ratatui::widgets::{Block, Borders, Paragraph};
 use ratatui::Frame;
@@ -68,7 +68,7 @@ fn render_project_timeline_with_label_width(
     let block = Block::default()
         .title(" Project Timeline - All Tracks ")
         .borders(Borders::ALL)
-        .border_style(Style::default().fg(if focused { Color::Cyan } else { Color::Gray }));
+        .border_style(Style::default().fg(app.theme.border(focused)));
 
     let inner = block.inner(area);
     frame.render_widget(block, area);
@@ -94,7 +94,8 @@ fn render_project_timeline_with_label_width(
 
     // Render time ruler at the top
     let ruler_rect = Rect::new(inner.x + label_width, inner.y, timeline_width, 1);
-    super::render_time_ruler(frame, ruler_rect, app.scroll_x, app.zoom);
+    super::render_time_ruler(frame, ruler_rect, &app.theme, app.scroll_x, app.zoom);
+    render_marker_flags(frame, ruler_rect, app);
 
     // Render each visible track
     for (display_idx, track_idx) in (start_track..end_track).enumerate() {
@@ -114,16 +115,16 @@ fn render_project_timeline_with_label_width(
         // Render track label
         let label_style = if is_active {
             Style::default()
-                .fg(Color::Green)
+                .fg(app.theme.active)
                 .add_modifier(Modifier::BOLD)
         } else if is_selected {
             Style::default()
-                .fg(Color::White)
+                .fg(app.theme.text)
                 .add_modifier(Modifier::BOLD)
         } else if is_muted {
-            Style::default().fg(Color::DarkGray)
+            Style::default().fg(app.theme.text_dim)
         } else {
-            Style::default().fg(Color::Gray)
+            Style::default().fg(app.theme.text_secondary)
         };
 
         // Build label with indicators - adapt to label width
@@ -166,8 +167,11 @@ fn render_project_timeline_with_label_width(
                 let playhead_x = inner.x + label_width + screen_col;
                 for row in 0..inner.height.saturating_sub(1) {
                     frame.render_widget(
-                        Paragraph::new("|")
-                            .style(Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)),
+                        Paragraph::new("|").style(
+                            Style::default()
+                                .fg(app.theme.playhead)
+                                .add_modifier(Modifier::BOLD),
+                        ),
                         Rect::new(playhead_x, inner.y + 1 + row, 1, 1),
                     );
                 }
@@ -178,6 +182,46 @@ fn render_project_timeline_with_label_width(
     Some(ruler_rect)
 }
 
+/// Draws marker flags (name labels) over the time ruler.
+///
+/// Each label is clipped so it doesn't run into the next marker.
+fn render_marker_flags(frame: &mut Frame, ruler: Rect, app: &App) {
+    let start_col = app.scroll_x / app.zoom;
+    let markers = app.project().markers();
+
+    for (idx, marker) in markers.iter().enumerate() {
+        let marker_col = marker.tick / app.zoom;
+        if marker_col < start_col {
+            continue;
+        }
+        let screen_col = marker_col - start_col;
+        if screen_col >= ruler.width as u32 {
+            break;
+        }
+
+        let next_col = markers
+            .get(idx + 1)
+            .map(|m| (m.tick / app.zoom).saturating_sub(start_col))
+            .unwrap_or(ruler.width as u32)
+            .min(ruler.width as u32);
+        let width = next_col.saturating_sub(screen_col).max(1) as u16;
+
+        let label: String = format!("\u{25BC}{}", marker.name)
+            .chars()
+            .take(width as usize)
+            .collect();
+        let label_width = label.chars().count() as u16;
+        frame.render_widget(
+            Paragraph::new(label).style(
+                Style::default()
+                    .fg(app.theme.marker_color(marker.color))
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Rect::new(ruler.x + screen_col as u16, ruler.y, label_width, 1),
+        );
+    }
+}
+
 /// Builds track label text adapted to the available width.
 fn build_track_label(
     track: &crate::midi::Track,
@@ -225,27 +269,52 @@ fn render_track_content(
 
     // Create a representation of notes in the visible range
     // Use different colors for different tracks for visual distinction
-    let track_colors = [
-        Color::Blue,
-        Color::Green,
-        Color::Yellow,
-        Color::Magenta,
-        Color::Cyan,
-        Color::Red,
-        Color::LightBlue,
-        Color::LightGreen,
-    ];
-    let base_color = track_colors[track_idx % track_colors.len()];
-
-    // Determine note color: white when active (and highlighting enabled), else track color
+    let base_color = app.theme.track_color(track_idx);
+
+    // Determine note color: highlighted when active (and highlighting enabled), else track color
     let note_color = if is_muted {
-        Color::DarkGray
+        app.theme.clip_muted_bg
     } else if is_active && app.highlight_timeline() {
-        Color::White
+        app.theme.note_playing
     } else {
         base_color
     };
 
+    // Clip blocks: (start, end, label). Linked clips are marked with '*'.
+    let project = app.project();
+    let clips: Vec<(u32, u32, Vec<char>)> = track
+        .clips()
+        .iter()
+        .chain(track.open_clip.as_ref())
+        .map(|clip| {
+            let name = project
+                .pattern(clip.pattern_id)
+                .map_or("?", |p| p.name.as_str());
+            let label = if track.open_clip.as_ref() == Some(clip) {
+                format!("[{}] editing", name)
+            } else if project.pattern_use_count(clip.pattern_id) > 1 {
+                format!("[{}]*", name)
+            } else {
+                format!("[{}]", name)
+            };
+            (
+                clip.start_tick,
+                project.clip_end_tick(clip),
+                label.chars().collect(),
+            )
+        })
+        .collect();
+    let notes = if clips.is_empty() && track.effects.is_empty() {
+        std::borrow::Cow::Borrowed(track.notes())
+    } else {
+        std::borrow::Cow::Owned(project.playback_notes(track))
+    };
+    let clip_color = if is_muted {
+        app.theme.clip_muted_bg
+    } else {
+        app.theme.clip_bg
+    };
+
     // Build the track content line by line
     for row in 0..area.height {
         let mut line_spans: Vec<Span> = Vec::with_capacity(area.width as usize);
@@ -255,18 +324,44 @@ fn render_track_content(
             let tick_end = tick + app.zoom;
 
             // Check if any note is active at this position
-            let has_note = track
-                .notes()
+            let has_note = notes
                 .iter()
                 .any(|n| n.start_tick < tick_end && n.end_tick() > tick);
 
             let is_cursor = is_selected && (tick / app.zoom == app.cursor_tick / app.zoom);
 
-            let (ch, style) = if has_note {
-                let bg = if is_cursor { Color::Cyan } else { note_color };
-                ('=', Style::default().fg(Color::Black).bg(bg))
+            // Clip covering this column (label on the first row)
+            let clip = clips
+                .iter()
+                .find(|(start, end, _)| *start < tick_end && *end > tick);
+            let clip_label_char = clip.filter(|_| row == 0).and_then(|(start, _, label)| {
+                let offset = tick.saturating_sub(*start) / app.zoom;
+                label.get(offset as usize).copied()
+            });
+
+            let (ch, style) = if let Some(ch) = clip_label_char {
+                let bg = if is_cursor {
+                    app.theme.cursor
+                } else {
+                    clip_color
+                };
+                (ch, Style::default().fg(app.theme.key_black_text).bg(bg))
+            } else if has_note {
+                let bg = if is_cursor {
+                    app.theme.cursor
+                } else {
+                    note_color
+                };
+                ('=', Style::default().fg(app.theme.note_text).bg(bg))
+            } else if clip.is_some() {
+                let bg = if is_cursor {
+                    app.theme.cursor
+                } else {
+                    clip_color
+                };
+                (' ', Style::default().bg(bg))
             } else if is_cursor && row == 0 {
-                ('_', Style::default().fg(Color::Cyan))
+                ('_', Style::default().fg(app.theme.cursor))
             } else {
                 // Grid background - use range-based detection for unaligned scroll
                 let is_measure = contains_measure(tick, app.zoom);
@@ -281,9 +376,9 @@ fn render_track_content(
                 };
 
                 let fg = if is_measure {
-                    Color::DarkGray
+                    app.theme.beat_line
                 } else {
-                    Color::Rgb(40, 40, 40)
+                    app.theme.grid_dot
                 };
 
                 (ch, Style::default().fg(fg))
@@ -0,0 +1,428 @@
+//! Staff notation rendering.
+//!
+//! Draws the selected track as a score (a grand staff for piano programs)
+//! with one row per staff line or space. Columns map linearly to sixteenth
+//! slots so the playhead and mouse seeking line up with the notes.
+
+use crate::app::{App, StaffLayout};
+use crate::midi::{
+    Accidental, Beam, Clef, Meter, NoteValue, Score, StaffEvent, FLAT_ORDER, SHARP_ORDER,
+    SLOT_TICKS,
+};
+use ratatui::layout::Rect;
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Paragraph};
+use ratatui::Frame;
+
+/// Highest staff step drawn (A5 on the treble staff).
+const TOP_STEP: i32 = 10;
+
+/// Lowest staff step drawn (middle C on the treble staff).
+const BOTTOM_STEP: i32 = -2;
+
+/// Rows per staff: the stem/beam row plus one row per step.
+const STAFF_HEIGHT: usize = 1 + (TOP_STEP - BOTTOM_STEP + 1) as usize;
+
+/// Columns per sixteenth slot (accidental and notehead).
+const SLOT_WIDTH: u16 = 2;
+
+/// A grid of styled characters the staff is drawn into.
+struct Canvas {
+    cells: Vec<Vec<(char, Style)>>,
+}
+
+impl Canvas {
+    fn new(width: u16, height: u16) -> Self {
+        Self {
+            cells: vec![vec![(' ', Style::default()); width as usize]; height as usize],
+        }
+    }
+
+    /// Sets a cell, ignoring positions outside the canvas.
+    fn put(&mut self, row: usize, col: u16, glyph: char, style: Style) {
+        if let Some(cell) = self
+            .cells
+            .get_mut(row)
+            .and_then(|r| r.get_mut(col as usize))
+        {
+            *cell = (glyph, style);
+        }
+    }
+
+    /// Writes a string starting at a cell.
+    fn put_str(&mut self, row: usize, col: u16, text: &str, style: Style) {
+        for (i, glyph) in text.chars().enumerate() {
+            self.put(row, col + i as u16, glyph, style);
+        }
+    }
+
+    /// Sets the background of a cell.
+    fn highlight(&mut self, row: usize, col: u16, bg: Color) {
+        if let Some(cell) = self
+            .cells
+            .get_mut(row)
+            .and_then(|r| r.get_mut(col as usize))
+        {
+            cell.1 = cell.1.bg(bg);
+        }
+    }
+
+    fn into_lines(self) -> Vec<Line<'static>> {
+        self.cells
+            .into_iter()
+            .map(|row| {
+                Line::from(
+                    row.into_iter()
+                        .map(|(glyph, style)| Span::styled(glyph.to_string(), style))
+                        .collect::<Vec<_>>(),
+                )
+            })
+            .collect()
+    }
+}
+
+/// Returns the canvas row of a staff step, or None outside the drawn range.
+fn step_row(top: usize, step: i32) -> Option<usize> {
+    (BOTTOM_STEP..=TOP_STEP)
+        .contains(&step)
+        .then(|| top + 1 + (TOP_STEP - step) as usize)
+}
+
+/// Returns the rest glyph for a note value.
+fn rest_glyph(value: NoteValue) -> char {
+    match value {
+        NoteValue::Whole => '𝄻',
+        NoteValue::Half => '𝄼',
+        NoteValue::Quarter => '𝄽',
+        NoteValue::Eighth => '𝄾',
+        NoteValue::Sixteenth => '𝄿',
+    }
+}
+
+/// Returns the stem row glyph of a note: stems, flags and beam junctions.
+fn stem_glyph(event: &StaffEvent) -> Option<char> {
+    let sixteenth = event.duration.value == NoteValue::Sixteenth;
+    match (event.duration.value, event.beam) {
+        (NoteValue::Whole, _) => None,
+        (NoteValue::Half | NoteValue::Quarter, _) => Some('│'),
+        (_, Beam::None) => Some(if sixteenth { '╕' } else { '┐' }),
+        (_, Beam::Start) => Some(if sixteenth { '╔' } else { '┏' }),
+        (_, Beam::Continue) => Some(if sixteenth { '╦' } else { '┳' }),
+        (_, Beam::End) => Some(if sixteenth { '╗' } else { '┓' }),
+    }
+}
+
+/// Renders the staff view for the selected track.
+///
+/// # Arguments
+///
+/// * `frame` - The frame to render to
+/// * `area` - The area to render in
+/// * `app` - Application state
+/// * `focused` - Whether this panel is focused
+///
+/// # Returns
+///
+/// The measure geometry for click-to-seek (empty if nothing is notated)
+pub fn render_staff(frame: &mut Frame, area: Rect, app: &App, focused: bool) -> StaffLayout {
+    let project = app.project();
+    let track = app.selected_track();
+    // General MIDI pianos (programs 0-7) get a grand staff
+    let grand = track.is_some_and(|t| t.program < 8);
+    let key_name = project
+        .scale
+        .map_or_else(|| "No key".to_string(), |s| s.name());
+    let title = match track {
+        Some(track) => format!(
+            " Staff - {} | {} | {}/{}{} ",
+            track.name,
+            key_name,
+            project.time_sig_numerator,
+            project.time_sig_denominator,
+            if grand { " | Grand staff" } else { "" }
+        ),
+        None => " Staff ".to_string(),
+    };
+    let block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(app.theme.border(focused)));
+
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let Some(track) = track.filter(|t| !t.is_percussion()) else {
+        let message = if track.is_some() {
+            " Drum tracks are not notated - use the step sequencer (g) instead"
+        } else {
+            " No track selected"
+        };
+        frame.render_widget(
+            Paragraph::new(Line::from(Span::styled(
+                message,
+                Style::default().fg(app.theme.text_dim),
+            ))),
+            inner,
+        );
+        return StaffLayout::default();
+    };
+
+    let meter = Meter {
+        numerator: project.time_sig_numerator,
+        denominator: project.time_sig_denominator,
+    };
+    let key_sharps = project
+        .scale
+        .and_then(|s| s.key_signature())
+        .map_or(0, |(sharps, _)| sharps);
+    let key_count = key_sharps.unsigned_abs() as u16;
+    let numerator = meter.numerator.to_string();
+    let denominator = meter.denominator.to_string();
+    let time_width = numerator.len().max(denominator.len()) as u16;
+
+    // Clef, key signature and time signature, each followed by a space
+    let prefix = 3 + key_count + 1 + time_width + 1;
+    let per_measure = meter.slots_per_measure();
+    let measure_width = 1 + per_measure as u16 * SLOT_WIDTH;
+    if inner.width <= prefix + measure_width || inner.height < 3 {
+        return StaffLayout::default();
+    }
+    let count = ((inner.width - prefix - 1) / measure_width).max(1) as u32;
+
+    // Page so the playhead (or cursor) measure is always shown
+    let measure_ticks = project.ticks_per_measure();
+    let playhead_measure = app.cursor_tick / measure_ticks;
+    let playhead_slot = (app.cursor_tick % measure_ticks) / SLOT_TICKS;
+    let first_measure = playhead_measure / count * count;
+    let score = Score::new(
+        track.notes(),
+        meter,
+        key_sharps,
+        grand,
+        first_measure..first_measure + count,
+    );
+
+    let width = prefix + count as u16 * measure_width + 1;
+    let height = 1 + score.staves.len() * STAFF_HEIGHT;
+    let mut canvas = Canvas::new(width, height as u16);
+    let playing = app.audio.is_playing();
+    let playhead_style = Style::default().fg(if playing {
+        app.theme.playhead
+    } else {
+        app.theme.cursor
+    });
+    let line_style = Style::default().fg(app.theme.text_secondary);
+    let note_style = Style::default().fg(app.theme.text);
+    let sounding_style = Style::default()
+        .fg(app.theme.active)
+        .add_modifier(Modifier::BOLD);
+    let barline_col = |m: u32| prefix + m as u16 * measure_width;
+    let slot_col = |m: u32, slot: u32| barline_col(m) + 1 + slot as u16 * SLOT_WIDTH;
+
+    // Ruler: bar numbers and the playhead
+    for m in 0..count {
+        canvas.put_str(
+            0,
+            barline_col(m),
+            &(first_measure + m + 1).to_string(),
+            Style::default()
+                .fg(app.theme.ruler_measure)
+                .add_modifier(Modifier::BOLD),
+        );
+    }
+    let playhead_col = (playhead_slot < per_measure)
+        .then(|| slot_col(playhead_measure - first_measure, playhead_slot));
+    if let Some(col) = playhead_col {
+        canvas.put(0, col + 1, '▼', playhead_style);
+    }
+
+    for (index, staff) in score.staves.iter().enumerate() {
+        let top = 1 + index * STAFF_HEIGHT;
+
+        // Staff lines with barlines crossing them
+        for step in 0..=8 {
+            let Some(row) = step_row(top, step) else {
+                continue;
+            };
+            if step % 2 == 0 {
+                for col in 1..width {
+                    canvas.put(row, col, '─', line_style);
+                }
+            }
+            for m in 0..=count {
+                let glyph = match step {
+                    8 => '┬',
+                    0 => '┴',
+                    _ if step % 2 == 0 => '┼',
+                    _ => '│',
+                };
+                canvas.put(row, barline_col(m), glyph, line_style);
+            }
+        }
+
+        // Clef, key signature and time signature
+        let symbol_style = Style::default()
+            .fg(app.theme.text)
+            .add_modifier(Modifier::BOLD);
+        let (clef_glyph, clef_step) = match staff.clef {
+            Clef::Treble => ('𝄞', 2),
+            Clef::Bass => ('𝄢', 6),
+        };
+        if let Some(row) = step_row(top, clef_step) {
+            canvas.put(row, 1, clef_glyph, symbol_style);
+        }
+        let (order, accidental) = if key_sharps >= 0 {
+            (&SHARP_ORDER, Accidental::Sharp)
+        } else {
+            (&FLAT_ORDER, Accidental::Flat)
+        };
+        for (i, &letter) in order.iter().take(key_count as usize).enumerate() {
+            let step = staff.clef.key_step(letter, key_sharps > 0);
+            if let Some(row) = step_row(top, step) {
+                canvas.put(row, 3 + i as u16, accidental.glyph(), symbol_style);
+            }
+        }
+        let time_col = 3 + key_count + 1;
+        if let Some(row) = step_row(top, 6) {
+            canvas.put_str(row, time_col, &numerator, symbol_style);
+        }
+        if let Some(row) = step_row(top, 2) {
+            canvas.put_str(row, time_col, &denominator, symbol_style);
+        }
+
+        for (m, events) in staff.measures.iter().enumerate() {
+            let m = m as u32;
+            for (i, event) in events.iter().enumerate() {
+                let col = slot_col(m, event.slot);
+                let head_col = col + 1;
+                let slots = event.duration.slots();
+                let sounding = playing
+                    && first_measure + m == playhead_measure
+                    && (event.slot..event.slot + slots).contains(&playhead_slot);
+                let style = if sounding { sounding_style } else { note_style };
+                let dot_col = head_col + 1;
+
+                if event.is_rest() {
+                    if let Some(row) = step_row(top, 4) {
+                        canvas.put(row, head_col, rest_glyph(event.duration.value), style);
+                        if event.duration.dotted {
+                            canvas.put(row, dot_col, '·', style);
+                        }
+                    }
+                    continue;
+                }
+
+                // Ties run to the head of the next event, skipping barlines
+                if event.tied {
+                    let end = m * per_measure + event.slot + slots;
+                    let next_head = slot_col(end / per_measure, end % per_measure) + 1;
+                    let start = if event.duration.dotted {
+                        dot_col + 1
+                    } else {
+                        dot_col
+                    };
+                    for head in &event.heads {
+                        let step = head.step.clamp(BOTTOM_STEP, TOP_STEP);
+                        let Some(row) = step_row(top, step) else {
+                            continue;
+                        };
+                        for c in start..next_head {
+                            if !(c - prefix).is_multiple_of(measure_width) {
+                                canvas.put(row, c, '‿', style);
+                            }
+                        }
+                    }
+                }
+
+                for head in &event.heads {
+                    let step = head.step.clamp(BOTTOM_STEP, TOP_STEP);
+                    let Some(row) = step_row(top, step) else {
+                        continue;
+                    };
+                    // Ledger lines for middle C and A5 (or notes beyond them)
+                    if step == BOTTOM_STEP || step == TOP_STEP {
+                        canvas.put(row, col, '─', line_style);
+                    }
+                    let glyph = if head.step > TOP_STEP {
+                        '▲'
+                    } else if head.step < BOTTOM_STEP {
+                        '▼'
+                    } else if matches!(event.duration.value, NoteValue::Whole | NoteValue::Half) {
+                        '○'
+                    } else {
+                        '●'
+                    };
+                    canvas.put(row, head_col, glyph, style);
+                    if let Some(accidental) = head.accidental {
+                        canvas.put(row, col, accidental.glyph(), style);
+                    }
+                    if event.duration.dotted {
+                        canvas.put(row, dot_col, '·', style);
+                    }
+                }
+
+                // Stems, flags and beams
+                if let Some(glyph) = stem_glyph(event) {
+                    canvas.put(top, head_col, glyph, line_style);
+                }
+                if matches!(event.beam, Beam::Start | Beam::Continue) {
+                    if let Some(next) = events.get(i + 1) {
+                        let beam = if event.duration.value == NoteValue::Sixteenth
+                            && next.duration.value == NoteValue::Sixteenth
+                        {
+                            '═'
+                        } else {
+                            '━'
+                        };
+                        for c in head_col + 1..slot_col(m, next.slot) + 1 {
+                            canvas.put(top, c, beam, line_style);
+                        }
+                    }
+                }
+            }
+        }
+
+        // Playhead column across the staff
+        if let Some(col) = playhead_col {
+            for row in top..top + STAFF_HEIGHT {
+                canvas.highlight(row, col, app.theme.playhead_bg);
+                canvas.highlight(row, col + 1, app.theme.playhead_bg);
+            }
+        }
+    }
+    frame.render_widget(Paragraph::new(canvas.into_lines()), inner);
+
+    let key_style = Style::default().fg(app.theme.accent);
+    let desc_style = Style::default().fg(app.theme.text_dim);
+    let hints = Line::from(vec![
+        Span::styled("Click", key_style),
+        Span::styled(" seek  ", desc_style),
+        Span::styled("h/l", key_style),
+        Span::styled(" beat  ", desc_style),
+        Span::styled("H/L", key_style),
+        Span::styled(" bar  ", desc_style),
+        Span::styled("S", key_style),
+        Span::styled(" key  ", desc_style),
+        Span::styled("g", key_style),
+        Span::styled(" next view", desc_style),
+    ]);
+    let hint_area = Rect {
+        y: inner.y + inner.height - 1,
+        height: 1,
+        ..inner
+    };
+    frame.render_widget(Paragraph::new(hints), hint_area);
+
+    StaffLayout {
+        measures: Rect {
+            x: inner.x + prefix,
+            y: inner.y,
+            width: (count as u16 * measure_width).min(inner.width - prefix),
+            height: (height as u16).min(inner.height - 1),
+        },
+        first_measure,
+        measure_width,
+        slot_width: SLOT_WIDTH,
+    }
+}
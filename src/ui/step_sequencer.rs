@@ -0,0 +1,209 @@
+//! Step sequencer (drum grid) rendering.
+//!
+//! Shows one row per drum instrument and one column per step of the current
+//! pattern. Filled cells are steps with a note; their glyph and color follow
+//! the step velocity so accents stand out.
+
+use crate::app::{App, StepGridLayout, ACCENT_VELOCITY};
+use ratatui::layout::Rect;
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Paragraph};
+use ratatui::Frame;
+
+/// Width of the instrument name column (longest GM name plus a space; longer names are cut).
+const NAME_WIDTH: u16 = 19;
+
+/// Returns the glyph for a step with a note of the given velocity.
+fn step_glyph(velocity: u8) -> &'static str {
+    if velocity >= ACCENT_VELOCITY {
+        "█"
+    } else if velocity >= 70 {
+        "▓"
+    } else {
+        "▒"
+    }
+}
+
+/// Renders the step sequencer for the selected drum track.
+///
+/// # Arguments
+///
+/// * `frame` - The frame to render to
+/// * `area` - The area to render in
+/// * `app` - Application state
+/// * `focused` - Whether this panel is focused
+///
+/// # Returns
+///
+/// The grid geometry for mouse hit testing (empty if no drum track is selected)
+pub fn render_step_sequencer(
+    frame: &mut Frame,
+    area: Rect,
+    app: &App,
+    focused: bool,
+) -> StepGridLayout {
+    let seq = &app.step_seq;
+    let track = app.selected_track().filter(|t| t.is_percussion());
+    let measure = app.project().ticks_per_measure();
+    let title = match track {
+        Some(track) => format!(
+            " Step Sequencer - {} ({}) | Bar {} | {} x 1/{}{} ",
+            track.name,
+            app.drum_map().name,
+            seq.pattern_start / measure + 1,
+            seq.steps,
+            seq.division,
+            if seq.looping { " | Loop" } else { "" }
+        ),
+        None => " Step Sequencer ".to_string(),
+    };
+    let block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(app.theme.border(focused)));
+
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let Some(track) = track else {
+        frame.render_widget(
+            Paragraph::new(Line::from(Span::styled(
+                " Select a drum track (channel 10 or with a drum map) to use the step sequencer",
+                Style::default().fg(app.theme.text_dim),
+            ))),
+            inner,
+        );
+        return StepGridLayout::default();
+    };
+    if inner.width <= NAME_WIDTH || inner.height < 4 {
+        return StepGridLayout::default();
+    }
+
+    let map = app.drum_map();
+    let rows = &map.entries;
+    let cell_width: u16 = if NAME_WIDTH + seq.steps as u16 * 2 <= inner.width {
+        2
+    } else {
+        1
+    };
+    // One line for the step ruler and one for the key hints
+    let visible_rows = (inner.height - 2) as usize;
+    let first_row = (seq.row + 1).saturating_sub(visible_rows);
+    let visible_steps = seq
+        .steps
+        .min(((inner.width - NAME_WIDTH) / cell_width) as u32);
+
+    // Step under the playhead, if it is inside the pattern
+    let playhead_step = app
+        .audio
+        .is_playing()
+        .then(|| app.cursor_tick.checked_sub(seq.pattern_start))
+        .flatten()
+        .map(|offset| offset / seq.step_ticks())
+        .filter(|&step| step < seq.steps);
+
+    let mut lines = Vec::with_capacity(inner.height as usize);
+
+    // Step ruler: beat numbers at every fourth step
+    let mut ruler = vec![Span::raw(" ".repeat(NAME_WIDTH as usize))];
+    for step in 0..visible_steps {
+        let label = if step % 4 == 0 {
+            format!("{:<width$}", step / 4 + 1, width = cell_width as usize)
+        } else {
+            " ".repeat(cell_width as usize)
+        };
+        let style = if playhead_step == Some(step) {
+            Style::default()
+                .fg(app.theme.text)
+                .bg(app.theme.playhead_bg)
+        } else {
+            Style::default().fg(app.theme.text_dim)
+        };
+        ruler.push(Span::styled(label, style));
+    }
+    lines.push(Line::from(ruler));
+
+    for (row, entry) in rows.iter().enumerate().skip(first_row).take(visible_rows) {
+        let used = track.notes().iter().any(|n| n.pitch == entry.pitch);
+        let name_style = if row == seq.row {
+            Style::default()
+                .fg(app.theme.accent)
+                .add_modifier(Modifier::BOLD)
+        } else if used {
+            Style::default().fg(app.theme.text)
+        } else {
+            Style::default().fg(app.theme.text_dim)
+        };
+        let mut spans = vec![Span::styled(
+            format!(
+                "{:<width$.prec$}",
+                entry.name,
+                width = NAME_WIDTH as usize,
+                prec = NAME_WIDTH as usize - 1
+            ),
+            name_style,
+        )];
+
+        for step in 0..visible_steps {
+            let note = app.step_note(entry.pitch, step);
+            let glyph = note.map_or("·", |n| step_glyph(n.velocity));
+            let mut style = match note {
+                Some(n) => Style::default().fg(app.theme.velocity_color(n.velocity)),
+                None => Style::default().fg(app.theme.text_dim),
+            };
+            // Alternate shading per beat group of four steps
+            if (step / 4) % 2 == 1 {
+                style = style.bg(app.theme.alternate_bg);
+            }
+            if playhead_step == Some(step) {
+                style = style.bg(app.theme.playhead_bg);
+            }
+            if focused && row == seq.row && step == seq.step {
+                style = style.bg(app.theme.cursor_bg).fg(app.theme.cursor);
+            }
+            let cell = format!("{:<width$}", glyph, width = cell_width as usize);
+            spans.push(Span::styled(cell, style));
+        }
+        lines.push(Line::from(spans));
+    }
+    frame.render_widget(Paragraph::new(lines), inner);
+
+    let key_style = Style::default().fg(app.theme.accent);
+    let desc_style = Style::default().fg(app.theme.text_dim);
+    let hints = Line::from(vec![
+        Span::styled("Enter", key_style),
+        Span::styled(" toggle  ", desc_style),
+        Span::styled("a", key_style),
+        Span::styled(" accent  ", desc_style),
+        Span::styled("+/-", key_style),
+        Span::styled(" velocity  ", desc_style),
+        Span::styled("H/L", key_style),
+        Span::styled(" pattern  ", desc_style),
+        Span::styled(",//", key_style),
+        Span::styled(" length  ", desc_style),
+        Span::styled("z", key_style),
+        Span::styled(" step  ", desc_style),
+        Span::styled("c", key_style),
+        Span::styled(" copy to next  ", desc_style),
+        Span::styled("o", key_style),
+        Span::styled(" loop", desc_style),
+    ]);
+    let hint_area = Rect {
+        y: inner.y + inner.height - 1,
+        height: 1,
+        ..inner
+    };
+    frame.render_widget(Paragraph::new(hints), hint_area);
+
+    StepGridLayout {
+        cells: Rect {
+            x: inner.x + NAME_WIDTH,
+            y: inner.y + 1,
+            width: visible_steps as u16 * cell_width,
+            height: (rows.len() - first_row).min(visible_rows) as u16,
+        },
+        cell_width,
+        first_row,
+    }
+}
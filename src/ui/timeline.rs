@@ -1,11 +1,13 @@
 //! Timeline and transport controls rendering.
 //!
-//! Displays the current position, tempo, time signature, and playback status.
+//! Displays the current position, tempo, time signature, the chord at the
+//! playhead, and playback status. The `:` command line takes over the bar
+//! while it is open.
 
 use crate::app::App;
 use crate::audio::PlaybackState;
 use ratatui::layout::{Constraint, Direction, Layout, Rect};
-use ratatui::style::{Color, Modifier, Style};
+use ratatui::style::{Modifier, Style};
 use ratatui::text::{Line, Span};
 use ratatui::widgets::{Block, Borders, Paragraph};
 use ratatui::Frame;
@@ -19,14 +21,33 @@ use ratatui::Frame;
 /// * `app` - Application state
 /// * `focused` - Whether this panel is focused
 pub fn render_timeline(frame: &mut Frame, area: Rect, app: &App, focused: bool) {
+    // Current file, with a marker while it has unsaved changes
+    let file_name = app
+        .project_path
+        .as_ref()
+        .and_then(|p| p.file_name())
+        .map_or_else(
+            || "untitled".to_string(),
+            |n| n.to_string_lossy().into_owned(),
+        );
+    let title = if app.has_unsaved_changes() {
+        format!(" Transport - {} [modified] ", file_name)
+    } else {
+        format!(" Transport - {} ", file_name)
+    };
     let block = Block::default()
-        .title(" Transport ")
+        .title(title)
         .borders(Borders::ALL)
-        .border_style(Style::default().fg(if focused { Color::Cyan } else { Color::Gray }));
+        .border_style(Style::default().fg(app.theme.border(focused)));
 
     let inner = block.inner(area);
     frame.render_widget(block, area);
 
+    if app.command_line.open {
+        render_command_line(frame, inner, app);
+        return;
+    }
+
     // Divide into sections
     let chunks = Layout::default()
         .direction(Direction::Horizontal)
@@ -35,6 +56,7 @@ pub fn render_timeline(frame: &mut Frame, area: Rect, app: &App, focused: bool)
             Constraint::Length(20), // Position
             Constraint::Length(15), // Tempo
             Constraint::Length(10), // Time sig
+            Constraint::Length(16), // Chord
             Constraint::Min(20),    // Status/mode
         ])
         .split(inner);
@@ -44,18 +66,20 @@ pub fn render_timeline(frame: &mut Frame, area: Rect, app: &App, focused: bool)
         PlaybackState::Playing => Span::styled(
             " [>] PLAY ",
             Style::default()
-                .fg(Color::Green)
+                .fg(app.theme.active)
                 .add_modifier(Modifier::BOLD),
         ),
         PlaybackState::Paused => Span::styled(
             " [||] PAUSE ",
             Style::default()
-                .fg(Color::Yellow)
+                .fg(app.theme.accent)
                 .add_modifier(Modifier::BOLD),
         ),
         PlaybackState::Stopped => Span::styled(
             " [.] STOP ",
-            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+            Style::default()
+                .fg(app.theme.alert)
+                .add_modifier(Modifier::BOLD),
         ),
     };
     frame.render_widget(Paragraph::new(Line::from(play_status)), chunks[0]);
@@ -63,11 +87,11 @@ pub fn render_timeline(frame: &mut Frame, area: Rect, app: &App, focused: bool)
     // Position display (measure:beat:tick)
     let position = app.position_string();
     let position_widget = Paragraph::new(Line::from(vec![
-        Span::styled("Pos: ", Style::default().fg(Color::DarkGray)),
+        Span::styled("Pos: ", Style::default().fg(app.theme.text_dim)),
         Span::styled(
             position,
             Style::default()
-                .fg(Color::White)
+                .fg(app.theme.text)
                 .add_modifier(Modifier::BOLD),
         ),
     ]));
@@ -75,10 +99,10 @@ pub fn render_timeline(frame: &mut Frame, area: Rect, app: &App, focused: bool)
 
     // Tempo display
     let tempo_widget = Paragraph::new(Line::from(vec![
-        Span::styled("BPM: ", Style::default().fg(Color::DarkGray)),
+        Span::styled("BPM: ", Style::default().fg(app.theme.text_dim)),
         Span::styled(
             format!("{}", app.project().tempo),
-            Style::default().fg(Color::White),
+            Style::default().fg(app.theme.text),
         ),
     ]));
     frame.render_widget(tempo_widget, chunks[2]);
@@ -91,33 +115,99 @@ pub fn render_timeline(frame: &mut Frame, area: Rect, app: &App, focused: bool)
     );
     let time_sig_widget = Paragraph::new(Line::from(vec![Span::styled(
         time_sig,
-        Style::default().fg(Color::White),
+        Style::default().fg(app.theme.text),
     )]));
     frame.render_widget(time_sig_widget, chunks[3]);
 
-    // Status message or mode indicator
-    let status_line = if let Some((msg, _)) = &app.status_message {
-        Line::from(Span::styled(
-            msg.as_str(),
-            Style::default()
-                .fg(Color::Yellow)
-                .add_modifier(Modifier::ITALIC),
-        ))
+    // Chord sounding at the playhead (or the chord track symbol if nothing sounds)
+    let detected = app.detected_chord();
+    let chord = detected.or_else(|| {
+        let project = app.project();
+        project
+            .chord_index_at(app.cursor_tick)
+            .map(|index| project.chords()[index].chord)
+    });
+    let chord_style = if detected.is_some() {
+        Style::default()
+            .fg(app.theme.info)
+            .add_modifier(Modifier::BOLD)
     } else {
-        let mode_str = match app.edit_mode {
-            crate::app::EditMode::Normal => "NORMAL",
-            crate::app::EditMode::Insert => "INSERT",
-            crate::app::EditMode::Select => "SELECT",
-        };
-        let mode_color = match app.edit_mode {
-            crate::app::EditMode::Normal => Color::Blue,
-            crate::app::EditMode::Insert => Color::Green,
-            crate::app::EditMode::Select => Color::Magenta,
-        };
-        Line::from(Span::styled(
-            format!("-- {} --", mode_str),
-            Style::default().fg(mode_color).add_modifier(Modifier::BOLD),
-        ))
+        Style::default().fg(app.theme.text_dim)
     };
-    frame.render_widget(Paragraph::new(status_line), chunks[4]);
+    let chord_widget = Paragraph::new(Line::from(vec![
+        Span::styled("Chord: ", Style::default().fg(app.theme.text_dim)),
+        Span::styled(
+            chord.map_or_else(|| "-".to_string(), |c| c.symbol()),
+            chord_style,
+        ),
+    ]));
+    frame.render_widget(chord_widget, chunks[4]);
+
+    // Status message or mode indicator, which also shows keys typed ahead
+    // of an action (a count, register or mark)
+    let pending = app.pending_keys.display();
+    let status_line =
+        if let Some((msg, _)) = app.status_message.as_ref().filter(|_| pending.is_empty()) {
+            Line::from(Span::styled(
+                msg.as_str(),
+                Style::default()
+                    .fg(app.theme.accent)
+                    .add_modifier(Modifier::ITALIC),
+            ))
+        } else {
+            let mode_str = match app.edit_mode {
+                crate::app::EditMode::Normal => "NORMAL",
+                crate::app::EditMode::Insert => "INSERT",
+                crate::app::EditMode::Select => "SELECT",
+                crate::app::EditMode::Lyric => "LYRIC",
+            };
+            let mode_color = match app.edit_mode {
+                crate::app::EditMode::Normal => app.theme.link,
+                crate::app::EditMode::Insert => app.theme.active,
+                crate::app::EditMode::Select => app.theme.note_selected,
+                crate::app::EditMode::Lyric => app.theme.accent,
+            };
+            Line::from(vec![
+                Span::styled(
+                    format!("-- {} --", mode_str),
+                    Style::default().fg(mode_color).add_modifier(Modifier::BOLD),
+                ),
+                Span::styled(format!(" {}", pending), Style::default().fg(app.theme.text)),
+            ])
+        };
+    frame.render_widget(Paragraph::new(status_line), chunks[5]);
+}
+
+/// Renders the command line being typed, followed by the completions Tab
+/// cycles through.
+fn render_command_line(frame: &mut Frame, area: Rect, app: &App) {
+    let mut spans = vec![Span::styled(
+        format!(":{}_", app.command_line.input),
+        Style::default().fg(app.theme.text),
+    )];
+
+    let (completions, current) = app.command_line.completions();
+    if completions.len() > 1 {
+        spans.push(Span::raw("   "));
+        for (index, completion) in completions.iter().enumerate() {
+            // Only the part that differs between candidates
+            let word = completion
+                .trim_end_matches(['/', '='])
+                .rsplit([' ', '/', '='])
+                .next()
+                .unwrap_or(completion);
+            let style = if index == current {
+                Style::default()
+                    .fg(app.theme.text)
+                    .bg(app.theme.current_row_bg)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(app.theme.text_dim)
+            };
+            spans.push(Span::styled(word.to_string(), style));
+            spans.push(Span::raw(" "));
+        }
+    }
+
+    frame.render_widget(Paragraph::new(Line::from(spans)), area);
 }
@@ -5,6 +5,7 @@
 //! Includes a "Remove Track" button and rename input functionality.
 
 use crate::app::App;
+use crate::theme::Theme;
 use ratatui::layout::{Constraint, Direction, Layout, Rect};
 use ratatui::style::{Color, Modifier, Style};
 use ratatui::text::{Line, Span};
@@ -16,15 +17,15 @@ const CONTROLS_HEIGHT: u16 = 2;
 
 /// Returns the display color for a volume value.
 ///
-/// Red for clipping (>100), yellow for hot (>80), green otherwise.
+/// Alert for clipping (>100), accent for hot (>80), active otherwise.
 #[inline]
-fn volume_color(volume: u8) -> Color {
+fn volume_color(theme: &Theme, volume: u8) -> Color {
     if volume > 100 {
-        Color::Red
+        theme.alert
     } else if volume > 80 {
-        Color::Yellow
+        theme.accent
     } else {
-        Color::Green
+        theme.active
     }
 }
 
@@ -54,7 +55,7 @@ pub fn render_track_list(frame: &mut Frame, area: Rect, app: &App, focused: bool
     let block = Block::default()
         .title(" Tracks ")
         .borders(Borders::ALL)
-        .border_style(Style::default().fg(if focused { Color::Cyan } else { Color::Gray }));
+        .border_style(Style::default().fg(app.theme.border(focused)));
 
     let inner = block.inner(area);
     frame.render_widget(block, area);
@@ -68,6 +69,9 @@ pub fn render_track_list(frame: &mut Frame, area: Rect, app: &App, focused: bool
         ])
         .split(inner);
 
+    // Tracks drawn as ghost notes show their ghost color
+    let ghost_tracks = app.ghost_track_indices();
+
     // Build list items from tracks
     let items: Vec<ListItem> = app
         .project()
@@ -85,21 +89,23 @@ pub fn render_track_list(frame: &mut Frame, area: Rect, app: &App, focused: bool
             let mute_indicator = if track.muted {
                 Span::styled(
                     "M",
-                    Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+                    Style::default()
+                        .fg(app.theme.alert)
+                        .add_modifier(Modifier::BOLD),
                 )
             } else {
-                Span::styled(".", Style::default().fg(Color::DarkGray))
+                Span::styled(".", Style::default().fg(app.theme.text_dim))
             };
 
             let solo_indicator = if track.solo {
                 Span::styled(
                     "S",
                     Style::default()
-                        .fg(Color::Yellow)
+                        .fg(app.theme.accent)
                         .add_modifier(Modifier::BOLD),
                 )
             } else {
-                Span::styled(".", Style::default().fg(Color::DarkGray))
+                Span::styled(".", Style::default().fg(app.theme.text_dim))
             };
 
             // Activity indicator (shows when track is playing audio)
@@ -107,28 +113,30 @@ pub fn render_track_list(frame: &mut Frame, area: Rect, app: &App, focused: bool
                 Span::styled(
                     "*",
                     Style::default()
-                        .fg(Color::Green)
+                        .fg(app.theme.active)
                         .add_modifier(Modifier::BOLD),
                 )
             } else {
-                Span::styled(" ", Style::default().fg(Color::DarkGray))
+                Span::styled(" ", Style::default().fg(app.theme.text_dim))
             };
 
             // Determine name style based on selection and activity
             let name_style = if i == app.selected_track_index {
                 if is_active {
                     Style::default()
-                        .fg(Color::Green)
+                        .fg(app.theme.active)
                         .add_modifier(Modifier::BOLD)
                 } else {
                     Style::default()
-                        .fg(Color::White)
+                        .fg(app.theme.text)
                         .add_modifier(Modifier::BOLD)
                 }
             } else if is_active {
-                Style::default().fg(Color::Green)
+                Style::default().fg(app.theme.active)
+            } else if ghost_tracks.contains(&i) {
+                Style::default().fg(app.theme.track_color(i))
             } else {
-                Style::default().fg(Color::Gray)
+                Style::default().fg(app.theme.text_secondary)
             };
 
             if app.expanded_tracks {
@@ -154,8 +162,8 @@ pub fn render_track_list(frame: &mut Frame, area: Rect, app: &App, focused: bool
                         Span::styled(
                             display_name,
                             Style::default()
-                                .fg(Color::Yellow)
-                                .bg(Color::DarkGray)
+                                .fg(app.theme.accent)
+                                .bg(app.theme.cursor_bg)
                                 .add_modifier(Modifier::BOLD),
                         ),
                     ])
@@ -188,11 +196,14 @@ pub fn render_track_list(frame: &mut Frame, area: Rect, app: &App, focused: bool
 
                 let line2 = Line::from(vec![
                     Span::raw("  "),
-                    Span::styled(vol_str, Style::default().fg(volume_color(track.volume))),
+                    Span::styled(
+                        vol_str,
+                        Style::default().fg(volume_color(&app.theme, track.volume)),
+                    ),
                     Span::raw(" "),
-                    Span::styled(pan_str, Style::default().fg(Color::Cyan)),
+                    Span::styled(pan_str, Style::default().fg(app.theme.info)),
                     Span::raw(" "),
-                    Span::styled(instrument_display, Style::default().fg(Color::DarkGray)),
+                    Span::styled(instrument_display, Style::default().fg(app.theme.text_dim)),
                 ]);
 
                 ListItem::new(vec![line1, line2])
@@ -202,7 +213,7 @@ pub fn render_track_list(frame: &mut Frame, area: Rect, app: &App, focused: bool
                     let vol_bar = format!("V{:3}", track.volume);
                     Some(Span::styled(
                         vol_bar,
-                        Style::default().fg(volume_color(track.volume)),
+                        Style::default().fg(volume_color(&app.theme, track.volume)),
                     ))
                 } else {
                     None
@@ -211,7 +222,7 @@ pub fn render_track_list(frame: &mut Frame, area: Rect, app: &App, focused: bool
                 let pan_span = if track.pan != 64 {
                     Some(Span::styled(
                         format_pan(track.pan),
-                        Style::default().fg(Color::Cyan),
+                        Style::default().fg(app.theme.info),
                     ))
                 } else {
                     None
@@ -254,8 +265,8 @@ pub fn render_track_list(frame: &mut Frame, area: Rect, app: &App, focused: bool
                     spans.push(Span::styled(
                         display_name,
                         Style::default()
-                            .fg(Color::Yellow)
-                            .bg(Color::DarkGray)
+                            .fg(app.theme.accent)
+                            .bg(app.theme.cursor_bg)
                             .add_modifier(Modifier::BOLD),
                     ));
                 } else {
@@ -278,7 +289,7 @@ pub fn render_track_list(frame: &mut Frame, area: Rect, app: &App, focused: bool
     let list = List::new(items)
         .highlight_style(
             Style::default()
-                .bg(Color::Rgb(40, 40, 40))
+                .bg(app.theme.current_row_bg)
                 .add_modifier(Modifier::BOLD),
         )
         .highlight_symbol("> ");
@@ -290,8 +301,8 @@ pub fn render_track_list(frame: &mut Frame, area: Rect, app: &App, focused: bool
     frame.render_stateful_widget(list, chunks[0], &mut state);
 
     // Render control hints
-    let key_style = Style::default().fg(Color::Yellow);
-    let desc_style = Style::default().fg(Color::DarkGray);
+    let key_style = Style::default().fg(app.theme.accent);
+    let desc_style = Style::default().fg(app.theme.text_dim);
 
     let line1 = Line::from(vec![
         Span::styled("[", desc_style),
@@ -0,0 +1,135 @@
+//! Velocity lane rendering.
+//!
+//! Draws one bar per grid column under the piano roll, showing the velocity
+//! of the notes starting in that column. Bars use eighth-block characters so
+//! each row gives eight levels of resolution.
+
+use crate::app::{App, VELOCITY_LANE_HEIGHT};
+use ratatui::layout::Rect;
+use ratatui::style::Style;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::Paragraph;
+use ratatui::Frame;
+
+/// Block characters from empty to full (eighths).
+const BAR_CHARS: [char; 9] = [' ', '▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// Minimum piano roll inner height (ruler + pitch rows) kept when the lane is shown.
+const MIN_GRID_HEIGHT: u16 = 6;
+
+/// Returns the height taken by the velocity lane inside the piano roll.
+///
+/// # Arguments
+///
+/// * `show` - Whether the lane is enabled
+/// * `inner_height` - Height of the piano roll inside its borders
+///
+/// # Returns
+///
+/// The lane height, or 0 if hidden or there is not enough room
+pub fn lane_height(show: bool, inner_height: u16) -> u16 {
+    if show && inner_height >= MIN_GRID_HEIGHT + VELOCITY_LANE_HEIGHT {
+        VELOCITY_LANE_HEIGHT
+    } else {
+        0
+    }
+}
+
+/// Renders the velocity lane.
+///
+/// # Arguments
+///
+/// * `frame` - The frame to render to
+/// * `area` - The lane area (header row plus bar rows), full piano roll inner width
+/// * `app` - Application state
+/// * `label_width` - Width of the piano key column on the left
+pub fn render_velocity_lane(frame: &mut Frame, area: Rect, app: &App, label_width: u16) {
+    if area.height < 2 {
+        return;
+    }
+    let grid_x = area.x + label_width;
+    let grid_width = area.width.saturating_sub(label_width);
+    let bar_rows = area.height - 1;
+    let notes = app.selected_track().map(|t| t.notes()).unwrap_or(&[]);
+
+    // Header: label plus the velocity of the note under the cursor
+    let cursor_velocity = notes
+        .iter()
+        .find(|n| n.pitch == app.cursor_pitch && n.is_active_at(app.cursor_tick))
+        .map(|n| format!(" {} ", n.velocity))
+        .unwrap_or_default();
+    let header_style = Style::default()
+        .fg(app.theme.text_dim)
+        .bg(app.theme.panel_bg);
+    frame.render_widget(
+        Paragraph::new(" Vel ").style(header_style),
+        Rect::new(area.x, area.y, label_width, 1),
+    );
+    let rule: String = "─".repeat(grid_width as usize);
+    frame.render_widget(
+        Paragraph::new(Line::from(vec![
+            Span::styled(cursor_velocity, Style::default().fg(app.theme.accent)),
+            Span::styled(rule, Style::default().fg(app.theme.text_dim)),
+        ])),
+        Rect::new(grid_x, area.y, grid_width, 1),
+    );
+
+    // Per-column bar: loudest note starting in the column
+    let columns: Vec<Option<(u8, bool)>> = (0..grid_width)
+        .map(|col| {
+            let tick = app.scroll_x + col as u32 * app.zoom;
+            let tick_end = tick + app.zoom;
+            notes
+                .iter()
+                .filter(|n| (tick..tick_end).contains(&n.start_tick))
+                .map(|n| (n.velocity, app.selected_notes.contains(&n.id)))
+                .max_by_key(|&(velocity, _)| velocity)
+        })
+        .collect();
+
+    let levels = bar_rows as u32 * 8;
+    for row in 0..bar_rows {
+        let y = area.y + 1 + row;
+        // Eighths already covered by the rows below this one
+        let below = (bar_rows - 1 - row) as u32 * 8;
+
+        frame.render_widget(
+            Paragraph::new(" ".repeat(label_width as usize))
+                .style(Style::default().bg(app.theme.panel_bg)),
+            Rect::new(area.x, y, label_width, 1),
+        );
+
+        let spans: Vec<Span> = columns
+            .iter()
+            .enumerate()
+            .map(|(col, column)| {
+                let tick = app.scroll_x + col as u32 * app.zoom;
+                let is_cursor_col = tick / app.zoom == app.cursor_tick / app.zoom;
+                let bg = if is_cursor_col {
+                    app.theme.lane_cursor_bg
+                } else {
+                    app.theme.lane_bg
+                };
+                let Some((velocity, selected)) = *column else {
+                    return Span::styled(" ", Style::default().bg(bg));
+                };
+                let height = (velocity as u32 * levels).div_ceil(127);
+                let eighths = height.saturating_sub(below).min(8) as usize;
+                let fg = if selected {
+                    app.theme.note_selected
+                } else {
+                    app.theme.velocity_color(velocity)
+                };
+                Span::styled(
+                    BAR_CHARS[eighths].to_string(),
+                    Style::default().fg(fg).bg(bg),
+                )
+            })
+            .collect();
+
+        frame.render_widget(
+            Paragraph::new(Line::from(spans)),
+            Rect::new(grid_x, y, grid_width, 1),
+        );
+    }
+}
@@ -0,0 +1,216 @@
+//! Counts, registers and marks.
+//!
+//! In Normal and Select mode keys typed before an action change what it
+//! does, vim style: a count repeats it (`8l`, `3>`), `"a` picks the register
+//! the next yank, delete or paste uses, `ma` saves the cursor as mark `a` and
+//! `'a` jumps back to it. This module keeps those keys until the action
+//! arrives, and holds what registers and marks store.
+
+use crate::midi::Note;
+
+/// The register used when none is named.
+pub const UNNAMED_REGISTER: char = '"';
+
+/// Largest count that can be typed.
+const MAX_COUNT: usize = 999;
+
+/// What the next key is read as.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Awaiting {
+    /// A register name, after `"`.
+    Register,
+    /// A mark to set, after `m`.
+    SetMark,
+    /// A mark to jump to, after `'`.
+    JumpToMark,
+}
+
+/// Keys typed ahead of an action.
+#[derive(Debug, Clone, Default)]
+pub struct PendingKeys {
+    /// The count typed so far.
+    count: Option<usize>,
+    /// The register named with `"`.
+    register: Option<char>,
+    /// Set while a register or mark name is expected.
+    awaiting: Option<Awaiting>,
+}
+
+impl PendingKeys {
+    /// Adds a digit to the count.
+    ///
+    /// Returns false if the digit doesn't belong to a count: a `0` with no
+    /// count before it is a key of its own.
+    pub fn push_digit(&mut self, c: char) -> bool {
+        let Some(digit) = c.to_digit(10) else {
+            return false;
+        };
+        if digit == 0 && self.count.is_none() {
+            return false;
+        }
+        let count = self.count.unwrap_or(0) * 10 + digit as usize;
+        self.count = Some(count.min(MAX_COUNT));
+        true
+    }
+
+    /// Takes the count, leaving the register for the action to read.
+    pub fn take_count(&mut self) -> Option<usize> {
+        self.count.take()
+    }
+
+    /// Reads the next key as a register or mark name.
+    pub fn await_key(&mut self, what: Awaiting) {
+        self.awaiting = Some(what);
+    }
+
+    /// Takes what the next key is read as, if anything.
+    pub fn take_awaiting(&mut self) -> Option<Awaiting> {
+        self.awaiting.take()
+    }
+
+    /// Names the register for the next action.
+    pub fn set_register(&mut self, name: char) {
+        self.register = Some(name);
+    }
+
+    /// Returns the register for the next action.
+    pub fn register(&self) -> char {
+        self.register.unwrap_or(UNNAMED_REGISTER)
+    }
+
+    /// Forgets everything typed.
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Returns the keys typed so far, as shown in the status line.
+    pub fn display(&self) -> String {
+        let mut keys = String::new();
+        if let Some(name) = self.register {
+            keys.push('"');
+            keys.push(name);
+        }
+        if let Some(count) = self.count {
+            keys.push_str(&count.to_string());
+        }
+        match self.awaiting {
+            Some(Awaiting::Register) => keys.push('"'),
+            Some(Awaiting::SetMark) => keys.push('m'),
+            Some(Awaiting::JumpToMark) => keys.push('\''),
+            None => {}
+        }
+        keys
+    }
+}
+
+/// Returns true if `c` names a register: `a`-`z` or the unnamed `"`.
+pub fn is_register(c: char) -> bool {
+    c == UNNAMED_REGISTER || c.is_ascii_lowercase()
+}
+
+/// Returns true if `c` names a mark: `a`-`z`.
+pub fn is_mark(c: char) -> bool {
+    c.is_ascii_lowercase()
+}
+
+/// Notes yanked or deleted into a register.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NoteRegister {
+    /// The notes, with starts relative to the earliest one.
+    notes: Vec<Note>,
+    /// Ticks from the earliest start to the latest end.
+    pub length: u32,
+}
+
+impl NoteRegister {
+    /// Copies notes into a register, or returns None if there are none.
+    pub fn from_notes<'a>(notes: impl IntoIterator<Item = &'a Note>) -> Option<Self> {
+        let mut notes: Vec<Note> = notes.into_iter().cloned().collect();
+        let start = notes.iter().map(|n| n.start_tick).min()?;
+        let end = notes.iter().map(|n| n.end_tick()).max()?;
+        notes.sort_by_key(|n| (n.start_tick, n.pitch));
+        for note in &mut notes {
+            note.start_tick -= start;
+        }
+        Some(Self {
+            notes,
+            length: end - start,
+        })
+    }
+
+    /// Returns the number of notes held.
+    pub fn note_count(&self) -> usize {
+        self.notes.len()
+    }
+
+    /// Returns fresh copies of the notes starting at `tick`.
+    pub fn notes_at(&self, tick: u32) -> Vec<Note> {
+        self.notes
+            .iter()
+            .map(|note| {
+                let mut copy = note.duplicate();
+                copy.start_tick += tick;
+                copy
+            })
+            .collect()
+    }
+}
+
+/// A saved cursor position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Mark {
+    pub tick: u32,
+    pub pitch: u8,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counts_and_registers() {
+        let mut pending = PendingKeys::default();
+        assert!(!pending.push_digit('0'));
+        assert!(pending.push_digit('1'));
+        assert!(pending.push_digit('0'));
+        pending.set_register('a');
+        assert_eq!(pending.display(), "\"a10");
+        assert_eq!(pending.take_count(), Some(10));
+        assert_eq!(pending.register(), 'a');
+
+        pending.clear();
+        assert_eq!(pending.register(), UNNAMED_REGISTER);
+        for _ in 0..6 {
+            pending.push_digit('9');
+        }
+        assert_eq!(pending.take_count(), Some(MAX_COUNT));
+    }
+
+    #[test]
+    fn test_awaiting_key() {
+        let mut pending = PendingKeys::default();
+        pending.push_digit('2');
+        pending.await_key(Awaiting::SetMark);
+        assert_eq!(pending.display(), "2m");
+        assert_eq!(pending.take_awaiting(), Some(Awaiting::SetMark));
+        assert_eq!(pending.take_awaiting(), None);
+        assert!(is_register('"') && is_register('q') && !is_register('Q'));
+        assert!(is_mark('a') && !is_mark('"'));
+    }
+
+    #[test]
+    fn test_register_pastes_relative_copies() {
+        let notes = [Note::new(64, 90, 960, 240), Note::new(60, 100, 480, 480)];
+        let register = NoteRegister::from_notes(&notes).unwrap();
+        assert_eq!(register.note_count(), 2);
+        assert_eq!(register.length, 720);
+
+        let pasted = register.notes_at(1920);
+        assert_eq!(pasted[0].start_tick, 1920);
+        assert_eq!(pasted[0].pitch, 60);
+        assert_eq!(pasted[1].start_tick, 2400);
+        assert!(pasted.iter().all(|n| notes.iter().all(|o| o.id != n.id)));
+
+        assert!(NoteRegister::from_notes(&[]).is_none());
+    }
+}